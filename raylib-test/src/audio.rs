@@ -4,7 +4,16 @@ mod audio_test {
     use raylib::prelude::*;
     #[test]
     fn test_init_audio() {
-        let _ = RaylibAudio::init_audio_device();
+        if let Ok(audio) = RaylibAudio::init_audio_device() {
+            // the device can only be opened once at a time, whatever the options
+            assert!(RaylibAudio::init_audio_device().is_err());
+            let err = RaylibAudio::builder()
+                .buffer_size(1024)
+                .build()
+                .unwrap_err();
+            assert!(err.contains("more than once"));
+            drop(audio);
+        }
     }
     #[test]
     fn test_load_wave() {
@@ -17,7 +26,7 @@ mod audio_test {
     fn test_load_music(_thread: &RaylibThread) {
         // TODO uncomment when music is fixed
         // {
-        //     let _ = Music::load_music_stream(thread, "resources/audio/chiptun1.mod")
+        //     let _ = audio.load_music_stream("resources/audio/chiptun1.mod")
         //         .expect("could not load music");
         // }
     }
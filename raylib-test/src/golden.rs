@@ -0,0 +1,218 @@
+//! Golden image regression checks.
+//!
+//! A frame is captured, either from the screen or from a `RenderTexture2D`, and compared pixel by pixel against
+//! a reference png checked in under `resources/golden`. Channels may differ by up to `tolerance` to absorb driver
+//! rounding. When a frame doesn't match, the captured frame and an image highlighting the differing pixels are
+//! written to `test_out`.
+//!
+//! Run the tests with `BLESS=1` to write the captured frames as the new references instead of comparing.
+//! References are meant to be produced with the software renderer used in CI:
+//! `LIBGL_ALWAYS_SOFTWARE=1 xvfb-run -a cargo test -p raylib-test`.
+//!
+//! The exception is `golden_rectangles.png`. It only holds axis-aligned solid rectangles, so it was written directly
+//! from the expected pixels rather than captured. Bless it again on the software renderer before adding references
+//! that depend on rasterization details such as antialiasing or texture filtering.
+use raylib::prelude::*;
+use std::path::PathBuf;
+
+pub const GOLDEN_DIR: &str = "resources/golden";
+pub const OUT_DIR: &str = "test_out";
+
+/// Asserts that a captured frame matches `resources/golden/<name>.png`.
+///
+/// `assert_frame_matches!(name, tolerance)` reads the screen, `assert_frame_matches!(name, tolerance, &target)`
+/// reads a render texture.
+macro_rules! assert_frame_matches {
+    ($name:expr, $tolerance:expr) => {
+        $crate::golden::assert_image_matches($name, $tolerance, &$crate::golden::capture_screen())
+    };
+    ($name:expr, $tolerance:expr, $target:expr) => {
+        $crate::golden::assert_image_matches(
+            $name,
+            $tolerance,
+            &$crate::golden::capture_render_texture($target),
+        )
+    };
+}
+
+/// Reads back the current framebuffer, like `RaylibHandle::get_screen_data`.
+///
+/// Pending draws are only flushed at the end of a frame, so call this after the drawing handle was dropped.
+pub fn capture_screen() -> Image {
+    unsafe { Image::from_raw(raylib::ffi::GetScreenData()) }
+}
+
+/// Reads back the color attachment of a render texture, top row first.
+pub fn capture_render_texture(target: &RenderTexture2D) -> Image {
+    let mut image = target
+        .get_texture_data()
+        .expect("couldn't read render texture");
+    // render textures are stored bottom up
+    image.flip_vertical();
+    image
+}
+
+/// Result of comparing two frames of the same size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameDiff {
+    /// Number of pixels with a channel off by more than the tolerance.
+    pub mismatched: usize,
+    /// Largest channel difference found.
+    pub max_delta: u8,
+    /// One entry per pixel, true where it mismatched.
+    pub mask: Vec<bool>,
+}
+
+/// Compares two pixel buffers channel by channel.
+pub fn diff_pixels(expected: &[Color], actual: &[Color], tolerance: u8) -> FrameDiff {
+    assert_eq!(expected.len(), actual.len(), "pixel buffers differ in size");
+    let mut diff = FrameDiff {
+        mismatched: 0,
+        max_delta: 0,
+        mask: Vec::with_capacity(actual.len()),
+    };
+    for (e, a) in expected.iter().zip(actual) {
+        let delta = [
+            channel_delta(e.r, a.r),
+            channel_delta(e.g, a.g),
+            channel_delta(e.b, a.b),
+            channel_delta(e.a, a.a),
+        ]
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(0);
+        diff.max_delta = diff.max_delta.max(delta);
+        let bad = delta > tolerance;
+        if bad {
+            diff.mismatched += 1;
+        }
+        diff.mask.push(bad);
+    }
+    diff
+}
+
+fn channel_delta(a: u8, b: u8) -> u8 {
+    (a as i16 - b as i16).abs() as u8
+}
+
+/// Builds an image showing `actual` faded to gray, with mismatched pixels in solid magenta.
+pub fn highlight_diff(actual: &[Color], mask: &[bool]) -> Vec<Color> {
+    actual
+        .iter()
+        .zip(mask)
+        .map(|(c, &bad)| {
+            if bad {
+                Color::MAGENTA
+            } else {
+                let luma = (c.r as u32 * 299 + c.g as u32 * 587 + c.b as u32 * 114) / 1000;
+                let v = (128 + luma / 2) as u8;
+                Color::new(v, v, v, 255)
+            }
+        })
+        .collect()
+}
+
+fn blessing() -> bool {
+    std::env::var("BLESS").map(|v| v != "0").unwrap_or(false)
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(GOLDEN_DIR).join(format!("{}.png", name))
+}
+
+fn out_path(name: &str) -> PathBuf {
+    PathBuf::from(OUT_DIR).join(format!("{}.png", name))
+}
+
+/// Compares `actual` with the reference named `name`, panicking with a description of the mismatch.
+///
+/// With `BLESS` set, the reference is overwritten instead.
+pub fn assert_image_matches(name: &str, tolerance: u8, actual: &Image) {
+    let golden = golden_path(name);
+    if blessing() {
+        std::fs::create_dir_all(GOLDEN_DIR).expect("couldn't create golden directory");
+        actual.export_image(&golden.to_string_lossy());
+        return;
+    }
+
+    std::fs::create_dir_all(OUT_DIR).expect("couldn't create output directory");
+    let actual_path = out_path(name);
+    actual.export_image(&actual_path.to_string_lossy());
+
+    let expected = match Image::load_image(&golden.to_string_lossy()) {
+        Ok(image) => image,
+        Err(_) => panic!(
+            "no reference image {}, run with BLESS=1 to create it from {}",
+            golden.display(),
+            actual_path.display()
+        ),
+    };
+    if (expected.width(), expected.height()) != (actual.width(), actual.height()) {
+        panic!(
+            "frame {} is {}x{}, reference {} is {}x{}",
+            name,
+            actual.width(),
+            actual.height(),
+            golden.display(),
+            expected.width(),
+            expected.height()
+        );
+    }
+
+    let actual_pixels = actual.get_image_data();
+    let diff = diff_pixels(&expected.get_image_data(), &actual_pixels, tolerance);
+    if diff.mismatched > 0 {
+        let diff_path = out_path(&format!("{}_diff", name));
+        let highlighted = highlight_diff(&actual_pixels, &diff.mask);
+        Image::load_image_ex(&highlighted, actual.width(), actual.height())
+            .expect("couldn't build diff image")
+            .export_image(&diff_path.to_string_lossy());
+        panic!(
+            "frame {} differs from {}: {} pixels off by up to {} (tolerance {}), see {}",
+            name,
+            golden.display(),
+            diff.mismatched,
+            diff.max_delta,
+            tolerance,
+            diff_path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod golden_test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_diff_pixels() {
+        let expected = [Color::WHITE, Color::RED, Color::BLUE];
+        let actual = [
+            Color::new(254, 255, 255, 255),
+            Color::RED,
+            Color::new(0, 121, 200, 255),
+        ];
+        let diff = diff_pixels(&expected, &actual, 1);
+        assert_eq!(diff.mismatched, 1);
+        assert_eq!(diff.max_delta, 41);
+        assert_eq!(diff.mask, vec![false, false, true]);
+        assert_eq!(diff_pixels(&expected, &actual, 41).mismatched, 0);
+    }
+
+    ray_test!(test_golden_render_texture);
+    fn test_golden_render_texture(thread: &RaylibThread) {
+        let mut handle = TEST_HANDLE.write().unwrap();
+        let mut rl = handle.as_mut().unwrap();
+        let mut target = rl
+            .load_render_texture(thread, 64, 48)
+            .expect("render texture created");
+        {
+            let mut d = rl.begin_texture_mode(thread, &mut target);
+            d.clear_background(Color::WHITE);
+            d.draw_rectangle(8, 8, 24, 16, Color::RED);
+            d.draw_rectangle(40, 24, 16, 16, Color::BLUE);
+        }
+        assert_frame_matches!("golden_rectangles", 2, &target);
+    }
+}
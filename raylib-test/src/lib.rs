@@ -31,6 +31,9 @@ extern crate test;
 #[cfg(test)]
 #[macro_use]
 mod tests;
+#[cfg(test)]
+#[macro_use]
+mod golden;
 
 mod audio;
 mod drawing;
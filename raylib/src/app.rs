@@ -0,0 +1,251 @@
+/* raylib-rs
+   app.rs - Fixed timestep application runner
+
+Copyright (c) 2018-2019 Paul Clement (@deltaphc)
+
+This software is provided "as-is", without any express or implied warranty. In no event will the authors be held liable for any damages arising from the use of this software.
+
+Permission is granted to anyone to use this software for any purpose, including commercial applications, and to alter it and redistribute it freely, subject to the following restrictions:
+
+  1. The origin of this software must not be misrepresented; you must not claim that you wrote the original software. If you use this software in a product, an acknowledgment in the product documentation would be appreciated but is not required.
+
+  2. Altered source versions must be plainly marked as such, and must not be misrepresented as being the original software.
+
+  3. This notice may not be removed or altered from any source distribution.
+*/
+
+//! Fixed timestep application runner.
+//!
+//! Instead of writing `while !rl.window_should_close() { ... }` with a variable `get_frame_time()` update, implement
+//! [`App`] and hand it to [`run`]. The simulation then advances in steps of constant duration, decoupled from the
+//! frame rate, and drawing receives an interpolation factor to smooth out the remainder.
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! use raylib::app::{self, App};
+//!
+//! struct Ball {
+//!     previous: Vector2,
+//!     position: Vector2,
+//!     velocity: Vector2,
+//! }
+//!
+//! impl App for Ball {
+//!     fn update(&mut self, _rl: &mut RaylibHandle, dt: f32) {
+//!         self.previous = self.position;
+//!         self.position += self.velocity * dt;
+//!     }
+//!
+//!     fn draw(&mut self, d: &mut RaylibDrawHandle, alpha: f32) {
+//!         d.clear_background(Color::WHITE);
+//!         let position = self.previous + (self.position - self.previous) * alpha;
+//!         d.draw_circle_v(position, 10.0, Color::RED);
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let (rl, thread) = raylib::init().size(640, 480).title("Ball").build();
+//!     let ball = Ball {
+//!         previous: Vector2::zero(),
+//!         position: Vector2::zero(),
+//!         velocity: Vector2::new(60.0, 40.0),
+//!     };
+//!     app::run(rl, thread, ball);
+//! }
+//! ```
+//!
+//! [`App`]: trait.App.html
+//! [`run`]: fn.run.html
+
+use crate::core::drawing::RaylibDrawHandle;
+use crate::core::{RaylibHandle, RaylibThread};
+
+/// An application driven by [`run`].
+///
+/// [`run`]: fn.run.html
+pub trait App {
+    /// Advances the simulation by exactly `dt` seconds, the fixed timestep.
+    fn update(&mut self, rl: &mut RaylibHandle, dt: f32);
+
+    /// Draws a frame. `alpha` in `[0, 1)` tells how far the current time is between the last two updates, for
+    /// interpolating positions.
+    fn draw(&mut self, d: &mut RaylibDrawHandle, alpha: f32);
+
+    /// Called when the window has been resized.
+    fn on_resize(&mut self, _rl: &mut RaylibHandle, _width: i32, _height: i32) {}
+
+    /// Called once after the window was asked to close, before the handle is dropped.
+    fn on_exit(&mut self, _rl: &mut RaylibHandle) {}
+
+    /// Checked every frame to let the application stop the loop itself.
+    fn should_exit(&self) -> bool {
+        false
+    }
+
+    /// The timestep used by `update`. Defaults to 60 updates per second.
+    fn timestep(&self) -> FixedTimestep {
+        FixedTimestep::default()
+    }
+
+    /// Whether the simulation stops while the window is minimized. Frames are still drawn so events get processed.
+    fn pause_when_minimized(&self) -> bool {
+        true
+    }
+}
+
+/// Accumulator for running a simulation at a fixed rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedTimestep {
+    step: f32,
+    max_steps: u32,
+    accumulator: f32,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        FixedTimestep::new(1.0 / 60.0)
+    }
+}
+
+impl FixedTimestep {
+    /// Creates a timestep of `step` seconds, allowing up to 8 updates per frame.
+    pub fn new(step: f32) -> FixedTimestep {
+        assert!(step > 0.0, "timestep must be positive");
+        FixedTimestep {
+            step,
+            max_steps: 8,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Creates a timestep running `rate` updates per second.
+    pub fn from_rate(rate: u32) -> FixedTimestep {
+        FixedTimestep::new(1.0 / rate as f32)
+    }
+
+    /// Sets how many updates a single frame may run. Time beyond that is dropped, so a slow frame cannot make the
+    /// next one slower still.
+    pub fn with_max_steps(mut self, max_steps: u32) -> FixedTimestep {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    /// Duration of one update, in seconds.
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Maximum number of updates per frame.
+    pub fn max_steps(&self) -> u32 {
+        self.max_steps
+    }
+
+    /// Accumulates `frame_time` seconds and returns how many updates should run.
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        if frame_time.is_finite() && frame_time > 0.0 {
+            self.accumulator += frame_time;
+        }
+        let steps = (self.accumulator / self.step) as u32;
+        if steps > self.max_steps {
+            self.accumulator %= self.step;
+            self.max_steps
+        } else {
+            self.accumulator -= steps as f32 * self.step;
+            steps
+        }
+    }
+
+    /// Fraction of a step left in the accumulator, in `[0, 1)`.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0)
+    }
+
+    /// Drops any accumulated time.
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
+
+/// State kept between frames by [`run`].
+///
+/// Driving the loop one `frame` at a time is what a platform main loop callback (such as emscripten's) needs, as it
+/// cannot block in a `while` loop.
+///
+/// [`run`]: fn.run.html
+pub struct AppRunner<A: App> {
+    app: A,
+    timestep: FixedTimestep,
+    screen_size: (i32, i32),
+}
+
+impl<A: App> AppRunner<A> {
+    pub fn new(rl: &RaylibHandle, app: A) -> AppRunner<A> {
+        AppRunner {
+            timestep: app.timestep(),
+            screen_size: (rl.get_screen_width(), rl.get_screen_height()),
+            app,
+        }
+    }
+
+    /// Runs the updates due since the last frame, then draws.
+    pub fn frame(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let size = (rl.get_screen_width(), rl.get_screen_height());
+        if rl.is_window_resized() || size != self.screen_size {
+            self.screen_size = size;
+            self.app.on_resize(rl, size.0, size.1);
+        }
+
+        if self.app.pause_when_minimized() && rl.is_window_minimized() {
+            self.timestep.reset();
+        } else {
+            let steps = self.timestep.advance(rl.get_frame_time());
+            for _ in 0..steps {
+                self.app.update(rl, self.timestep.step());
+            }
+        }
+
+        let mut d = rl.begin_drawing(thread);
+        self.app.draw(&mut d, self.timestep.alpha());
+    }
+
+    /// Notifies the application that it is exiting and gives it back.
+    pub fn exit(mut self, rl: &mut RaylibHandle) -> A {
+        self.app.on_exit(rl);
+        self.app
+    }
+
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut A {
+        &mut self.app
+    }
+}
+
+/// Runs `app` until the window is closed or `App::should_exit` returns true.
+pub fn run<A: App>(mut rl: RaylibHandle, thread: RaylibThread, app: A) {
+    let mut runner = AppRunner::new(&rl, app);
+    while !rl.window_should_close() && !runner.app().should_exit() {
+        runner.frame(&mut rl, &thread);
+    }
+    runner.exit(&mut rl);
+}
+
+#[cfg(test)]
+mod app_test {
+    use super::FixedTimestep;
+
+    #[test]
+    fn test_fixed_timestep() {
+        let mut t = FixedTimestep::new(0.25).with_max_steps(4);
+        assert_eq!(t.advance(0.1), 0);
+        assert_eq!(t.advance(0.2), 1);
+        assert!((t.alpha() - 0.2).abs() < 1e-5, "bad interpolation factor");
+        assert_eq!(t.advance(0.5), 2);
+        // a long hitch only runs `max_steps` updates and drops the backlog
+        assert_eq!(t.advance(10.0), 4);
+        assert!(t.alpha() < 1.0);
+        assert_eq!(t.advance(0.0), 0);
+    }
+}
@@ -0,0 +1,720 @@
+//! Frequency spectra, level meters and beat detection
+//!
+//! [`Spectrum::analyze`] runs a windowed FFT over samples, and [`Levels`] measures their peak and RMS. Both work on
+//! [`Wave`] data, or on what is playing right now through a [`Tap`], which keeps the latest samples that went
+//! through an `AudioStream` or a [`Source`]:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run(rl: &mut RaylibHandle, thread: &RaylibThread, audio: &RaylibAudio, song: MusicTrack) -> Result<(), String> {
+//! let tap = Tap::new(4096, song.channels(), song.sample_rate());
+//! let source = tap.wrap(song.source());
+//! let mut stream = ProceduralStream::new(audio, 44100, 16, 2, 1024, source)?;
+//! stream.play();
+//! while !rl.window_should_close() {
+//!     stream.pump();
+//!     let spectrum = tap.spectrum(2048, Window::Hann);
+//!     let mut d = rl.begin_drawing(thread);
+//!     d.clear_background(Color::BLACK);
+//!     spectrum.draw_bars(&mut d, Rectangle::new(0.0, 0.0, 800.0, 450.0), 32, -60.0, Color::SKYBLUE);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`OnsetDetector`] finds where notes and drum hits start, and [`estimate_tempo`] the beats per minute of a wave.
+//!
+//! [`Spectrum::analyze`]: struct.Spectrum.html#method.analyze
+//! [`Levels`]: struct.Levels.html
+//! [`Wave`]: ../audio/struct.Wave.html
+//! [`Tap`]: struct.Tap.html
+//! [`Source`]: ../procedural/trait.Source.html
+//! [`OnsetDetector`]: struct.OnsetDetector.html
+//! [`estimate_tempo`]: fn.estimate_tempo.html
+use crate::core::audio::{AudioSample, AudioStream, Wave};
+use crate::core::color::Color;
+use crate::core::drawing::RaylibDraw;
+use crate::core::math::Rectangle;
+use crate::core::procedural::Source;
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::sync::{Arc, Mutex};
+
+/// In place radix 2 FFT. The lengths must be equal powers of two.
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let tre = re[b] * cos - im[b] * sin;
+                let tim = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - tre;
+                im[b] = im[a] - tim;
+                re[a] += tre;
+                im[a] += tim;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Interleaved samples mixed down to mono.
+fn to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    samples
+        .chunks(channels.max(1))
+        .map(|f| f.iter().sum::<f32>() / f.len() as f32)
+        .collect()
+}
+
+/// Window applied before the FFT, trading frequency resolution for less leakage between bins.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl Window {
+    fn weight(self, i: usize, n: usize) -> f32 {
+        let x = 2.0 * PI * i as f32 / (n - 1).max(1) as f32;
+        match self {
+            Window::Rectangular => 1.0,
+            Window::Hann => 0.5 - 0.5 * x.cos(),
+            Window::Hamming => 0.54 - 0.46 * x.cos(),
+            Window::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+        }
+    }
+}
+
+/// Magnitudes of the frequencies in a block of samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum {
+    /// One per bin from 0 Hz up to half the sample rate, scaled so a full scale sine peaks at `1.0`.
+    pub magnitudes: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+impl Spectrum {
+    /// Analyzes the last `size` frames of interleaved `samples`, mixed to mono. `size` is rounded up to a power of
+    /// two, and missing frames count as silence.
+    pub fn analyze(
+        samples: &[f32],
+        channels: u32,
+        sample_rate: u32,
+        size: usize,
+        window: Window,
+    ) -> Spectrum {
+        let size = size.max(2).next_power_of_two();
+        let mono = to_mono(samples, channels as usize);
+        let mut re = vec![0.0; size];
+        let start = size.saturating_sub(mono.len());
+        re[start..].copy_from_slice(&mono[mono.len().saturating_sub(size)..]);
+        let mut gain = 0.0;
+        for (i, s) in re.iter_mut().enumerate() {
+            let w = window.weight(i, size);
+            *s *= w;
+            gain += w;
+        }
+        let mut im = vec![0.0; size];
+        fft(&mut re, &mut im);
+        let scale = 2.0 / gain;
+        Spectrum {
+            magnitudes: re[..size / 2]
+                .iter()
+                .zip(&im)
+                .map(|(r, i)| (r * r + i * i).sqrt() * scale)
+                .collect(),
+            sample_rate,
+        }
+    }
+
+    /// Analyzes the `size` frames of `wave` starting `time` seconds in.
+    pub fn of_wave(wave: &Wave, time: f32, size: usize, window: Window) -> Spectrum {
+        let channels = wave.channels().max(1) as usize;
+        let samples = wave.samples();
+        let size = size.max(2).next_power_of_two();
+        let start =
+            ((time.max(0.0) * wave.smaple_rate() as f32) as usize * channels).min(samples.len());
+        let end = (start + size * channels).min(samples.len());
+        Spectrum::analyze(
+            &samples[start..end],
+            channels as u32,
+            wave.smaple_rate(),
+            size,
+            window,
+        )
+    }
+
+    /// Hz per bin.
+    pub fn bin_width(&self) -> f32 {
+        self.sample_rate as f32 / (self.magnitudes.len() * 2) as f32
+    }
+
+    /// Center frequency of `bin` in Hz.
+    pub fn frequency(&self, bin: usize) -> f32 {
+        bin as f32 * self.bin_width()
+    }
+
+    /// The bin holding `frequency`.
+    pub fn bin(&self, frequency: f32) -> usize {
+        ((frequency / self.bin_width()).round().max(0.0) as usize).min(self.magnitudes.len() - 1)
+    }
+
+    /// Frequency of the loudest bin, ignoring 0 Hz.
+    pub fn peak_frequency(&self) -> f32 {
+        let bin = (1..self.magnitudes.len())
+            .max_by(|&a, &b| self.magnitudes[a].partial_cmp(&self.magnitudes[b]).unwrap())
+            .unwrap_or(0);
+        self.frequency(bin)
+    }
+
+    /// Splits the spectrum into `count` bands evenly spaced in pitch from `min_frequency` to half the sample rate,
+    /// as a graphic equalizer shows them. Each band has its loudest magnitude.
+    pub fn bands(&self, count: usize, min_frequency: f32) -> Vec<f32> {
+        let nyquist = self.sample_rate as f32 / 2.0;
+        let low = min_frequency.clamp(self.bin_width().max(1.0), nyquist);
+        let ratio = (nyquist / low).powf(1.0 / count.max(1) as f32);
+        (0..count)
+            .map(|i| {
+                let from = self.bin(low * ratio.powi(i as i32));
+                let to = self.bin(low * ratio.powi(i as i32 + 1)).max(from);
+                self.magnitudes[from..=to]
+                    .iter()
+                    .cloned()
+                    .fold(0.0, f32::max)
+            })
+            .collect()
+    }
+
+    /// Rectangles of a bar graph of [`bands`](#method.bands) inside `bounds`, from 20 Hz up. Bars are as tall as
+    /// their level between `min_db` decibels and 0 dB, and stand on the bottom of `bounds`.
+    pub fn bar_rects(&self, bounds: Rectangle, bars: usize, min_db: f32) -> Vec<Rectangle> {
+        let width = bounds.width / bars.max(1) as f32;
+        self.bands(bars, 20.0)
+            .iter()
+            .enumerate()
+            .map(|(i, &magnitude)| {
+                let db = 20.0 * magnitude.max(1e-9).log10();
+                let level = if min_db < 0.0 {
+                    (1.0 - db / min_db).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let height = bounds.height * level;
+                Rectangle::new(
+                    bounds.x + i as f32 * width,
+                    bounds.y + bounds.height - height,
+                    (width - 1.0).max(1.0),
+                    height,
+                )
+            })
+            .collect()
+    }
+
+    /// Draws [`bar_rects`](#method.bar_rects) in `color`.
+    pub fn draw_bars<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        bounds: Rectangle,
+        bars: usize,
+        min_db: f32,
+        color: Color,
+    ) {
+        for rect in self.bar_rects(bounds, bars, min_db) {
+            d.draw_rectangle_rec(rect, color);
+        }
+    }
+}
+
+/// Converts a level to decibels, `-inf` for silence.
+pub fn to_db(level: f32) -> f32 {
+    20.0 * level.log10()
+}
+
+/// Peak and RMS level of a block of samples.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Levels {
+    /// Highest absolute sample.
+    pub peak: f32,
+    /// Root mean square, closer to how loud the block sounds.
+    pub rms: f32,
+}
+
+impl Levels {
+    pub fn of(samples: &[f32]) -> Levels {
+        if samples.is_empty() {
+            return Levels::default();
+        }
+        Levels {
+            peak: samples.iter().fold(0.0, |p, s| s.abs().max(p)),
+            rms: (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt(),
+        }
+    }
+}
+
+/// Smooths [`Levels`](struct.Levels.html) for display, as a VU meter with a peak indicator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelMeter {
+    /// Decibels per second the level falls at.
+    pub release: f32,
+    /// Seconds the peak indicator holds before falling.
+    pub peak_hold: f32,
+    level: f32,
+    peak: f32,
+    held: f32,
+}
+
+impl Default for LevelMeter {
+    fn default() -> LevelMeter {
+        LevelMeter {
+            release: 20.0,
+            peak_hold: 1.0,
+            level: 0.0,
+            peak: 0.0,
+            held: 0.0,
+        }
+    }
+}
+
+impl LevelMeter {
+    /// Takes in the levels measured over the last `dt` seconds. Rises are shown at once, falls at the release rate.
+    pub fn update(&mut self, levels: Levels, dt: f32) {
+        let fall = 10f32.powf(-self.release * dt / 20.0);
+        self.level = levels.rms.max(self.level * fall);
+        if levels.peak >= self.peak {
+            self.peak = levels.peak;
+            self.held = 0.0;
+        } else {
+            self.held += dt;
+            if self.held > self.peak_hold {
+                self.peak = levels.peak.max(self.peak * fall);
+            }
+        }
+    }
+
+    /// The smoothed RMS level.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// The held peak level.
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+}
+
+#[derive(Debug)]
+struct TapBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+/// Keeps the latest samples that went through a stream or source, for analysis while they play. Clones share the
+/// same samples, so one can stay with the audio and another with the visualizer.
+#[derive(Debug, Clone)]
+pub struct Tap {
+    buffer: Arc<Mutex<TapBuffer>>,
+    channels: u32,
+    sample_rate: u32,
+}
+
+impl Tap {
+    /// A tap keeping the last `frames` frames of `channels` channels at `sample_rate`.
+    pub fn new(frames: usize, channels: u32, sample_rate: u32) -> Tap {
+        let capacity = frames * channels.max(1) as usize;
+        Tap {
+            buffer: Arc::new(Mutex::new(TapBuffer {
+                samples: VecDeque::from(vec![0.0; capacity]),
+                capacity,
+            })),
+            channels: channels.max(1),
+            sample_rate,
+        }
+    }
+
+    pub fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Adds interleaved samples, dropping the oldest beyond the capacity.
+    pub fn record<T: AudioSample>(&self, samples: &[T]) {
+        let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        let skip = samples.len().saturating_sub(buffer.capacity);
+        buffer
+            .samples
+            .extend(samples[skip..].iter().map(|s| s.to_f32()));
+        let excess = buffer.samples.len().saturating_sub(buffer.capacity);
+        buffer.samples.drain(..excess);
+    }
+
+    /// Sends `data` to `stream`, as [`AudioStream::update`] does, and records it.
+    ///
+    /// [`AudioStream::update`]: ../audio/struct.AudioStream.html#method.update
+    pub fn update_stream<T: AudioSample>(
+        &self,
+        stream: &mut AudioStream<T>,
+        data: &[T],
+    ) -> Result<(), String> {
+        stream.update(data)?;
+        self.record(data);
+        Ok(())
+    }
+
+    /// Wraps `source` so everything it produces is recorded. The tap should have the source's channels and rate.
+    pub fn wrap<S: Source>(&self, source: S) -> TapSource<S> {
+        TapSource {
+            source,
+            tap: self.clone(),
+        }
+    }
+
+    /// The last `frames` frames, interleaved.
+    pub fn latest(&self, frames: usize) -> Vec<f32> {
+        let buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        let count = (frames * self.channels as usize).min(buffer.samples.len());
+        buffer
+            .samples
+            .iter()
+            .skip(buffer.samples.len() - count)
+            .cloned()
+            .collect()
+    }
+
+    /// Spectrum of the last `size` frames.
+    pub fn spectrum(&self, size: usize, window: Window) -> Spectrum {
+        let size = size.max(2).next_power_of_two();
+        Spectrum::analyze(
+            &self.latest(size),
+            self.channels,
+            self.sample_rate,
+            size,
+            window,
+        )
+    }
+
+    /// Levels of the last `frames` frames.
+    pub fn levels(&self, frames: usize) -> Levels {
+        Levels::of(&self.latest(frames))
+    }
+}
+
+/// A [`Source`](../procedural/trait.Source.html) recording into a [`Tap`](struct.Tap.html).
+pub struct TapSource<S> {
+    source: S,
+    tap: Tap,
+}
+
+impl<S> TapSource<S> {
+    pub fn inner(&self) -> &S {
+        &self.source
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+}
+
+impl<S: Source> Source for TapSource<S> {
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn channels(&self) -> u32 {
+        self.source.channels()
+    }
+
+    fn fill(&mut self, out: &mut [f32]) {
+        self.source.fill(out);
+        self.tap.record(out);
+    }
+}
+
+/// Finds onsets, the starts of notes and hits, from rises in the spectrum between blocks of samples (spectral
+/// flux). Takes mono samples a block at a time, so it works on streams too.
+#[derive(Debug, Clone)]
+pub struct OnsetDetector {
+    sample_rate: u32,
+    size: usize,
+    hop: usize,
+    /// How far above the recent average the flux must rise, `1.5` by default.
+    pub sensitivity: f32,
+    /// Seconds before another onset can be reported.
+    pub min_interval: f32,
+    pending: VecDeque<f32>,
+    previous: Vec<f32>,
+    /// Recent flux values, for the threshold.
+    history: VecDeque<f32>,
+    /// Flux of the last two blocks, for picking peaks.
+    last: [f32; 2],
+    blocks: u64,
+    last_onset: Option<u64>,
+    /// Flux of every block so far, when kept for tempo estimation.
+    envelope: Option<Vec<f32>>,
+}
+
+/// Blocks of flux history the threshold averages.
+const ONSET_HISTORY: usize = 16;
+
+impl OnsetDetector {
+    /// A detector at `sample_rate`, analyzing 1024 samples every 512.
+    pub fn new(sample_rate: u32) -> OnsetDetector {
+        OnsetDetector {
+            sample_rate,
+            size: 1024,
+            hop: 512,
+            sensitivity: 1.5,
+            min_interval: 0.05,
+            pending: VecDeque::new(),
+            previous: Vec::new(),
+            history: VecDeque::new(),
+            last: [0.0; 2],
+            blocks: 0,
+            last_onset: None,
+            envelope: None,
+        }
+    }
+
+    /// Seconds between blocks.
+    fn hop_time(&self) -> f32 {
+        self.hop as f32 / self.sample_rate.max(1) as f32
+    }
+
+    /// Takes in mono `samples`, returning the times of the onsets found, in seconds since the first sample.
+    pub fn push(&mut self, samples: &[f32]) -> Vec<f32> {
+        self.pending.extend(samples);
+        let mut onsets = Vec::new();
+        while self.pending.len() >= self.size {
+            let block: Vec<f32> = self.pending.iter().take(self.size).cloned().collect();
+            self.pending.drain(..self.hop);
+            let spectrum = Spectrum::analyze(&block, 1, self.sample_rate, self.size, Window::Hann);
+            let flux: f32 = if self.previous.is_empty() {
+                0.0
+            } else {
+                spectrum
+                    .magnitudes
+                    .iter()
+                    .zip(&self.previous)
+                    .map(|(m, p)| (m - p).max(0.0))
+                    .sum()
+            };
+            self.previous = spectrum.magnitudes;
+            if let Some(envelope) = self.envelope.as_mut() {
+                envelope.push(flux);
+            }
+
+            // the previous block is an onset if it peaks above the threshold
+            let candidate = self.last[1];
+            let mean = self.history.iter().sum::<f32>() / self.history.len().max(1) as f32;
+            let threshold = mean * self.sensitivity + 0.01;
+            let gap = (self.min_interval / self.hop_time()) as u64;
+            if self.blocks > 0
+                && candidate > threshold
+                && candidate >= self.last[0]
+                && candidate > flux
+                && !matches!(self.last_onset, Some(l) if self.blocks - 1 < l + gap)
+            {
+                let block = self.blocks - 1;
+                self.last_onset = Some(block);
+                onsets.push(
+                    (block as f32 * self.hop as f32 + self.size as f32 / 2.0)
+                        / self.sample_rate as f32,
+                );
+            }
+            self.history.push_back(candidate);
+            if self.history.len() > ONSET_HISTORY {
+                self.history.pop_front();
+            }
+            self.last = [candidate, flux];
+            self.blocks += 1;
+        }
+        onsets
+    }
+}
+
+/// Times in seconds of the onsets in `wave`, see [`OnsetDetector`](struct.OnsetDetector.html).
+pub fn detect_onsets(wave: &Wave, sensitivity: f32) -> Vec<f32> {
+    let mut detector = OnsetDetector::new(wave.smaple_rate());
+    detector.sensitivity = sensitivity;
+    let mut onsets = detector.push(&to_mono(&wave.samples(), wave.channels() as usize));
+    onsets.extend(detector.push(&vec![0.0; detector.size]));
+    onsets
+}
+
+/// Beats per minute of mono `samples`, between 70 and 180, from how regularly onsets recur. `None` for audio
+/// without a steady pulse.
+pub fn estimate_tempo_samples(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let mut detector = OnsetDetector::new(sample_rate);
+    detector.envelope = Some(Vec::new());
+    detector.push(samples);
+    let envelope = detector.envelope.take().unwrap_or_default();
+    let hops_per_minute = 60.0 / detector.hop_time();
+    let min_lag = ((hops_per_minute / 180.0).floor() as usize).max(2);
+    let max_lag = (hops_per_minute / 70.0).ceil() as usize;
+    if envelope.len() < max_lag * 2 {
+        return None;
+    }
+    let mean = envelope.iter().sum::<f32>() / envelope.len() as f32;
+    let centered: Vec<f32> = envelope.iter().map(|e| e - mean).collect();
+    let correlation = |lag: usize| -> f32 {
+        let terms = centered.len() - lag;
+        (0..terms)
+            .map(|i| centered[i] * centered[i + lag])
+            .sum::<f32>()
+            / terms as f32
+    };
+    let scores: Vec<f32> = (min_lag - 1..=max_lag + 1).map(correlation).collect();
+    let best = (1..scores.len() - 1).max_by(|&a, &b| scores[a].partial_cmp(&scores[b]).unwrap())?;
+    if scores[best] <= 0.0 {
+        return None;
+    }
+    // a parabola through the best lag and its neighbours finds the peak between whole lags
+    let (a, b, c) = (scores[best - 1], scores[best], scores[best + 1]);
+    let denominator = a - 2.0 * b + c;
+    let offset = if denominator.abs() > 1e-12 {
+        (0.5 * (a - c) / denominator).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+    let lag = (min_lag - 1 + best) as f32 + offset;
+    Some(hops_per_minute / lag)
+}
+
+/// Beats per minute of `wave`, see [`estimate_tempo_samples`](fn.estimate_tempo_samples.html).
+pub fn estimate_tempo(wave: &Wave) -> Option<f32> {
+    estimate_tempo_samples(
+        &to_mono(&wave.samples(), wave.channels() as usize),
+        wave.smaple_rate(),
+    )
+}
+
+#[cfg(test)]
+mod analysis_test {
+    use super::*;
+
+    fn sine(frequency: f32, rate: u32, frames: usize, amplitude: f32) -> Vec<f32> {
+        (0..frames)
+            .map(|i| (i as f32 / rate as f32 * frequency * PI * 2.0).sin() * amplitude)
+            .collect()
+    }
+
+    /// Short decaying noise bursts at `times`, over silence.
+    fn clicks(times: &[f32], rate: u32, seconds: f32) -> Vec<f32> {
+        let mut samples = vec![0.0; (seconds * rate as f32) as usize];
+        let mut seed = 12345u32;
+        for &t in times {
+            let start = (t * rate as f32) as usize;
+            for i in 0..400 {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                let noise = seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+                if let Some(s) = samples.get_mut(start + i) {
+                    *s = noise * (1.0 - i as f32 / 400.0);
+                }
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_spectrum() {
+        // 1000 Hz falls exactly on bin 64 of a 1024 point FFT at 16000 Hz
+        let tone = sine(1000.0, 16000, 1024, 0.5);
+        let spectrum = Spectrum::analyze(&tone, 1, 16000, 1000, Window::Hann);
+        assert_eq!(spectrum.magnitudes.len(), 512);
+        assert_eq!(spectrum.bin_width(), 15.625);
+        assert_eq!(spectrum.peak_frequency(), 1000.0);
+        assert!((spectrum.magnitudes[64] - 0.5).abs() < 0.01);
+        assert!(spectrum.magnitudes[100] < 1e-3);
+
+        let bands = spectrum.bands(8, 20.0);
+        let loudest = (0..8)
+            .max_by(|&a, &b| bands[a].partial_cmp(&bands[b]).unwrap())
+            .unwrap();
+        // bands edges go up by (8000 / 20) ^ (1 / 8), about 2.1, so 1000 Hz lands in the sixth
+        assert_eq!(loudest, 5);
+
+        let bounds = Rectangle::new(0.0, 0.0, 80.0, 100.0);
+        let bars = spectrum.bar_rects(bounds, 8, -60.0);
+        assert_eq!(bars.len(), 8);
+        // -6 dB of a 60 dB range
+        assert!((bars[5].height - 90.0).abs() < 1.0);
+        assert!((bars[5].y + bars[5].height - 100.0).abs() < 1e-4);
+        assert_eq!(bars[5].x, 50.0);
+    }
+
+    #[test]
+    fn test_levels_and_tap() {
+        let levels = Levels::of(&[0.5, -0.5, 0.5, -1.0]);
+        assert_eq!(levels.peak, 1.0);
+        assert!((levels.rms - 0.661_437_8).abs() < 1e-5);
+        assert!((to_db(0.5) + 6.0206).abs() < 1e-3);
+
+        let mut meter = LevelMeter::default();
+        meter.update(
+            Levels {
+                peak: 1.0,
+                rms: 1.0,
+            },
+            0.1,
+        );
+        meter.update(Levels::default(), 0.5);
+        assert!((to_db(meter.level()) + 10.0).abs() < 1e-3);
+        assert_eq!(meter.peak(), 1.0);
+
+        let tap = Tap::new(3, 2, 100);
+        let mut source = tap.wrap(crate::core::procedural::source_fn(100, 2, |out| {
+            out.iter_mut().enumerate().for_each(|(i, s)| *s = i as f32)
+        }));
+        let mut out = vec![0.0; 4];
+        source.fill(&mut out);
+        assert_eq!(tap.latest(3), vec![0.0, 0.0, 0.0, 1.0, 2.0, 3.0]);
+        tap.record(&[16384i16, -16384]);
+        let latest = tap.latest(1);
+        assert!((latest[0] - 0.5).abs() < 1e-4 && (latest[1] + 0.5).abs() < 1e-4);
+        assert_eq!(tap.latest(10).len(), 6);
+    }
+
+    #[test]
+    fn test_onsets_and_tempo() {
+        let times = [0.5, 1.2, 1.5, 2.6];
+        let samples = clicks(&times, 22050, 3.0);
+        let mut detector = OnsetDetector::new(22050);
+        let mut found = Vec::new();
+        // fed in odd sized pieces, as a stream would
+        for chunk in samples.chunks(700) {
+            found.extend(detector.push(chunk));
+        }
+        assert_eq!(found.len(), times.len(), "{:?}", found);
+        for (f, t) in found.iter().zip(&times) {
+            assert!((f - t).abs() < 0.05, "{:?}", found);
+        }
+
+        let beats: Vec<f32> = (0..20).map(|i| 0.25 + i as f32 * 0.5).collect();
+        let tempo = estimate_tempo_samples(&clicks(&beats, 22050, 10.5), 22050).unwrap();
+        assert!((tempo - 120.0).abs() < 2.0, "{}", tempo);
+        assert_eq!(estimate_tempo_samples(&vec![0.0; 22050 * 4], 22050), None);
+    }
+}
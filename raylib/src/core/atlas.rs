@@ -0,0 +1,728 @@
+//! Packing many images into a single texture atlas
+//!
+//! A [`TextureAtlasBuilder`] collects named images and packs them into one image, so a whole set of sprites can be
+//! drawn from a single texture. The resulting [`TextureAtlas`] keeps a map from each name to the [`Rectangle`] it
+//! occupies, ready to pass as the source rectangle of `draw_texture_rec` or `draw_texture_pro`.
+//!
+//! Packing only touches pixels in memory, so it also works in a build script. [`TextureAtlas::export`] writes the
+//! image and a JSON description, which [`AtlasMap::load`] reads back at runtime:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn main() -> Result<(), String> {
+//! let atlas = TextureAtlasBuilder::new()
+//!     .padding(2)
+//!     .extrude(1)
+//!     .add_file("hero", "assets/hero.png")?
+//!     .add_file("coin", "assets/coin.png")?
+//!     .build()?;
+//! atlas.export("atlas.png", "atlas.json")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`TextureAtlasBuilder`]: struct.TextureAtlasBuilder.html
+//! [`TextureAtlas`]: struct.TextureAtlas.html
+//! [`TextureAtlas::export`]: struct.TextureAtlas.html#method.export
+//! [`AtlasMap::load`]: struct.AtlasMap.html#method.load
+//! [`Rectangle`]: ../math/struct.Rectangle.html
+use crate::core::color::Color;
+use crate::core::json::JsonValue;
+use crate::core::math::Rectangle;
+use crate::core::texture::{Image, Texture2D};
+use crate::core::{RaylibHandle, RaylibThread};
+use std::collections::BTreeMap;
+
+/// How free space is tracked while packing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PackingAlgorithm {
+    /// Keeps every maximal free rectangle and places each image where it leaves the least space on its shorter
+    /// side. Packs tightest.
+    #[default]
+    MaxRects,
+    /// Keeps only the top outline of the placed images and places each image as low as possible. Faster, but
+    /// leaves gaps under overhangs.
+    Skyline,
+}
+
+/// Where an image ended up in an atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion {
+    /// Area of the atlas holding the image, without padding or extrusion.
+    pub rec: Rectangle,
+    /// The image was turned 90 degrees clockwise to fit, so `rec` has its width and height swapped. Draw it with
+    /// `draw_texture_pro` rotated by -90 degrees to turn it back.
+    pub rotated: bool,
+}
+
+/// The name to region map of an atlas, without its pixels.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AtlasMap {
+    pub width: i32,
+    pub height: i32,
+    pub regions: BTreeMap<String, AtlasRegion>,
+}
+
+impl AtlasMap {
+    /// Source rectangle of the image added as `name`.
+    pub fn get(&self, name: &str) -> Option<Rectangle> {
+        self.regions.get(name).map(|region| region.rec)
+    }
+
+    /// Region of the image added as `name`, including whether it was rotated.
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.regions.get(name)
+    }
+
+    /// Describes the atlas as JSON:
+    ///
+    /// ```json
+    /// {
+    ///   "width": 256,
+    ///   "height": 128,
+    ///   "regions": {
+    ///     "hero": { "x": 2, "y": 2, "width": 16, "height": 24, "rotated": false }
+    ///   }
+    /// }
+    /// ```
+    pub fn to_json(&self) -> String {
+        let number = |n: f32| JsonValue::Number(n as f64);
+        let regions = self
+            .regions
+            .iter()
+            .map(|(name, region)| {
+                let rec = region.rec;
+                let value = JsonValue::Object(vec![
+                    ("x".to_string(), number(rec.x)),
+                    ("y".to_string(), number(rec.y)),
+                    ("width".to_string(), number(rec.width)),
+                    ("height".to_string(), number(rec.height)),
+                    ("rotated".to_string(), JsonValue::Bool(region.rotated)),
+                ]);
+                (name.clone(), value)
+            })
+            .collect();
+        JsonValue::Object(vec![
+            ("width".to_string(), number(self.width as f32)),
+            ("height".to_string(), number(self.height as f32)),
+            ("regions".to_string(), JsonValue::Object(regions)),
+        ])
+        .to_string_pretty()
+    }
+
+    /// Parses a description written by [`to_json`](#method.to_json).
+    pub fn from_json(json: &str) -> Result<AtlasMap, String> {
+        let value = JsonValue::parse(json)?;
+        let mut map = AtlasMap {
+            width: value.number("width")? as i32,
+            height: value.number("height")? as i32,
+            regions: BTreeMap::new(),
+        };
+        let regions = value
+            .get("regions")
+            .and_then(JsonValue::as_object)
+            .ok_or_else(|| "missing object \"regions\"".to_string())?;
+        for (name, region) in regions {
+            let rec = Rectangle::new(
+                region.number("x")? as f32,
+                region.number("y")? as f32,
+                region.number("width")? as f32,
+                region.number("height")? as f32,
+            );
+            let rotated = region
+                .get("rotated")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(false);
+            map.regions
+                .insert(name.clone(), AtlasRegion { rec, rotated });
+        }
+        Ok(map)
+    }
+
+    /// Reads a JSON description from a file.
+    pub fn load(filename: &str) -> Result<AtlasMap, String> {
+        let json = std::fs::read_to_string(filename)
+            .map_err(|e| format!("Cannot read atlas {}: {}", filename, e))?;
+        AtlasMap::from_json(&json).map_err(|e| format!("Cannot parse atlas {}: {}", filename, e))
+    }
+
+    /// Writes the JSON description to a file.
+    pub fn save(&self, filename: &str) -> Result<(), String> {
+        std::fs::write(filename, self.to_json())
+            .map_err(|e| format!("Cannot write atlas {}: {}", filename, e))
+    }
+}
+
+/// A packed atlas image and where each image went.
+#[derive(Debug)]
+pub struct TextureAtlas {
+    pub image: Image,
+    pub map: AtlasMap,
+}
+
+impl TextureAtlas {
+    /// Source rectangle of the image added as `name`.
+    pub fn get(&self, name: &str) -> Option<Rectangle> {
+        self.map.get(name)
+    }
+
+    /// Uploads the atlas image to the GPU.
+    pub fn load_texture(
+        &self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) -> Result<Texture2D, String> {
+        rl.load_texture_from_image(thread, &self.image)
+    }
+
+    /// Writes the atlas image and its JSON description, for example from a build script.
+    pub fn export(&self, image_filename: &str, json_filename: &str) -> Result<(), String> {
+        self.image.export_image(image_filename);
+        self.map.save(json_filename)
+    }
+}
+
+/// Collects named images and packs them into a [`TextureAtlas`](struct.TextureAtlas.html).
+#[derive(Debug)]
+pub struct TextureAtlasBuilder {
+    images: Vec<(String, Image)>,
+    padding: i32,
+    extrude: i32,
+    rotation: bool,
+    algorithm: PackingAlgorithm,
+    max_size: i32,
+}
+
+impl Default for TextureAtlasBuilder {
+    fn default() -> TextureAtlasBuilder {
+        TextureAtlasBuilder {
+            images: Vec::new(),
+            padding: 0,
+            extrude: 0,
+            rotation: false,
+            algorithm: PackingAlgorithm::default(),
+            max_size: 4096,
+        }
+    }
+}
+
+impl TextureAtlasBuilder {
+    pub fn new() -> TextureAtlasBuilder {
+        TextureAtlasBuilder::default()
+    }
+
+    /// Adds an image under `name`. Adding a name twice replaces the earlier image.
+    pub fn add(&mut self, name: &str, image: Image) -> &mut Self {
+        self.images.retain(|(n, _)| n != name);
+        self.images.push((name.to_string(), image));
+        self
+    }
+
+    /// Loads an image file and adds it under `name`.
+    pub fn add_file(&mut self, name: &str, filename: &str) -> Result<&mut Self, String> {
+        let image = Image::load_image(filename)?;
+        Ok(self.add(name, image))
+    }
+
+    /// Leaves `pixels` of empty space between images and around the edges of the atlas.
+    pub fn padding(&mut self, pixels: u32) -> &mut Self {
+        self.padding = pixels as i32;
+        self
+    }
+
+    /// Repeats the border pixels of every image outwards by `pixels`, so filtering at the edge of a region doesn't
+    /// sample its neighbours.
+    pub fn extrude(&mut self, pixels: u32) -> &mut Self {
+        self.extrude = pixels as i32;
+        self
+    }
+
+    /// Allows images to be turned 90 degrees when that packs them better. Check
+    /// [`AtlasRegion::rotated`](struct.AtlasRegion.html#structfield.rotated) when drawing.
+    pub fn rotation(&mut self, allow: bool) -> &mut Self {
+        self.rotation = allow;
+        self
+    }
+
+    pub fn algorithm(&mut self, algorithm: PackingAlgorithm) -> &mut Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Largest width and height the atlas may grow to, 4096 by default.
+    pub fn max_size(&mut self, pixels: u32) -> &mut Self {
+        self.max_size = pixels as i32;
+        self
+    }
+
+    /// Packs the images into the smallest power of two sized atlas that fits them. Images with no pixels are an
+    /// error.
+    pub fn build(&self) -> Result<TextureAtlas, String> {
+        if let Some((name, _)) = self
+            .images
+            .iter()
+            .find(|(_, image)| image.width() <= 0 || image.height() <= 0)
+        {
+            return Err(format!("image {:?} is empty", name));
+        }
+        let border = 2 * self.extrude + self.padding;
+        let mut order: Vec<usize> = (0..self.images.len()).collect();
+        // placing big images first wastes less space
+        order.sort_by_key(|&i| {
+            let image = &self.images[i].1;
+            std::cmp::Reverse((
+                image.width().max(image.height()),
+                image.width().min(image.height()),
+            ))
+        });
+        let sizes: Vec<(i32, i32)> = order
+            .iter()
+            .map(|&i| {
+                let image = &self.images[i].1;
+                (image.width() + border, image.height() + border)
+            })
+            .collect();
+
+        let (width, height, placements) = self.pack(&sizes)?;
+        let mut pixels = vec![Color::new(0, 0, 0, 0); (width * height) as usize];
+        let mut map = AtlasMap {
+            width,
+            height,
+            regions: BTreeMap::new(),
+        };
+        for (&i, placement) in order.iter().zip(placements) {
+            let (name, image) = &self.images[i];
+            let (mut w, mut h, mut colors) = (image.width(), image.height(), image.colors());
+            if placement.rotated {
+                colors = rotate_clockwise(w, h, &colors);
+                std::mem::swap(&mut w, &mut h);
+            }
+            let (x, y) = (
+                self.padding + placement.x + self.extrude,
+                self.padding + placement.y + self.extrude,
+            );
+            blit(&mut pixels, width, &colors, w, h, x, y, self.extrude);
+            map.regions.insert(
+                name.clone(),
+                AtlasRegion {
+                    rec: Rectangle::new(x as f32, y as f32, w as f32, h as f32),
+                    rotated: placement.rotated,
+                },
+            );
+        }
+        Ok(TextureAtlas {
+            image: Image::from_colors(width, height, &pixels),
+            map,
+        })
+    }
+
+    /// Tries growing power of two sizes until every rectangle fits.
+    fn pack(&self, sizes: &[(i32, i32)]) -> Result<(i32, i32, Vec<Placement>), String> {
+        let area: i64 = sizes.iter().map(|&(w, h)| w as i64 * h as i64).sum();
+        let longest = sizes
+            .iter()
+            .map(|&(w, h)| if self.rotation { w.min(h) } else { w }.max(1))
+            .max()
+            .unwrap_or(1);
+        let mut width = ((longest + self.padding).max(1) as u32).next_power_of_two() as i32;
+        let mut height = width;
+        while (width as i64) * (height as i64) < area {
+            if height < width {
+                height *= 2;
+            } else {
+                width *= 2;
+            }
+        }
+        while width <= self.max_size && height <= self.max_size {
+            let inner = (width - self.padding, height - self.padding);
+            let placed = match self.algorithm {
+                PackingAlgorithm::MaxRects => MaxRects::new(inner).pack(sizes, self.rotation),
+                PackingAlgorithm::Skyline => Skyline::new(inner).pack(sizes, self.rotation),
+            };
+            if let Some(placements) = placed {
+                return Ok((width, height, placements));
+            }
+            if height < width {
+                height *= 2;
+            } else {
+                width *= 2;
+            }
+        }
+        Err(format!(
+            "images don't fit in a {0}x{0} atlas",
+            self.max_size
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Placement {
+    x: i32,
+    y: i32,
+    rotated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rect {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+}
+
+impl Rect {
+    fn contains(&self, other: &Rect) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.x + other.w <= self.x + self.w
+            && other.y + other.h <= self.y + self.h
+    }
+
+    fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.x + other.w
+            && other.x < self.x + self.w
+            && self.y < other.y + other.h
+            && other.y < self.y + self.h
+    }
+}
+
+/// Candidate orientations of a `w` by `h` rectangle, the unrotated one first.
+fn orientations(w: i32, h: i32, rotation: bool) -> Vec<(i32, i32, bool)> {
+    if rotation && w != h {
+        vec![(w, h, false), (h, w, true)]
+    } else {
+        vec![(w, h, false)]
+    }
+}
+
+struct MaxRects {
+    free: Vec<Rect>,
+}
+
+impl MaxRects {
+    fn new((width, height): (i32, i32)) -> MaxRects {
+        MaxRects {
+            free: vec![Rect {
+                x: 0,
+                y: 0,
+                w: width,
+                h: height,
+            }],
+        }
+    }
+
+    fn pack(mut self, sizes: &[(i32, i32)], rotation: bool) -> Option<Vec<Placement>> {
+        sizes
+            .iter()
+            .map(|&(w, h)| self.insert(w, h, rotation))
+            .collect()
+    }
+
+    /// Places a rectangle using the best short side fit heuristic.
+    fn insert(&mut self, w: i32, h: i32, rotation: bool) -> Option<Placement> {
+        let mut best: Option<((i32, i32), Rect, bool)> = None;
+        for free in &self.free {
+            for &(rw, rh, rotated) in &orientations(w, h, rotation) {
+                if rw > free.w || rh > free.h {
+                    continue;
+                }
+                let (a, b) = (free.w - rw, free.h - rh);
+                let score = (a.min(b), a.max(b));
+                if !matches!(best, Some((s, _, _)) if s <= score) {
+                    let placed = Rect {
+                        x: free.x,
+                        y: free.y,
+                        w: rw,
+                        h: rh,
+                    };
+                    best = Some((score, placed, rotated));
+                }
+            }
+        }
+        let (_, placed, rotated) = best?;
+        self.split(&placed);
+        Some(Placement {
+            x: placed.x,
+            y: placed.y,
+            rotated,
+        })
+    }
+
+    /// Replaces every free rectangle overlapping `used` by the free space left around it.
+    fn split(&mut self, used: &Rect) {
+        let mut free = Vec::with_capacity(self.free.len() + 4);
+        for f in &self.free {
+            if !f.overlaps(used) {
+                free.push(*f);
+                continue;
+            }
+            if used.x > f.x {
+                free.push(Rect {
+                    w: used.x - f.x,
+                    ..*f
+                });
+            }
+            if used.x + used.w < f.x + f.w {
+                free.push(Rect {
+                    x: used.x + used.w,
+                    w: f.x + f.w - (used.x + used.w),
+                    ..*f
+                });
+            }
+            if used.y > f.y {
+                free.push(Rect {
+                    h: used.y - f.y,
+                    ..*f
+                });
+            }
+            if used.y + used.h < f.y + f.h {
+                free.push(Rect {
+                    y: used.y + used.h,
+                    h: f.y + f.h - (used.y + used.h),
+                    ..*f
+                });
+            }
+        }
+        // drop rectangles contained in others, keeping one of any duplicates
+        let mut kept: Vec<Rect> = Vec::with_capacity(free.len());
+        for (i, r) in free.iter().enumerate() {
+            let redundant = free
+                .iter()
+                .enumerate()
+                .any(|(j, o)| i != j && o.contains(r) && (o != r || j < i));
+            if !redundant {
+                kept.push(*r);
+            }
+        }
+        self.free = kept;
+    }
+}
+
+/// Score, segment index, height and orientation of a candidate position on the skyline.
+type SkylineFit = ((i32, i32), usize, i32, (i32, i32, bool));
+
+struct Skyline {
+    width: i32,
+    height: i32,
+    /// Segments of the outline as `(x, y, width)`, left to right.
+    segments: Vec<(i32, i32, i32)>,
+}
+
+impl Skyline {
+    fn new((width, height): (i32, i32)) -> Skyline {
+        Skyline {
+            width,
+            height,
+            segments: vec![(0, 0, width)],
+        }
+    }
+
+    fn pack(mut self, sizes: &[(i32, i32)], rotation: bool) -> Option<Vec<Placement>> {
+        sizes
+            .iter()
+            .map(|&(w, h)| self.insert(w, h, rotation))
+            .collect()
+    }
+
+    /// Height at which a `w` wide rectangle rests when its left edge is at segment `index`.
+    fn fit(&self, index: usize, w: i32, h: i32) -> Option<i32> {
+        let x = self.segments[index].0;
+        if x + w > self.width {
+            return None;
+        }
+        let mut y = 0;
+        let mut remaining = w;
+        for &(_, sy, sw) in &self.segments[index..] {
+            y = y.max(sy);
+            if y + h > self.height {
+                return None;
+            }
+            remaining -= sw;
+            if remaining <= 0 {
+                return Some(y);
+            }
+        }
+        None
+    }
+
+    /// Places a rectangle at the lowest position, preferring narrower segments on ties.
+    fn insert(&mut self, w: i32, h: i32, rotation: bool) -> Option<Placement> {
+        let mut best: Option<SkylineFit> = None;
+        for index in 0..self.segments.len() {
+            for &(rw, rh, rotated) in &orientations(w, h, rotation) {
+                if let Some(y) = self.fit(index, rw, rh) {
+                    let score = (y + rh, self.segments[index].2);
+                    if !matches!(best, Some((s, ..)) if s <= score) {
+                        best = Some((score, index, y, (rw, rh, rotated)));
+                    }
+                }
+            }
+        }
+        let (_, index, y, (rw, rh, rotated)) = best?;
+        let x = self.segments[index].0;
+        self.raise(index, x, y + rh, rw);
+        Some(Placement { x, y, rotated })
+    }
+
+    /// Puts a segment at height `y` over `x..x + w`, cutting away what it covers.
+    fn raise(&mut self, index: usize, x: i32, y: i32, w: i32) {
+        self.segments.insert(index, (x, y, w));
+        let right = x + w;
+        let i = index + 1;
+        while i < self.segments.len() {
+            let (sx, sy, sw) = self.segments[i];
+            if sx >= right {
+                break;
+            }
+            if sx + sw <= right {
+                self.segments.remove(i);
+            } else {
+                self.segments[i] = (right, sy, sx + sw - right);
+                break;
+            }
+        }
+        // merge neighbours at the same height
+        let mut i = 0;
+        while i + 1 < self.segments.len() {
+            if self.segments[i].1 == self.segments[i + 1].1 {
+                self.segments[i].2 += self.segments[i + 1].2;
+                self.segments.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Pixels of a `w` by `h` image turned 90 degrees clockwise.
+fn rotate_clockwise(w: i32, h: i32, pixels: &[Color]) -> Vec<Color> {
+    let mut rotated = Vec::with_capacity(pixels.len());
+    for y in 0..w {
+        for x in 0..h {
+            rotated.push(pixels[((h - 1 - x) * w + y) as usize]);
+        }
+    }
+    rotated
+}
+
+/// Copies a `w` by `h` image to `(x, y)`, repeating its border `extrude` pixels outwards.
+#[allow(clippy::too_many_arguments)]
+fn blit(
+    canvas: &mut [Color],
+    canvas_width: i32,
+    pixels: &[Color],
+    w: i32,
+    h: i32,
+    x: i32,
+    y: i32,
+    extrude: i32,
+) {
+    for dy in -extrude..h + extrude {
+        for dx in -extrude..w + extrude {
+            let sx = dx.clamp(0, w - 1);
+            let sy = dy.clamp(0, h - 1);
+            canvas[((y + dy) * canvas_width + x + dx) as usize] = pixels[(sy * w + sx) as usize];
+        }
+    }
+}
+
+#[cfg(test)]
+mod atlas_test {
+    use super::*;
+
+    fn solid(width: i32, height: i32, color: Color) -> Image {
+        Image::from_colors(width, height, &vec![color; (width * height) as usize])
+    }
+
+    fn overlapping(map: &AtlasMap) -> bool {
+        let recs: Vec<Rectangle> = map.regions.values().map(|r| r.rec).collect();
+        recs.iter().enumerate().any(|(i, a)| {
+            recs[i + 1..].iter().any(|b| {
+                a.x < b.x + b.width
+                    && b.x < a.x + a.width
+                    && a.y < b.y + b.height
+                    && b.y < a.y + a.height
+            })
+        })
+    }
+
+    #[test]
+    fn test_atlas_packing() {
+        for &algorithm in &[PackingAlgorithm::MaxRects, PackingAlgorithm::Skyline] {
+            let mut builder = TextureAtlasBuilder::new();
+            builder.algorithm(algorithm).padding(1);
+            for i in 0..20 {
+                builder.add(
+                    &format!("sprite{}", i),
+                    solid(4 + i % 5, 6 + i % 3, Color::RED),
+                );
+            }
+            let atlas = builder.build().unwrap();
+            assert_eq!(atlas.map.regions.len(), 20);
+            assert!(!overlapping(&atlas.map), "{:?} overlaps", algorithm);
+            assert!(atlas.image.width() <= 64 && atlas.image.height() <= 64);
+            for region in atlas.map.regions.values() {
+                assert!(region.rec.x >= 1.0 && region.rec.y >= 1.0);
+                assert!(region.rec.x + region.rec.width < atlas.image.width() as f32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_atlas_pixels() {
+        let mut image = solid(3, 1, Color::RED);
+        image.set_colors(3, 1, &[Color::RED, Color::GREEN, Color::BLUE]);
+        let atlas = TextureAtlasBuilder::new()
+            .extrude(1)
+            .rotation(true)
+            .add("strip", image)
+            .add("wide", solid(8, 2, Color::WHITE))
+            .build()
+            .unwrap();
+        let region = *atlas.map.region("strip").unwrap();
+        let colors = atlas.image.colors();
+        let at = |x: f32, y: f32| colors[(y as i32 * atlas.image.width() + x as i32) as usize];
+        let rec = region.rec;
+        if region.rotated {
+            assert_eq!((rec.width, rec.height), (1.0, 3.0));
+            assert_eq!(at(rec.x, rec.y), Color::RED);
+            assert_eq!(at(rec.x, rec.y + 2.0), Color::BLUE);
+            assert_eq!(at(rec.x, rec.y + 3.0), Color::BLUE);
+        } else {
+            assert_eq!(at(rec.x, rec.y), Color::RED);
+            assert_eq!(at(rec.x - 1.0, rec.y), Color::RED);
+            assert_eq!(at(rec.x + 3.0, rec.y + 1.0), Color::BLUE);
+        }
+        assert_eq!(
+            rotate_clockwise(3, 1, &[Color::RED, Color::GREEN, Color::BLUE])[2],
+            Color::BLUE
+        );
+    }
+
+    #[test]
+    fn test_atlas_empty_image() {
+        let result = TextureAtlasBuilder::new()
+            .add("a", solid(4, 4, Color::RED))
+            .add("b", solid(0, 4, Color::RED))
+            .build();
+        assert_eq!(result.err(), Some("image \"b\" is empty".to_string()));
+    }
+
+    #[test]
+    fn test_atlas_json() {
+        let atlas = TextureAtlasBuilder::new()
+            .padding(2)
+            .add("a \"quoted\" name", solid(5, 5, Color::RED))
+            .add("b", solid(3, 7, Color::BLUE))
+            .build()
+            .unwrap();
+        let map = AtlasMap::from_json(&atlas.map.to_json()).unwrap();
+        assert_eq!(map, atlas.map);
+        assert_eq!(map.get("b").unwrap().width, 3.0);
+        assert!(TextureAtlasBuilder::new()
+            .max_size(8)
+            .add("big", solid(16, 16, Color::RED))
+            .build()
+            .is_err());
+    }
+}
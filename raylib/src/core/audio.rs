@@ -1,32 +1,350 @@
 //! Contains code related to audio. [`RaylibAudio`] plays sounds and music.
+//!
+//! Sounds, music and streams are loaded through the [`RaylibAudio`] device and hold it open, so it's only closed
+//! once they're all unloaded. [`Wave`]s are plain sample data and don't need the device.
+//!
+//! # Limitations of raylib 3.0
+//!
+//! The raylib these bindings are built on mixes at a rate fixed when it's compiled, [`AUDIO_DEVICE_SAMPLE_RATE`],
+//! has no panning, and streams [`Music`] from its file without seeking. The other audio modules work around this
+//! in Rust where they can: [`ProceduralStream`]s, and the [`VoicePool`]s playing through them, are mixed in Rust, so
+//! they can be panned, and [`MusicTrack`]s are decoded into memory so they can seek. Plain sounds and music only get
+//! volume and pitch.
+//!
+//! [`RaylibAudio`]: struct.RaylibAudio.html
+//! [`Wave`]: struct.Wave.html
+//! [`AUDIO_DEVICE_SAMPLE_RATE`]: constant.AUDIO_DEVICE_SAMPLE_RATE.html
+//! [`Music`]: struct.Music.html
+//! [`ProceduralStream`]: ../procedural/struct.ProceduralStream.html
+//! [`VoicePool`]: ../voice/struct.VoicePool.html
+//! [`MusicTrack`]: ../music/struct.MusicTrack.html
 
-use crate::core::RaylibThread;
 use crate::ffi;
 use std::ffi::CString;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::Arc;
+
+/// Like `make_thin_wrapper!`, for resources that need the audio device until they're unloaded.
+macro_rules! make_audio_wrapper {
+    ($name:ident, $t:ty, $dropfunc:expr) => {
+        #[derive(Debug)]
+        pub struct $name(pub(crate) $t, Arc<AudioDevice>);
+
+        impl $name {
+            /// Take the raw ffi type. Must manually free memory by calling the proper unload function
+            ///
+            /// # Safety
+            ///
+            /// The raw resource no longer keeps the audio device open, it must be unloaded before the device closes.
+            pub unsafe fn unwrap(self) -> $t {
+                self.to_raw()
+            }
+
+            /// returns the unwrapped raylib-sys object, which no longer keeps the audio device open
+            pub fn to_raw(self) -> $t {
+                let this = std::mem::ManuallyDrop::new(self);
+                unsafe { drop(std::ptr::read(&this.1)) };
+                this.0
+            }
+
+            /// converts raylib-sys object to a "safe"
+            /// version. Make sure to call this function
+            /// from the thread the resource was created.
+            ///
+            /// # Safety
+            ///
+            /// `raw` must have been loaded from the device `audio` opened, and be owned by nobody else.
+            pub unsafe fn from_raw(audio: &RaylibAudio, raw: $t) -> Self {
+                $name(raw, audio.device())
+            }
+        }
+
+        impl Drop for $name {
+            fn drop(&mut self) {
+                unsafe {
+                    ($dropfunc)(self.0);
+                }
+            }
+        }
+
+        impl std::convert::AsRef<$t> for $name {
+            fn as_ref(&self) -> &$t {
+                &self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = $t;
+            #[inline]
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+    };
+}
 
 make_thin_wrapper!(Wave, ffi::Wave, ffi::UnloadWave);
-make_thin_wrapper!(Sound, ffi::Sound, ffi::UnloadSound);
-make_thin_wrapper!(Music, ffi::Music, ffi::UnloadMusicStream);
-make_thin_wrapper!(AudioStream, ffi::AudioStream, ffi::CloseAudioStream);
+make_audio_wrapper!(Sound, ffi::Sound, ffi::UnloadSound);
+make_audio_wrapper!(Music, ffi::Music, ffi::UnloadMusicStream);
+
+/// Rate the audio device mixes at, fixed when raylib is compiled. See the
+/// [module docs](index.html#limitations-of-raylib-30).
+pub const AUDIO_DEVICE_SAMPLE_RATE: u32 = 44100;
 
-/// A marker trait specifying an audio sample (`u8`, `i16`, or `f32`).
-pub trait AudioSample {}
-impl AudioSample for u8 {}
-impl AudioSample for i16 {}
-impl AudioSample for f32 {}
+/// Set while the audio device is open, which outlasts the `RaylibAudio` while resources hold it.
+static AUDIO_INITIALIZED: AtomicBool = AtomicBool::new(false);
+/// Buffer size new streams get, as last given to `SetAudioStreamBufferSizeDefault`.
+static STREAM_BUFFER_SIZE: AtomicI32 = AtomicI32::new(0);
 
-/// This token is used to indicate VR is initialized
+/// Holds the audio device open; the last one dropped closes it.
 #[derive(Debug)]
-pub struct RaylibAudio(());
+pub(crate) struct AudioDevice(());
 
-impl RaylibAudio {
-    /// Initializes audio device and context.
+impl Drop for AudioDevice {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::StopSoundMulti();
+            ffi::CloseAudioDevice();
+        }
+        AUDIO_INITIALIZED.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Initializes a stream with buffers of `buffer_frames`, leaving the default for other streams as it was.
+pub(crate) fn init_stream_with_buffer(
+    sample_rate: u32,
+    sample_size: u32,
+    channels: u32,
+    buffer_frames: i32,
+) -> ffi::AudioStream {
+    unsafe {
+        ffi::SetAudioStreamBufferSizeDefault(buffer_frames);
+        let stream = ffi::InitAudioStream(sample_rate, sample_size, channels);
+        ffi::SetAudioStreamBufferSizeDefault(STREAM_BUFFER_SIZE.load(Ordering::Relaxed));
+        stream
+    }
+}
+
+/// A sample format raylib streams take: 8 bit `u8`, 16 bit `i16` or 32 bit `f32`.
+pub trait AudioSample: Copy + private::Sealed {
+    /// Bits per sample, as raylib's `sampleSize`.
+    const SAMPLE_SIZE: u32;
+
+    /// The sample between `-1.0` and `1.0`.
+    fn to_f32(self) -> f32;
+}
+impl AudioSample for u8 {
+    const SAMPLE_SIZE: u32 = 8;
+
+    fn to_f32(self) -> f32 {
+        (self as f32 - 128.0) / 127.0
+    }
+}
+impl AudioSample for i16 {
+    const SAMPLE_SIZE: u32 = 16;
+
+    fn to_f32(self) -> f32 {
+        self as f32 / 32767.0
+    }
+}
+impl AudioSample for f32 {
+    const SAMPLE_SIZE: u32 = 32;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+}
+
+mod private {
+    /// Keeps [`AudioSample`](../trait.AudioSample.html) to the formats raylib knows.
+    pub trait Sealed {}
+    impl Sealed for u8 {}
+    impl Sealed for i16 {}
+    impl Sealed for f32 {}
+}
+
+/// Checks `data` holds whole frames of samples in the format of `stream`.
+fn check_samples<T: AudioSample>(stream: &ffi::AudioStream, data: &[T]) -> Result<(), String> {
+    if stream.sampleSize != T::SAMPLE_SIZE {
+        return Err(format!(
+            "{} bit samples given to a {} bit stream",
+            T::SAMPLE_SIZE,
+            stream.sampleSize
+        ));
+    }
+    if stream.channels == 0
+        || !data
+            .chunks_exact(stream.channels as usize)
+            .remainder()
+            .is_empty()
+    {
+        return Err(format!(
+            "{} samples don't make whole frames of {} channels",
+            data.len(),
+            stream.channels
+        ));
+    }
+    Ok(())
+}
+
+/// A stream of raw PCM samples of type `T`, which sets its sample size. The raw stream can be read but not changed,
+/// its buffers were allocated for the format it describes.
+#[derive(Debug)]
+pub struct AudioStream<T: AudioSample = i16>(
+    pub(crate) ffi::AudioStream,
+    Arc<AudioDevice>,
+    PhantomData<T>,
+);
+
+impl<T: AudioSample> Drop for AudioStream<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::CloseAudioStream(self.0);
+        }
+    }
+}
+
+impl<T: AudioSample> AsRef<ffi::AudioStream> for AudioStream<T> {
+    fn as_ref(&self) -> &ffi::AudioStream {
+        &self.0
+    }
+}
+
+impl<T: AudioSample> Deref for AudioStream<T> {
+    type Target = ffi::AudioStream;
     #[inline]
-    pub fn init_audio_device() -> RaylibAudio {
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Options for the audio device, chosen before initialization. See [`RaylibAudio::builder`].
+///
+/// [`RaylibAudio::builder`]: struct.RaylibAudio.html#method.builder
+#[derive(Debug, Clone)]
+pub struct RaylibAudioBuilder {
+    sample_rate: u32,
+    buffer_size: i32,
+}
+
+impl RaylibAudioBuilder {
+    /// Sets the rate the device mixes at. The raylib 3.0 these bindings link compiles its rate in as
+    /// [`AUDIO_DEVICE_SAMPLE_RATE`] and has no way to change it, so [`build`](#method.build) refuses any other rate
+    /// rather than open a device that plays everything at the wrong speed.
+    ///
+    /// [`AUDIO_DEVICE_SAMPLE_RATE`]: constant.AUDIO_DEVICE_SAMPLE_RATE.html
+    pub fn sample_rate(&mut self, rate: u32) -> &mut Self {
+        self.sample_rate = rate;
+        self
+    }
+
+    /// Sets the buffer size, in frames, of audio streams and music; `0` keeps raylib's default of 4096. Smaller
+    /// buffers lower latency but need updating more often.
+    pub fn buffer_size(&mut self, frames: i32) -> &mut Self {
+        self.buffer_size = frames;
+        self
+    }
+
+    /// Initializes the audio device.
+    ///
+    /// Fails if the device is open already, which it stays while resources loaded through an earlier
+    /// [`RaylibAudio`](struct.RaylibAudio.html) are alive.
+    pub fn build(&self) -> Result<RaylibAudio, String> {
+        if self.sample_rate != AUDIO_DEVICE_SAMPLE_RATE {
+            return Err(format!(
+                "raylib mixes at {} Hz and can't be set to {} Hz",
+                AUDIO_DEVICE_SAMPLE_RATE, self.sample_rate
+            ));
+        }
+        if AUDIO_INITIALIZED
+            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err("Attempted to initialize the audio device more than once".to_string());
+        }
         unsafe {
             ffi::InitAudioDevice();
         }
-        RaylibAudio(())
+        if !unsafe { ffi::IsAudioDeviceReady() } {
+            AUDIO_INITIALIZED.store(false, Ordering::Relaxed);
+            return Err("Could not initialize the audio device".to_string());
+        }
+        let audio = RaylibAudio(Arc::new(AudioDevice(())));
+        audio.set_audio_stream_buffer_size_default(self.buffer_size);
+        Ok(audio)
+    }
+}
+
+/// The audio device. Sounds, music and streams loaded through it keep the device open until they're dropped too.
+#[derive(Debug)]
+pub struct RaylibAudio(Arc<AudioDevice>);
+
+impl RaylibAudio {
+    /// Initializes audio device and context with the default options.
+    ///
+    /// Fails if the device is open already, see [`RaylibAudioBuilder::build`].
+    ///
+    /// [`RaylibAudioBuilder::build`]: struct.RaylibAudioBuilder.html#method.build
+    #[inline]
+    pub fn init_audio_device() -> Result<RaylibAudio, String> {
+        RaylibAudio::builder().build()
+    }
+
+    /// Creates a `RaylibAudioBuilder` for choosing device options before initialization.
+    pub fn builder() -> RaylibAudioBuilder {
+        RaylibAudioBuilder {
+            sample_rate: AUDIO_DEVICE_SAMPLE_RATE,
+            buffer_size: 0,
+        }
+    }
+
+    /// A guard holding the device open.
+    pub(crate) fn device(&self) -> Arc<AudioDevice> {
+        self.0.clone()
+    }
+
+    /// Loads sound from file.
+    pub fn load_sound(&self, filename: &str) -> Result<Sound, String> {
+        let c_filename = CString::new(filename).unwrap();
+        let s = unsafe { ffi::LoadSound(c_filename.as_ptr()) };
+        if s.stream.buffer.is_null() {
+            return Err(format!("failed to load sound {}", filename));
+        }
+        Ok(Sound(s, self.device()))
+    }
+
+    /// Loads sound from wave data.
+    pub fn load_sound_from_wave(&self, wave: &Wave) -> Result<Sound, String> {
+        let s = unsafe { ffi::LoadSoundFromWave(wave.0) };
+        if s.stream.buffer.is_null() {
+            return Err("failed to load sound from wave".to_string());
+        }
+        Ok(Sound(s, self.device()))
+    }
+
+    /// Loads music stream from file.
+    pub fn load_music_stream(&self, filename: &str) -> Result<Music, String> {
+        let c_filename = CString::new(filename).unwrap();
+        let m = unsafe { ffi::LoadMusicStream(c_filename.as_ptr()) };
+        if m.stream.buffer.is_null() {
+            return Err(format!("music could not be loaded from file {}", filename));
+        }
+        Ok(Music(m, self.device()))
+    }
+
+    /// Initializes audio stream (to stream raw PCM data), its sample size set by `T`.
+    pub fn init_audio_stream<T: AudioSample>(
+        &self,
+        sample_rate: u32,
+        channels: u32,
+    ) -> Result<AudioStream<T>, String> {
+        let stream = unsafe { ffi::InitAudioStream(sample_rate, T::SAMPLE_SIZE, channels) };
+        if stream.buffer.is_null() {
+            return Err("failed to initialize audio stream".to_string());
+        }
+        Ok(AudioStream(stream, self.device(), PhantomData))
     }
 
     /// Checks if audio device is ready.
@@ -58,6 +376,8 @@ impl RaylibAudio {
     }
 
     /// Play a sound (using multichannel buffer pool)
+    ///
+    /// The copies can't be controlled individually, see [`VoicePool`](../voice/struct.VoicePool.html) for that.
     #[inline]
     pub fn play_sound_multi(&mut self, sound: &Sound) {
         unsafe {
@@ -203,7 +523,7 @@ impl RaylibAudio {
 
     /// Plays audio stream.
     #[inline]
-    pub fn play_audio_stream(&mut self, stream: &mut AudioStream) {
+    pub fn play_audio_stream(&mut self, stream: &mut AudioStream<impl AudioSample>) {
         unsafe {
             ffi::PlayAudioStream(stream.0);
         }
@@ -211,7 +531,7 @@ impl RaylibAudio {
 
     /// Pauses audio stream.
     #[inline]
-    pub fn pause_audio_stream(&mut self, stream: &mut AudioStream) {
+    pub fn pause_audio_stream(&mut self, stream: &mut AudioStream<impl AudioSample>) {
         unsafe {
             ffi::PauseAudioStream(stream.0);
         }
@@ -219,7 +539,7 @@ impl RaylibAudio {
 
     /// Resumes audio stream.
     #[inline]
-    pub fn resume_audio_stream(&mut self, stream: &mut AudioStream) {
+    pub fn resume_audio_stream(&mut self, stream: &mut AudioStream<impl AudioSample>) {
         unsafe {
             ffi::ResumeAudioStream(stream.0);
         }
@@ -227,13 +547,13 @@ impl RaylibAudio {
 
     /// Checks if audio stream is currently playing.
     #[inline]
-    pub fn is_audio_stream_playing(&self, stream: &AudioStream) -> bool {
+    pub fn is_audio_stream_playing(&self, stream: &AudioStream<impl AudioSample>) -> bool {
         unsafe { ffi::IsAudioStreamPlaying(stream.0) }
     }
 
     /// Stops audio stream.
     #[inline]
-    pub fn stop_audio_stream(&mut self, stream: &mut AudioStream) {
+    pub fn stop_audio_stream(&mut self, stream: &mut AudioStream<impl AudioSample>) {
         unsafe {
             ffi::StopAudioStream(stream.0);
         }
@@ -241,7 +561,11 @@ impl RaylibAudio {
 
     /// Sets volume for audio stream (`1.0` is max level).
     #[inline]
-    pub fn set_audio_stream_volume(&mut self, stream: &mut AudioStream, volume: f32) {
+    pub fn set_audio_stream_volume(
+        &mut self,
+        stream: &mut AudioStream<impl AudioSample>,
+        volume: f32,
+    ) {
         unsafe {
             ffi::SetAudioStreamVolume(stream.0, volume);
         }
@@ -249,7 +573,11 @@ impl RaylibAudio {
 
     /// Sets pitch for audio stream (`1.0` is base level).
     #[inline]
-    pub fn set_audio_stream_pitch(&mut self, stream: &mut AudioStream, pitch: f32) {
+    pub fn set_audio_stream_pitch(
+        &mut self,
+        stream: &mut AudioStream<impl AudioSample>,
+        pitch: f32,
+    ) {
         unsafe {
             ffi::SetAudioStreamPitch(stream.0, pitch);
         }
@@ -257,16 +585,16 @@ impl RaylibAudio {
 
     /// Sets pitch for audio stream (`1.0` is base level).
     #[inline]
-    pub fn is_audio_stream_processed(&mut self, stream: &AudioStream) -> bool {
+    pub fn is_audio_stream_processed(&mut self, stream: &AudioStream<impl AudioSample>) -> bool {
         unsafe { ffi::IsAudioStreamProcessed(stream.0) }
     }
-}
 
-impl Drop for RaylibAudio {
-    fn drop(&mut self) {
+    /// Sets the buffer size, in frames, of audio streams initialized after this; `0` restores raylib's default.
+    #[inline]
+    pub fn set_audio_stream_buffer_size_default(&self, size: i32) {
+        STREAM_BUFFER_SIZE.store(size, Ordering::Relaxed);
         unsafe {
-            ffi::StopSoundMulti();
-            ffi::CloseAudioDevice();
+            ffi::SetAudioStreamBufferSizeDefault(size);
         }
     }
 }
@@ -341,7 +669,8 @@ impl Wave {
     pub fn get_wave_data(&self) -> Vec<f32> {
         unsafe {
             let data = ffi::GetWaveData(self.0);
-            let data_size = (self.sampleCount * self.channels) as usize;
+            // sampleCount already counts every channel
+            let data_size = self.sampleCount as usize;
             let mut samples = Vec::with_capacity(data_size);
             samples.set_len(data_size);
             std::ptr::copy(data, samples.as_mut_ptr(), data_size);
@@ -357,68 +686,45 @@ impl AsRef<ffi::AudioStream> for Sound {
     }
 }
 
-impl AsMut<ffi::AudioStream> for Sound {
-    fn as_mut(&mut self) -> &mut ffi::AudioStream {
-        &mut self.0.stream
-    }
-}
-
 impl Sound {
     pub fn sample_count(&self) -> u32 {
         self.0.sampleCount
     }
     pub unsafe fn inner(self) -> ffi::Sound {
-        let inner = self.0;
-        std::mem::forget(self);
-        inner
-    }
-    /// Loads sound from file.
-    pub fn load_sound(filename: &str) -> Result<Sound, String> {
-        let c_filename = CString::new(filename).unwrap();
-        let s = unsafe { ffi::LoadSound(c_filename.as_ptr()) };
-        if s.stream.buffer.is_null() {
-            return Err(format!("failed to load sound {}", filename));
+        self.to_raw()
+    }
+
+    /// Replaces the start of the sound with `data`, interleaved samples in the format of its `stream`, and stops it.
+    /// Sounds raylib loads are converted to the device's format, which is 32 bit float.
+    pub fn update<T: AudioSample>(&mut self, data: &[T]) -> Result<(), String> {
+        check_samples(&self.0.stream, data)?;
+        let channels = self.0.stream.channels as usize;
+        let frames = data.len() / channels;
+        let capacity = self.0.sampleCount as usize / channels;
+        if frames > capacity {
+            return Err(format!(
+                "{} frames don't fit in a sound of {}",
+                frames, capacity
+            ));
+        }
+        unsafe {
+            ffi::UpdateSound(
+                self.0,
+                data.as_ptr() as *const std::os::raw::c_void,
+                frames as i32,
+            );
         }
-        Ok(Sound(s))
+        Ok(())
     }
-
-    /// Loads sound from wave data.
-    pub fn load_sound_from_wave(wave: &Wave) -> Result<Sound, String> {
-        let s = unsafe { ffi::LoadSoundFromWave(wave.0) };
-        if s.stream.buffer.is_null() {
-            return Err(format!("failed to load sound from wave"));
-        }
-        Ok(Sound(s))
-    }
-
-    // Figure out how to make this safe
-    // /// Updates sound buffer with new data.
-    // #[inline]
-    // pub fn update_sound(&mut self, data: &[impl AudioSample]) {
-    //     unsafe {
-    //         ffi::UpdateSound(
-    //             self.0,
-    //             data.as_ptr() as *const std::os::raw::c_void,
-    //             data.len() as i32,
-    //         );
-    //     }
-    // }
 }
 
-impl Music {
-    /// Loads music stream from file.
-    // #[inline]
-    pub fn load_music_stream(_: &RaylibThread, filename: &str) -> Result<Music, String> {
-        let c_filename = CString::new(filename).unwrap();
-        let m = unsafe { ffi::LoadMusicStream(c_filename.as_ptr()) };
-        if m.stream.buffer.is_null() {
-            return Err(format!("music could not be loaded from file {}", filename));
-        }
-        Ok(Music(m))
+impl AsRef<ffi::AudioStream> for Music {
+    fn as_ref(&self) -> &ffi::AudioStream {
+        &self.0.stream
     }
 }
 
-impl AudioStream {
+impl<T: AudioSample> AudioStream<T> {
     pub fn sample_rate(&self) -> u32 {
         self.0.sampleRate
     }
@@ -430,30 +736,80 @@ impl AudioStream {
     }
 
     pub unsafe fn inner(self) -> ffi::AudioStream {
-        let inner = self.0;
-        std::mem::forget(self);
-        inner
+        self.to_raw()
     }
-    /// Initializes audio stream (to stream raw PCM data).
-    #[inline]
-    pub fn init_audio_stream(
-        _: &RaylibThread,
-        sample_rate: u32,
-        sample_size: u32,
-        channels: u32,
-    ) -> AudioStream {
-        unsafe { AudioStream(ffi::InitAudioStream(sample_rate, sample_size, channels)) }
+
+    /// returns the unwrapped raylib-sys object, which no longer keeps the audio device open
+    pub fn to_raw(self) -> ffi::AudioStream {
+        let this = std::mem::ManuallyDrop::new(self);
+        unsafe { drop(std::ptr::read(&this.1)) };
+        this.0
+    }
+
+    /// converts raylib-sys object to a "safe"
+    /// version. Make sure to call this function
+    /// from the thread the resource was created.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be a stream initialized on the device `audio` opened, owned by nobody else. A sample size other
+    /// than `T`'s makes [`update`](#method.update) fail.
+    pub unsafe fn from_raw(audio: &RaylibAudio, raw: ffi::AudioStream) -> Self {
+        AudioStream(raw, audio.device(), PhantomData)
     }
 
-    /// Updates audio stream buffers with data.
+    /// Updates audio stream buffers with interleaved samples, once raylib processed one. Fails unless `data` holds
+    /// whole frames in the stream's format; raylib itself refuses more than half the stream's buffer with a warning.
     #[inline]
-    pub fn update_audio_stream<T: AudioSample>(&mut self, data: &[T]) {
+    pub fn update(&mut self, data: &[T]) -> Result<(), String> {
+        check_samples(&self.0, data)?;
         unsafe {
             ffi::UpdateAudioStream(
                 self.0,
                 data.as_ptr() as *const std::os::raw::c_void,
-                (data.len() * std::mem::size_of::<T>()) as i32,
+                data.len() as i32,
             );
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod audio_test {
+    use super::*;
+
+    fn stream(sample_size: u32, channels: u32) -> ffi::AudioStream {
+        ffi::AudioStream {
+            buffer: std::ptr::null_mut(),
+            sampleRate: 44100,
+            sampleSize: sample_size,
+            channels,
+        }
+    }
+
+    #[test]
+    fn test_samples_checked_against_stream() {
+        assert!(check_samples(&stream(16, 2), &[0i16; 8]).is_ok());
+        assert!(check_samples(&stream(32, 1), &[0.0f32; 3]).is_ok());
+        assert!(check_samples(&stream(16, 2), &[0i16; 7]).is_err());
+        assert!(check_samples(&stream(16, 2), &[0.0f32; 8]).is_err());
+        assert!(check_samples(&stream(8, 0), &[0u8; 8]).is_err());
+
+        // a raw stream that doesn't match its type is refused too, the guard is leaked so no device closes
+        let audio = RaylibAudio(Arc::new(AudioDevice(())));
+        let mut raw = unsafe { AudioStream::<u8>::from_raw(&audio, stream(16, 1)) };
+        assert!(raw.update(&[128u8; 4]).is_err());
+        std::mem::forget(raw);
+        std::mem::forget(audio);
+    }
+
+    #[test]
+    fn test_unsupported_sample_rate() {
+        // refused before the device is touched
+        let err = RaylibAudio::builder()
+            .sample_rate(48000)
+            .build()
+            .unwrap_err();
+        assert!(err.contains("48000"));
     }
 }
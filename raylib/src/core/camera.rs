@@ -1,5 +1,5 @@
 //! Utility code for using Raylib [`Camera3D`] and [`Camera2D`]
-use crate::core::math::{Vector2, Vector3};
+use crate::core::math::{Rectangle, Vector2, Vector3};
 use crate::core::RaylibHandle;
 use crate::ffi;
 
@@ -70,6 +70,30 @@ impl Into<ffi::Camera2D> for &Camera2D {
     }
 }
 
+impl Camera2D {
+    /// The world area visible through the camera on a screen of the given size: the bounding box of the screen
+    /// corners once the camera's offset, rotation and zoom are undone.
+    pub fn visible_area(&self, screen_width: f32, screen_height: f32) -> Rectangle {
+        let (sin, cos) = (-self.rotation.to_radians()).sin_cos();
+        let zoom = if self.zoom == 0.0 { 1.0 } else { self.zoom };
+        let corners = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(screen_width, 0.0),
+            Vector2::new(0.0, screen_height),
+            Vector2::new(screen_width, screen_height),
+        ];
+        let mut min = Vector2::new(f32::INFINITY, f32::INFINITY);
+        let mut max = Vector2::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for corner in &corners {
+            let p = (*corner - self.offset) / zoom;
+            let world = Vector2::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos) + self.target;
+            min = Vector2::new(min.x.min(world.x), min.y.min(world.y));
+            max = Vector2::new(max.x.max(world.x), max.y.max(world.y));
+        }
+        Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+}
+
 impl Camera3D {
     pub fn camera_type(&self) -> crate::consts::CameraType {
         unsafe { std::mem::transmute(self.type_.clone()) }
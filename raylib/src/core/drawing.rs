@@ -1,5 +1,5 @@
 //! Contains code related to drawing. Types that can be set as a surface to draw will implement the [`RaylibDraw`] trait
-use crate::core::camera::Camera3D;
+use crate::core::camera::{Camera2D, Camera3D};
 use crate::core::math::Ray;
 use crate::core::math::Vector2;
 
@@ -27,6 +27,7 @@ pub struct RaylibDrawHandle<'a>(&'a mut RaylibHandle);
 
 impl<'a> Drop for RaylibDrawHandle<'a> {
     fn drop(&mut self) {
+        crate::core::replay::end_frame();
         unsafe {
             ffi::EndDrawing();
         }
@@ -276,9 +277,89 @@ impl<D: RaylibDraw> RaylibScissorModeExt for D {}
 impl<'a, T> RaylibDraw for RaylibScissorMode<'a, T> {}
 impl<'a, T: RaylibDraw3D> RaylibDraw3D for RaylibScissorMode<'a, T> {}
 
+/// A drawing mode entered through [`RaylibDraw::begin_mode`].
+///
+/// [`RaylibDraw::begin_mode`]: trait.RaylibDraw.html#method.begin_mode
+#[derive(Debug, Clone, Copy)]
+pub enum DrawMode {
+    Mode2D(Camera2D),
+    Mode3D(Camera3D),
+    Blend(crate::consts::BlendMode),
+    Scissor {
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+}
+
+/// What has to be known about a [`DrawMode`] to leave it.
+///
+/// [`DrawMode`]: enum.DrawMode.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawModeKind {
+    Mode2D,
+    Mode3D,
+    Blend,
+    Scissor,
+}
+
+impl DrawMode {
+    pub fn kind(&self) -> DrawModeKind {
+        match self {
+            DrawMode::Mode2D(_) => DrawModeKind::Mode2D,
+            DrawMode::Mode3D(_) => DrawModeKind::Mode3D,
+            DrawMode::Blend(_) => DrawModeKind::Blend,
+            DrawMode::Scissor { .. } => DrawModeKind::Scissor,
+        }
+    }
+}
+
 // Actual drawing functions
 
 pub trait RaylibDraw {
+    /// Enters `mode` until the matching `end_mode`.
+    ///
+    /// Unlike the `begin_*` guards, which always go straight to raylib, this goes through the surface, so generic
+    /// code can change modes on surfaces that don't draw immediately, such as a `DrawRecorder`.
+    fn begin_mode(&mut self, mode: DrawMode) {
+        unsafe {
+            match mode {
+                DrawMode::Mode2D(camera) => ffi::BeginMode2D(camera.into()),
+                DrawMode::Mode3D(camera) => ffi::BeginMode3D(camera.into()),
+                DrawMode::Blend(blend_mode) => ffi::BeginBlendMode((blend_mode as u32) as i32),
+                DrawMode::Scissor {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => ffi::BeginScissorMode(x, y, width, height),
+            }
+        }
+    }
+
+    /// Leaves a mode entered with `begin_mode`.
+    fn end_mode(&mut self, kind: DrawModeKind) {
+        unsafe {
+            match kind {
+                DrawModeKind::Mode2D => ffi::EndMode2D(),
+                DrawModeKind::Mode3D => ffi::EndMode3D(),
+                DrawModeKind::Blend => ffi::EndBlendMode(),
+                DrawModeKind::Scissor => ffi::EndScissorMode(),
+            }
+        }
+    }
+
+    /// Calls `draw` with `mode` entered through `begin_mode`.
+    fn draw_in_mode(&mut self, mode: DrawMode, draw: impl FnOnce(&mut Self))
+    where
+        Self: Sized,
+    {
+        self.begin_mode(mode);
+        draw(self);
+        self.end_mode(mode.kind());
+    }
+
     /// Sets background color (framebuffer clear color).
     #[inline]
     fn clear_background(&mut self, color: impl Into<ffi::Color>) {
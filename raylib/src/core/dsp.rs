@@ -0,0 +1,640 @@
+//! Offline processing of [`Wave`] data on the CPU
+//!
+//! These operations decode the wave to `f32`, work on the interleaved samples and store the result back in the
+//! wave's own sample size, so an 8 bit wave stays 8 bit:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run() -> Result<(), String> {
+//! let mut voice = Wave::load_wave("line.wav")?;
+//! voice.trim_silence(0.01);
+//! voice.resample(48000);
+//! voice.set_channels(2);
+//! voice.high_pass(80.0, 0.707);
+//! voice.pad(1.5);
+//! voice.reverb(0.6, 0.5, 0.2);
+//! voice.normalize_peak(0.9);
+//! voice.fade_out(0.25);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Biquad`] filters also work on any interleaved buffer, such as the ones filled by a
+//! [`Source`](../procedural/trait.Source.html).
+//!
+//! [`Wave`]: ../audio/struct.Wave.html
+//! [`Biquad`]: struct.Biquad.html
+use crate::core::audio::{AudioSample, Wave};
+use crate::core::procedural::{encode_samples, remix_frame};
+use crate::ffi;
+use std::f64::consts::PI;
+
+/// Zero crossings of the resampling kernel on each side.
+const SINC_ZEROS: f64 = 16.0;
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Blackman window over `-1..1`.
+fn blackman(x: f64) -> f64 {
+    if x.abs() >= 1.0 {
+        0.0
+    } else {
+        let t = (x + 1.0) * 0.5;
+        0.42 - 0.5 * (2.0 * PI * t).cos() + 0.08 * (4.0 * PI * t).cos()
+    }
+}
+
+/// Resamples interleaved `samples` with a windowed sinc, low passed below the lower of the two rates.
+pub(crate) fn resample(samples: &[f32], channels: usize, from: u32, to: u32) -> Vec<f32> {
+    let frames = samples.len() / channels;
+    if from == to || frames == 0 {
+        return samples.to_vec();
+    }
+    let ratio = to as f64 / from as f64;
+    let cutoff = ratio.min(1.0);
+    let width = SINC_ZEROS / cutoff;
+    let out_frames = (frames as f64 * ratio).round() as usize;
+    let mut out = vec![0.0; out_frames * channels];
+    let mut sums = vec![0.0f64; channels];
+    for (j, frame) in out.chunks_mut(channels).enumerate() {
+        let t = j as f64 / ratio;
+        let first = (t - width).ceil().max(0.0) as usize;
+        let last = ((t + width).floor() as usize).min(frames - 1);
+        let mut total = 0.0;
+        sums.iter_mut().for_each(|s| *s = 0.0);
+        for i in first..=last {
+            let x = t - i as f64;
+            let weight = sinc(cutoff * x) * blackman(x / width);
+            total += weight;
+            for (c, sum) in sums.iter_mut().enumerate() {
+                *sum += samples[i * channels + c] as f64 * weight;
+            }
+        }
+        // dividing by the weights keeps the gain right near the ends
+        for (sample, sum) in frame.iter_mut().zip(&sums) {
+            *sample = if total.abs() > 1e-9 {
+                (sum / total) as f32
+            } else {
+                0.0
+            };
+        }
+    }
+    out
+}
+
+/// A second order IIR filter, with the coefficients from the Audio EQ Cookbook. Each channel keeps its own state,
+/// so a filter can process a stream one buffer at a time.
+#[derive(Debug, Clone)]
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    /// x1, x2, y1, y2 of each channel.
+    state: Vec<[f32; 4]>,
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Biquad {
+        Biquad {
+            b0: (b[0] / a[0]) as f32,
+            b1: (b[1] / a[0]) as f32,
+            b2: (b[2] / a[0]) as f32,
+            a1: (a[1] / a[0]) as f32,
+            a2: (a[2] / a[0]) as f32,
+            state: Vec::new(),
+        }
+    }
+
+    fn omega(sample_rate: u32, cutoff: f32, q: f32) -> (f64, f64) {
+        let w = 2.0 * PI * (cutoff as f64 / sample_rate as f64).clamp(1e-5, 0.499);
+        (w.cos(), w.sin() / (2.0 * (q as f64).max(1e-3)))
+    }
+
+    /// Passes frequencies below `cutoff` Hz. A `q` of `0.707` gives the flattest response, higher values resonate.
+    pub fn low_pass(sample_rate: u32, cutoff: f32, q: f32) -> Biquad {
+        let (cos, alpha) = Biquad::omega(sample_rate, cutoff, q);
+        Biquad::new(
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    /// Passes frequencies above `cutoff` Hz.
+    pub fn high_pass(sample_rate: u32, cutoff: f32, q: f32) -> Biquad {
+        let (cos, alpha) = Biquad::omega(sample_rate, cutoff, q);
+        Biquad::new(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    /// Filters interleaved `samples` of `channels` channels in place.
+    pub fn process(&mut self, samples: &mut [f32], channels: usize) {
+        if self.state.len() != channels {
+            self.state = vec![[0.0; 4]; channels];
+        }
+        for frame in samples.chunks_mut(channels) {
+            for (x, s) in frame.iter_mut().zip(self.state.iter_mut()) {
+                let y = self.b0 * *x + self.b1 * s[0] + self.b2 * s[1]
+                    - self.a1 * s[2]
+                    - self.a2 * s[3];
+                *s = [*x, s[0], y, s[2]];
+                *x = y;
+            }
+        }
+    }
+
+    /// Forgets the previous samples.
+    pub fn reset(&mut self) {
+        self.state.clear();
+    }
+}
+
+/// Adds copies of the signal every `delay` frames, each `feedback` times quieter, at `mix` level.
+fn echo(samples: &mut [f32], channels: usize, delay: usize, feedback: f32, mix: f32) {
+    let offset = delay * channels;
+    if offset == 0 || offset >= samples.len() {
+        return;
+    }
+    // the echoes alone, fed back into themselves
+    let mut wet = vec![0.0; samples.len()];
+    for i in offset..samples.len() {
+        wet[i] = samples[i - offset] + feedback * wet[i - offset];
+    }
+    for (s, w) in samples.iter_mut().zip(wet) {
+        *s += w * mix;
+    }
+}
+
+/// Comb filter of a reverb, with a low pass in its feedback.
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    store: f32,
+}
+
+impl Comb {
+    fn process(&mut self, input: f32, feedback: f32, damp: f32) -> f32 {
+        let output = self.buffer[self.index];
+        self.store = output * (1.0 - damp) + self.store * damp;
+        self.buffer[self.index] = input + self.store * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        output
+    }
+}
+
+struct AllPass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl AllPass {
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.index];
+        self.buffer[self.index] = input + delayed * 0.5;
+        self.index = (self.index + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+/// Freeverb's delay lengths at 44100 Hz.
+const COMB_TUNING: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+/// Extra delay of each channel after the first, so stereo reverb sounds wide.
+const STEREO_SPREAD: usize = 23;
+
+/// Schroeder reverb in the style of Freeverb, one network per channel.
+fn reverb(
+    samples: &mut [f32],
+    channels: usize,
+    sample_rate: u32,
+    room_size: f32,
+    damping: f32,
+    mix: f32,
+) {
+    let scale = |length: usize, channel: usize| {
+        ((length + channel * STEREO_SPREAD) as f64 * sample_rate as f64 / 44100.0).max(1.0) as usize
+    };
+    let feedback = room_size.clamp(0.0, 1.0) * 0.28 + 0.7;
+    let damp = damping.clamp(0.0, 1.0) * 0.4;
+    for channel in 0..channels {
+        let mut combs: Vec<Comb> = COMB_TUNING
+            .iter()
+            .map(|&l| Comb {
+                buffer: vec![0.0; scale(l, channel)],
+                index: 0,
+                store: 0.0,
+            })
+            .collect();
+        let mut allpasses: Vec<AllPass> = ALLPASS_TUNING
+            .iter()
+            .map(|&l| AllPass {
+                buffer: vec![0.0; scale(l, channel)],
+                index: 0,
+            })
+            .collect();
+        for sample in samples.iter_mut().skip(channel).step_by(channels) {
+            let input = *sample * 0.015;
+            let mut wet: f32 = combs
+                .iter_mut()
+                .map(|c| c.process(input, feedback, damp))
+                .sum();
+            for allpass in allpasses.iter_mut() {
+                wet = allpass.process(wet);
+            }
+            *sample = *sample * (1.0 - mix) + wet * 3.0 * mix;
+        }
+    }
+}
+
+impl Wave {
+    /// Creates a 32 bit float wave holding a copy of interleaved `samples`.
+    ///
+    /// # Panics
+    ///
+    /// If `channels` is zero or `samples` doesn't hold a whole number of frames.
+    pub fn from_samples(samples: &[f32], sample_rate: u32, channels: u32) -> Wave {
+        assert!(
+            channels > 0
+                && samples
+                    .chunks_exact(channels as usize)
+                    .remainder()
+                    .is_empty(),
+            "{} samples don't make whole frames of {} channels",
+            samples.len(),
+            channels
+        );
+        unsafe {
+            let data = libc::malloc(samples.len().max(1) * std::mem::size_of::<f32>()) as *mut f32;
+            std::ptr::copy_nonoverlapping(samples.as_ptr(), data, samples.len());
+            Wave::from_raw(ffi::Wave {
+                sampleCount: samples.len() as u32,
+                sampleRate: sample_rate,
+                sampleSize: 32,
+                channels,
+                data: data as *mut std::os::raw::c_void,
+            })
+        }
+    }
+
+    /// Number of frames, each holding one sample per channel.
+    pub fn frame_count(&self) -> u32 {
+        self.0.sampleCount / self.0.channels.max(1)
+    }
+
+    /// Length in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frame_count() as f32 / self.0.sampleRate.max(1) as f32
+    }
+
+    /// Samples as `f32`, read in Rust rather than through raylib.
+    pub(crate) fn samples(&self) -> Vec<f32> {
+        let len = self.0.sampleCount as usize;
+        if self.0.data.is_null() || len == 0 {
+            return Vec::new();
+        }
+        unsafe {
+            match self.0.sampleSize {
+                8 => std::slice::from_raw_parts(self.0.data as *const u8, len)
+                    .iter()
+                    .map(|&s| s.to_f32())
+                    .collect(),
+                16 => std::slice::from_raw_parts(self.0.data as *const i16, len)
+                    .iter()
+                    .map(|&s| s.to_f32())
+                    .collect(),
+                _ => std::slice::from_raw_parts(self.0.data as *const f32, len).to_vec(),
+            }
+        }
+    }
+
+    /// Replaces the wave data, keeping the sample size.
+    fn set_samples(&mut self, samples: &[f32], sample_rate: u32, channels: u32) {
+        let mut bytes = Vec::new();
+        encode_samples(samples, self.0.sampleSize, &mut bytes);
+        unsafe {
+            let data = libc::malloc(bytes.len().max(1)) as *mut u8;
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len());
+            libc::free(self.0.data);
+            self.0.data = data as *mut std::os::raw::c_void;
+        }
+        self.0.sampleCount = samples.len() as u32;
+        self.0.sampleRate = sample_rate;
+        self.0.channels = channels;
+    }
+
+    /// Runs `f` over the samples and stores what it leaves.
+    fn process(&mut self, f: impl FnOnce(&mut Vec<f32>, usize, u32)) {
+        let mut samples = self.samples();
+        let (rate, channels) = (self.0.sampleRate, self.0.channels);
+        f(&mut samples, channels.max(1) as usize, rate);
+        self.set_samples(&samples, rate, channels);
+    }
+
+    /// Samples converted to `sample_rate` and `channels`, for combining with this wave.
+    fn samples_as(&self, sample_rate: u32, channels: u32) -> Vec<f32> {
+        let own = self.0.channels.max(1) as usize;
+        let samples = self.samples();
+        let mut remixed = vec![0.0; samples.len() / own * channels as usize];
+        for (from, to) in samples
+            .chunks(own)
+            .zip(remixed.chunks_mut(channels as usize))
+        {
+            remix_frame(from, to);
+        }
+        resample(
+            &remixed,
+            channels as usize,
+            self.0.sampleRate.max(1),
+            sample_rate,
+        )
+    }
+
+    /// Converts to `sample_rate` with a windowed sinc filter, which keeps the frequencies both rates can hold
+    /// without aliasing.
+    pub fn resample(&mut self, sample_rate: u32) {
+        if sample_rate == 0 || sample_rate == self.0.sampleRate {
+            return;
+        }
+        let samples = self.samples_as(sample_rate, self.0.channels.max(1));
+        let channels = self.0.channels;
+        self.set_samples(&samples, sample_rate, channels);
+    }
+
+    /// Converts to `channels` channels. Mono is copied to every channel and mixing down to mono averages.
+    pub fn set_channels(&mut self, channels: u32) {
+        if channels == 0 || channels == self.0.channels {
+            return;
+        }
+        let samples = self.samples_as(self.0.sampleRate, channels);
+        let rate = self.0.sampleRate;
+        self.set_samples(&samples, rate, channels);
+    }
+
+    /// Highest absolute sample value.
+    pub fn peak(&self) -> f32 {
+        self.samples().iter().fold(0.0, |peak, s| s.abs().max(peak))
+    }
+
+    /// Root mean square of the samples, the average level.
+    pub fn rms(&self) -> f32 {
+        let samples = self.samples();
+        if samples.is_empty() {
+            return 0.0;
+        }
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// Multiplies every sample by `gain`.
+    pub fn amplify(&mut self, gain: f32) {
+        self.process(|samples, _, _| samples.iter_mut().for_each(|s| *s *= gain));
+    }
+
+    /// Scales the wave so its peak is `peak`. Silence is left alone.
+    pub fn normalize_peak(&mut self, peak: f32) {
+        let current = self.peak();
+        if current > 0.0 {
+            self.amplify(peak / current);
+        }
+    }
+
+    /// Scales the wave so its RMS level is `rms`. Loud waves may clip, see [`peak`](#method.peak).
+    pub fn normalize_rms(&mut self, rms: f32) {
+        let current = self.rms();
+        if current > 0.0 {
+            self.amplify(rms / current);
+        }
+    }
+
+    /// Ramps the volume up from silence over the first `seconds`.
+    pub fn fade_in(&mut self, seconds: f32) {
+        self.process(|samples, channels, rate| {
+            let frames = (seconds * rate as f32) as usize;
+            for (i, frame) in samples.chunks_mut(channels).take(frames).enumerate() {
+                let gain = i as f32 / frames as f32;
+                frame.iter_mut().for_each(|s| *s *= gain);
+            }
+        });
+    }
+
+    /// Ramps the volume down to silence over the last `seconds`.
+    pub fn fade_out(&mut self, seconds: f32) {
+        self.process(|samples, channels, rate| {
+            let frames = (seconds * rate as f32) as usize;
+            for (i, frame) in samples.chunks_mut(channels).rev().take(frames).enumerate() {
+                let gain = i as f32 / frames as f32;
+                frame.iter_mut().for_each(|s| *s *= gain);
+            }
+        });
+    }
+
+    /// Plays the wave backwards.
+    pub fn reverse(&mut self) {
+        self.process(|samples, channels, _| {
+            let mut frames: Vec<&[f32]> = samples.chunks(channels).collect();
+            frames.reverse();
+            *samples = frames.concat();
+        });
+    }
+
+    /// Appends `other`, converted to this wave's rate and channels.
+    pub fn concat(&mut self, other: &Wave) {
+        let tail = other.samples_as(self.0.sampleRate, self.0.channels.max(1));
+        self.process(|samples, _, _| samples.extend(tail));
+    }
+
+    /// Adds `seconds` of silence at the end, room for the tail of an echo or reverb.
+    pub fn pad(&mut self, seconds: f32) {
+        self.process(|samples, channels, rate| {
+            let frames = (seconds.max(0.0) * rate as f32) as usize;
+            samples.resize(samples.len() + frames * channels, 0.0);
+        });
+    }
+
+    /// Mixes in `other` times `gain`, starting `offset` seconds in. The wave grows if `other` ends after it.
+    pub fn mix(&mut self, other: &Wave, gain: f32, offset: f32) {
+        let added = other.samples_as(self.0.sampleRate, self.0.channels.max(1));
+        self.process(|samples, channels, rate| {
+            let start = (offset.max(0.0) * rate as f32) as usize * channels;
+            if samples.len() < start + added.len() {
+                samples.resize(start + added.len(), 0.0);
+            }
+            for (s, a) in samples[start..].iter_mut().zip(added) {
+                *s += a * gain;
+            }
+        });
+    }
+
+    /// Removes the frames at both ends whose samples are all quieter than `threshold`.
+    pub fn trim_silence(&mut self, threshold: f32) {
+        self.process(|samples, channels, _| {
+            let loud = |frame: &[f32]| frame.iter().any(|s| s.abs() >= threshold);
+            let frames: Vec<&[f32]> = samples.chunks(channels).collect();
+            let start = frames.iter().position(|f| loud(f)).unwrap_or(frames.len());
+            let end = frames
+                .iter()
+                .rposition(|f| loud(f))
+                .map_or(start, |i| i + 1);
+            *samples = frames[start..end].concat();
+        });
+    }
+
+    /// Removes frequencies above `cutoff` Hz, see [`Biquad::low_pass`](struct.Biquad.html#method.low_pass).
+    pub fn low_pass(&mut self, cutoff: f32, q: f32) {
+        let mut filter = Biquad::low_pass(self.0.sampleRate, cutoff, q);
+        self.process(|samples, channels, _| filter.process(samples, channels));
+    }
+
+    /// Removes frequencies below `cutoff` Hz, see [`Biquad::high_pass`](struct.Biquad.html#method.high_pass).
+    pub fn high_pass(&mut self, cutoff: f32, q: f32) {
+        let mut filter = Biquad::high_pass(self.0.sampleRate, cutoff, q);
+        self.process(|samples, channels, _| filter.process(samples, channels));
+    }
+
+    /// Repeats the sound every `delay` seconds, each repeat `feedback` times the previous one, mixed in at `mix`.
+    /// Echoes past the end are cut, [`pad`](#method.pad) the wave first to keep them.
+    pub fn echo(&mut self, delay: f32, feedback: f32, mix: f32) {
+        self.process(|samples, channels, rate| {
+            let frames = (delay.max(0.0) * rate as f32) as usize;
+            echo(samples, channels, frames, feedback, mix)
+        });
+    }
+
+    /// Adds room reverberation. `room_size` and `damping` go from `0.0` to `1.0`, bigger rooms ring longer and damped
+    /// ones lose their highs sooner. `mix` is the share of reverberated sound. The tail is cut at the end of the
+    /// wave, [`pad`](#method.pad) it first to keep it.
+    pub fn reverb(&mut self, room_size: f32, damping: f32, mix: f32) {
+        self.process(|samples, channels, rate| {
+            reverb(
+                samples,
+                channels,
+                rate,
+                room_size,
+                damping,
+                mix.clamp(0.0, 1.0),
+            )
+        });
+    }
+}
+
+#[cfg(test)]
+mod dsp_test {
+    use super::*;
+
+    fn sine(frequency: f32, rate: u32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|i| (i as f32 / rate as f32 * frequency * std::f32::consts::PI * 2.0).sin())
+            .collect()
+    }
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-4, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_wave_edits() {
+        let mut wave = Wave::from_samples(&[0.0, 0.5, 1.0, -1.0], 4, 2);
+        assert_eq!(wave.frame_count(), 2);
+        assert_eq!(wave.duration(), 0.5);
+        wave.reverse();
+        assert_close(&wave.samples(), &[1.0, -1.0, 0.0, 0.5]);
+        wave.set_channels(1);
+        assert_close(&wave.samples(), &[0.0, 0.25]);
+        wave.normalize_peak(0.5);
+        assert_close(&wave.samples(), &[0.0, 0.5]);
+
+        let other = Wave::from_samples(&[0.2, 0.2, 0.4, 0.4], 4, 2);
+        wave.concat(&other);
+        assert_close(&wave.samples(), &[0.0, 0.5, 0.2, 0.4]);
+        wave.mix(&other, 0.5, 0.75);
+        assert_close(&wave.samples(), &[0.0, 0.5, 0.2, 0.5, 0.2]);
+
+        let mut ramp = Wave::from_samples(&[1.0; 4], 4, 1);
+        ramp.fade_in(0.5);
+        ramp.fade_out(0.5);
+        assert_close(&ramp.samples(), &[0.0, 0.5, 0.5, 0.0]);
+
+        let mut quiet = Wave::from_samples(&[0.0, 0.001, 0.3, 0.0, -0.2, 0.0], 6, 1);
+        quiet.trim_silence(0.01);
+        assert_close(&quiet.samples(), &[0.3, 0.0, -0.2]);
+        quiet.pad(0.5);
+        assert_eq!(quiet.frame_count(), 6);
+
+        let mut level = Wave::from_samples(&[0.5, -0.5], 2, 1);
+        level.normalize_rms(0.25);
+        assert_close(&level.samples(), &[0.25, -0.25]);
+
+        // 16 bit waves stay 16 bit
+        let mut short = Wave::from_samples(&[0.5], 1, 1);
+        short.0.sampleSize = 16;
+        short.set_samples(&[0.5, -0.5], 1, 1);
+        assert_eq!(short.sample_size(), 16);
+        assert_close(&short.samples(), &[0.5, -0.5]);
+    }
+
+    #[test]
+    fn test_resample() {
+        let tone = sine(440.0, 8000, 8000);
+        let mut wave = Wave::from_samples(&tone, 8000, 1);
+        wave.resample(16000);
+        assert_eq!(wave.frame_count(), 16000);
+        assert_eq!(wave.smaple_rate(), 16000);
+        // away from the edges the tone matches one generated at the new rate
+        let expected = sine(440.0, 16000, 16000);
+        let samples = wave.samples();
+        for i in 100..15900 {
+            assert!((samples[i] - expected[i]).abs() < 0.01, "frame {}", i);
+        }
+
+        // frequencies the lower rate can't hold are removed rather than aliased
+        let mut high = Wave::from_samples(&sine(6000.0, 16000, 16000), 16000, 1);
+        high.resample(8000);
+        assert_eq!(high.frame_count(), 8000);
+        let samples = high.samples();
+        assert!(samples[100..7900].iter().all(|s| s.abs() < 0.05));
+    }
+
+    #[test]
+    fn test_effects() {
+        let mut low = Wave::from_samples(&sine(4000.0, 16000, 4000), 16000, 1);
+        low.low_pass(200.0, 0.707);
+        assert!(low.samples()[1000..].iter().all(|s| s.abs() < 0.05));
+        let mut kept = Wave::from_samples(&sine(50.0, 16000, 4000), 16000, 1);
+        kept.low_pass(2000.0, 0.707);
+        assert!(kept.peak() > 0.95);
+
+        let mut high = Wave::from_samples(&vec![1.0; 4000], 16000, 1);
+        high.high_pass(100.0, 0.707);
+        assert!(high.samples()[2000..].iter().all(|s| s.abs() < 0.01));
+
+        let mut click = vec![0.0; 10];
+        click[0] = 1.0;
+        let mut echoed = Wave::from_samples(&click, 10, 1);
+        echoed.echo(0.3, 0.5, 1.0);
+        assert_close(
+            &echoed.samples(),
+            &[1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.25],
+        );
+
+        let mut click = vec![0.0; 44100];
+        click[0] = 1.0;
+        let mut room = Wave::from_samples(&click, 44100, 1);
+        room.reverb(0.8, 0.5, 0.5);
+        let samples = room.samples();
+        // the dry click, then a tail after the first comb delay
+        assert_eq!(samples[0], 0.5);
+        assert!(samples[1..1116].iter().all(|s| *s == 0.0));
+        assert!(samples[1116..].iter().any(|s| s.abs() > 1e-4));
+    }
+}
@@ -0,0 +1,671 @@
+//! Spatial filters on [`Image`], computed on the CPU
+//!
+//! These work on the pixels in memory and never touch the GPU, so they can run before a window exists, in build
+//! scripts or in tests. Images that aren't R8G8B8A8 are converted for filtering and converted back afterwards.
+//!
+//! Filters that mix neighbouring pixels weight colors by their alpha, so the color of fully transparent pixels
+//! doesn't bleed into the edges of a sprite.
+//!
+//! [`Image`]: ../texture/struct.Image.html
+use crate::core::color::Color;
+use crate::core::texture::Image;
+use crate::ffi;
+
+/// Weights of a convolution, laid out row by row around a center pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kernel {
+    width: usize,
+    height: usize,
+    weights: Vec<f32>,
+}
+
+impl Kernel {
+    /// Creates a kernel from row-major `weights`. Both dimensions must be odd so the kernel has a center.
+    pub fn new(width: usize, height: usize, weights: Vec<f32>) -> Kernel {
+        assert!(
+            width % 2 == 1 && height % 2 == 1,
+            "kernel dimensions must be odd"
+        );
+        assert_eq!(
+            weights.len(),
+            width * height,
+            "kernel needs width * height weights"
+        );
+        Kernel {
+            width,
+            height,
+            weights,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Scales the weights to sum to one, so the kernel keeps the overall brightness. Kernels summing to zero are
+    /// returned unchanged.
+    pub fn normalized(mut self) -> Kernel {
+        let sum: f32 = self.weights.iter().sum();
+        if sum != 0.0 {
+            self.weights.iter_mut().for_each(|w| *w /= sum);
+        }
+        self
+    }
+
+    /// Averages a square of `2 * radius + 1` pixels.
+    pub fn box_blur(radius: usize) -> Kernel {
+        let size = 2 * radius + 1;
+        Kernel::new(size, size, vec![1.0; size * size]).normalized()
+    }
+
+    /// Gaussian blur with standard deviation `sigma`, in pixels.
+    pub fn gaussian(sigma: f32) -> Kernel {
+        let line = gaussian_weights(sigma);
+        let size = line.len();
+        let weights = line
+            .iter()
+            .flat_map(|y| line.iter().map(move |x| x * y))
+            .collect();
+        Kernel::new(size, size, weights)
+    }
+
+    pub fn sharpen() -> Kernel {
+        Kernel::new(3, 3, vec![0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0])
+    }
+
+    pub fn emboss() -> Kernel {
+        Kernel::new(3, 3, vec![-2.0, -1.0, 0.0, -1.0, 1.0, 1.0, 0.0, 1.0, 2.0])
+    }
+
+    /// Edge detection kernel, which is zero over flat areas.
+    pub fn laplacian() -> Kernel {
+        Kernel::new(
+            3,
+            3,
+            vec![-1.0, -1.0, -1.0, -1.0, 8.0, -1.0, -1.0, -1.0, -1.0],
+        )
+    }
+}
+
+/// One dimensional gaussian weights covering three standard deviations on each side, summing to one.
+fn gaussian_weights(sigma: f32) -> Vec<f32> {
+    if sigma <= 0.0 {
+        return vec![1.0];
+    }
+    let radius = (sigma * 3.0).ceil() as i32;
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = weights.iter().sum();
+    weights.into_iter().map(|w| w / sum).collect()
+}
+
+/// Pixels with premultiplied alpha, in the `0.0..=255.0` range.
+struct Buffer {
+    width: usize,
+    height: usize,
+    data: Vec<[f32; 4]>,
+}
+
+impl Buffer {
+    fn new(width: usize, height: usize) -> Buffer {
+        Buffer {
+            width,
+            height,
+            data: vec![[0.0; 4]; width * height],
+        }
+    }
+
+    fn from_colors(width: usize, height: usize, colors: &[Color]) -> Buffer {
+        let data = colors
+            .iter()
+            .map(|c| {
+                let a = c.a as f32 / 255.0;
+                [c.r as f32 * a, c.g as f32 * a, c.b as f32 * a, c.a as f32]
+            })
+            .collect();
+        Buffer {
+            width,
+            height,
+            data,
+        }
+    }
+
+    fn to_colors(&self) -> Vec<Color> {
+        self.data
+            .iter()
+            .map(|p| {
+                let a = p[3].clamp(0.0, 255.0);
+                if a.round() == 0.0 {
+                    return Color::new(0, 0, 0, 0);
+                }
+                let c = |v: f32| (v * 255.0 / a).round().clamp(0.0, 255.0) as u8;
+                Color::new(c(p[0]), c(p[1]), c(p[2]), a.round() as u8)
+            })
+            .collect()
+    }
+
+    /// Pixel at `(x, y)`, with coordinates clamped to the edges.
+    fn clamped(&self, x: isize, y: isize) -> [f32; 4] {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.data[y * self.width + x]
+    }
+
+    fn convolve(&self, kernel: &Kernel) -> Buffer {
+        // Kernels that don't sum to one, like edge detection, would also scale alpha: those filter the straight
+        // colors and keep the alpha of the source
+        let normalized = (kernel.weights.iter().sum::<f32>() - 1.0).abs() < 1e-4;
+        let mut out = Buffer::new(self.width, self.height);
+        let (rx, ry) = ((kernel.width / 2) as isize, (kernel.height / 2) as isize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let mut sum = [0.0; 4];
+                for (i, w) in kernel.weights.iter().enumerate() {
+                    if *w == 0.0 {
+                        continue;
+                    }
+                    let kx = (i % kernel.width) as isize - rx;
+                    let ky = (i / kernel.width) as isize - ry;
+                    let mut p = self.clamped(x as isize + kx, y as isize + ky);
+                    if !normalized && p[3] > 0.0 {
+                        for c in 0..3 {
+                            p[c] *= 255.0 / p[3];
+                        }
+                    }
+                    for c in 0..4 {
+                        sum[c] += p[c] * w;
+                    }
+                }
+                if !normalized {
+                    let a = self.data[y * self.width + x][3];
+                    sum = [
+                        sum[0] * a / 255.0,
+                        sum[1] * a / 255.0,
+                        sum[2] * a / 255.0,
+                        a,
+                    ];
+                }
+                out.data[y * self.width + x] = sum;
+            }
+        }
+        out
+    }
+
+    /// Applies the same one dimensional `weights` horizontally then vertically.
+    fn convolve_separable(&self, weights: &[f32]) -> Buffer {
+        let radius = (weights.len() / 2) as isize;
+        let pass = |src: &Buffer, horizontal: bool| {
+            let mut out = Buffer::new(src.width, src.height);
+            for y in 0..src.height {
+                for x in 0..src.width {
+                    let mut sum = [0.0; 4];
+                    for (i, w) in weights.iter().enumerate() {
+                        let o = i as isize - radius;
+                        let p = if horizontal {
+                            src.clamped(x as isize + o, y as isize)
+                        } else {
+                            src.clamped(x as isize, y as isize + o)
+                        };
+                        for c in 0..4 {
+                            sum[c] += p[c] * w;
+                        }
+                    }
+                    out.data[y * src.width + x] = sum;
+                }
+            }
+            out
+        };
+        let horizontal = pass(self, true);
+        pass(&horizontal, false)
+    }
+
+    /// Box blur with running sums, so the cost doesn't depend on the radius.
+    fn box_blur(&self, radius: usize) -> Buffer {
+        let r = radius as isize;
+        let size = (2 * radius + 1) as f32;
+        let pass = |src: &Buffer, horizontal: bool| {
+            let mut out = Buffer::new(src.width, src.height);
+            let (lines, len) = if horizontal {
+                (src.height, src.width)
+            } else {
+                (src.width, src.height)
+            };
+            let at = |line: usize, i: isize| {
+                if horizontal {
+                    src.clamped(i, line as isize)
+                } else {
+                    src.clamped(line as isize, i)
+                }
+            };
+            for line in 0..lines {
+                let mut sum = [0.0; 4];
+                for i in -r..=r {
+                    let p = at(line, i);
+                    for c in 0..4 {
+                        sum[c] += p[c];
+                    }
+                }
+                for i in 0..len {
+                    let index = if horizontal {
+                        line * src.width + i
+                    } else {
+                        i * src.width + line
+                    };
+                    out.data[index] = [sum[0] / size, sum[1] / size, sum[2] / size, sum[3] / size];
+                    let (leaving, entering) =
+                        (at(line, i as isize - r), at(line, i as isize + r + 1));
+                    for c in 0..4 {
+                        sum[c] += entering[c] - leaving[c];
+                    }
+                }
+            }
+            out
+        };
+        let horizontal = pass(self, true);
+        pass(&horizontal, false)
+    }
+}
+
+/// Composites `src` over `dst`.
+pub(crate) fn over(src: Color, dst: Color) -> Color {
+    let sa = src.a as f32 / 255.0;
+    let da = dst.a as f32 / 255.0;
+    let a = sa + da * (1.0 - sa);
+    if a <= 0.0 {
+        return Color::new(0, 0, 0, 0);
+    }
+    let mix = |s: u8, d: u8| {
+        ((s as f32 * sa + d as f32 * da * (1.0 - sa)) / a)
+            .round()
+            .clamp(0.0, 255.0) as u8
+    };
+    Color::new(
+        mix(src.r, dst.r),
+        mix(src.g, dst.g),
+        mix(src.b, dst.b),
+        (a * 255.0).round() as u8,
+    )
+}
+
+/// Offsets within a disk of `radius` pixels.
+fn disk(radius: u32) -> Vec<(isize, isize)> {
+    let r = radius as isize;
+    let limit = (radius as f32 + 0.5).powi(2);
+    let mut offsets = Vec::new();
+    for dy in -r..=r {
+        for dx in -r..=r {
+            if ((dx * dx + dy * dy) as f32) <= limit {
+                offsets.push((dx, dy));
+            }
+        }
+    }
+    offsets
+}
+
+impl Image {
+    fn buffer(&self) -> Buffer {
+        Buffer::from_colors(
+            self.width() as usize,
+            self.height() as usize,
+            &self.colors(),
+        )
+    }
+
+    fn set_buffer(&mut self, buffer: &Buffer) {
+        self.set_colors(
+            buffer.width as i32,
+            buffer.height as i32,
+            &buffer.to_colors(),
+        );
+    }
+
+    /// Convolves the image with `kernel`. Pixels past the edges repeat the border. Kernels whose weights don't sum to
+    /// one leave the alpha channel as it was.
+    pub fn convolve(&mut self, kernel: &Kernel) {
+        let filtered = self.buffer().convolve(kernel);
+        self.set_buffer(&filtered);
+    }
+
+    /// Gaussian blur with standard deviation `sigma`, in pixels. Applied as two one dimensional passes.
+    pub fn gaussian_blur(&mut self, sigma: f32) {
+        let filtered = self.buffer().convolve_separable(&gaussian_weights(sigma));
+        self.set_buffer(&filtered);
+    }
+
+    /// Averages each pixel with its neighbours up to `radius` pixels away. Runs in constant time per pixel
+    /// whatever the radius.
+    pub fn box_blur(&mut self, radius: u32) {
+        let filtered = self.buffer().box_blur(radius as usize);
+        self.set_buffer(&filtered);
+    }
+
+    /// Sharpens by adding back `amount` times the difference between the image and a gaussian blur of it. Pixels
+    /// whose difference is at most `threshold` on every channel are left alone, to avoid sharpening noise.
+    pub fn unsharp_mask(&mut self, sigma: f32, amount: f32, threshold: u8) {
+        let mut image = self.buffer();
+        let blurred = image.convolve_separable(&gaussian_weights(sigma));
+        for (p, b) in image.data.iter_mut().zip(&blurred.data) {
+            let difference = (0..4).map(|c| (p[c] - b[c]).abs()).fold(0.0, f32::max);
+            if difference > threshold as f32 {
+                for c in 0..4 {
+                    p[c] += amount * (p[c] - b[c]);
+                }
+                // keep the result a valid premultiplied color
+                p[3] = p[3].clamp(0.0, 255.0);
+                for c in 0..3 {
+                    p[c] = p[c].clamp(0.0, p[3]);
+                }
+            }
+        }
+        self.set_buffer(&image);
+    }
+
+    /// Replaces the image with the magnitude of its luminance gradient, as opaque grayscale. Transparent areas count
+    /// as black, so the outline of a sprite shows up as an edge.
+    pub fn sobel_edges(&mut self) {
+        let image = self.buffer();
+        let luma = |x: isize, y: isize| {
+            let p = image.clamped(x, y);
+            0.299 * p[0] + 0.587 * p[1] + 0.114 * p[2]
+        };
+        let mut edges = Vec::with_capacity(image.data.len());
+        for y in 0..image.height as isize {
+            for x in 0..image.width as isize {
+                let gx = luma(x + 1, y - 1) + 2.0 * luma(x + 1, y) + luma(x + 1, y + 1)
+                    - luma(x - 1, y - 1)
+                    - 2.0 * luma(x - 1, y)
+                    - luma(x - 1, y + 1);
+                let gy = luma(x - 1, y + 1) + 2.0 * luma(x, y + 1) + luma(x + 1, y + 1)
+                    - luma(x - 1, y - 1)
+                    - 2.0 * luma(x, y - 1)
+                    - luma(x + 1, y - 1);
+                // a step from black to white along one axis maps to white
+                let m = ((gx * gx + gy * gy).sqrt() / 4.0).round().clamp(0.0, 255.0) as u8;
+                edges.push(Color::new(m, m, m, 255));
+            }
+        }
+        self.set_colors(self.width(), self.height(), &edges);
+    }
+
+    /// Replaces each channel by its median over the square of `2 * radius + 1` pixels around it. Removes isolated
+    /// noisy pixels while keeping edges sharp.
+    pub fn median_filter(&mut self, radius: u32) {
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        let colors = self.colors();
+        let r = radius as isize;
+        let mut window: Vec<[u8; 4]> = Vec::with_capacity(((2 * r + 1) * (2 * r + 1)) as usize);
+        let mut channel = Vec::with_capacity(window.capacity());
+        let mut out = Vec::with_capacity(colors.len());
+        for y in 0..height {
+            for x in 0..width {
+                window.clear();
+                for wy in (y - r).max(0)..=(y + r).min(height - 1) {
+                    for wx in (x - r).max(0)..=(x + r).min(width - 1) {
+                        let c = colors[(wy * width + wx) as usize];
+                        window.push([c.r, c.g, c.b, c.a]);
+                    }
+                }
+                let mut median = [0u8; 4];
+                for (c, m) in median.iter_mut().enumerate() {
+                    channel.clear();
+                    channel.extend(window.iter().map(|p| p[c]));
+                    let middle = channel.len() / 2;
+                    *m = *channel.select_nth_unstable(middle).1;
+                }
+                out.push(Color::new(median[0], median[1], median[2], median[3]));
+            }
+        }
+        self.set_colors(self.width(), self.height(), &out);
+    }
+
+    /// Grows opaque areas: each pixel takes the highest alpha within `radius` pixels. Colors are left untouched.
+    pub fn dilate_alpha(&mut self, radius: u32) {
+        self.morphology(radius, true);
+    }
+
+    /// Shrinks opaque areas: each pixel takes the lowest alpha within `radius` pixels, counting the outside of the
+    /// image as transparent. Colors are left untouched.
+    pub fn erode_alpha(&mut self, radius: u32) {
+        self.morphology(radius, false);
+    }
+
+    fn morphology(&mut self, radius: u32, dilate: bool) {
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        let mut colors = self.colors();
+        let alpha: Vec<u8> = colors.iter().map(|c| c.a).collect();
+        let offsets = disk(radius);
+        for y in 0..height {
+            for x in 0..width {
+                let samples = offsets.iter().map(|(dx, dy)| {
+                    let (sx, sy) = (x + dx, y + dy);
+                    if sx < 0 || sy < 0 || sx >= width || sy >= height {
+                        0
+                    } else {
+                        alpha[(sy * width + sx) as usize]
+                    }
+                });
+                let a = if dilate { samples.max() } else { samples.min() };
+                colors[(y * width + x) as usize].a = a.unwrap_or(0);
+            }
+        }
+        self.set_colors(self.width(), self.height(), &colors);
+    }
+
+    /// Surrounds the opaque parts of the image with a `thickness` pixels wide outline. The image grows by
+    /// `thickness` on every side to make room for it.
+    pub fn outline(&mut self, thickness: u32, color: impl Into<ffi::Color>) {
+        let color: Color = color.into().into();
+        let t = thickness as i32;
+        let (width, height) = (self.width() + 2 * t, self.height() + 2 * t);
+        let content = self.placed(t, t, width, height);
+
+        let mut mask = Image::from_colors(width, height, &content);
+        mask.dilate_alpha(thickness);
+        let pixels: Vec<Color> = mask
+            .colors()
+            .iter()
+            .zip(&content)
+            .map(|(m, c)| {
+                let a = (m.a as u32 * color.a as u32 / 255) as u8;
+                over(*c, Color::new(color.r, color.g, color.b, a))
+            })
+            .collect();
+        self.set_colors(width, height, &pixels);
+    }
+
+    /// Casts a shadow of the image's opaque parts, moved by `offset_x` and `offset_y` and blurred with standard
+    /// deviation `blur`. The image grows to fit the shadow; the returned position is where the original image now
+    /// starts.
+    pub fn drop_shadow(
+        &mut self,
+        offset_x: i32,
+        offset_y: i32,
+        blur: f32,
+        color: impl Into<ffi::Color>,
+    ) -> (i32, i32) {
+        let color: Color = color.into().into();
+        let spread = (blur.max(0.0) * 3.0).ceil() as i32;
+        let left = (spread - offset_x).max(0);
+        let top = (spread - offset_y).max(0);
+        let width = self.width() + left + (spread + offset_x).max(0);
+        let height = self.height() + top + (spread + offset_y).max(0);
+        let content = self.placed(left, top, width, height);
+
+        let mut shadow = Image::from_colors(
+            width,
+            height,
+            &self
+                .placed(left + offset_x, top + offset_y, width, height)
+                .iter()
+                .map(|c| Color::new(color.r, color.g, color.b, c.a))
+                .collect::<Vec<_>>(),
+        );
+        if blur > 0.0 {
+            shadow.gaussian_blur(blur);
+        }
+        let pixels: Vec<Color> = shadow
+            .colors()
+            .iter()
+            .zip(&content)
+            .map(|(s, c)| {
+                let a = (s.a as u32 * color.a as u32 / 255) as u8;
+                over(*c, Color::new(color.r, color.g, color.b, a))
+            })
+            .collect();
+        self.set_colors(width, height, &pixels);
+        (left, top)
+    }
+
+    /// The image's pixels placed at `(x, y)` on a transparent canvas of `width` by `height`.
+    fn placed(&self, x: i32, y: i32, width: i32, height: i32) -> Vec<Color> {
+        let colors = self.colors();
+        let mut canvas = vec![Color::new(0, 0, 0, 0); (width * height) as usize];
+        for sy in 0..self.height() {
+            let ty = sy + y;
+            if ty < 0 || ty >= height {
+                continue;
+            }
+            for sx in 0..self.width() {
+                let tx = sx + x;
+                if tx < 0 || tx >= width {
+                    continue;
+                }
+                canvas[(ty * width + tx) as usize] = colors[(sy * self.width() + sx) as usize];
+            }
+        }
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod filter_test {
+    use super::*;
+
+    const CLEAR: Color = Color {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 0,
+    };
+
+    fn dot(size: i32, color: Color, background: Color) -> Image {
+        let mut pixels = vec![background; (size * size) as usize];
+        pixels[(size / 2 * size + size / 2) as usize] = color;
+        Image::from_colors(size, size, &pixels)
+    }
+
+    #[test]
+    fn test_convolve() {
+        let mut image = dot(3, Color::WHITE, Color::BLACK);
+        image.convolve(&Kernel::new(1, 1, vec![1.0]));
+        assert_eq!(image.colors(), dot(3, Color::WHITE, Color::BLACK).colors());
+
+        image.convolve(&Kernel::box_blur(1));
+        assert!(image
+            .colors()
+            .iter()
+            .all(|c| *c == Color::new(28, 28, 28, 255)));
+
+        let kernel = Kernel::gaussian(1.0);
+        assert_eq!(kernel.width(), 7);
+        assert!((kernel.weights().iter().sum::<f32>() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_convolve_keeps_alpha() {
+        // flat areas have no edges, but stay opaque
+        let mut flat = Image::from_colors(4, 4, &[Color::ORANGE; 16]);
+        flat.convolve(&Kernel::laplacian());
+        assert!(flat.colors().iter().all(|c| *c == Color::BLACK));
+
+        let mut image = dot(3, Color::WHITE, Color::BLACK);
+        image.convolve(&Kernel::laplacian());
+        let colors = image.colors();
+        assert_eq!(colors[4], Color::WHITE);
+        assert!(colors.iter().all(|c| c.a == 255));
+
+        // transparent pixels stay transparent, half transparent ones keep their alpha
+        let mut sprite = dot(3, Color::new(255, 0, 0, 128), CLEAR);
+        sprite.convolve(&Kernel::laplacian());
+        let colors = sprite.colors();
+        assert_eq!(colors[0], CLEAR);
+        assert_eq!(colors[4], Color::new(255, 0, 0, 128));
+    }
+
+    #[test]
+    fn test_blurs() {
+        let mut flat = Image::from_colors(4, 4, &[Color::ORANGE; 16]);
+        flat.gaussian_blur(1.5);
+        flat.box_blur(2);
+        assert!(flat.colors().iter().all(|c| *c == Color::ORANGE));
+
+        // transparent neighbours don't darken a blurred sprite
+        let mut sprite = dot(5, Color::RED, CLEAR);
+        sprite.box_blur(1);
+        let center = sprite.colors()[12];
+        assert_eq!((center.r, center.g, center.b), (230, 41, 55));
+        assert_eq!(center.a, 28);
+
+        let mut sharp = dot(5, Color::GRAY, Color::DARKGRAY);
+        sharp.unsharp_mask(1.0, 1.0, 0);
+        assert!(sharp.colors()[12].r > Color::GRAY.r);
+    }
+
+    #[test]
+    fn test_sobel_and_median() {
+        let mut pixels = vec![Color::BLACK; 16];
+        for row in pixels.chunks_mut(4) {
+            row[2] = Color::WHITE;
+            row[3] = Color::WHITE;
+        }
+        let mut image = Image::from_colors(4, 4, &pixels);
+        image.sobel_edges();
+        let edges = image.colors();
+        assert_eq!(edges[0], Color::new(0, 0, 0, 255));
+        assert_eq!(edges[1], Color::new(255, 255, 255, 255));
+        assert_eq!(edges[3], Color::new(0, 0, 0, 255));
+
+        let mut noisy = dot(3, Color::WHITE, Color::BLACK);
+        noisy.median_filter(1);
+        assert!(noisy.colors().iter().all(|c| *c == Color::BLACK));
+    }
+
+    #[test]
+    fn test_morphology() {
+        let mut image = dot(5, Color::WHITE, CLEAR);
+        image.dilate_alpha(1);
+        let opaque = image.colors().iter().filter(|c| c.a == 255).count();
+        assert_eq!(opaque, 9);
+        image.erode_alpha(1);
+        let opaque = image.colors().iter().filter(|c| c.a == 255).count();
+        assert_eq!(opaque, 1);
+    }
+
+    #[test]
+    fn test_outline_and_shadow() {
+        let mut image = dot(1, Color::WHITE, CLEAR);
+        image.outline(1, Color::BLACK);
+        assert_eq!((image.width(), image.height()), (3, 3));
+        let colors = image.colors();
+        assert_eq!(colors[4], Color::WHITE);
+        assert!(colors.iter().filter(|c| **c == Color::BLACK).count() == 8);
+
+        let mut image = dot(1, Color::WHITE, CLEAR);
+        let origin = image.drop_shadow(2, 1, 0.0, Color::BLACK);
+        assert_eq!(origin, (0, 0));
+        assert_eq!((image.width(), image.height()), (3, 2));
+        let colors = image.colors();
+        assert_eq!(colors[0], Color::WHITE);
+        assert_eq!(colors[5], Color::BLACK);
+        assert_eq!(colors[1], CLEAR);
+    }
+}
@@ -0,0 +1,302 @@
+//! Conversions between [`Image`] and the [`image`](https://docs.rs/image) crate, enabled by the `image` feature
+//!
+//! Image buffers whose pixel type has a matching [`PixelFormat`] convert with a single copy of their pixels, and a
+//! raylib image can be borrowed as an `ImageBuffer` without copying at all through
+//! [`Image::as_image_buffer`](../texture/struct.Image.html#method.as_image_buffer). Other formats are expanded:
+//! packed 16 bit raylib formats become 8 bit per channel, and 16 bit `image` buffers become 32 bit float, since
+//! raylib has no 16 bit per channel formats.
+//!
+//! [`Image`]: ../texture/struct.Image.html
+//! [`PixelFormat`]: ../../consts/enum.PixelFormat.html
+use crate::consts::PixelFormat;
+use crate::core::texture::{Image, Texture2D};
+use crate::ffi;
+use image::{DynamicImage, ImageBuffer, Luma, LumaA, Pixel, Rgb, Rgba};
+use std::convert::TryFrom;
+use std::ops::Deref;
+
+/// Pixel types of the `image` crate laid out exactly like an uncompressed raylib [`PixelFormat`].
+///
+/// [`PixelFormat`]: ../../consts/enum.PixelFormat.html
+pub trait FormatPixel: Pixel {
+    const FORMAT: PixelFormat;
+}
+
+impl FormatPixel for Luma<u8> {
+    const FORMAT: PixelFormat = PixelFormat::UNCOMPRESSED_GRAYSCALE;
+}
+
+impl FormatPixel for LumaA<u8> {
+    const FORMAT: PixelFormat = PixelFormat::UNCOMPRESSED_GRAY_ALPHA;
+}
+
+impl FormatPixel for Rgb<u8> {
+    const FORMAT: PixelFormat = PixelFormat::UNCOMPRESSED_R8G8B8;
+}
+
+impl FormatPixel for Rgba<u8> {
+    const FORMAT: PixelFormat = PixelFormat::UNCOMPRESSED_R8G8B8A8;
+}
+
+impl FormatPixel for Luma<f32> {
+    const FORMAT: PixelFormat = PixelFormat::UNCOMPRESSED_R32;
+}
+
+impl FormatPixel for Rgb<f32> {
+    const FORMAT: PixelFormat = PixelFormat::UNCOMPRESSED_R32G32B32;
+}
+
+impl FormatPixel for Rgba<f32> {
+    const FORMAT: PixelFormat = PixelFormat::UNCOMPRESSED_R32G32B32A32;
+}
+
+/// Number of subpixels in the top mipmap of an image in format `P`.
+fn subpixel_count<P: FormatPixel>(width: i32, height: i32) -> usize {
+    width.max(0) as usize * height.max(0) as usize * P::CHANNEL_COUNT as usize
+}
+
+impl Image {
+    /// Creates an image holding a copy of `subpixels`, laid out in format `P`.
+    fn from_subpixels<P: FormatPixel>(width: u32, height: u32, subpixels: &[P::Subpixel]) -> Image {
+        let size = std::mem::size_of_val(subpixels);
+        unsafe {
+            let data = libc::malloc(size.max(1));
+            std::ptr::copy_nonoverlapping(subpixels.as_ptr() as *const u8, data as *mut u8, size);
+            Image::from_raw(ffi::Image {
+                data,
+                width: width as i32,
+                height: height as i32,
+                mipmaps: 1,
+                format: P::FORMAT as i32,
+            })
+        }
+    }
+
+    /// Subpixels of the top mipmap, if the image is in format `P`.
+    fn subpixels<P: FormatPixel>(&self) -> Option<&[P::Subpixel]> {
+        if self.format() != P::FORMAT || self.0.data.is_null() {
+            return None;
+        }
+        let len = subpixel_count::<P>(self.width(), self.height());
+        unsafe {
+            Some(std::slice::from_raw_parts(
+                self.0.data as *const P::Subpixel,
+                len,
+            ))
+        }
+    }
+
+    /// Borrows the pixels as an `ImageBuffer` without copying them. Returns `None` unless the image is in the
+    /// format matching `P`, for example `UNCOMPRESSED_R8G8B8A8` for `Rgba<u8>`.
+    pub fn as_image_buffer<P: FormatPixel>(&self) -> Option<ImageBuffer<P, &[P::Subpixel]>> {
+        ImageBuffer::from_raw(
+            self.width() as u32,
+            self.height() as u32,
+            self.subpixels::<P>()?,
+        )
+    }
+
+    /// Mutably borrows the pixels as an `ImageBuffer` without copying them. Returns `None` unless the image is in
+    /// the format matching `P`.
+    pub fn as_image_buffer_mut<P: FormatPixel>(
+        &mut self,
+    ) -> Option<ImageBuffer<P, &mut [P::Subpixel]>> {
+        let len = self.subpixels::<P>()?.len();
+        let subpixels =
+            unsafe { std::slice::from_raw_parts_mut(self.0.data as *mut P::Subpixel, len) };
+        ImageBuffer::from_raw(self.width() as u32, self.height() as u32, subpixels)
+    }
+}
+
+impl<P, C> From<&ImageBuffer<P, C>> for Image
+where
+    P: FormatPixel,
+    C: Deref<Target = [P::Subpixel]>,
+{
+    fn from(buffer: &ImageBuffer<P, C>) -> Image {
+        let len = subpixel_count::<P>(buffer.width() as i32, buffer.height() as i32);
+        Image::from_subpixels::<P>(buffer.width(), buffer.height(), &buffer.as_raw()[..len])
+    }
+}
+
+impl<P: FormatPixel> TryFrom<&Image> for ImageBuffer<P, Vec<P::Subpixel>> {
+    type Error = String;
+
+    /// Copies the pixels of an image in the format matching `P`. Use [`DynamicImage`] to convert from any format.
+    fn try_from(image: &Image) -> Result<Self, String> {
+        let subpixels = image
+            .subpixels::<P>()
+            .ok_or_else(|| format!("image is {:?}, expected {:?}", image.format(), P::FORMAT))?;
+        ImageBuffer::from_raw(
+            image.width() as u32,
+            image.height() as u32,
+            subpixels.to_vec(),
+        )
+        .ok_or_else(|| "image has an invalid size".to_string())
+    }
+}
+
+impl From<&DynamicImage> for Image {
+    fn from(image: &DynamicImage) -> Image {
+        match image {
+            DynamicImage::ImageLuma8(buffer) => buffer.into(),
+            DynamicImage::ImageLumaA8(buffer) => buffer.into(),
+            DynamicImage::ImageRgb8(buffer) => buffer.into(),
+            DynamicImage::ImageRgba8(buffer) => buffer.into(),
+            DynamicImage::ImageRgb32F(buffer) => buffer.into(),
+            DynamicImage::ImageRgba32F(buffer) => buffer.into(),
+            DynamicImage::ImageLuma16(buffer) => {
+                let gray: Vec<f32> = buffer
+                    .as_raw()
+                    .iter()
+                    .map(|v| *v as f32 / u16::MAX as f32)
+                    .collect();
+                Image::from_subpixels::<Luma<f32>>(buffer.width(), buffer.height(), &gray)
+            }
+            DynamicImage::ImageRgb16(_) => (&image.to_rgb32f()).into(),
+            _ => (&image.to_rgba32f()).into(),
+        }
+    }
+}
+
+impl From<DynamicImage> for Image {
+    fn from(image: DynamicImage) -> Image {
+        Image::from(&image)
+    }
+}
+
+impl TryFrom<&Image> for DynamicImage {
+    type Error = String;
+
+    /// Copies the pixels of an uncompressed image. Formats without an `image` crate equivalent are widened:
+    /// `UNCOMPRESSED_R5G6B5` becomes `ImageRgb8`, the other packed formats `ImageRgba8`, and `UNCOMPRESSED_R32`
+    /// becomes `ImageRgb32F`.
+    fn try_from(image: &Image) -> Result<Self, String> {
+        use crate::consts::PixelFormat::*;
+        let (width, height) = (image.width() as u32, image.height() as u32);
+        let invalid = || "image has an invalid size".to_string();
+        Ok(match image.format() {
+            UNCOMPRESSED_GRAYSCALE => DynamicImage::ImageLuma8(ImageBuffer::try_from(image)?),
+            UNCOMPRESSED_GRAY_ALPHA => DynamicImage::ImageLumaA8(ImageBuffer::try_from(image)?),
+            UNCOMPRESSED_R8G8B8 => DynamicImage::ImageRgb8(ImageBuffer::try_from(image)?),
+            UNCOMPRESSED_R8G8B8A8 => DynamicImage::ImageRgba8(ImageBuffer::try_from(image)?),
+            UNCOMPRESSED_R32G32B32 => DynamicImage::ImageRgb32F(ImageBuffer::try_from(image)?),
+            UNCOMPRESSED_R32G32B32A32 => DynamicImage::ImageRgba32F(ImageBuffer::try_from(image)?),
+            UNCOMPRESSED_R32 => {
+                let gray = image.subpixels::<Luma<f32>>().ok_or_else(invalid)?;
+                let rgb = gray.iter().flat_map(|v| vec![*v; 3]).collect();
+                DynamicImage::ImageRgb32F(
+                    ImageBuffer::from_raw(width, height, rgb).ok_or_else(invalid)?,
+                )
+            }
+            UNCOMPRESSED_R5G6B5 => {
+                let rgb = image
+                    .get_image_data()
+                    .iter()
+                    .flat_map(|c| vec![c.r, c.g, c.b])
+                    .collect();
+                DynamicImage::ImageRgb8(
+                    ImageBuffer::from_raw(width, height, rgb).ok_or_else(invalid)?,
+                )
+            }
+            UNCOMPRESSED_R5G5B5A1 | UNCOMPRESSED_R4G4B4A4 => {
+                let rgba = image
+                    .get_image_data()
+                    .iter()
+                    .flat_map(|c| vec![c.r, c.g, c.b, c.a])
+                    .collect();
+                DynamicImage::ImageRgba8(
+                    ImageBuffer::from_raw(width, height, rgba).ok_or_else(invalid)?,
+                )
+            }
+            format => return Err(format!("{:?} images can't be converted", format)),
+        })
+    }
+}
+
+impl Texture2D {
+    /// Uploads the pixels of `buffer` to the texture. The buffer must have the texture's size and a pixel type
+    /// matching its format, for example an `RgbaImage` for an `UNCOMPRESSED_R8G8B8A8` texture.
+    pub fn update_from<P, C>(&mut self, buffer: &ImageBuffer<P, C>) -> Result<(), String>
+    where
+        P: FormatPixel,
+        C: Deref<Target = [P::Subpixel]>,
+    {
+        if self.format != P::FORMAT as i32 {
+            return Err(format!(
+                "texture format {} doesn't match {:?}",
+                self.format,
+                P::FORMAT
+            ));
+        }
+        if (self.width, self.height) != (buffer.width() as i32, buffer.height() as i32) {
+            return Err(format!(
+                "texture is {}x{}, image is {}x{}",
+                self.width,
+                self.height,
+                buffer.width(),
+                buffer.height()
+            ));
+        }
+        unsafe {
+            ffi::UpdateTexture(
+                self.0,
+                buffer.as_raw().as_ptr() as *const std::os::raw::c_void,
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod image_interop_test {
+    use super::*;
+    use crate::core::color::Color;
+    use image::RgbaImage;
+
+    #[test]
+    fn test_image_buffer_roundtrip() {
+        let mut buffer = RgbaImage::new(3, 2);
+        buffer.put_pixel(1, 1, Rgba([10, 20, 30, 40]));
+        let mut image = Image::from(&buffer);
+        assert_eq!(image.format(), PixelFormat::UNCOMPRESSED_R8G8B8A8);
+        assert_eq!(image.colors()[4], Color::new(10, 20, 30, 40));
+
+        let view = image.as_image_buffer::<Rgba<u8>>().unwrap();
+        assert_eq!(view.get_pixel(1, 1), &Rgba([10, 20, 30, 40]));
+        assert!(image.as_image_buffer::<Rgb<u8>>().is_none());
+
+        image
+            .as_image_buffer_mut::<Rgba<u8>>()
+            .unwrap()
+            .put_pixel(0, 0, Rgba([1, 2, 3, 4]));
+        let copy = RgbaImage::try_from(&image).unwrap();
+        assert_eq!(copy.get_pixel(0, 0), &Rgba([1, 2, 3, 4]));
+        assert_eq!(copy.get_pixel(1, 1), &Rgba([10, 20, 30, 40]));
+    }
+
+    #[test]
+    fn test_dynamic_image() {
+        let gray =
+            DynamicImage::ImageLuma16(ImageBuffer::from_raw(2, 1, vec![0u16, u16::MAX]).unwrap());
+        let image = Image::from(&gray);
+        assert_eq!(image.format(), PixelFormat::UNCOMPRESSED_R32);
+        assert_eq!(image.subpixels::<Luma<f32>>().unwrap(), &[0.0, 1.0]);
+
+        let back = DynamicImage::try_from(&image).unwrap();
+        assert_eq!(
+            back.as_rgb32f().unwrap().get_pixel(1, 0),
+            &Rgb([1.0, 1.0, 1.0])
+        );
+
+        let rgba16 = DynamicImage::ImageRgba16(
+            ImageBuffer::from_raw(1, 1, vec![u16::MAX, 0, 0, u16::MAX]).unwrap(),
+        );
+        let image = Image::from(rgba16);
+        assert_eq!(image.format(), PixelFormat::UNCOMPRESSED_R32G32B32A32);
+        assert_eq!(
+            image.subpixels::<Rgba<f32>>().unwrap(),
+            &[1.0, 0.0, 0.0, 1.0]
+        );
+    }
+}
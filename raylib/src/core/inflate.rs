@@ -0,0 +1,327 @@
+//! DEFLATE decoding for zlib and gzip streams, as found in compressed Tiled layers.
+//!
+//! raylib's `DecompressData` can't be trusted with these (see `data.rs`), and maps are often parsed before a window
+//! exists, so this is done in Rust.
+
+use crate::core::checksum::{adler32, crc32};
+
+const MAX_BITS: usize = 15;
+
+/// Base lengths for length symbols 257..=285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order the code length code lengths are stored in.
+const CLEN_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+struct Bits<'a> {
+    data: &'a [u8],
+    /// Position in bits.
+    pos: usize,
+}
+
+impl<'a> Bits<'a> {
+    fn bit(&mut self) -> Result<u32, String> {
+        let byte = self
+            .data
+            .get(self.pos / 8)
+            .ok_or_else(|| "compressed data ends early".to_string())?;
+        let bit = (byte >> (self.pos % 8)) & 1;
+        self.pos += 1;
+        Ok(bit as u32)
+    }
+
+    /// Reads `n` bits, least significant first.
+    fn bits(&mut self, n: u8) -> Result<u32, String> {
+        let mut value = 0;
+        for i in 0..n {
+            value |= self.bit()? << i;
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        self.pos = self.pos.div_ceil(8) * 8;
+    }
+}
+
+/// Canonical Huffman code, decoded one bit at a time.
+struct Huffman {
+    /// Number of codes of each length.
+    counts: [u16; MAX_BITS + 1],
+    /// Symbols ordered by code.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Huffman, String> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = left * 2 - count as i32;
+            if left < 0 {
+                return Err("invalid Huffman code".to_string());
+            }
+        }
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; offsets[MAX_BITS + 1] as usize];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..=MAX_BITS {
+            code |= bits.bit()? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("invalid Huffman code".to_string())
+    }
+}
+
+/// Decodes a raw DEFLATE stream, returning the data and the number of bytes consumed.
+fn inflate(data: &[u8]) -> Result<(Vec<u8>, usize), String> {
+    let mut bits = Bits { data, pos: 0 };
+    let mut out = Vec::new();
+    loop {
+        let last = bits.bit()? == 1;
+        match bits.bits(2)? {
+            0 => {
+                bits.align();
+                let at = bits.pos / 8;
+                let header = data
+                    .get(at..at + 4)
+                    .ok_or_else(|| "compressed data ends early".to_string())?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                let nlen = u16::from_le_bytes([header[2], header[3]]);
+                if len != !nlen {
+                    return Err("corrupt stored block".to_string());
+                }
+                let stored = data
+                    .get(at + 4..at + 4 + len as usize)
+                    .ok_or_else(|| "compressed data ends early".to_string())?;
+                out.extend_from_slice(stored);
+                bits.pos = (at + 4 + len as usize) * 8;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].iter_mut().for_each(|l| *l = 8);
+                lengths[144..256].iter_mut().for_each(|l| *l = 9);
+                lengths[256..280].iter_mut().for_each(|l| *l = 7);
+                lengths[280..].iter_mut().for_each(|l| *l = 8);
+                let literals = Huffman::new(&lengths)?;
+                let distances = Huffman::new(&[5; 30])?;
+                inflate_block(&mut bits, &mut out, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut bits)?;
+                inflate_block(&mut bits, &mut out, &literals, &distances)?;
+            }
+            _ => return Err("invalid block type".to_string()),
+        }
+        if last {
+            bits.align();
+            return Ok((out, bits.pos / 8));
+        }
+    }
+}
+
+fn dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), String> {
+    let literal_count = bits.bits(5)? as usize + 257;
+    let distance_count = bits.bits(5)? as usize + 1;
+    let clen_count = bits.bits(4)? as usize + 4;
+    let mut clen_lengths = [0u8; 19];
+    for &i in &CLEN_ORDER[..clen_count] {
+        clen_lengths[i] = bits.bits(3)? as u8;
+    }
+    let clen = Huffman::new(&clen_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match clen.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths
+                    .last()
+                    .ok_or_else(|| "repeated code length without a previous one".to_string())?;
+                (previous, 3 + bits.bits(2)?)
+            }
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        lengths.resize(lengths.len() + repeat as usize, value);
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err("too many code lengths".to_string());
+    }
+    let (literal_lengths, distance_lengths) = lengths.split_at(literal_count);
+    Ok((
+        Huffman::new(literal_lengths)?,
+        Huffman::new(distance_lengths)?,
+    ))
+}
+
+fn inflate_block(
+    bits: &mut Bits,
+    out: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        if symbol < 256 {
+            out.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Ok(());
+        }
+        let symbol = symbol - 257;
+        if symbol >= LENGTH_BASE.len() {
+            return Err("invalid length symbol".to_string());
+        }
+        let length = LENGTH_BASE[symbol] as usize + bits.bits(LENGTH_EXTRA[symbol])? as usize;
+        let symbol = distances.decode(bits)? as usize;
+        if symbol >= DIST_BASE.len() {
+            return Err("invalid distance symbol".to_string());
+        }
+        let distance = DIST_BASE[symbol] as usize + bits.bits(DIST_EXTRA[symbol])? as usize;
+        if distance > out.len() {
+            return Err("distance past the start of the data".to_string());
+        }
+        let start = out.len() - distance;
+        // copies may overlap the bytes they produce
+        for i in 0..length {
+            out.push(out[start + i]);
+        }
+    }
+}
+
+/// Decompresses a zlib stream.
+pub(crate) fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 6
+        || data[0] & 0x0F != 8
+        || !u16::from_be_bytes([data[0], data[1]]).is_multiple_of(31)
+    {
+        return Err("not a zlib stream".to_string());
+    }
+    if data[1] & 0x20 != 0 {
+        return Err("zlib streams with a preset dictionary are not supported".to_string());
+    }
+    let (out, used) = inflate(&data[2..])?;
+    let check = data
+        .get(2 + used..6 + used)
+        .ok_or_else(|| "zlib stream ends early".to_string())?;
+    if u32::from_be_bytes([check[0], check[1], check[2], check[3]]) != adler32(&out) {
+        return Err("zlib checksum mismatch".to_string());
+    }
+    Ok(out)
+}
+
+/// Decompresses a gzip stream.
+pub(crate) fn gzip_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+
+    let early = || "gzip stream ends early".to_string();
+    if data.len() < 18 || data[0] != 0x1F || data[1] != 0x8B || data[2] != 8 {
+        return Err("not a gzip stream".to_string());
+    }
+    let flags = data[3];
+    let mut at = 10;
+    if flags & FEXTRA != 0 {
+        let len = data.get(at..at + 2).ok_or_else(early)?;
+        at += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in &[FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = data
+                .get(at..)
+                .and_then(|rest| rest.iter().position(|&b| b == 0))
+                .ok_or_else(early)?;
+            at += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        at += 2;
+    }
+    let (out, used) = inflate(data.get(at..).ok_or_else(early)?)?;
+    let trailer = data.get(at + used..at + used + 8).ok_or_else(early)?;
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if crc != crc32(&out) || size != out.len() as u32 {
+        return Err("gzip checksum mismatch".to_string());
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod inflate_test {
+    use super::*;
+
+    const HELLO: &[u8] = b"hello hello hello hello";
+
+    #[test]
+    fn test_zlib() {
+        // fixed Huffman codes
+        let mut data = [
+            0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01, 0x68, 0x03,
+            0x08, 0xb1,
+        ];
+        assert_eq!(zlib_decompress(&data).unwrap(), HELLO);
+        data[15] ^= 1;
+        assert!(zlib_decompress(&data).is_err());
+
+        // stored block
+        let stored = [
+            0x78, 0x01, 0x01, 0x03, 0x00, 0xfc, 0xff, 0x61, 0x62, 0x63, 0x02, 0x4d, 0x01, 0x27,
+        ];
+        assert_eq!(zlib_decompress(&stored).unwrap(), b"abc");
+        assert!(zlib_decompress(&stored[..9]).is_err());
+        assert!(zlib_decompress(HELLO).is_err());
+    }
+
+    #[test]
+    fn test_gzip() {
+        let data = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+            0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01, 0xe3, 0x51, 0x3d, 0x8d, 0x17, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(gzip_decompress(&data).unwrap(), HELLO);
+        assert!(gzip_decompress(&data[..20]).is_err());
+    }
+}
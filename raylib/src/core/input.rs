@@ -1,41 +1,66 @@
 //! Keyboard, Controller, and Mouse related functions
-use crate::consts::GestureType;
+use crate::consts::{
+    GamepadAxis, GamepadButton, GamepadNumber, GestureType, KeyboardKey, MouseButton,
+};
 use crate::core::math::Vector2;
+use crate::core::replay::{
+    record_key_pressed, replayed, replayed_gamepad_button_pressed, replayed_key_pressed,
+};
+use crate::core::replay::{GamepadFrame, InputFrame, InputRecording};
 use crate::core::RaylibHandle;
 use crate::ffi;
 
+use std::collections::VecDeque;
 use std::ffi::{CStr, CString};
 
 impl RaylibHandle {
     /// Detect if a key has been pressed once.
     #[inline]
     pub fn is_key_pressed(&self, key: crate::consts::KeyboardKey) -> bool {
+        if let Some(pressed) = replayed(|cur, prev| cur.is_key_down(key) && !prev.is_key_down(key))
+        {
+            return pressed;
+        }
         unsafe { ffi::IsKeyPressed((key as u32) as i32) }
     }
 
     /// Detect if a key is being pressed.
     #[inline]
     pub fn is_key_down(&self, key: crate::consts::KeyboardKey) -> bool {
+        if let Some(down) = replayed(|cur, _| cur.is_key_down(key)) {
+            return down;
+        }
         unsafe { ffi::IsKeyDown((key as u32) as i32) }
     }
 
     /// Detect if a key has been released once.
     #[inline]
     pub fn is_key_released(&self, key: crate::consts::KeyboardKey) -> bool {
+        if let Some(released) = replayed(|cur, prev| !cur.is_key_down(key) && prev.is_key_down(key))
+        {
+            return released;
+        }
         unsafe { ffi::IsKeyReleased((key as u32) as i32) }
     }
 
     /// Detect if a key is NOT being pressed.
     #[inline]
     pub fn is_key_up(&self, key: crate::consts::KeyboardKey) -> bool {
+        if let Some(up) = replayed(|cur, _| !cur.is_key_down(key)) {
+            return up;
+        }
         unsafe { ffi::IsKeyUp((key as u32) as i32) }
     }
 
     /// Gets latest key pressed.
     #[inline]
     pub fn get_key_pressed(&mut self) -> Option<crate::consts::KeyboardKey> {
+        if let Some(key) = replayed_key_pressed() {
+            return key.and_then(|k| key_from_i32(k as i32));
+        }
         let key = unsafe { ffi::GetKeyPressed() };
         if key > 0 {
+            record_key_pressed(key as u32);
             return key_from_i32(key);
         }
         None
@@ -44,8 +69,12 @@ impl RaylibHandle {
     /// Gets latest key pressed.
     #[inline]
     pub fn get_key_pressed_number(&mut self) -> Option<u32> {
+        if let Some(key) = replayed_key_pressed() {
+            return key;
+        }
         let key = unsafe { ffi::GetKeyPressed() };
         if key > 0 {
+            record_key_pressed(key as u32);
             return Some(key as u32);
         }
         None
@@ -65,6 +94,9 @@ impl RaylibHandle {
     /// Detect if a gamepad is available.
     #[inline]
     pub fn is_gamepad_available(&self, gamepad: crate::consts::GamepadNumber) -> bool {
+        if let Some(available) = replayed(|cur, _| cur.is_gamepad_available(gamepad)) {
+            return available;
+        }
         unsafe { ffi::IsGamepadAvailable(gamepad as i32) }
     }
 
@@ -94,6 +126,12 @@ impl RaylibHandle {
         gamepad: crate::consts::GamepadNumber,
         button: crate::consts::GamepadButton,
     ) -> bool {
+        if let Some(pressed) = replayed(|cur, prev| {
+            cur.is_gamepad_button_down(gamepad, button)
+                && !prev.is_gamepad_button_down(gamepad, button)
+        }) {
+            return pressed;
+        }
         unsafe { ffi::IsGamepadButtonPressed(gamepad as i32, (button as u32) as i32) }
     }
 
@@ -104,6 +142,9 @@ impl RaylibHandle {
         gamepad: crate::consts::GamepadNumber,
         button: crate::consts::GamepadButton,
     ) -> bool {
+        if let Some(down) = replayed(|cur, _| cur.is_gamepad_button_down(gamepad, button)) {
+            return down;
+        }
         unsafe { ffi::IsGamepadButtonDown(gamepad as i32, (button as u32) as i32) }
     }
 
@@ -114,6 +155,12 @@ impl RaylibHandle {
         gamepad: crate::consts::GamepadNumber,
         button: crate::consts::GamepadButton,
     ) -> bool {
+        if let Some(released) = replayed(|cur, prev| {
+            !cur.is_gamepad_button_down(gamepad, button)
+                && prev.is_gamepad_button_down(gamepad, button)
+        }) {
+            return released;
+        }
         unsafe { ffi::IsGamepadButtonReleased(gamepad as i32, (button as u32) as i32) }
     }
 
@@ -124,12 +171,18 @@ impl RaylibHandle {
         gamepad: crate::consts::GamepadNumber,
         button: crate::consts::GamepadButton,
     ) -> bool {
+        if let Some(up) = replayed(|cur, _| !cur.is_gamepad_button_down(gamepad, button)) {
+            return up;
+        }
         unsafe { ffi::IsGamepadButtonUp(gamepad as i32, (button as u32) as i32) }
     }
 
     /// Gets the last gamepad button pressed.
     #[inline]
     pub fn get_gamepad_button_pressed(&self) -> Option<crate::consts::GamepadButton> {
+        if let Some(button) = replayed_gamepad_button_pressed() {
+            return button;
+        }
         let button = unsafe { ffi::GetGamepadButtonPressed() };
         if button >= 0 {
             return Some(unsafe { std::mem::transmute(button as u32) });
@@ -140,6 +193,13 @@ impl RaylibHandle {
     /// Returns gamepad axis count for a gamepad.
     #[inline]
     pub fn get_gamepad_axis_count(&self, gamepad: crate::consts::GamepadNumber) -> i32 {
+        if let Some(count) = replayed(|cur, _| {
+            cur.gamepads[gamepad as usize]
+                .as_ref()
+                .map_or(0, |g| g.axes.len() as i32)
+        }) {
+            return count;
+        }
         unsafe { ffi::GetGamepadAxisCount(gamepad as i32) }
     }
 
@@ -150,48 +210,76 @@ impl RaylibHandle {
         gamepad: crate::consts::GamepadNumber,
         axis: crate::consts::GamepadAxis,
     ) -> f32 {
+        if let Some(movement) = replayed(|cur, _| cur.gamepad_axis_movement(gamepad, axis)) {
+            return movement;
+        }
         unsafe { ffi::GetGamepadAxisMovement(gamepad as i32, axis as i32) }
     }
 
     /// Detect if a mouse button has been pressed once.
     #[inline]
     pub fn is_mouse_button_pressed(&self, button: crate::consts::MouseButton) -> bool {
+        if let Some(pressed) = replayed(|cur, prev| {
+            cur.is_mouse_button_down(button) && !prev.is_mouse_button_down(button)
+        }) {
+            return pressed;
+        }
         unsafe { ffi::IsMouseButtonPressed(button as i32) }
     }
 
     /// Detect if a mouse button is being pressed.
     #[inline]
     pub fn is_mouse_button_down(&self, button: crate::consts::MouseButton) -> bool {
+        if let Some(down) = replayed(|cur, _| cur.is_mouse_button_down(button)) {
+            return down;
+        }
         unsafe { ffi::IsMouseButtonDown(button as i32) }
     }
 
     /// Detect if a mouse button has been released once.
     #[inline]
     pub fn is_mouse_button_released(&self, button: crate::consts::MouseButton) -> bool {
+        if let Some(released) = replayed(|cur, prev| {
+            !cur.is_mouse_button_down(button) && prev.is_mouse_button_down(button)
+        }) {
+            return released;
+        }
         unsafe { ffi::IsMouseButtonReleased(button as i32) }
     }
 
     /// Detect if a mouse button is NOT being pressed.
     #[inline]
     pub fn is_mouse_button_up(&self, button: crate::consts::MouseButton) -> bool {
+        if let Some(up) = replayed(|cur, _| !cur.is_mouse_button_down(button)) {
+            return up;
+        }
         unsafe { ffi::IsMouseButtonUp(button as i32) }
     }
 
     /// Returns mouse position X.
     #[inline]
     pub fn get_mouse_x(&self) -> i32 {
+        if let Some(x) = replayed(|cur, _| cur.mouse_position.x as i32) {
+            return x;
+        }
         unsafe { ffi::GetMouseX() }
     }
 
     /// Returns mouse position Y.
     #[inline]
     pub fn get_mouse_y(&self) -> i32 {
+        if let Some(y) = replayed(|cur, _| cur.mouse_position.y as i32) {
+            return y;
+        }
         unsafe { ffi::GetMouseY() }
     }
 
     /// Returns mouse position.
     #[inline]
     pub fn get_mouse_position(&self) -> Vector2 {
+        if let Some(position) = replayed(|cur, _| cur.mouse_position) {
+            return position;
+        }
         unsafe { ffi::GetMousePosition().into() }
     }
 
@@ -224,6 +312,9 @@ impl RaylibHandle {
     /// Returns mouse wheel movement Y.
     #[inline]
     pub fn get_mouse_wheel_move(&self) -> i32 {
+        if let Some(wheel) = replayed(|cur, _| cur.mouse_wheel_move) {
+            return wheel;
+        }
         unsafe { ffi::GetMouseWheelMove() }
     }
 
@@ -302,6 +393,513 @@ impl RaylibHandle {
     }
 }
 
+/// Read-only access to keyboard, mouse, gamepad, touch and gesture state.
+///
+/// Game logic written against `&impl InputSource` instead of `&RaylibHandle` can be driven by a [`MockInput`] in
+/// plain unit tests, without opening a window.
+///
+/// ```
+/// use raylib::prelude::*;
+///
+/// fn wants_jump(input: &impl InputSource) -> bool {
+///     input.is_key_pressed(KeyboardKey::KEY_SPACE)
+///         || input.is_gamepad_button_pressed(
+///             GamepadNumber::GAMEPAD_PLAYER1,
+///             GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
+///         )
+/// }
+///
+/// let mut input = MockInput::new();
+/// assert!(!wants_jump(&input));
+/// input.key_down(KeyboardKey::KEY_SPACE);
+/// assert!(wants_jump(&input));
+/// input.next_frame();
+/// assert!(!wants_jump(&input));
+/// ```
+///
+/// [`MockInput`]: struct.MockInput.html
+pub trait InputSource {
+    /// Detect if a key has been pressed once.
+    fn is_key_pressed(&self, key: KeyboardKey) -> bool;
+    /// Detect if a key is being pressed.
+    fn is_key_down(&self, key: KeyboardKey) -> bool;
+    /// Detect if a key has been released once.
+    fn is_key_released(&self, key: KeyboardKey) -> bool;
+    /// Detect if a key is NOT being pressed.
+    fn is_key_up(&self, key: KeyboardKey) -> bool;
+    /// Gets latest key pressed.
+    fn get_key_pressed(&mut self) -> Option<KeyboardKey>;
+
+    /// Detect if a gamepad is available.
+    fn is_gamepad_available(&self, gamepad: GamepadNumber) -> bool;
+    /// Detect if a gamepad button has been pressed once.
+    fn is_gamepad_button_pressed(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool;
+    /// Detect if a gamepad button is being pressed.
+    fn is_gamepad_button_down(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool;
+    /// Detect if a gamepad button has been released once.
+    fn is_gamepad_button_released(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool;
+    /// Detect if a gamepad button is NOT being pressed.
+    fn is_gamepad_button_up(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool;
+    /// Gets the last gamepad button pressed.
+    fn get_gamepad_button_pressed(&self) -> Option<GamepadButton>;
+    /// Returns gamepad axis count for a gamepad.
+    fn get_gamepad_axis_count(&self, gamepad: GamepadNumber) -> i32;
+    /// Returns axis movement value for a gamepad axis.
+    fn get_gamepad_axis_movement(&self, gamepad: GamepadNumber, axis: GamepadAxis) -> f32;
+
+    /// Detect if a mouse button has been pressed once.
+    fn is_mouse_button_pressed(&self, button: MouseButton) -> bool;
+    /// Detect if a mouse button is being pressed.
+    fn is_mouse_button_down(&self, button: MouseButton) -> bool;
+    /// Detect if a mouse button has been released once.
+    fn is_mouse_button_released(&self, button: MouseButton) -> bool;
+    /// Detect if a mouse button is NOT being pressed.
+    fn is_mouse_button_up(&self, button: MouseButton) -> bool;
+    /// Returns mouse position X.
+    fn get_mouse_x(&self) -> i32 {
+        self.get_mouse_position().x as i32
+    }
+    /// Returns mouse position Y.
+    fn get_mouse_y(&self) -> i32 {
+        self.get_mouse_position().y as i32
+    }
+    /// Returns mouse position.
+    fn get_mouse_position(&self) -> Vector2;
+    /// Returns mouse wheel movement Y.
+    fn get_mouse_wheel_move(&self) -> i32;
+
+    /// Returns touch position X for touch point 0 (relative to screen size).
+    fn get_touch_x(&self) -> i32 {
+        self.get_touch_position(0).x as i32
+    }
+    /// Returns touch position Y for touch point 0 (relative to screen size).
+    fn get_touch_y(&self) -> i32 {
+        self.get_touch_position(0).y as i32
+    }
+    /// Returns touch position XY for a touch point index (relative to screen size).
+    fn get_touch_position(&self, index: u32) -> Vector2;
+    /// Gets touch points count.
+    fn get_touch_points_count(&self) -> u32;
+
+    /// Checks if a gesture have been detected.
+    fn is_gesture_detected(&self, gesture: GestureType) -> bool;
+    /// Gets latest detected gesture.
+    fn get_gesture_detected(&self) -> GestureType;
+    /// Gets gesture hold time in milliseconds.
+    fn get_gesture_hold_duration(&self) -> f32;
+    /// Gets gesture drag vector.
+    fn get_gesture_drag_vector(&self) -> Vector2;
+    /// Gets gesture drag angle.
+    fn get_gesture_drag_angle(&self) -> f32;
+    /// Gets gesture pinch delta.
+    fn get_gesture_pinch_vector(&self) -> Vector2;
+    /// Gets gesture pinch angle.
+    fn get_gesture_pinch_angle(&self) -> f32;
+}
+
+impl InputSource for RaylibHandle {
+    fn is_key_pressed(&self, key: KeyboardKey) -> bool {
+        RaylibHandle::is_key_pressed(self, key)
+    }
+    fn is_key_down(&self, key: KeyboardKey) -> bool {
+        RaylibHandle::is_key_down(self, key)
+    }
+    fn is_key_released(&self, key: KeyboardKey) -> bool {
+        RaylibHandle::is_key_released(self, key)
+    }
+    fn is_key_up(&self, key: KeyboardKey) -> bool {
+        RaylibHandle::is_key_up(self, key)
+    }
+    fn get_key_pressed(&mut self) -> Option<KeyboardKey> {
+        RaylibHandle::get_key_pressed(self)
+    }
+
+    fn is_gamepad_available(&self, gamepad: GamepadNumber) -> bool {
+        RaylibHandle::is_gamepad_available(self, gamepad)
+    }
+    fn is_gamepad_button_pressed(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool {
+        RaylibHandle::is_gamepad_button_pressed(self, gamepad, button)
+    }
+    fn is_gamepad_button_down(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool {
+        RaylibHandle::is_gamepad_button_down(self, gamepad, button)
+    }
+    fn is_gamepad_button_released(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool {
+        RaylibHandle::is_gamepad_button_released(self, gamepad, button)
+    }
+    fn is_gamepad_button_up(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool {
+        RaylibHandle::is_gamepad_button_up(self, gamepad, button)
+    }
+    fn get_gamepad_button_pressed(&self) -> Option<GamepadButton> {
+        RaylibHandle::get_gamepad_button_pressed(self)
+    }
+    fn get_gamepad_axis_count(&self, gamepad: GamepadNumber) -> i32 {
+        RaylibHandle::get_gamepad_axis_count(self, gamepad)
+    }
+    fn get_gamepad_axis_movement(&self, gamepad: GamepadNumber, axis: GamepadAxis) -> f32 {
+        RaylibHandle::get_gamepad_axis_movement(self, gamepad, axis)
+    }
+
+    fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        RaylibHandle::is_mouse_button_pressed(self, button)
+    }
+    fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        RaylibHandle::is_mouse_button_down(self, button)
+    }
+    fn is_mouse_button_released(&self, button: MouseButton) -> bool {
+        RaylibHandle::is_mouse_button_released(self, button)
+    }
+    fn is_mouse_button_up(&self, button: MouseButton) -> bool {
+        RaylibHandle::is_mouse_button_up(self, button)
+    }
+    fn get_mouse_x(&self) -> i32 {
+        RaylibHandle::get_mouse_x(self)
+    }
+    fn get_mouse_y(&self) -> i32 {
+        RaylibHandle::get_mouse_y(self)
+    }
+    fn get_mouse_position(&self) -> Vector2 {
+        RaylibHandle::get_mouse_position(self)
+    }
+    fn get_mouse_wheel_move(&self) -> i32 {
+        RaylibHandle::get_mouse_wheel_move(self)
+    }
+
+    fn get_touch_x(&self) -> i32 {
+        RaylibHandle::get_touch_x(self)
+    }
+    fn get_touch_y(&self) -> i32 {
+        RaylibHandle::get_touch_y(self)
+    }
+    fn get_touch_position(&self, index: u32) -> Vector2 {
+        RaylibHandle::get_touch_position(self, index)
+    }
+    fn get_touch_points_count(&self) -> u32 {
+        RaylibHandle::get_touch_points_count(self)
+    }
+
+    fn is_gesture_detected(&self, gesture: GestureType) -> bool {
+        RaylibHandle::is_gesture_detected(self, gesture)
+    }
+    fn get_gesture_detected(&self) -> GestureType {
+        RaylibHandle::get_gesture_detected(self)
+    }
+    fn get_gesture_hold_duration(&self) -> f32 {
+        RaylibHandle::get_gesture_hold_duration(self)
+    }
+    fn get_gesture_drag_vector(&self) -> Vector2 {
+        RaylibHandle::get_gesture_drag_vector(self)
+    }
+    fn get_gesture_drag_angle(&self) -> f32 {
+        RaylibHandle::get_gesture_drag_angle(self)
+    }
+    fn get_gesture_pinch_vector(&self) -> Vector2 {
+        RaylibHandle::get_gesture_pinch_vector(self)
+    }
+    fn get_gesture_pinch_angle(&self) -> f32 {
+        RaylibHandle::get_gesture_pinch_angle(self)
+    }
+}
+
+/// A scripted `InputSource` for tests.
+///
+/// The state set through `key_down`, `mouse_move`... applies to the current frame and carries over to the following
+/// ones. Calling `next_frame` moves to the next frame, so "pressed" and "released" queries only report a change once.
+/// Whole frames can also be queued in advance with `queue_frame`, for instance from a recorded `InputRecording`.
+#[derive(Debug, Clone)]
+pub struct MockInput {
+    current: InputFrame,
+    previous: InputFrame,
+    queued: VecDeque<InputFrame>,
+    pending_keys: VecDeque<KeyboardKey>,
+    /// Touch points, index 0 being the primary one.
+    pub touch_points: Vec<Vector2>,
+    /// Gesture reported by `get_gesture_detected`.
+    pub gesture: GestureType,
+    pub gesture_hold_duration: f32,
+    pub gesture_drag_vector: Vector2,
+    pub gesture_drag_angle: f32,
+    pub gesture_pinch_vector: Vector2,
+    pub gesture_pinch_angle: f32,
+}
+
+impl Default for MockInput {
+    fn default() -> Self {
+        MockInput {
+            current: InputFrame::default(),
+            previous: InputFrame::default(),
+            queued: VecDeque::new(),
+            pending_keys: VecDeque::new(),
+            touch_points: Vec::new(),
+            gesture: GestureType::GESTURE_NONE,
+            gesture_hold_duration: 0.0,
+            gesture_drag_vector: Vector2::zero(),
+            gesture_drag_angle: 0.0,
+            gesture_pinch_vector: Vector2::zero(),
+            gesture_pinch_angle: 0.0,
+        }
+    }
+}
+
+impl MockInput {
+    /// Creates a mock with nothing pressed and the mouse at the origin.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a mock that plays back the frames of a recording, one per call to `next_frame`.
+    pub fn from_recording(recording: &InputRecording) -> Self {
+        let mut input = Self::new();
+        for frame in recording.frames() {
+            input.queue_frame(frame.clone());
+        }
+        input.next_frame();
+        input
+    }
+
+    /// State of the current frame.
+    pub fn frame(&self) -> &InputFrame {
+        &self.current
+    }
+
+    /// Mutable state of the current frame.
+    pub fn frame_mut(&mut self) -> &mut InputFrame {
+        &mut self.current
+    }
+
+    /// Queues a frame to be used by a following `next_frame`.
+    pub fn queue_frame(&mut self, frame: InputFrame) -> &mut Self {
+        self.queued.push_back(frame);
+        self
+    }
+
+    /// Moves to the next frame, taking it from the queue or carrying the current state over.
+    pub fn next_frame(&mut self) -> &mut Self {
+        let next = self.queued.pop_front().unwrap_or_else(|| InputFrame {
+            mouse_wheel_move: 0,
+            ..self.current.clone()
+        });
+        self.previous = std::mem::replace(&mut self.current, next);
+        let previous = &self.previous;
+        self.pending_keys = self
+            .current
+            .keys_down
+            .iter()
+            .filter(|k| !previous.keys_down.contains(k))
+            .filter_map(|&k| key_from_i32(k as i32))
+            .collect();
+        self
+    }
+
+    /// Holds `key` down from the current frame on.
+    pub fn key_down(&mut self, key: KeyboardKey) -> &mut Self {
+        if !self.current.is_key_down(key) {
+            self.current.keys_down.push(key as u16);
+            self.pending_keys.push_back(key);
+        }
+        self
+    }
+
+    /// Releases `key` from the current frame on.
+    pub fn key_up(&mut self, key: KeyboardKey) -> &mut Self {
+        self.current.keys_down.retain(|&k| k != key as u16);
+        self.pending_keys.retain(|&k| k != key);
+        self
+    }
+
+    /// Moves the mouse to `position`.
+    pub fn mouse_move(&mut self, position: impl Into<Vector2>) -> &mut Self {
+        self.current.mouse_position = position.into();
+        self
+    }
+
+    /// Holds `button` down from the current frame on.
+    pub fn mouse_button_down(&mut self, button: MouseButton) -> &mut Self {
+        self.current.mouse_buttons |= 1 << button as u32;
+        self
+    }
+
+    /// Releases `button` from the current frame on.
+    pub fn mouse_button_up(&mut self, button: MouseButton) -> &mut Self {
+        self.current.mouse_buttons &= !(1 << button as u32);
+        self
+    }
+
+    /// Sets the wheel movement of the current frame only.
+    pub fn mouse_wheel(&mut self, amount: i32) -> &mut Self {
+        self.current.mouse_wheel_move = amount;
+        self
+    }
+
+    /// Connects or disconnects `gamepad`.
+    pub fn set_gamepad_available(&mut self, gamepad: GamepadNumber, available: bool) -> &mut Self {
+        let slot = &mut self.current.gamepads[gamepad as usize];
+        if !available {
+            *slot = None;
+        } else if slot.is_none() {
+            *slot = Some(GamepadFrame {
+                buttons: 0,
+                axes: vec![0.0; GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER as usize + 1],
+            });
+        }
+        self
+    }
+
+    /// Holds `button` of `gamepad` down from the current frame on, connecting the gamepad if needed.
+    pub fn gamepad_button_down(
+        &mut self,
+        gamepad: GamepadNumber,
+        button: GamepadButton,
+    ) -> &mut Self {
+        self.set_gamepad_available(gamepad, true);
+        if let Some(g) = self.current.gamepads[gamepad as usize].as_mut() {
+            g.buttons |= 1 << button as u32;
+        }
+        self
+    }
+
+    /// Releases `button` of `gamepad` from the current frame on.
+    pub fn gamepad_button_up(
+        &mut self,
+        gamepad: GamepadNumber,
+        button: GamepadButton,
+    ) -> &mut Self {
+        if let Some(g) = self.current.gamepads[gamepad as usize].as_mut() {
+            g.buttons &= !(1 << button as u32);
+        }
+        self
+    }
+
+    /// Sets the movement of `axis` of `gamepad`, connecting the gamepad if needed.
+    pub fn gamepad_axis(
+        &mut self,
+        gamepad: GamepadNumber,
+        axis: GamepadAxis,
+        value: f32,
+    ) -> &mut Self {
+        self.set_gamepad_available(gamepad, true);
+        if let Some(g) = self.current.gamepads[gamepad as usize].as_mut() {
+            let index = axis as usize;
+            if g.axes.len() <= index {
+                g.axes.resize(index + 1, 0.0);
+            }
+            g.axes[index] = value;
+        }
+        self
+    }
+}
+
+impl InputSource for MockInput {
+    fn is_key_pressed(&self, key: KeyboardKey) -> bool {
+        self.current.is_key_down(key) && !self.previous.is_key_down(key)
+    }
+    fn is_key_down(&self, key: KeyboardKey) -> bool {
+        self.current.is_key_down(key)
+    }
+    fn is_key_released(&self, key: KeyboardKey) -> bool {
+        !self.current.is_key_down(key) && self.previous.is_key_down(key)
+    }
+    fn is_key_up(&self, key: KeyboardKey) -> bool {
+        !self.current.is_key_down(key)
+    }
+    fn get_key_pressed(&mut self) -> Option<KeyboardKey> {
+        self.pending_keys.pop_front()
+    }
+
+    fn is_gamepad_available(&self, gamepad: GamepadNumber) -> bool {
+        self.current.is_gamepad_available(gamepad)
+    }
+    fn is_gamepad_button_pressed(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool {
+        self.current.is_gamepad_button_down(gamepad, button)
+            && !self.previous.is_gamepad_button_down(gamepad, button)
+    }
+    fn is_gamepad_button_down(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool {
+        self.current.is_gamepad_button_down(gamepad, button)
+    }
+    fn is_gamepad_button_released(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool {
+        !self.current.is_gamepad_button_down(gamepad, button)
+            && self.previous.is_gamepad_button_down(gamepad, button)
+    }
+    fn is_gamepad_button_up(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool {
+        !self.current.is_gamepad_button_down(gamepad, button)
+    }
+    fn get_gamepad_button_pressed(&self) -> Option<GamepadButton> {
+        use crate::consts::GamepadNumber::*;
+        for &gamepad in &[
+            GAMEPAD_PLAYER1,
+            GAMEPAD_PLAYER2,
+            GAMEPAD_PLAYER3,
+            GAMEPAD_PLAYER4,
+        ] {
+            for button in 1..=GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB as u32 {
+                let button: GamepadButton = unsafe { std::mem::transmute(button) };
+                if self.is_gamepad_button_pressed(gamepad, button) {
+                    return Some(button);
+                }
+            }
+        }
+        None
+    }
+    fn get_gamepad_axis_count(&self, gamepad: GamepadNumber) -> i32 {
+        self.current.gamepads[gamepad as usize]
+            .as_ref()
+            .map_or(0, |g| g.axes.len() as i32)
+    }
+    fn get_gamepad_axis_movement(&self, gamepad: GamepadNumber, axis: GamepadAxis) -> f32 {
+        self.current.gamepad_axis_movement(gamepad, axis)
+    }
+
+    fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.current.is_mouse_button_down(button) && !self.previous.is_mouse_button_down(button)
+    }
+    fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.current.is_mouse_button_down(button)
+    }
+    fn is_mouse_button_released(&self, button: MouseButton) -> bool {
+        !self.current.is_mouse_button_down(button) && self.previous.is_mouse_button_down(button)
+    }
+    fn is_mouse_button_up(&self, button: MouseButton) -> bool {
+        !self.current.is_mouse_button_down(button)
+    }
+    fn get_mouse_position(&self) -> Vector2 {
+        self.current.mouse_position
+    }
+    fn get_mouse_wheel_move(&self) -> i32 {
+        self.current.mouse_wheel_move
+    }
+
+    fn get_touch_position(&self, index: u32) -> Vector2 {
+        self.touch_points
+            .get(index as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+    fn get_touch_points_count(&self) -> u32 {
+        self.touch_points.len() as u32
+    }
+
+    fn is_gesture_detected(&self, gesture: GestureType) -> bool {
+        self.gesture == gesture
+    }
+    fn get_gesture_detected(&self) -> GestureType {
+        self.gesture
+    }
+    fn get_gesture_hold_duration(&self) -> f32 {
+        self.gesture_hold_duration
+    }
+    fn get_gesture_drag_vector(&self) -> Vector2 {
+        self.gesture_drag_vector
+    }
+    fn get_gesture_drag_angle(&self) -> f32 {
+        self.gesture_drag_angle
+    }
+    fn get_gesture_pinch_vector(&self) -> Vector2 {
+        self.gesture_pinch_vector
+    }
+    fn get_gesture_pinch_angle(&self) -> f32 {
+        self.gesture_pinch_angle
+    }
+}
+
 pub fn key_from_i32(key: i32) -> Option<crate::consts::KeyboardKey> {
     use crate::consts::KeyboardKey::*;
     match key {
@@ -413,3 +1011,37 @@ pub fn key_from_i32(key: i32) -> Option<crate::consts::KeyboardKey> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod input_test {
+    use super::*;
+    use crate::consts::KeyboardKey::*;
+
+    #[test]
+    fn test_mock_input_frames() {
+        let mut input = MockInput::new();
+        input
+            .key_down(KEY_SPACE)
+            .mouse_move(Vector2::new(10.0, 20.0));
+        assert!(input.is_key_pressed(KEY_SPACE));
+        assert_eq!(input.get_key_pressed(), Some(KEY_SPACE));
+        assert_eq!(input.get_key_pressed(), None);
+        assert_eq!(input.get_mouse_x(), 10);
+
+        input.next_frame();
+        assert!(!input.is_key_pressed(KEY_SPACE), "press reported twice");
+        assert!(input.is_key_down(KEY_SPACE));
+        assert_eq!(input.get_mouse_position(), Vector2::new(10.0, 20.0));
+
+        input.key_up(KEY_SPACE);
+        assert!(input.is_key_released(KEY_SPACE));
+        input.next_frame();
+        assert!(input.is_key_up(KEY_SPACE) && !input.is_key_released(KEY_SPACE));
+
+        let mut frame = InputFrame::default();
+        frame.keys_down.push(KEY_ENTER as u16);
+        input.queue_frame(frame).next_frame();
+        assert!(input.is_key_pressed(KEY_ENTER));
+        assert_eq!(input.get_mouse_position(), Vector2::zero());
+    }
+}
@@ -0,0 +1,673 @@
+//! Volume buses grouping sounds, music and streams
+//!
+//! A [`Mixer`] holds a tree of buses under a master bus, by default `music`, `sfx`, `voice` and `ui`. Each bus has a
+//! volume, and can be muted or soloed. Audio assigned to a bus plays at its own volume times the volumes of the bus
+//! and every bus above it, set again on every [`Mixer::update`]. Positional audio is spatialized through its
+//! [`BusMember`], so the distance volume is kept under the bus volume:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run(
+//! #     rl: &mut RaylibHandle,
+//! #     audio: &mut RaylibAudio,
+//! #     theme: &mut Music,
+//! #     line: &mut Sound,
+//! #     listener: &AudioListener,
+//! #     speaker: &AudioEmitter,
+//! # ) {
+//! let mut mixer = Mixer::new();
+//! let mut theme = mixer.assign(theme, BusId::MUSIC, 1.0);
+//! let mut line = mixer.assign(line, BusId::VOICE, 1.0);
+//! // music drops to 30% while a voice line plays
+//! mixer.add_duck(BusId::VOICE, BusId::MUSIC, 0.3, 0.1, 0.5);
+//! audio.play_music_stream(&mut theme);
+//! audio.play_sound(&line);
+//!
+//! let settings = mixer.snapshot();
+//! let mut paused = settings.clone();
+//! paused.set_volume(BusId::SFX, 0.0);
+//! paused.set_volume(BusId::MUSIC, 0.4);
+//! while !rl.window_should_close() {
+//!     if rl.is_key_pressed(KeyboardKey::KEY_P) {
+//!         mixer.blend_to(&paused, 0.5);
+//!     }
+//!     line.apply_spatial(&listener.spatialize(&speaker));
+//!     mixer.update(rl.get_frame_time());
+//!     audio.update_music_stream(&mut theme);
+//! }
+//! # }
+//! ```
+//!
+//! [`Mixer`]: struct.Mixer.html
+//! [`Mixer::update`]: struct.Mixer.html#method.update
+//! [`BusMember`]: struct.BusMember.html
+use crate::core::spatial::{SpatialParams, Spatialize};
+use crate::ffi;
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::rc::{Rc, Weak};
+
+/// A bus of a [`Mixer`](struct.Mixer.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BusId(usize);
+
+impl BusId {
+    /// The bus every other bus ends up in.
+    pub const MASTER: BusId = BusId(0);
+    pub const MUSIC: BusId = BusId(1);
+    pub const SFX: BusId = BusId(2);
+    pub const VOICE: BusId = BusId(3);
+    pub const UI: BusId = BusId(4);
+}
+
+#[derive(Debug, Clone)]
+struct Bus {
+    name: String,
+    parent: Option<BusId>,
+    volume: f32,
+    muted: bool,
+    solo: bool,
+    /// Set by the game, see [`Mixer::set_active`](struct.Mixer.html#method.set_active).
+    active: bool,
+    /// Gain from ducking, `1.0` when not ducked.
+    duck: f32,
+}
+
+/// Lowers the `target` bus while anything plays on the `trigger` bus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Duck {
+    trigger: BusId,
+    target: BusId,
+    /// Gain of the target while ducked.
+    gain: f32,
+    /// Seconds to duck.
+    attack: f32,
+    /// Seconds to recover.
+    release: f32,
+    /// How far ducked, from `0.0` to `1.0`.
+    amount: f32,
+}
+
+/// Bus volumes and mutes, to blend to with [`Mixer::blend_to`](struct.Mixer.html#method.blend_to).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixerSnapshot {
+    volumes: Vec<f32>,
+    muted: Vec<bool>,
+}
+
+impl MixerSnapshot {
+    pub fn volume(&self, bus: BusId) -> f32 {
+        self.volumes.get(bus.0).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_volume(&mut self, bus: BusId, volume: f32) {
+        if let Some(v) = self.volumes.get_mut(bus.0) {
+            *v = volume.max(0.0);
+        }
+    }
+
+    pub fn is_muted(&self, bus: BusId) -> bool {
+        self.muted.get(bus.0).copied().unwrap_or(false)
+    }
+
+    pub fn set_muted(&mut self, bus: BusId, muted: bool) {
+        if let Some(m) = self.muted.get_mut(bus.0) {
+            *m = muted;
+        }
+    }
+}
+
+/// A snapshot being blended to.
+#[derive(Debug, Clone)]
+struct Blend {
+    from: Vec<f32>,
+    to: MixerSnapshot,
+    duration: f32,
+    elapsed: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Member {
+    stream: ffi::AudioStream,
+    bus: BusId,
+    volume: f32,
+    /// Distance volume from [`BusMember::apply_spatial`](struct.BusMember.html#method.apply_spatial).
+    gain: f32,
+}
+
+#[derive(Debug, Default)]
+struct MixerState {
+    buses: Vec<Bus>,
+    ducks: Vec<Duck>,
+    blend: Option<Blend>,
+    members: Vec<Option<Member>>,
+}
+
+impl MixerState {
+    /// Whether `bus` is `ancestor` or inside it.
+    fn is_within(&self, mut bus: BusId, ancestor: BusId) -> bool {
+        loop {
+            if bus == ancestor {
+                return true;
+            }
+            match self.buses[bus.0].parent {
+                Some(parent) => bus = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Whether soloing lets `bus` through: nothing is soloed, or a bus above or below it is.
+    fn passes_solo(&self, bus: BusId) -> bool {
+        let soloed: Vec<BusId> = (0..self.buses.len())
+            .map(BusId)
+            .filter(|b| self.buses[b.0].solo)
+            .collect();
+        soloed.is_empty()
+            || soloed
+                .iter()
+                .any(|&s| self.is_within(bus, s) || self.is_within(s, bus))
+    }
+
+    fn effective_volume(&self, bus: BusId) -> f32 {
+        if !self.passes_solo(bus) {
+            return 0.0;
+        }
+        let mut volume = 1.0;
+        let mut current = Some(bus);
+        while let Some(id) = current {
+            let b = &self.buses[id.0];
+            if b.muted {
+                return 0.0;
+            }
+            volume *= b.volume * b.duck;
+            current = b.parent;
+        }
+        volume
+    }
+
+    /// The volume `member` plays at.
+    fn member_volume(&self, member: &Member) -> f32 {
+        member.volume * member.gain * self.effective_volume(member.bus)
+    }
+
+    /// Sets the distance volume of the member at `index`, and returns the volume it now plays at.
+    fn spatialize(&mut self, index: usize, gain: f32) -> Option<f32> {
+        let member = self.members[index].as_mut()?;
+        member.gain = gain.max(0.0);
+        let member = *member;
+        Some(self.member_volume(&member))
+    }
+
+    fn apply(&self) {
+        for member in self.members.iter().flatten() {
+            unsafe { ffi::SetAudioStreamVolume(member.stream, self.member_volume(member)) }
+        }
+    }
+
+    /// Whether something plays on `bus` or a bus inside it.
+    fn is_active(&self, bus: BusId) -> bool {
+        (0..self.buses.len()).any(|b| self.buses[b].active && self.is_within(BusId(b), bus))
+            || self.members.iter().flatten().any(|m| {
+                self.is_within(m.bus, bus) && unsafe { ffi::IsAudioStreamPlaying(m.stream) }
+            })
+    }
+
+    /// Advances ducking and blending by `dt` seconds.
+    fn step(&mut self, dt: f32, active: impl Fn(&MixerState, BusId) -> bool) {
+        if let Some(blend) = self.blend.as_mut() {
+            blend.elapsed += dt;
+            let t = if blend.duration > 0.0 {
+                (blend.elapsed / blend.duration).min(1.0)
+            } else {
+                1.0
+            };
+            for (i, bus) in self.buses.iter_mut().enumerate() {
+                let from = blend.from.get(i).copied().unwrap_or(bus.volume);
+                bus.volume = from + (blend.to.volume(BusId(i)) - from) * t;
+                if t >= 1.0 {
+                    bus.muted = blend.to.is_muted(BusId(i));
+                }
+            }
+            if t >= 1.0 {
+                self.blend = None;
+            }
+        }
+
+        let triggers: Vec<bool> = self.ducks.iter().map(|d| active(self, d.trigger)).collect();
+        for (duck, on) in self.ducks.iter_mut().zip(triggers) {
+            duck.amount = if on {
+                if duck.attack > 0.0 {
+                    (duck.amount + dt / duck.attack).min(1.0)
+                } else {
+                    1.0
+                }
+            } else if duck.release > 0.0 {
+                (duck.amount - dt / duck.release).max(0.0)
+            } else {
+                0.0
+            };
+        }
+        self.apply_ducks();
+    }
+
+    /// Sets the duck gains of the buses from the ducks.
+    fn apply_ducks(&mut self) {
+        for bus in self.buses.iter_mut() {
+            bus.duck = 1.0;
+        }
+        for duck in &self.ducks {
+            let gain = 1.0 + (duck.gain - 1.0) * duck.amount;
+            // the deepest of several ducks on one bus wins
+            let bus = &mut self.buses[duck.target.0];
+            bus.duck = bus.duck.min(gain);
+        }
+    }
+}
+
+/// A tree of volume buses. See the [module docs](index.html).
+#[derive(Debug)]
+pub struct Mixer {
+    state: Rc<RefCell<MixerState>>,
+}
+
+impl Default for Mixer {
+    fn default() -> Mixer {
+        Mixer::new()
+    }
+}
+
+impl Mixer {
+    /// A mixer with the `master` bus and the `music`, `sfx`, `voice` and `ui` buses inside it.
+    pub fn new() -> Mixer {
+        let mixer = Mixer {
+            state: Rc::new(RefCell::new(MixerState::default())),
+        };
+        mixer.state.borrow_mut().buses.push(Bus {
+            name: "master".to_string(),
+            parent: None,
+            volume: 1.0,
+            muted: false,
+            solo: false,
+            active: false,
+            duck: 1.0,
+        });
+        for name in &["music", "sfx", "voice", "ui"] {
+            mixer.add_bus(name, BusId::MASTER);
+        }
+        mixer
+    }
+
+    /// Adds a bus inside `parent`.
+    pub fn add_bus(&self, name: &str, parent: BusId) -> BusId {
+        let mut state = self.state.borrow_mut();
+        state.buses.push(Bus {
+            name: name.to_string(),
+            parent: Some(parent),
+            volume: 1.0,
+            muted: false,
+            solo: false,
+            active: false,
+            duck: 1.0,
+        });
+        if let Some(blend) = state.blend.as_mut() {
+            blend.from.push(1.0);
+            blend.to.volumes.push(1.0);
+            blend.to.muted.push(false);
+        }
+        BusId(state.buses.len() - 1)
+    }
+
+    /// The bus called `name`.
+    pub fn bus(&self, name: &str) -> Option<BusId> {
+        self.state
+            .borrow()
+            .buses
+            .iter()
+            .position(|b| b.name == name)
+            .map(BusId)
+    }
+
+    pub fn bus_name(&self, bus: BusId) -> String {
+        self.state.borrow().buses[bus.0].name.clone()
+    }
+
+    /// Every bus, master first.
+    pub fn buses(&self) -> Vec<BusId> {
+        (0..self.state.borrow().buses.len()).map(BusId).collect()
+    }
+
+    pub fn parent(&self, bus: BusId) -> Option<BusId> {
+        self.state.borrow().buses[bus.0].parent
+    }
+
+    /// The bus's own volume, as set by a slider.
+    pub fn volume(&self, bus: BusId) -> f32 {
+        self.state.borrow().buses[bus.0].volume
+    }
+
+    /// Sets the bus's own volume (`1.0` is max level).
+    pub fn set_volume(&mut self, bus: BusId, volume: f32) {
+        self.change(|state| state.buses[bus.0].volume = volume.max(0.0));
+    }
+
+    /// The volume audio on `bus` plays at, after the buses above it, mutes, solos and ducking.
+    pub fn effective_volume(&self, bus: BusId) -> f32 {
+        self.state.borrow().effective_volume(bus)
+    }
+
+    pub fn is_muted(&self, bus: BusId) -> bool {
+        self.state.borrow().buses[bus.0].muted
+    }
+
+    /// Silences the bus and the buses inside it.
+    pub fn set_muted(&mut self, bus: BusId, muted: bool) {
+        self.change(|state| state.buses[bus.0].muted = muted);
+    }
+
+    pub fn is_solo(&self, bus: BusId) -> bool {
+        self.state.borrow().buses[bus.0].solo
+    }
+
+    /// While any bus is soloed, only soloed buses, the buses inside them and the buses they're in are heard.
+    pub fn set_solo(&mut self, bus: BusId, solo: bool) {
+        self.change(|state| state.buses[bus.0].solo = solo);
+    }
+
+    /// Marks the bus as playing, for ducking, in addition to the audio assigned to it. Useful for sounds played
+    /// outside the mixer, such as the copies [`RaylibAudio::play_sound_multi`] starts.
+    ///
+    /// [`RaylibAudio::play_sound_multi`]: ../audio/struct.RaylibAudio.html#method.play_sound_multi
+    pub fn set_active(&mut self, bus: BusId, active: bool) {
+        self.state.borrow_mut().buses[bus.0].active = active;
+    }
+
+    /// Lowers `target` to `gain` over `attack` seconds while anything plays on `trigger`, and brings it back over
+    /// `release` seconds after.
+    pub fn add_duck(
+        &mut self,
+        trigger: BusId,
+        target: BusId,
+        gain: f32,
+        attack: f32,
+        release: f32,
+    ) {
+        self.state.borrow_mut().ducks.push(Duck {
+            trigger,
+            target,
+            gain: gain.clamp(0.0, 1.0),
+            attack,
+            release,
+            amount: 0.0,
+        });
+    }
+
+    /// Removes the ducks of `target` by `trigger`.
+    pub fn remove_duck(&mut self, trigger: BusId, target: BusId) {
+        self.change(|state| {
+            state
+                .ducks
+                .retain(|d| d.trigger != trigger || d.target != target);
+            state.apply_ducks();
+        });
+    }
+
+    /// The bus volumes and mutes now.
+    pub fn snapshot(&self) -> MixerSnapshot {
+        let state = self.state.borrow();
+        MixerSnapshot {
+            volumes: state.buses.iter().map(|b| b.volume).collect(),
+            muted: state.buses.iter().map(|b| b.muted).collect(),
+        }
+    }
+
+    /// Moves the bus volumes to `snapshot`'s over `duration` seconds of [`update`](#method.update)s. Mutes switch
+    /// when the blend ends.
+    pub fn blend_to(&mut self, snapshot: &MixerSnapshot, duration: f32) {
+        let mut to = snapshot.clone();
+        {
+            let state = self.state.borrow();
+            to.volumes.resize(state.buses.len(), 1.0);
+            to.muted.resize(state.buses.len(), false);
+        }
+        let from = self.snapshot().volumes;
+        self.state.borrow_mut().blend = Some(Blend {
+            from,
+            to,
+            duration,
+            elapsed: 0.0,
+        });
+        if duration <= 0.0 {
+            self.update(0.0);
+        }
+    }
+
+    /// Whether a snapshot blend is under way.
+    pub fn is_blending(&self) -> bool {
+        self.state.borrow().blend.is_some()
+    }
+
+    /// Advances ducking and snapshot blends by `dt` seconds, and sets the volume of every member. Call once a frame.
+    pub fn update(&mut self, dt: f32) {
+        let mut state = self.state.borrow_mut();
+        state.step(dt, MixerState::is_active);
+        state.apply();
+    }
+
+    fn change(&mut self, f: impl FnOnce(&mut MixerState)) {
+        let mut state = self.state.borrow_mut();
+        f(&mut state);
+        state.apply();
+    }
+
+    /// Puts `audio` on `bus`, playing at `volume` times the bus's effective volume until the returned member is
+    /// dropped. The member stands in for the audio meanwhile.
+    pub fn assign<'a, T: AsRef<ffi::AudioStream>>(
+        &mut self,
+        audio: &'a mut T,
+        bus: BusId,
+        volume: f32,
+    ) -> BusMember<'a, T> {
+        let member = Member {
+            stream: *audio.as_ref(),
+            bus,
+            volume,
+            gain: 1.0,
+        };
+        let mut state = self.state.borrow_mut();
+        let index = match state.members.iter().position(Option::is_none) {
+            Some(free) => free,
+            None => {
+                state.members.push(None);
+                state.members.len() - 1
+            }
+        };
+        state.members[index] = Some(member);
+        state.apply();
+        BusMember {
+            audio,
+            mixer: Rc::downgrade(&self.state),
+            index,
+        }
+    }
+}
+
+/// Audio assigned to a bus by [`Mixer::assign`](struct.Mixer.html#method.assign). Derefs to the audio, and takes it
+/// off the bus when dropped.
+///
+/// The mixer sets the audio's volume on every update, so change it through [`set_volume`](#method.set_volume), and
+/// spatialize the member rather than the audio.
+pub struct BusMember<'a, T> {
+    audio: &'a mut T,
+    mixer: Weak<RefCell<MixerState>>,
+    index: usize,
+}
+
+impl<'a, T> BusMember<'a, T> {
+    fn with_member(&self, f: impl FnOnce(&mut Member)) {
+        if let Some(state) = self.mixer.upgrade() {
+            let mut state = state.borrow_mut();
+            if let Some(member) = state.members[self.index].as_mut() {
+                f(member);
+            }
+            state.apply();
+        }
+    }
+
+    /// Sets the member's own volume, before the bus's.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.with_member(|m| m.volume = volume);
+    }
+
+    /// Moves to another bus.
+    pub fn set_bus(&mut self, bus: BusId) {
+        self.with_member(|m| m.bus = bus);
+    }
+
+    /// The bus the member plays on.
+    pub fn bus(&self) -> Option<BusId> {
+        let state = self.mixer.upgrade()?;
+        let state = state.borrow();
+        state.members[self.index].map(|m| m.bus)
+    }
+
+    /// The volume the audio plays at: its own, times the distance volume and the bus's effective volume.
+    pub fn effective_volume(&self) -> Option<f32> {
+        let state = self.mixer.upgrade()?;
+        let state = state.borrow();
+        state.members[self.index].map(|m| state.member_volume(&m))
+    }
+}
+
+/// Keeps the distance volume as a gain under the member's and the bus's volumes, so bus changes don't undo it.
+impl<'a, T: Spatialize> Spatialize for BusMember<'a, T> {
+    fn apply_spatial(&mut self, params: &SpatialParams) {
+        let mut params = *params;
+        if let Some(state) = self.mixer.upgrade() {
+            if let Some(volume) = state.borrow_mut().spatialize(self.index, params.volume) {
+                params.volume = volume;
+            }
+        }
+        self.audio.apply_spatial(&params);
+    }
+}
+
+impl<'a, T> Deref for BusMember<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.audio
+    }
+}
+
+impl<'a, T> DerefMut for BusMember<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.audio
+    }
+}
+
+impl<'a, T> Drop for BusMember<'a, T> {
+    fn drop(&mut self) {
+        if let Some(state) = self.mixer.upgrade() {
+            state.borrow_mut().members[self.index] = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod mixer_test {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_bus_volumes() {
+        let mut mixer = Mixer::new();
+        assert_eq!(mixer.bus("voice"), Some(BusId::VOICE));
+        let footsteps = mixer.add_bus("footsteps", BusId::SFX);
+        assert_eq!(mixer.parent(footsteps), Some(BusId::SFX));
+
+        mixer.set_volume(BusId::MASTER, 0.5);
+        mixer.set_volume(BusId::SFX, 0.5);
+        mixer.set_volume(footsteps, 0.5);
+        assert_close(mixer.effective_volume(footsteps), 0.125);
+        assert_close(mixer.effective_volume(BusId::MUSIC), 0.5);
+
+        mixer.set_muted(BusId::SFX, true);
+        assert_close(mixer.effective_volume(footsteps), 0.0);
+        mixer.set_muted(BusId::SFX, false);
+
+        mixer.set_solo(footsteps, true);
+        assert_close(mixer.effective_volume(BusId::MUSIC), 0.0);
+        assert_close(mixer.effective_volume(BusId::SFX), 0.25);
+        assert_close(mixer.effective_volume(footsteps), 0.125);
+    }
+
+    #[test]
+    fn test_ducking() {
+        let mut mixer = Mixer::new();
+        mixer.add_duck(BusId::VOICE, BusId::MUSIC, 0.2, 0.5, 1.0);
+        mixer.set_active(BusId::VOICE, true);
+        mixer.update(0.25);
+        assert_close(mixer.effective_volume(BusId::MUSIC), 0.6);
+        mixer.update(0.5);
+        assert_close(mixer.effective_volume(BusId::MUSIC), 0.2);
+        assert_close(mixer.effective_volume(BusId::SFX), 1.0);
+
+        mixer.set_active(BusId::VOICE, false);
+        mixer.update(0.5);
+        assert_close(mixer.effective_volume(BusId::MUSIC), 0.6);
+        mixer.update(0.5);
+        assert_close(mixer.effective_volume(BusId::MUSIC), 1.0);
+    }
+
+    #[test]
+    fn test_snapshot_blend() {
+        let mut mixer = Mixer::new();
+        mixer.set_volume(BusId::MUSIC, 0.8);
+        let normal = mixer.snapshot();
+        let mut paused = normal.clone();
+        paused.set_volume(BusId::MUSIC, 0.4);
+        paused.set_muted(BusId::SFX, true);
+
+        mixer.blend_to(&paused, 1.0);
+        mixer.update(0.5);
+        assert!(mixer.is_blending());
+        assert_close(mixer.volume(BusId::MUSIC), 0.6);
+        assert!(!mixer.is_muted(BusId::SFX));
+        mixer.update(0.5);
+        assert!(!mixer.is_blending());
+        assert_close(mixer.volume(BusId::MUSIC), 0.4);
+        assert!(mixer.is_muted(BusId::SFX));
+
+        mixer.blend_to(&normal, 0.0);
+        assert_close(mixer.volume(BusId::MUSIC), 0.8);
+        assert!(!mixer.is_muted(BusId::SFX));
+    }
+
+    #[test]
+    fn test_spatialized_member() {
+        let mixer = Mixer::new();
+        let mut state = mixer.state.borrow_mut();
+        state.buses[BusId::SFX.0].volume = 0.5;
+        state.members.push(Some(Member {
+            stream: unsafe { std::mem::zeroed() },
+            bus: BusId::SFX,
+            volume: 0.8,
+            gain: 1.0,
+        }));
+        assert_close(state.member_volume(&state.members[0].unwrap()), 0.4);
+
+        assert_close(state.spatialize(0, 0.5).unwrap(), 0.2);
+        assert_close(state.spatialize(0, -1.0).unwrap(), 0.0);
+        state.spatialize(0, 0.5);
+
+        // the distance volume stays under later bus changes
+        state.buses[BusId::SFX.0].volume = 1.0;
+        assert_close(state.member_volume(&state.members[0].unwrap()), 0.4);
+        state.members[0] = None;
+        assert_eq!(state.spatialize(0, 0.5), None);
+    }
+}
@@ -1,23 +1,48 @@
 #[macro_use]
 mod macros;
 
+pub mod analysis;
+pub mod atlas;
 pub mod audio;
 pub mod camera;
+mod checksum;
 pub mod collision;
 pub mod color;
 pub mod data;
 pub mod drawing;
+pub mod dsp;
 pub mod file;
+pub mod filter;
+#[cfg(feature = "image")]
+pub mod image_interop;
+mod inflate;
 pub mod input;
+pub(crate) mod json;
 pub mod logging;
 pub mod math;
 pub mod misc;
+pub mod mixer;
 pub mod models;
+pub mod music;
+pub mod particles;
+pub mod pixels;
+pub mod procedural;
+pub mod raster;
+pub mod recorder;
+pub mod replay;
+pub(crate) mod rng;
+pub mod sfx;
 pub mod shaders;
+pub mod spatial;
+pub mod sprite;
+pub mod svg;
 pub mod text;
 pub mod texture;
+pub mod tilemap;
+pub mod voice;
 pub mod vr;
 pub mod window;
+pub(crate) mod xml;
 
 use crate::ffi;
 use std::ffi::CString;
@@ -70,6 +95,14 @@ impl Drop for RaylibHandle {
     }
 }
 
+#[cfg(test)]
+impl RaylibHandle {
+    /// Handle for unit tests that never reach raylib. Dropping it leaves the window alone.
+    pub(crate) fn for_test() -> (RaylibHandle, RaylibThread) {
+        (RaylibHandle(()), RaylibThread(PhantomData))
+    }
+}
+
 /// A builder that allows more customization of the game window shown to the user before the `RaylibHandle` is created.
 #[derive(Debug, Default)]
 pub struct RaylibBuilder {
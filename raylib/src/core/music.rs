@@ -0,0 +1,657 @@
+//! Music with seeking, intro and loop sections, crossfades and playlists
+//!
+//! [`Music`] can't seek (see the [`audio`] module). A [`MusicTrack`] is decoded into memory instead, so it can jump
+//! anywhere, sample accurately, and loop a section after an intro. OGG files tagged with `LOOPSTART` and
+//! `LOOPLENGTH` or `LOOPEND`, as RPG Maker and many trackers write them, get their loop points from the tags.
+//!
+//! A [`MusicPlayer`] plays tracks on a stream of its own, crossfading from one to the next and working through a
+//! queue:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run(rl: &mut RaylibHandle) -> Result<(), String> {
+//! let audio = RaylibAudio::init_audio_device()?;
+//! let mut player = MusicPlayer::new(&audio, 44100, 2)?;
+//! player.play(MusicTrack::load("town.ogg")?);
+//! player.start_thread();
+//! while !rl.window_should_close() {
+//!     if rl.is_key_pressed(KeyboardKey::KEY_B) {
+//!         player.crossfade_to(MusicTrack::load("battle.ogg")?, 1.5);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Decoded music takes memory, about 10 MB per minute of 44.1 kHz stereo, so long soundtracks may still be better
+//! off as [`Music`].
+//!
+//! [`Music`]: ../audio/struct.Music.html
+//! [`audio`]: ../audio/index.html#limitations-of-raylib-30
+//! [`MusicTrack`]: struct.MusicTrack.html
+//! [`MusicPlayer`]: struct.MusicPlayer.html
+use crate::core::audio::{RaylibAudio, Wave};
+use crate::core::dsp::resample;
+use crate::core::procedural::{remix_frame, ProceduralStream, Source};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Loop points from the Vorbis comments of an OGG file, in frames: `LOOPSTART`, and the end from `LOOPEND` or
+/// `LOOPSTART + LOOPLENGTH`.
+pub(crate) fn ogg_loop_points(data: &[u8]) -> Option<(u64, Option<u64>)> {
+    let mut packets: Vec<Vec<u8>> = vec![Vec::new()];
+    let mut pos = 0;
+    // the comment header is the second packet
+    while packets.len() < 3 && data.len() >= pos + 27 && &data[pos..pos + 4] == b"OggS" {
+        let segments = data[pos + 26] as usize;
+        let table = data.get(pos + 27..pos + 27 + segments)?;
+        let mut body = pos + 27 + segments;
+        for &lace in table {
+            let chunk = data.get(body..body + lace as usize)?;
+            packets.last_mut()?.extend_from_slice(chunk);
+            body += lace as usize;
+            if lace < 255 {
+                packets.push(Vec::new());
+            }
+        }
+        pos = body;
+    }
+    let comments = packets.get(1)?;
+    if comments.len() < 7 || &comments[..7] != b"\x03vorbis" {
+        return None;
+    }
+
+    let read_u32 = |at: usize| -> Option<u32> {
+        let b = comments.get(at..at + 4)?;
+        Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let mut at = 7;
+    at += 4 + read_u32(at)? as usize;
+    let count = read_u32(at)?;
+    at += 4;
+    let (mut start, mut length, mut end) = (None, None, None);
+    for _ in 0..count {
+        let len = read_u32(at)? as usize;
+        let entry = String::from_utf8_lossy(comments.get(at + 4..at + 4 + len)?);
+        at += 4 + len;
+        let mut parts = entry.splitn(2, '=');
+        let key = parts.next().unwrap_or("").to_ascii_uppercase();
+        let value = parts.next().and_then(|v| v.trim().parse::<u64>().ok());
+        match key.as_str() {
+            "LOOPSTART" => start = value,
+            "LOOPLENGTH" => length = value,
+            "LOOPEND" => end = value,
+            _ => {}
+        }
+    }
+    let start = start?;
+    Some((start, end.or_else(|| length.map(|l| start + l))))
+}
+
+/// Music decoded into memory. Cheap to clone, clones share the samples.
+#[derive(Debug, Clone)]
+pub struct MusicTrack {
+    samples: Arc<Vec<f32>>,
+    sample_rate: u32,
+    channels: u32,
+    /// Frame playback returns to after reaching the loop end.
+    pub loop_start: u64,
+    /// Frame ending the loop, the end of the track when `None`.
+    pub loop_end: Option<u64>,
+    /// Whether the track loops, or stops at the end.
+    pub looping: bool,
+}
+
+impl MusicTrack {
+    /// A track holding a copy of `wave`'s samples, playing once.
+    pub fn from_wave(wave: &Wave) -> MusicTrack {
+        MusicTrack {
+            samples: Arc::new(wave.samples()),
+            sample_rate: wave.smaple_rate(),
+            channels: wave.channels().max(1),
+            loop_start: 0,
+            loop_end: None,
+            looping: false,
+        }
+    }
+
+    /// Decodes a music file. OGG files with loop tags loop between them.
+    pub fn load(filename: &str) -> Result<MusicTrack, String> {
+        let mut track = MusicTrack::from_wave(&Wave::load_wave(filename)?);
+        if filename.to_ascii_lowercase().ends_with(".ogg") {
+            let data = std::fs::read(filename)
+                .map_err(|e| format!("Cannot read music {}: {}", filename, e))?;
+            if let Some((start, end)) = ogg_loop_points(&data) {
+                track.set_loop_frames(start, end);
+            }
+        }
+        Ok(track)
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        (self.samples.len() / self.channels as usize) as u64
+    }
+
+    /// Length in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frame_count() as f32 / self.sample_rate.max(1) as f32
+    }
+
+    /// Loops from `start` to `end` seconds after playing up to `end` the first time, `end` being the end of the
+    /// track when `None`.
+    pub fn set_loop(&mut self, start: f32, end: Option<f32>) {
+        let rate = self.sample_rate as f32;
+        self.set_loop_frames(
+            (start.max(0.0) * rate) as u64,
+            end.map(|e| (e.max(0.0) * rate) as u64),
+        );
+    }
+
+    /// Like [`set_loop`](#method.set_loop) in frames.
+    pub fn set_loop_frames(&mut self, start: u64, end: Option<u64>) {
+        let frames = self.frame_count();
+        let end = end.map(|e| e.min(frames));
+        self.loop_start = start.min(end.unwrap_or(frames).saturating_sub(1));
+        self.loop_end = end;
+        self.looping = true;
+    }
+
+    /// The track at `sample_rate`, resampled if needed. The player resamples before taking the stream's lock, so
+    /// the refill thread isn't held up.
+    fn at_rate(&self, sample_rate: u32) -> MusicTrack {
+        if sample_rate == self.sample_rate || self.sample_rate == 0 {
+            return self.clone();
+        }
+        let ratio = sample_rate as f64 / self.sample_rate as f64;
+        let scale = |frame: u64| (frame as f64 * ratio).round() as u64;
+        MusicTrack {
+            samples: Arc::new(resample(
+                &self.samples,
+                self.channels as usize,
+                self.sample_rate,
+                sample_rate,
+            )),
+            sample_rate,
+            channels: self.channels,
+            loop_start: scale(self.loop_start),
+            loop_end: self.loop_end.map(scale),
+            looping: self.looping,
+        }
+    }
+
+    /// A [`Source`](../procedural/trait.Source.html) playing the track from the start.
+    pub fn source(&self) -> TrackSource {
+        TrackSource {
+            track: self.clone(),
+            position: 0,
+        }
+    }
+}
+
+/// Plays a [`MusicTrack`](struct.MusicTrack.html), see [`MusicTrack::source`](struct.MusicTrack.html#method.source).
+#[derive(Debug, Clone)]
+pub struct TrackSource {
+    track: MusicTrack,
+    /// Next frame.
+    position: u64,
+}
+
+impl TrackSource {
+    pub fn track(&self) -> &MusicTrack {
+        &self.track
+    }
+
+    /// Seconds played.
+    pub fn position(&self) -> f32 {
+        self.position as f32 / self.track.sample_rate.max(1) as f32
+    }
+
+    /// Jumps to `seconds` into the track.
+    pub fn seek(&mut self, seconds: f32) {
+        let frame = (seconds.max(0.0) * self.track.sample_rate as f32) as u64;
+        self.position = frame.min(self.track.frame_count());
+    }
+
+    /// Whether a track that doesn't loop has played to its end.
+    pub fn is_finished(&self) -> bool {
+        !self.track.looping && self.position >= self.track.frame_count()
+    }
+
+    /// Frames left before the end, `None` when looping.
+    fn remaining(&self) -> Option<u64> {
+        if self.track.looping {
+            None
+        } else {
+            Some(self.track.frame_count().saturating_sub(self.position))
+        }
+    }
+
+    /// Reads the next frame into `out`, remixed to its length. Silence once finished.
+    fn next_frame(&mut self, out: &mut [f32]) {
+        let frames = self.track.frame_count();
+        let end = self.track.loop_end.unwrap_or(frames).min(frames);
+        // seeking past the loop end plays on to the end of the track before looping
+        if self.track.looping && (self.position == end || self.position >= frames) {
+            self.position = self.track.loop_start;
+        }
+        if self.position >= frames {
+            out.iter_mut().for_each(|s| *s = 0.0);
+            return;
+        }
+        let channels = self.track.channels as usize;
+        let start = self.position as usize * channels;
+        remix_frame(&self.track.samples[start..start + channels], out);
+        self.position += 1;
+    }
+}
+
+impl Source for TrackSource {
+    fn sample_rate(&self) -> u32 {
+        self.track.sample_rate
+    }
+
+    fn channels(&self) -> u32 {
+        self.track.channels
+    }
+
+    fn fill(&mut self, out: &mut [f32]) {
+        let channels = self.track.channels as usize;
+        for frame in out.chunks_mut(channels) {
+            self.next_frame(frame);
+        }
+    }
+}
+
+/// A track fading out under the next one.
+#[derive(Debug)]
+struct Fade {
+    outgoing: TrackSource,
+    frames: u64,
+    elapsed: u64,
+}
+
+/// What a [`MusicPlayer`](struct.MusicPlayer.html) plays, mixing the current track with the one fading out.
+#[derive(Debug)]
+pub struct PlayerSource {
+    sample_rate: u32,
+    channels: u32,
+    current: Option<TrackSource>,
+    fade: Option<Fade>,
+    queue: VecDeque<MusicTrack>,
+    /// Seconds of crossfade between queued tracks.
+    queue_crossfade: f32,
+    /// Whether finished queued tracks go back to the end of the queue.
+    repeat: bool,
+    frame: Vec<f32>,
+    other: Vec<f32>,
+}
+
+impl PlayerSource {
+    fn new(sample_rate: u32, channels: u32) -> PlayerSource {
+        PlayerSource {
+            sample_rate,
+            channels,
+            current: None,
+            fade: None,
+            queue: VecDeque::new(),
+            queue_crossfade: 0.0,
+            repeat: false,
+            frame: vec![0.0; channels as usize],
+            other: vec![0.0; channels as usize],
+        }
+    }
+
+    /// Switches to `track`, fading the current one out over `seconds`.
+    fn start(&mut self, track: &MusicTrack, seconds: f32) {
+        let next = track.at_rate(self.sample_rate).source();
+        let frames = (seconds.max(0.0) * self.sample_rate as f32) as u64;
+        let previous = self.current.replace(next);
+        if self.repeat {
+            if let Some(previous) = previous.as_ref() {
+                self.queue.push_back(previous.track.clone());
+            }
+        }
+        self.fade = match previous {
+            Some(outgoing) if frames > 0 => Some(Fade {
+                outgoing,
+                frames,
+                elapsed: 0,
+            }),
+            _ => None,
+        };
+    }
+
+    /// Starts the next queued track if the current one is about to end.
+    fn advance(&mut self) {
+        if self.fade.is_some() || self.queue.is_empty() {
+            return;
+        }
+        let crossfade = (self.queue_crossfade * self.sample_rate as f32) as u64;
+        let due = match self.current.as_ref() {
+            None => true,
+            Some(current) => matches!(current.remaining(), Some(r) if r <= crossfade),
+        };
+        if due {
+            if let Some(next) = self.queue.pop_front() {
+                let seconds = match self.current.as_ref() {
+                    Some(current) => {
+                        current.remaining().unwrap_or(0) as f32 / self.sample_rate as f32
+                    }
+                    None => 0.0,
+                };
+                self.start(&next, seconds);
+            }
+        }
+    }
+}
+
+impl Source for PlayerSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    fn fill(&mut self, out: &mut [f32]) {
+        let channels = self.channels as usize;
+        for frame in out.chunks_mut(channels) {
+            self.advance();
+            match self.current.as_mut() {
+                Some(current) => current.next_frame(&mut self.frame),
+                None => self.frame.iter_mut().for_each(|s| *s = 0.0),
+            }
+            if let Some(fade) = self.fade.as_mut() {
+                fade.outgoing.next_frame(&mut self.other);
+                // equal power, so the loudness holds through the fade
+                let t = fade.elapsed as f32 / fade.frames as f32;
+                let angle = t * std::f32::consts::FRAC_PI_2;
+                let (fade_in, fade_out) = (angle.sin(), angle.cos());
+                for (s, o) in self.frame.iter_mut().zip(&self.other) {
+                    *s = *s * fade_in + o * fade_out;
+                }
+                fade.elapsed += 1;
+                if fade.elapsed >= fade.frames {
+                    self.fade = None;
+                }
+            }
+            frame.copy_from_slice(&self.frame);
+        }
+        if matches!(self.current.as_ref(), Some(c) if c.is_finished()) && self.queue.is_empty() {
+            self.current = None;
+        }
+    }
+}
+
+/// Plays [`MusicTrack`](struct.MusicTrack.html)s with crossfades and a queue. See the [module docs](index.html).
+pub struct MusicPlayer {
+    stream: ProceduralStream<PlayerSource>,
+}
+
+impl MusicPlayer {
+    /// Creates a player with a stream of `channels` channels at `sample_rate`. Tracks at other rates are resampled
+    /// when they start.
+    pub fn new(
+        audio: &RaylibAudio,
+        sample_rate: u32,
+        channels: u32,
+    ) -> Result<MusicPlayer, String> {
+        let source = PlayerSource::new(sample_rate, channels);
+        let stream = ProceduralStream::new(audio, sample_rate, 16, channels, 4096, source)?;
+        Ok(MusicPlayer { stream })
+    }
+
+    /// Plays `track` right away, dropping the playlist.
+    pub fn play(&mut self, track: MusicTrack) {
+        let track = track.at_rate(self.stream.sample_rate());
+        self.stream.with_source(|s| {
+            s.queue.clear();
+            s.repeat = false;
+            s.start(&track, 0.0);
+        });
+        if !self.stream.is_playing() {
+            self.stream.play();
+        }
+    }
+
+    /// Fades from the current track to `track` over `seconds`, dropping the playlist.
+    pub fn crossfade_to(&mut self, track: MusicTrack, seconds: f32) {
+        let track = track.at_rate(self.stream.sample_rate());
+        self.stream.with_source(|s| {
+            s.queue.clear();
+            s.repeat = false;
+            s.start(&track, seconds);
+        });
+        if !self.stream.is_playing() {
+            self.stream.play();
+        }
+    }
+
+    /// Adds `track` to the queue, played when the tracks before it finish. Looping tracks never finish, use
+    /// [`skip`](#method.skip) to move on from them.
+    pub fn enqueue(&mut self, track: MusicTrack) {
+        let track = track.at_rate(self.stream.sample_rate());
+        self.stream.with_source(|s| s.queue.push_back(track));
+        if !self.stream.is_playing() {
+            self.stream.play();
+        }
+    }
+
+    /// Replaces the queue with `tracks`, starting the first now. With `repeat`, finished tracks go back to the end
+    /// of the queue so the playlist cycles.
+    pub fn set_playlist(&mut self, tracks: Vec<MusicTrack>, repeat: bool) {
+        let rate = self.stream.sample_rate();
+        let tracks: Vec<MusicTrack> = tracks.iter().map(|t| t.at_rate(rate)).collect();
+        self.stream.with_source(|s| {
+            s.repeat = repeat;
+            s.queue = tracks.into();
+            s.current = None;
+            s.fade = None;
+            s.advance();
+        });
+        if !self.stream.is_playing() {
+            self.stream.play();
+        }
+    }
+
+    /// Seconds of crossfade between queued tracks.
+    pub fn set_queue_crossfade(&mut self, seconds: f32) {
+        self.stream
+            .with_source(|s| s.queue_crossfade = seconds.max(0.0));
+    }
+
+    /// Moves on to the next queued track, fading over `seconds`.
+    pub fn skip(&mut self, seconds: f32) {
+        self.stream.with_source(|s| {
+            if let Some(next) = s.queue.pop_front() {
+                s.start(&next, seconds);
+            }
+        });
+    }
+
+    /// Number of tracks waiting in the queue.
+    pub fn queue_len(&self) -> usize {
+        self.stream.with_source(|s| s.queue.len())
+    }
+
+    /// Jumps to `seconds` into the current track.
+    pub fn seek(&mut self, seconds: f32) {
+        self.stream.with_source(|s| {
+            if let Some(current) = s.current.as_mut() {
+                current.seek(seconds);
+            }
+        });
+    }
+
+    /// Seconds into the current track. The buffered audio not heard yet is included.
+    pub fn position(&self) -> f32 {
+        self.stream
+            .with_source(|s| s.current.as_ref().map_or(0.0, TrackSource::position))
+    }
+
+    /// Whether a track is playing, or fading out.
+    pub fn has_track(&self) -> bool {
+        self.stream
+            .with_source(|s| s.current.is_some() || s.fade.is_some())
+    }
+
+    /// Refills the stream, see [`ProceduralStream::pump`](../procedural/struct.ProceduralStream.html#method.pump).
+    pub fn update(&mut self) {
+        self.stream.pump();
+    }
+
+    /// Refills the stream from its own thread, so [`update`](#method.update) isn't needed.
+    pub fn start_thread(&mut self) {
+        self.stream.start_thread();
+    }
+
+    pub fn pause(&mut self) {
+        self.stream.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.stream.resume();
+    }
+
+    /// Stops playing and drops the current track and the queue.
+    pub fn stop(&mut self) {
+        self.stream.stop();
+        self.stream.with_source(|s| {
+            s.current = None;
+            s.fade = None;
+            s.queue.clear();
+        });
+    }
+
+    /// Sets the volume (`1.0` is max level).
+    pub fn set_volume(&mut self, volume: f32) {
+        self.stream.set_volume(volume);
+    }
+
+    /// The stream the player plays on, to assign it to a [`Mixer`](../mixer/struct.Mixer.html) bus.
+    pub fn stream_mut(&mut self) -> &mut ProceduralStream<PlayerSource> {
+        &mut self.stream
+    }
+}
+
+#[cfg(test)]
+mod music_test {
+    use super::*;
+
+    /// A mono track counting up by one per frame.
+    fn ramp(frames: usize, sample_rate: u32) -> MusicTrack {
+        MusicTrack {
+            samples: Arc::new((0..frames).map(|i| i as f32).collect()),
+            sample_rate,
+            channels: 1,
+            loop_start: 0,
+            loop_end: None,
+            looping: false,
+        }
+    }
+
+    fn fill(source: &mut impl Source, frames: usize) -> Vec<f32> {
+        let mut out = vec![0.0; frames * source.channels() as usize];
+        source.fill(&mut out);
+        out
+    }
+
+    fn ogg_page(packets: &[&[u8]]) -> Vec<u8> {
+        let mut table = Vec::new();
+        let mut body = Vec::new();
+        for packet in packets {
+            let mut len = packet.len();
+            while len >= 255 {
+                table.push(255);
+                len -= 255;
+            }
+            table.push(len as u8);
+            body.extend_from_slice(packet);
+        }
+        let mut page = b"OggS".to_vec();
+        page.extend_from_slice(&[0; 22]);
+        page.push(table.len() as u8);
+        page.extend(table);
+        page.extend(body);
+        page
+    }
+
+    #[test]
+    fn test_ogg_loop_points() {
+        let mut comments = b"\x03vorbis".to_vec();
+        let vendor = b"raylib test";
+        comments.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        comments.extend_from_slice(vendor);
+        let entries: [&[u8]; 3] = [b"TITLE=Town", b"LOOPSTART=44100", b"looplength=88200"];
+        comments.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        for entry in &entries {
+            comments.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+            comments.extend_from_slice(entry);
+        }
+        // long enough to continue over a lacing value of 255
+        comments.resize(300, 0);
+        let mut data = ogg_page(&[b"\x01vorbis identification"]);
+        data.extend(ogg_page(&[&comments, b"\x05vorbis setup"]));
+        assert_eq!(ogg_loop_points(&data), Some((44100, Some(132300))));
+
+        let untagged = ogg_page(&[b"\x01vorbis", b"\x03vorbis\x00\x00\x00\x00\x00\x00\x00\x00"]);
+        assert_eq!(ogg_loop_points(&untagged), None);
+        assert_eq!(ogg_loop_points(b"RIFF"), None);
+    }
+
+    #[test]
+    fn test_track_loop_and_seek() {
+        let mut track = ramp(6, 10);
+        track.set_loop_frames(2, Some(4));
+        let mut source = track.source();
+        assert_eq!(
+            fill(&mut source, 9),
+            vec![0.0, 1.0, 2.0, 3.0, 2.0, 3.0, 2.0, 3.0, 2.0]
+        );
+        source.seek(0.5);
+        assert_eq!(fill(&mut source, 2), vec![5.0, 2.0]);
+        assert!(!source.is_finished());
+
+        let mut once = ramp(3, 10).source();
+        assert_eq!(fill(&mut once, 4), vec![0.0, 1.0, 2.0, 0.0]);
+        assert!(once.is_finished());
+        assert_eq!(once.position(), 0.3);
+    }
+
+    #[test]
+    fn test_player_queue_and_crossfade() {
+        let mut player = PlayerSource::new(10, 1);
+        player.queue.push_back(ramp(3, 10));
+        player.queue.push_back(ramp(2, 10));
+        assert_eq!(fill(&mut player, 6), vec![0.0, 1.0, 2.0, 0.0, 1.0, 0.0]);
+        assert!(player.current.is_none());
+
+        let mut constant = ramp(1, 10);
+        constant.samples = Arc::new(vec![1.0; 10]);
+        let mut silent = ramp(1, 10);
+        silent.samples = Arc::new(vec![0.0; 10]);
+        player.start(&constant, 0.0);
+        player.start(&silent, 0.4);
+        let out = fill(&mut player, 5);
+        // the old track fades out with a quarter cosine
+        for (i, s) in out.iter().enumerate().take(4) {
+            let expected = (i as f32 / 4.0 * std::f32::consts::FRAC_PI_2).cos();
+            assert!((s - expected).abs() < 1e-5);
+        }
+        assert_eq!(out[4], 0.0);
+        assert!(player.fade.is_none());
+
+        // resampled when the rates differ
+        player.start(&ramp(10, 5), 0.0);
+        assert_eq!(player.current.as_ref().unwrap().track.frame_count(), 20);
+    }
+}
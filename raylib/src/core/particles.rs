@@ -0,0 +1,974 @@
+//! CPU particle systems for 2D and 3D effects
+//!
+//! A [`ParticleEmitter`] describes where particles spawn, how many, and how they move and look over their lifetime. A
+//! [`ParticleSystem`] runs the simulation for one emitter. It doesn't touch the GPU, so it can be updated and inspected
+//! without a window. Drawing turns the particles into textured quads, or billboards in 3D:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+//! let spark = rl.load_texture(thread, "spark.png")?;
+//! let mut sparks = ParticleSystem::new(ParticleEmitter {
+//!     shape: EmitterShape::Circle { radius: 8.0, edge: false },
+//!     position: Vector3::new(400.0, 300.0, 0.0),
+//!     rate: 200.0,
+//!     speed: (60.0, 120.0),
+//!     gravity: Vector3::new(0.0, 200.0, 0.0),
+//!     size: Curve::new(12.0, 0.0, ease::quad_in),
+//!     color: ColorCurve::new(Color::GOLD, Color::new(255, 64, 0, 0), ease::linear_none),
+//!     blend_mode: BlendMode::BLEND_ADDITIVE,
+//!     ..Default::default()
+//! });
+//!
+//! while !rl.window_should_close() {
+//!     sparks.update(rl.get_frame_time());
+//!     let mut d = rl.begin_drawing(thread);
+//!     d.clear_background(Color::BLACK);
+//!     sparks.draw_2d(&mut d, &spark);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Emitters are planar by default: everything happens in the XY plane, which is the screen in 2D. Set `planar` to
+//! `false` for 3D effects.
+//!
+//! [`ParticleEmitter`]: struct.ParticleEmitter.html
+//! [`ParticleSystem`]: struct.ParticleSystem.html
+use crate::consts::BlendMode;
+use crate::core::camera::Camera3D;
+use crate::core::color::Color;
+use crate::core::drawing::{DrawMode, RaylibDraw, RaylibDraw3D};
+use crate::core::math::{Rectangle, Vector2, Vector3};
+use crate::core::models::RaylibMesh;
+use crate::core::rng::Rng;
+use crate::core::texture::Texture2D;
+use crate::ease::{self, EaseFn};
+use crate::ffi;
+use std::f32::consts::PI;
+use std::mem::ManuallyDrop;
+
+/// A value changing over a particle's lifetime, from `start` at birth to `end` at death.
+#[derive(Clone, Copy)]
+pub struct Curve {
+    pub start: f32,
+    pub end: f32,
+    pub ease: EaseFn,
+}
+
+impl Curve {
+    pub fn new(start: f32, end: f32, ease: EaseFn) -> Curve {
+        Curve { start, end, ease }
+    }
+
+    /// A value that doesn't change.
+    pub fn constant(value: f32) -> Curve {
+        Curve::new(value, value, ease::linear_none)
+    }
+
+    /// The value at `t`, from 0 at birth to 1 at death.
+    pub fn sample(&self, t: f32) -> f32 {
+        (self.ease)(t.clamp(0.0, 1.0), self.start, self.end - self.start, 1.0)
+    }
+}
+
+impl std::fmt::Debug for Curve {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Curve")
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .finish()
+    }
+}
+
+/// A color changing over a particle's lifetime.
+#[derive(Clone, Copy)]
+pub struct ColorCurve {
+    pub start: Color,
+    pub end: Color,
+    pub ease: EaseFn,
+}
+
+impl ColorCurve {
+    pub fn new(start: Color, end: Color, ease: EaseFn) -> ColorCurve {
+        ColorCurve { start, end, ease }
+    }
+
+    pub fn constant(color: Color) -> ColorCurve {
+        ColorCurve::new(color, color, ease::linear_none)
+    }
+
+    /// The color at `t`, from 0 at birth to 1 at death.
+    pub fn sample(&self, t: f32) -> Color {
+        let amount = (self.ease)(t.clamp(0.0, 1.0), 0.0, 1.0, 1.0);
+        let mix = |a: u8, b: u8| {
+            (a as f32 + (b as f32 - a as f32) * amount)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        };
+        Color::new(
+            mix(self.start.r, self.end.r),
+            mix(self.start.g, self.end.g),
+            mix(self.start.b, self.end.b),
+            mix(self.start.a, self.end.a),
+        )
+    }
+}
+
+impl std::fmt::Debug for ColorCurve {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ColorCurve")
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .finish()
+    }
+}
+
+/// Triangles particles can spawn on, with a chance proportional to their area.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MeshSurface {
+    triangles: Vec<[Vector3; 3]>,
+    /// Running total of the triangle areas, for picking one.
+    areas: Vec<f32>,
+}
+
+impl MeshSurface {
+    pub fn new(triangles: Vec<[Vector3; 3]>) -> MeshSurface {
+        let mut total = 0.0;
+        let areas = triangles
+            .iter()
+            .map(|[a, b, c]| {
+                total += (*b - *a).cross(*c - *a).length() / 2.0;
+                total
+            })
+            .collect();
+        MeshSurface { triangles, areas }
+    }
+
+    /// The triangles of a mesh, in model space.
+    pub fn from_mesh(mesh: &impl RaylibMesh) -> MeshSurface {
+        let raw = mesh.as_ref();
+        let vertices = mesh.vertices();
+        let triangles = if raw.indices.is_null() {
+            vertices
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]])
+                .collect()
+        } else {
+            let indices =
+                unsafe { std::slice::from_raw_parts(raw.indices, raw.triangleCount as usize * 3) };
+            indices
+                .chunks_exact(3)
+                .filter_map(|t| {
+                    Some([
+                        *vertices.get(t[0] as usize)?,
+                        *vertices.get(t[1] as usize)?,
+                        *vertices.get(t[2] as usize)?,
+                    ])
+                })
+                .collect()
+        };
+        MeshSurface::new(triangles)
+    }
+
+    pub fn triangles(&self) -> &[[Vector3; 3]] {
+        &self.triangles
+    }
+
+    /// A random point on the surface and the normal there.
+    fn sample(&self, rng: &mut Rng) -> (Vector3, Vector3) {
+        let total = match self.areas.last() {
+            Some(&total) if total > 0.0 => total,
+            _ => return (Vector3::zero(), Vector3::up()),
+        };
+        let pick = rng.next_f32() * total;
+        let index = self
+            .areas
+            .iter()
+            .position(|&area| pick < area)
+            .unwrap_or(self.areas.len() - 1);
+        let [a, b, c] = self.triangles[index];
+        let (mut u, mut v) = (rng.next_f32(), rng.next_f32());
+        if u + v > 1.0 {
+            u = 1.0 - u;
+            v = 1.0 - v;
+        }
+        let point = a + (b - a) * u + (c - a) * v;
+        (point, (b - a).cross(c - a).normalized())
+    }
+}
+
+/// Where particles spawn, relative to the emitter position.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmitterShape {
+    Point,
+    /// A disc facing the emitter direction in 3D, or lying in the plane in 2D. With `edge`, only its outline.
+    Circle {
+        radius: f32,
+        edge: bool,
+    },
+    /// A box of the given half size. Planar emitters ignore `z`.
+    Box {
+        half_size: Vector3,
+    },
+    /// Particles leave a disc of `radius` within `angle` degrees of the emitter direction. Replaces `spread`.
+    Cone {
+        angle: f32,
+        radius: f32,
+    },
+    /// The surface of a mesh, with particles leaving along its normals instead of the emitter direction.
+    Mesh(MeshSurface),
+}
+
+/// A group of particles spawned at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Burst {
+    /// Seconds after the emitter starts.
+    pub time: f32,
+    pub count: u32,
+    /// Repeats the burst this often while the emitter runs.
+    pub interval: Option<f32>,
+}
+
+/// Pulls or, with a negative strength, pushes particles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attractor {
+    pub position: Vector3,
+    /// Acceleration towards the attractor, in units per second squared.
+    pub strength: f32,
+    /// Particles further than this are unaffected. 0 for no limit.
+    pub radius: f32,
+}
+
+/// How particles spawn, move and look.
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    pub shape: EmitterShape,
+    pub position: Vector3,
+    /// Direction particles leave in.
+    pub direction: Vector3,
+    /// Particles leave up to this many degrees away from `direction`.
+    pub spread: f32,
+    /// Keeps everything in the XY plane, for 2D.
+    pub planar: bool,
+    /// Particles per second.
+    pub rate: f32,
+    pub bursts: Vec<Burst>,
+    /// How long the emitter spawns particles for, in seconds. `None` runs forever.
+    pub duration: Option<f32>,
+    /// Starts over once `duration` passes, firing the bursts again.
+    pub looping: bool,
+    /// No more particles spawn while this many are alive.
+    pub max_particles: usize,
+    /// Lifetime range in seconds.
+    pub lifetime: (f32, f32),
+    /// Initial speed range.
+    pub speed: (f32, f32),
+    /// Scales the velocity over the lifetime.
+    pub speed_scale: Curve,
+    /// Size over the lifetime, in units for the height of a quad or billboard.
+    pub size: Curve,
+    pub color: ColorCurve,
+    /// Initial rotation range of quads, in degrees.
+    pub rotation: (f32, f32),
+    /// Rotation speed range of quads, in degrees per second.
+    pub angular_velocity: (f32, f32),
+    pub gravity: Vector3,
+    /// Fraction of the velocity lost per second.
+    pub drag: f32,
+    pub attractors: Vec<Attractor>,
+    /// Areas of the texture particles use. Empty uses the whole texture.
+    pub frames: Vec<Rectangle>,
+    /// Steps through `frames` over the lifetime, instead of giving each particle a random one.
+    pub animate_frames: bool,
+    pub blend_mode: BlendMode,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> ParticleEmitter {
+        ParticleEmitter {
+            shape: EmitterShape::Point,
+            position: Vector3::zero(),
+            direction: Vector3::new(0.0, -1.0, 0.0),
+            spread: 180.0,
+            planar: true,
+            rate: 10.0,
+            bursts: Vec::new(),
+            duration: None,
+            looping: false,
+            max_particles: 1000,
+            lifetime: (1.0, 1.0),
+            speed: (50.0, 100.0),
+            speed_scale: Curve::constant(1.0),
+            size: Curve::constant(8.0),
+            color: ColorCurve::new(
+                Color::WHITE,
+                Color::new(255, 255, 255, 0),
+                ease::linear_none,
+            ),
+            rotation: (0.0, 0.0),
+            angular_velocity: (0.0, 0.0),
+            gravity: Vector3::zero(),
+            drag: 0.0,
+            attractors: Vec::new(),
+            frames: Vec::new(),
+            animate_frames: false,
+            blend_mode: BlendMode::BLEND_ALPHA,
+        }
+    }
+}
+
+/// One live particle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub position: Vector3,
+    pub velocity: Vector3,
+    /// Seconds since it spawned.
+    pub age: f32,
+    pub lifetime: f32,
+    /// In degrees.
+    pub rotation: f32,
+    pub angular_velocity: f32,
+    /// Index into the emitter frames, when not animated.
+    pub frame: usize,
+}
+
+impl Particle {
+    /// How far through its life the particle is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        if self.lifetime > 0.0 {
+            (self.age / self.lifetime).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Where to draw a particle with `draw_texture_pro`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleQuad {
+    pub source: Rectangle,
+    pub dest: Rectangle,
+    pub origin: Vector2,
+    pub rotation: f32,
+    pub tint: Color,
+}
+
+/// Where to draw a particle with `draw_billboard_rec`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleBillboard {
+    pub source: Rectangle,
+    pub center: Vector3,
+    pub size: f32,
+    pub tint: Color,
+}
+
+/// Two unit vectors perpendicular to `axis` and each other.
+fn basis(axis: Vector3) -> (Vector3, Vector3) {
+    let other = if axis.x.abs() < 0.9 {
+        Vector3::new(1.0, 0.0, 0.0)
+    } else {
+        Vector3::new(0.0, 1.0, 0.0)
+    };
+    let u = axis.cross(other).normalized();
+    (u, axis.cross(u))
+}
+
+/// Simulates the particles of one emitter.
+#[derive(Debug, Clone)]
+pub struct ParticleSystem {
+    pub emitter: ParticleEmitter,
+    particles: Vec<Particle>,
+    rng: Rng,
+    time: f32,
+    pending: f32,
+    emitting: bool,
+}
+
+impl ParticleSystem {
+    pub fn new(emitter: ParticleEmitter) -> ParticleSystem {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        ParticleSystem::with_seed(emitter, seed)
+    }
+
+    /// A system whose random choices depend only on `seed`, so runs can be repeated.
+    pub fn with_seed(emitter: ParticleEmitter, seed: u64) -> ParticleSystem {
+        ParticleSystem {
+            emitter,
+            particles: Vec::new(),
+            rng: Rng::new(seed),
+            time: 0.0,
+            pending: 0.0,
+            emitting: true,
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Seconds since the emitter started, or since it last looped.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+
+    /// Whether the emitter stopped and every particle died.
+    pub fn is_finished(&self) -> bool {
+        !self.emitting && self.particles.is_empty()
+    }
+
+    /// Starts the emitter over.
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+        self.pending = 0.0;
+        self.emitting = true;
+    }
+
+    /// Stops spawning particles. Live ones carry on.
+    pub fn stop(&mut self) {
+        self.emitting = false;
+    }
+
+    /// Removes every particle.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// Spawns `count` particles now, on top of the rate and bursts.
+    pub fn emit(&mut self, count: u32) {
+        for _ in 0..count {
+            if self.particles.len() >= self.emitter.max_particles {
+                break;
+            }
+            let particle = self.spawn();
+            self.particles.push(particle);
+        }
+    }
+
+    fn random_direction(&mut self, axis: Vector3, angle: f32) -> Vector3 {
+        let axis = axis.normalized();
+        let angle = angle.clamp(0.0, 180.0).to_radians();
+        if self.emitter.planar {
+            let turn = (self.rng.next_f32() * 2.0 - 1.0) * angle;
+            let (sin, cos) = turn.sin_cos();
+            return Vector3::new(
+                axis.x * cos - axis.y * sin,
+                axis.x * sin + axis.y * cos,
+                0.0,
+            );
+        }
+        // uniform over the spherical cap
+        let cos_theta = 1.0 - self.rng.next_f32() * (1.0 - angle.cos());
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = self.rng.next_f32() * PI * 2.0;
+        let (u, v) = basis(axis);
+        u * (sin_theta * phi.cos()) + v * (sin_theta * phi.sin()) + axis * cos_theta
+    }
+
+    fn spawn(&mut self) -> Particle {
+        let direction = self.emitter.direction.normalized();
+        let planar = self.emitter.planar;
+        // the shape is taken out while sampling, so mesh surfaces aren't copied for every particle
+        let shape = std::mem::replace(&mut self.emitter.shape, EmitterShape::Point);
+        let (offset, heading) = match &shape {
+            EmitterShape::Point => (Vector3::zero(), None),
+            &EmitterShape::Circle { radius, edge } => {
+                let angle = self.rng.next_f32() * PI * 2.0;
+                // square root keeps filled discs evenly covered
+                let r = if edge {
+                    radius
+                } else {
+                    radius * self.rng.next_f32().sqrt()
+                };
+                let (u, v) = if planar {
+                    (Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0))
+                } else {
+                    basis(direction)
+                };
+                (u * (r * angle.cos()) + v * (r * angle.sin()), None)
+            }
+            &EmitterShape::Box { half_size } => {
+                let mut point = Vector3::new(
+                    (self.rng.next_f32() * 2.0 - 1.0) * half_size.x,
+                    (self.rng.next_f32() * 2.0 - 1.0) * half_size.y,
+                    (self.rng.next_f32() * 2.0 - 1.0) * half_size.z,
+                );
+                if planar {
+                    point.z = 0.0;
+                }
+                (point, None)
+            }
+            &EmitterShape::Cone { angle, radius } => {
+                let offset = if planar {
+                    let side = Vector3::new(-direction.y, direction.x, 0.0);
+                    side * ((self.rng.next_f32() * 2.0 - 1.0) * radius)
+                } else {
+                    let (u, v) = basis(direction);
+                    let a = self.rng.next_f32() * PI * 2.0;
+                    let r = radius * self.rng.next_f32().sqrt();
+                    u * (r * a.cos()) + v * (r * a.sin())
+                };
+                (offset, Some(self.random_direction(direction, angle)))
+            }
+            EmitterShape::Mesh(surface) => {
+                let (point, normal) = surface.sample(&mut self.rng);
+                (
+                    point,
+                    Some(self.random_direction(normal, self.emitter.spread)),
+                )
+            }
+        };
+        self.emitter.shape = shape;
+        let heading = match heading {
+            Some(heading) => heading,
+            None => self.random_direction(direction, self.emitter.spread),
+        };
+        let speed = self.rng.range(self.emitter.speed);
+        let frame = if self.emitter.frames.is_empty() {
+            0
+        } else {
+            (self.rng.next_f32() * self.emitter.frames.len() as f32) as usize
+        };
+        let mut position = self.emitter.position + offset;
+        let mut velocity = heading * speed;
+        if planar {
+            position.z = self.emitter.position.z;
+            velocity.z = 0.0;
+        }
+        Particle {
+            position,
+            velocity,
+            age: 0.0,
+            lifetime: self.rng.range(self.emitter.lifetime),
+            rotation: self.rng.range(self.emitter.rotation),
+            angular_velocity: self.rng.range(self.emitter.angular_velocity),
+            frame: frame.min(self.emitter.frames.len().saturating_sub(1)),
+        }
+    }
+
+    /// Number of bursts due between `from` and `to` seconds.
+    fn bursts_between(&self, from: f32, to: f32) -> u32 {
+        let mut count = 0;
+        for burst in &self.emitter.bursts {
+            // how many times the burst fired up to and including t
+            let due = |t: f32| -> i64 {
+                if t < burst.time {
+                    return 0;
+                }
+                match burst.interval {
+                    Some(interval) if interval > 0.0 => ((t - burst.time) / interval) as i64 + 1,
+                    _ => 1,
+                }
+            };
+            let fired = (due(to) - due(from)).max(0) as u32;
+            count += fired * burst.count;
+        }
+        count
+    }
+
+    /// Advances the simulation by `dt` seconds: moves and ages the particles, removes dead ones and spawns new ones.
+    pub fn update(&mut self, dt: f32) {
+        let emitter = &self.emitter;
+        let keep = (-emitter.drag * dt).exp();
+        for particle in &mut self.particles {
+            particle.age += dt;
+            let mut acceleration = emitter.gravity;
+            for attractor in &emitter.attractors {
+                let to = attractor.position - particle.position;
+                let distance = to.length();
+                if distance > 1e-4 && (attractor.radius <= 0.0 || distance < attractor.radius) {
+                    acceleration += to * (attractor.strength / distance);
+                }
+            }
+            if emitter.planar {
+                acceleration.z = 0.0;
+            }
+            particle.velocity = (particle.velocity + acceleration * dt) * keep;
+            let scale = emitter.speed_scale.sample(particle.progress());
+            particle.position += particle.velocity * (scale * dt);
+            particle.rotation += particle.angular_velocity * dt;
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+
+        if !self.emitting {
+            return;
+        }
+        let start = self.time;
+        let mut end = self.time + dt;
+        if let (Some(duration), false) = (self.emitter.duration, self.emitter.looping) {
+            end = end.min(duration);
+        }
+        self.pending += self.emitter.rate.max(0.0) * (end - start).max(0.0);
+        let mut count = self.pending.floor() as u32 + self.bursts_between(start, end);
+        self.pending -= self.pending.floor();
+        self.time += dt;
+        if let Some(duration) = self.emitter.duration {
+            if self.time >= duration {
+                if self.emitter.looping && duration > 0.0 {
+                    self.time -= duration;
+                    count += self.bursts_between(-f32::EPSILON, self.time);
+                } else {
+                    self.emitting = false;
+                }
+            }
+        }
+        // bursts count firings after the previous update, so ones at the very start fire on the first
+        if start == 0.0 {
+            count += self
+                .emitter
+                .bursts
+                .iter()
+                .filter(|burst| burst.time <= 0.0)
+                .map(|burst| burst.count)
+                .sum::<u32>();
+        }
+        self.emit(count);
+    }
+
+    fn source(&self, particle: &Particle, texture: &ffi::Texture2D) -> Rectangle {
+        let frames = &self.emitter.frames;
+        if frames.is_empty() {
+            return Rectangle::new(0.0, 0.0, texture.width as f32, texture.height as f32);
+        }
+        let index = if self.emitter.animate_frames {
+            ((particle.progress() * frames.len() as f32) as usize).min(frames.len() - 1)
+        } else {
+            particle.frame.min(frames.len() - 1)
+        };
+        frames[index]
+    }
+
+    /// The particles as quads on the XY plane, sized from the texture and emitter frames, in the order they spawned.
+    pub fn quads(&self, texture: &ffi::Texture2D) -> Vec<ParticleQuad> {
+        self.particles
+            .iter()
+            .map(|particle| {
+                let t = particle.progress();
+                let source = self.source(particle, texture);
+                let height = self.emitter.size.sample(t);
+                let width = if source.height != 0.0 {
+                    height * source.width / source.height
+                } else {
+                    height
+                };
+                ParticleQuad {
+                    source,
+                    dest: Rectangle::new(particle.position.x, particle.position.y, width, height),
+                    origin: Vector2::new(width / 2.0, height / 2.0),
+                    rotation: particle.rotation,
+                    tint: self.emitter.color.sample(t),
+                }
+            })
+            .collect()
+    }
+
+    /// The particles as billboards facing `camera`. With alpha blending they are sorted back to front, so nearer
+    /// particles cover further ones.
+    pub fn billboards(
+        &self,
+        camera: &Camera3D,
+        texture: &ffi::Texture2D,
+    ) -> Vec<ParticleBillboard> {
+        let mut billboards: Vec<ParticleBillboard> = self
+            .particles
+            .iter()
+            .map(|particle| {
+                let t = particle.progress();
+                ParticleBillboard {
+                    source: self.source(particle, texture),
+                    center: particle.position,
+                    size: self.emitter.size.sample(t),
+                    tint: self.emitter.color.sample(t),
+                }
+            })
+            .collect();
+        if self.emitter.blend_mode == BlendMode::BLEND_ALPHA {
+            let eye = camera.position;
+            billboards.sort_by(|a, b| {
+                let da = (a.center - eye).length();
+                let db = (b.center - eye).length();
+                db.partial_cmp(&da).unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+        billboards
+    }
+
+    /// Draws the particles as textured quads with the emitter's blend mode.
+    pub fn draw_2d<D: RaylibDraw>(&self, d: &mut D, texture: impl AsRef<ffi::Texture2D>) {
+        let quads = self.quads(texture.as_ref());
+        d.draw_in_mode(DrawMode::Blend(self.emitter.blend_mode), |d| {
+            for quad in quads {
+                d.draw_texture_pro(
+                    &texture,
+                    quad.source,
+                    quad.dest,
+                    quad.origin,
+                    quad.rotation,
+                    quad.tint,
+                );
+            }
+        });
+    }
+
+    /// Draws the particles as billboards with the emitter's blend mode. Call this inside 3D mode.
+    pub fn draw_3d<D: RaylibDraw + RaylibDraw3D>(
+        &self,
+        d: &mut D,
+        camera: Camera3D,
+        texture: impl AsRef<ffi::Texture2D>,
+    ) {
+        let billboards = self.billboards(&camera, texture.as_ref());
+        // draw_billboard_rec wants an owned texture; this one is only borrowed, so it must not be unloaded
+        let texture = ManuallyDrop::new(Texture2D(*texture.as_ref()));
+        d.draw_in_mode(DrawMode::Blend(self.emitter.blend_mode), |d| {
+            for billboard in billboards {
+                d.draw_billboard_rec(
+                    camera,
+                    &texture,
+                    billboard.source,
+                    billboard.center,
+                    billboard.size,
+                    billboard.tint,
+                );
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod particles_test {
+    use super::*;
+    use crate::core::recorder::{DrawCommand, DrawRecorder};
+    use crate::core::texture::WeakTexture2D;
+
+    fn texture() -> ffi::Texture2D {
+        ffi::Texture2D {
+            id: 1,
+            width: 32,
+            height: 16,
+            mipmaps: 1,
+            format: 7,
+        }
+    }
+
+    #[test]
+    fn test_curves() {
+        let size = Curve::new(10.0, 0.0, ease::linear_none);
+        assert_eq!(size.sample(0.0), 10.0);
+        assert_eq!(size.sample(0.5), 5.0);
+        assert_eq!(size.sample(2.0), 0.0);
+        let fade = ColorCurve::new(Color::WHITE, Color::new(255, 0, 0, 0), ease::linear_none);
+        assert_eq!(fade.sample(0.5), Color::new(255, 128, 128, 128));
+        assert!(ease::quad_in(0.5, 0.0, 1.0, 1.0) < 0.5);
+    }
+
+    #[test]
+    fn test_rate_and_bursts() {
+        let emitter = ParticleEmitter {
+            rate: 10.0,
+            lifetime: (10.0, 10.0),
+            bursts: vec![
+                Burst {
+                    time: 0.0,
+                    count: 5,
+                    interval: None,
+                },
+                Burst {
+                    time: 0.5,
+                    count: 3,
+                    interval: Some(0.5),
+                },
+            ],
+            duration: Some(1.0),
+            ..Default::default()
+        };
+        let mut system = ParticleSystem::with_seed(emitter, 7);
+        system.update(0.25);
+        // 2.5 from the rate, rounded down, and the start burst
+        assert_eq!(system.particles().len(), 2 + 5);
+        system.update(0.25);
+        // the repeating burst fires at 0.5
+        assert_eq!(system.particles().len(), 5 + 5 + 3);
+        system.update(0.5);
+        // and at 1.0, when the emitter stops
+        assert_eq!(system.particles().len(), 10 + 5 + 6);
+        assert!(!system.is_emitting());
+        system.update(1.0);
+        assert_eq!(system.particles().len(), 21);
+
+        let mut capped = ParticleSystem::with_seed(
+            ParticleEmitter {
+                max_particles: 4,
+                ..Default::default()
+            },
+            1,
+        );
+        capped.emit(10);
+        assert_eq!(capped.particles().len(), 4);
+    }
+
+    #[test]
+    fn test_motion() {
+        let emitter = ParticleEmitter {
+            rate: 0.0,
+            spread: 0.0,
+            direction: Vector3::new(1.0, 0.0, 0.0),
+            speed: (10.0, 10.0),
+            gravity: Vector3::new(0.0, 10.0, 0.0),
+            lifetime: (1.0, 1.0),
+            ..Default::default()
+        };
+        let mut system = ParticleSystem::with_seed(emitter, 3);
+        system.emit(1);
+        assert_eq!(system.particles()[0].velocity, Vector3::new(10.0, 0.0, 0.0));
+        system.update(0.5);
+        let particle = system.particles()[0];
+        assert_eq!(particle.velocity, Vector3::new(10.0, 5.0, 0.0));
+        assert_eq!(particle.position, Vector3::new(5.0, 2.5, 0.0));
+        assert_eq!(particle.progress(), 0.5);
+        system.update(0.5);
+        assert!(system.particles().is_empty());
+        system.stop();
+        assert!(system.is_finished());
+
+        // attractors pull, drag slows
+        let mut pulled = ParticleSystem::with_seed(
+            ParticleEmitter {
+                rate: 0.0,
+                speed: (0.0, 0.0),
+                attractors: vec![Attractor {
+                    position: Vector3::new(0.0, 10.0, 0.0),
+                    strength: 4.0,
+                    radius: 0.0,
+                }],
+                drag: 1.0,
+                ..Default::default()
+            },
+            3,
+        );
+        pulled.emit(1);
+        pulled.update(0.5);
+        let velocity = pulled.particles()[0].velocity;
+        assert_eq!(velocity.x, 0.0);
+        assert!((velocity.y - 2.0 * (-0.5f32).exp()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_shapes() {
+        let shapes = vec![
+            EmitterShape::Circle {
+                radius: 5.0,
+                edge: true,
+            },
+            EmitterShape::Box {
+                half_size: Vector3::new(2.0, 3.0, 4.0),
+            },
+            EmitterShape::Cone {
+                angle: 30.0,
+                radius: 1.0,
+            },
+            EmitterShape::Mesh(MeshSurface::new(vec![[
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 1.0),
+            ]])),
+        ];
+        for (i, shape) in shapes.into_iter().enumerate() {
+            let mut system = ParticleSystem::with_seed(
+                ParticleEmitter {
+                    shape,
+                    planar: i != 3,
+                    direction: Vector3::new(0.0, 1.0, 0.0),
+                    spread: 0.0,
+                    rate: 0.0,
+                    ..Default::default()
+                },
+                11,
+            );
+            system.emit(50);
+            for particle in system.particles() {
+                let p = particle.position;
+                let heading = particle.velocity.normalized();
+                match i {
+                    0 => assert!((Vector2::new(p.x, p.y).length() - 5.0).abs() < 1e-4),
+                    1 => assert!(p.x.abs() <= 2.0 && p.y.abs() <= 3.0 && p.z == 0.0),
+                    2 => {
+                        assert!(p.x.abs() <= 1.0 && p.y == 0.0);
+                        assert!(heading.y >= 30f32.to_radians().cos() - 1e-4);
+                    }
+                    _ => {
+                        assert!(p.y == 0.0 && p.x >= 0.0 && p.z >= 0.0 && p.x + p.z <= 1.0 + 1e-5);
+                        // the triangle faces down, and spread is 0
+                        assert!((heading.y + 1.0).abs() < 1e-4);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_quads_and_billboards() {
+        let emitter = ParticleEmitter {
+            rate: 0.0,
+            speed: (0.0, 0.0),
+            position: Vector3::new(4.0, 8.0, 0.0),
+            size: Curve::new(16.0, 8.0, ease::linear_none),
+            frames: vec![
+                Rectangle::new(0.0, 0.0, 16.0, 16.0),
+                Rectangle::new(16.0, 0.0, 8.0, 16.0),
+            ],
+            animate_frames: true,
+            lifetime: (2.0, 2.0),
+            ..Default::default()
+        };
+        let mut system = ParticleSystem::with_seed(emitter, 5);
+        system.emit(1);
+        let quad = system.quads(&texture())[0];
+        assert_eq!(quad.source, Rectangle::new(0.0, 0.0, 16.0, 16.0));
+        assert_eq!(quad.dest, Rectangle::new(4.0, 8.0, 16.0, 16.0));
+        assert_eq!(quad.origin, Vector2::new(8.0, 8.0));
+        system.update(1.0);
+        let quad = system.quads(&texture())[0];
+        assert_eq!(quad.source.x, 16.0);
+        assert_eq!(quad.dest, Rectangle::new(4.0, 8.0, 6.0, 12.0));
+        assert_eq!(quad.tint.a, 128);
+
+        system.emitter.frames.clear();
+        system.emitter.position = Vector3::new(0.0, 0.0, 5.0);
+        system.emit(1);
+        let camera = Camera3D::perspective(
+            Vector3::new(0.0, 0.0, 10.0),
+            Vector3::zero(),
+            Vector3::up(),
+            45.0,
+        );
+        let billboards = system.billboards(&camera, &texture());
+        assert_eq!(billboards.len(), 2);
+        // the further particle comes first
+        assert_eq!(billboards[0].center, Vector3::new(4.0, 8.0, 0.0));
+        assert_eq!(billboards[1].source, Rectangle::new(0.0, 0.0, 32.0, 16.0));
+
+        // the blend mode goes through the surface, so it is recorded along with the particles
+        let mut recorder = DrawRecorder::new();
+        let texture = unsafe { WeakTexture2D::from_raw(texture()) };
+        system.draw_2d(&mut recorder, &texture);
+        system.draw_3d(&mut recorder, camera, &texture);
+        let commands = recorder.commands();
+        assert_eq!(commands.len(), 8);
+        for (start, drawn) in &[(0, "Texture"), (4, "Billboard")] {
+            assert!(matches!(
+                commands[*start],
+                DrawCommand::BeginBlendMode(BlendMode::BLEND_ALPHA)
+            ));
+            assert!(format!("{:?}", commands[start + 1]).starts_with(drawn));
+            assert!(matches!(commands[start + 3], DrawCommand::EndBlendMode));
+        }
+    }
+}
@@ -0,0 +1,565 @@
+//! Typed views over the pixels of an [`Image`], read and written in place
+//!
+//! Each uncompressed [`PixelFormat`] has a marker type, such as [`R8G8B8A8`], naming the struct its pixels are
+//! stored as, such as [`Rgba8`]. [`Image::pixels`] borrows the top mipmap as a slice of those structs after checking
+//! the image's format, so no copy or conversion takes place:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn fog(mask: &mut Image) -> Result<(), String> {
+//! let mut view = mask.pixels_mut::<GrayAlpha>()?;
+//! view.set_pixel(4, 2, GrayAlpha8 { gray: 0, alpha: 0 });
+//! for row in view.rows_mut() {
+//!     row[0].alpha = 255;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Image`]: ../texture/struct.Image.html
+//! [`Image::pixels`]: ../texture/struct.Image.html#method.pixels
+//! [`PixelFormat`]: ../../consts/enum.PixelFormat.html
+//! [`R8G8B8A8`]: struct.R8G8B8A8.html
+//! [`Rgba8`]: type.Rgba8.html
+use crate::consts::PixelFormat;
+use crate::core::color::Color;
+use crate::core::texture::Image;
+use std::ops::{Deref, DerefMut};
+
+/// Marker for an uncompressed [`PixelFormat`], naming the type its pixels are stored as.
+///
+/// # Safety
+///
+/// `Pixel` must have exactly the size and layout of one pixel in `FORMAT`.
+///
+/// [`PixelFormat`]: ../../consts/enum.PixelFormat.html
+pub unsafe trait UncompressedFormat {
+    type Pixel: Copy + Into<Color> + From<Color>;
+    const FORMAT: PixelFormat;
+}
+
+macro_rules! uncompressed_format {
+    ($(#[$doc:meta])* $name:ident, $pixel:ty, $format:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name;
+
+        unsafe impl UncompressedFormat for $name {
+            type Pixel = $pixel;
+            const FORMAT: PixelFormat = PixelFormat::$format;
+        }
+    };
+}
+
+uncompressed_format!(
+    /// 8 bit grayscale, stored as [`Gray8`](struct.Gray8.html).
+    Grayscale,
+    Gray8,
+    UNCOMPRESSED_GRAYSCALE
+);
+uncompressed_format!(
+    /// 8 bit grayscale with alpha, stored as [`GrayAlpha8`](struct.GrayAlpha8.html).
+    GrayAlpha,
+    GrayAlpha8,
+    UNCOMPRESSED_GRAY_ALPHA
+);
+uncompressed_format!(
+    /// 16 bit packed color without alpha, stored as [`Rgb565`](struct.Rgb565.html).
+    R5G6B5,
+    Rgb565,
+    UNCOMPRESSED_R5G6B5
+);
+uncompressed_format!(
+    /// 24 bit color, stored as [`Rgb8`](struct.Rgb8.html).
+    R8G8B8,
+    Rgb8,
+    UNCOMPRESSED_R8G8B8
+);
+uncompressed_format!(
+    /// 16 bit packed color with a 1 bit alpha, stored as [`Rgba5551`](struct.Rgba5551.html).
+    R5G5B5A1,
+    Rgba5551,
+    UNCOMPRESSED_R5G5B5A1
+);
+uncompressed_format!(
+    /// 16 bit packed color with a 4 bit alpha, stored as [`Rgba4444`](struct.Rgba4444.html).
+    R4G4B4A4,
+    Rgba4444,
+    UNCOMPRESSED_R4G4B4A4
+);
+uncompressed_format!(
+    /// 32 bit color, stored as [`Rgba8`](type.Rgba8.html).
+    R8G8B8A8,
+    Rgba8,
+    UNCOMPRESSED_R8G8B8A8
+);
+uncompressed_format!(
+    /// 32 bit float grayscale, stored as [`Gray32f`](struct.Gray32f.html).
+    R32,
+    Gray32f,
+    UNCOMPRESSED_R32
+);
+uncompressed_format!(
+    /// 32 bit float color, stored as [`Rgb32f`](struct.Rgb32f.html).
+    R32G32B32,
+    Rgb32f,
+    UNCOMPRESSED_R32G32B32
+);
+uncompressed_format!(
+    /// 32 bit float color with alpha, stored as [`Rgba32f`](struct.Rgba32f.html).
+    R32G32B32A32,
+    Rgba32f,
+    UNCOMPRESSED_R32G32B32A32
+);
+
+/// Pixel of an `UNCOMPRESSED_R8G8B8A8` image, which is laid out like [`Color`](../color/struct.Color.html).
+pub type Rgba8 = Color;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Gray8(pub u8);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GrayAlpha8 {
+    pub gray: u8,
+    pub alpha: u8,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgb8 {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// Red in the top 5 bits, green in the next 6 and blue in the low 5.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgb565(pub u16);
+
+/// Red, green and blue in 5 bits each from the top, with the lowest bit set for opaque pixels.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgba5551(pub u16);
+
+/// Red, green, blue and alpha in 4 bits each, red in the top bits.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgba4444(pub u16);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Gray32f(pub f32);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rgb32f {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rgba32f {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+/// Luminance weights used by raylib when converting to grayscale.
+fn luminance(c: Color) -> f32 {
+    c.r as f32 * 0.299 + c.g as f32 * 0.587 + c.b as f32 * 0.114
+}
+
+/// Scales a `bits` wide channel to 8 bits.
+fn widen(value: u16, bits: u32) -> u8 {
+    let max = (1u32 << bits) - 1;
+    ((value as u32 & max) * 255 / max) as u8
+}
+
+/// Scales an 8 bit channel to `bits` bits.
+fn narrow(value: u8, bits: u32) -> u16 {
+    let max = (1u32 << bits) - 1;
+    ((value as u32 * max + 127) / 255) as u16
+}
+
+fn unit(value: u8) -> f32 {
+    value as f32 / 255.0
+}
+
+fn byte(value: f32) -> u8 {
+    (value * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+impl From<Gray8> for Color {
+    fn from(p: Gray8) -> Color {
+        Color::new(p.0, p.0, p.0, 255)
+    }
+}
+
+impl From<Color> for Gray8 {
+    fn from(c: Color) -> Gray8 {
+        Gray8(luminance(c).round() as u8)
+    }
+}
+
+impl From<GrayAlpha8> for Color {
+    fn from(p: GrayAlpha8) -> Color {
+        Color::new(p.gray, p.gray, p.gray, p.alpha)
+    }
+}
+
+impl From<Color> for GrayAlpha8 {
+    fn from(c: Color) -> GrayAlpha8 {
+        GrayAlpha8 {
+            gray: luminance(c).round() as u8,
+            alpha: c.a,
+        }
+    }
+}
+
+impl From<Rgb8> for Color {
+    fn from(p: Rgb8) -> Color {
+        Color::new(p.r, p.g, p.b, 255)
+    }
+}
+
+impl From<Color> for Rgb8 {
+    fn from(c: Color) -> Rgb8 {
+        Rgb8 {
+            r: c.r,
+            g: c.g,
+            b: c.b,
+        }
+    }
+}
+
+impl From<Rgb565> for Color {
+    fn from(p: Rgb565) -> Color {
+        Color::new(widen(p.0 >> 11, 5), widen(p.0 >> 5, 6), widen(p.0, 5), 255)
+    }
+}
+
+impl From<Color> for Rgb565 {
+    fn from(c: Color) -> Rgb565 {
+        Rgb565(narrow(c.r, 5) << 11 | narrow(c.g, 6) << 5 | narrow(c.b, 5))
+    }
+}
+
+impl From<Rgba5551> for Color {
+    fn from(p: Rgba5551) -> Color {
+        let a = if p.0 & 1 == 1 { 255 } else { 0 };
+        Color::new(
+            widen(p.0 >> 11, 5),
+            widen(p.0 >> 6, 5),
+            widen(p.0 >> 1, 5),
+            a,
+        )
+    }
+}
+
+impl From<Color> for Rgba5551 {
+    /// Pixels with an alpha above 50 become opaque, like in raylib's `ImageFormat`.
+    fn from(c: Color) -> Rgba5551 {
+        let a = if c.a > 50 { 1 } else { 0 };
+        Rgba5551(narrow(c.r, 5) << 11 | narrow(c.g, 5) << 6 | narrow(c.b, 5) << 1 | a)
+    }
+}
+
+impl From<Rgba4444> for Color {
+    fn from(p: Rgba4444) -> Color {
+        Color::new(
+            widen(p.0 >> 12, 4),
+            widen(p.0 >> 8, 4),
+            widen(p.0 >> 4, 4),
+            widen(p.0, 4),
+        )
+    }
+}
+
+impl From<Color> for Rgba4444 {
+    fn from(c: Color) -> Rgba4444 {
+        Rgba4444(narrow(c.r, 4) << 12 | narrow(c.g, 4) << 8 | narrow(c.b, 4) << 4 | narrow(c.a, 4))
+    }
+}
+
+impl From<Gray32f> for Color {
+    fn from(p: Gray32f) -> Color {
+        let v = byte(p.0);
+        Color::new(v, v, v, 255)
+    }
+}
+
+impl From<Color> for Gray32f {
+    fn from(c: Color) -> Gray32f {
+        Gray32f(luminance(c) / 255.0)
+    }
+}
+
+impl From<Rgb32f> for Color {
+    fn from(p: Rgb32f) -> Color {
+        Color::new(byte(p.r), byte(p.g), byte(p.b), 255)
+    }
+}
+
+impl From<Color> for Rgb32f {
+    fn from(c: Color) -> Rgb32f {
+        Rgb32f {
+            r: unit(c.r),
+            g: unit(c.g),
+            b: unit(c.b),
+        }
+    }
+}
+
+impl From<Rgba32f> for Color {
+    fn from(p: Rgba32f) -> Color {
+        Color::new(byte(p.r), byte(p.g), byte(p.b), byte(p.a))
+    }
+}
+
+impl From<Color> for Rgba32f {
+    fn from(c: Color) -> Rgba32f {
+        Rgba32f {
+            r: unit(c.r),
+            g: unit(c.g),
+            b: unit(c.b),
+            a: unit(c.a),
+        }
+    }
+}
+
+/// Pixels of an image borrowed in place. Derefs to a slice of every pixel, row by row.
+#[derive(Debug)]
+pub struct PixelView<'a, P> {
+    width: usize,
+    height: usize,
+    pixels: &'a [P],
+}
+
+/// Mutable pixels of an image borrowed in place. Derefs to a slice of every pixel, row by row.
+#[derive(Debug)]
+pub struct PixelViewMut<'a, P> {
+    width: usize,
+    height: usize,
+    pixels: &'a mut [P],
+}
+
+/// Index of `(x, y)` in a `width` by `height` image.
+///
+/// # Panics
+///
+/// Panics if the position is outside the image.
+fn index(width: usize, height: usize, x: i32, y: i32) -> usize {
+    if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+        panic!(
+            "pixel ({}, {}) is outside of {}x{} image",
+            x, y, width, height
+        );
+    }
+    y as usize * width + x as usize
+}
+
+impl<'a, P: Copy> PixelView<'a, P> {
+    pub fn width(&self) -> i32 {
+        self.width as i32
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height as i32
+    }
+
+    /// Pixel at `(x, y)`, panicking when outside the image.
+    pub fn get_pixel(&self, x: i32, y: i32) -> P {
+        self.pixels[index(self.width, self.height, x, y)]
+    }
+
+    /// Row `y`, from left to right.
+    pub fn row(&self, y: usize) -> &'a [P] {
+        &self.pixels[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Rows from top to bottom.
+    pub fn rows(&self) -> std::slice::ChunksExact<'a, P> {
+        self.pixels.chunks_exact(self.width.max(1))
+    }
+}
+
+impl<'a, P: Copy> PixelViewMut<'a, P> {
+    pub fn width(&self) -> i32 {
+        self.width as i32
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height as i32
+    }
+
+    /// Pixel at `(x, y)`, panicking when outside the image.
+    pub fn get_pixel(&self, x: i32, y: i32) -> P {
+        self.pixels[index(self.width, self.height, x, y)]
+    }
+
+    /// Replaces the pixel at `(x, y)`, panicking when outside the image.
+    pub fn set_pixel(&mut self, x: i32, y: i32, pixel: P) {
+        self.pixels[index(self.width, self.height, x, y)] = pixel;
+    }
+
+    /// Row `y`, from left to right.
+    pub fn row(&self, y: usize) -> &[P] {
+        &self.pixels[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Row `y`, from left to right.
+    pub fn row_mut(&mut self, y: usize) -> &mut [P] {
+        &mut self.pixels[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Rows from top to bottom.
+    pub fn rows(&self) -> std::slice::ChunksExact<'_, P> {
+        self.pixels.chunks_exact(self.width.max(1))
+    }
+
+    /// Rows from top to bottom.
+    pub fn rows_mut(&mut self) -> std::slice::ChunksExactMut<'_, P> {
+        self.pixels.chunks_exact_mut(self.width.max(1))
+    }
+}
+
+impl<'a, P> Deref for PixelView<'a, P> {
+    type Target = [P];
+
+    fn deref(&self) -> &[P] {
+        self.pixels
+    }
+}
+
+impl<'a, P> Deref for PixelViewMut<'a, P> {
+    type Target = [P];
+
+    fn deref(&self) -> &[P] {
+        self.pixels
+    }
+}
+
+impl<'a, P> DerefMut for PixelViewMut<'a, P> {
+    fn deref_mut(&mut self) -> &mut [P] {
+        self.pixels
+    }
+}
+
+impl Image {
+    /// Number of pixels in the top mipmap, after checking the image is in format `F`.
+    fn pixel_count<F: UncompressedFormat>(&self) -> Result<usize, String> {
+        if self.format() != F::FORMAT {
+            return Err(format!("image is {:?}, not {:?}", self.format(), F::FORMAT));
+        }
+        if self.0.data.is_null() {
+            return Err("image has no pixel data".to_string());
+        }
+        Ok(self.width().max(0) as usize * self.height().max(0) as usize)
+    }
+
+    /// Borrows the pixels of the top mipmap as format `F`, without copying them. Fails if the image is in another
+    /// format.
+    pub fn pixels<F: UncompressedFormat>(&self) -> Result<PixelView<'_, F::Pixel>, String> {
+        let len = self.pixel_count::<F>()?;
+        Ok(PixelView {
+            width: self.width() as usize,
+            height: self.height() as usize,
+            pixels: unsafe { std::slice::from_raw_parts(self.0.data as *const F::Pixel, len) },
+        })
+    }
+
+    /// Mutably borrows the pixels of the top mipmap as format `F`, without copying them. Fails if the image is in
+    /// another format. Lower mipmaps are left as they were.
+    pub fn pixels_mut<F: UncompressedFormat>(
+        &mut self,
+    ) -> Result<PixelViewMut<'_, F::Pixel>, String> {
+        let len = self.pixel_count::<F>()?;
+        Ok(PixelViewMut {
+            width: self.width() as usize,
+            height: self.height() as usize,
+            pixels: unsafe { std::slice::from_raw_parts_mut(self.0.data as *mut F::Pixel, len) },
+        })
+    }
+}
+
+#[cfg(test)]
+mod pixels_test {
+    use super::*;
+    use crate::ffi;
+
+    fn raw_image<P: Copy>(width: i32, height: i32, format: PixelFormat, pixels: &[P]) -> Image {
+        unsafe {
+            let size = std::mem::size_of_val(pixels);
+            let data = libc::malloc(size);
+            std::ptr::copy_nonoverlapping(pixels.as_ptr() as *const u8, data as *mut u8, size);
+            Image::from_raw(ffi::Image {
+                data,
+                width,
+                height,
+                mipmaps: 1,
+                format: format as i32,
+            })
+        }
+    }
+
+    #[test]
+    fn test_pixel_views() {
+        let mut image = Image::from_colors(3, 2, &[Color::BLACK; 6]);
+        assert!(image.pixels::<R8G8B8>().is_err());
+        {
+            let mut view = image.pixels_mut::<R8G8B8A8>().unwrap();
+            view.set_pixel(2, 1, Color::RED);
+            view.row_mut(0)[1] = Color::BLUE;
+            assert_eq!(view.rows().count(), 2);
+        }
+        let view = image.pixels::<R8G8B8A8>().unwrap();
+        assert_eq!(view.get_pixel(2, 1), Color::RED);
+        assert_eq!(view.row(0), &[Color::BLACK, Color::BLUE, Color::BLACK]);
+        assert_eq!(view.len(), 6);
+
+        let gray = raw_image(
+            2,
+            1,
+            PixelFormat::UNCOMPRESSED_GRAY_ALPHA,
+            &[10u8, 20, 30, 40],
+        );
+        let view = gray.pixels::<GrayAlpha>().unwrap();
+        assert_eq!(
+            view[1],
+            GrayAlpha8 {
+                gray: 30,
+                alpha: 40
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pixel_out_of_bounds() {
+        let image = Image::from_colors(2, 2, &[Color::BLACK; 4]);
+        image.pixels::<R8G8B8A8>().unwrap().get_pixel(2, 0);
+    }
+
+    #[test]
+    fn test_packed_pixels() {
+        let image = raw_image(1, 1, PixelFormat::UNCOMPRESSED_R5G6B5, &[0xF800u16]);
+        let pixel = image.pixels::<R5G6B5>().unwrap()[0];
+        assert_eq!(Color::from(pixel), Color::new(255, 0, 0, 255));
+
+        assert_eq!(Rgb565::from(Color::new(0, 255, 0, 255)), Rgb565(0x07E0));
+        assert_eq!(Rgba5551::from(Color::new(0, 0, 255, 40)), Rgba5551(0x003E));
+        assert_eq!(
+            Rgba4444::from(Color::new(255, 0, 136, 255)),
+            Rgba4444(0xF08F)
+        );
+        assert_eq!(Color::from(Rgba4444(0xF08F)), Color::new(255, 0, 136, 255));
+        assert_eq!(Color::from(Rgba32f::from(Color::GOLD)), Color::GOLD);
+        assert_eq!(Gray8::from(Color::WHITE), Gray8(255));
+    }
+}
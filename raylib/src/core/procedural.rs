@@ -0,0 +1,508 @@
+//! Audio generated by Rust code as it plays
+//!
+//! A [`ProceduralStream`] owns an audio stream and keeps it fed from a [`Source`], or any `FnMut(&mut [f32])` wrapped
+//! with [`source_fn`]. Sources produce interleaved `f32` samples at their own rate and channel count, which are
+//! resampled, remixed and converted to the stream's format on the way.
+//!
+//! The stream refills its buffers either when [`pump`] is called, typically once a frame, or on its own thread after
+//! [`start_thread`], so hitches on the main loop don't starve the audio:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+//! let audio = RaylibAudio::init_audio_device()?;
+//! let mut phase = 0.0f32;
+//! let tone = source_fn(44100, 1, move |out| {
+//!     for sample in out.iter_mut() {
+//!         *sample = (phase * std::f32::consts::PI * 2.0).sin() * 0.2;
+//!         phase = (phase + 440.0 / 44100.0) % 1.0;
+//!     }
+//! });
+//! let mut stream = ProceduralStream::new(&audio, 48000, 16, 2, 1024, tone)?;
+//! stream.start_thread();
+//! stream.play();
+//! while !rl.window_should_close() {
+//!     rl.begin_drawing(thread).clear_background(Color::WHITE);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ProceduralStream`]: struct.ProceduralStream.html
+//! [`Source`]: trait.Source.html
+//! [`source_fn`]: fn.source_fn.html
+//! [`pump`]: struct.ProceduralStream.html#method.pump
+//! [`start_thread`]: struct.ProceduralStream.html#method.start_thread
+use crate::core::audio::{init_stream_with_buffer, AudioDevice, RaylibAudio};
+use crate::ffi;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Produces audio samples on demand.
+pub trait Source: Send + 'static {
+    /// Samples per second of one channel.
+    fn sample_rate(&self) -> u32;
+
+    fn channels(&self) -> u32;
+
+    /// Fills `out` with interleaved samples between `-1.0` and `1.0`. Its length is always a whole number of frames.
+    fn fill(&mut self, out: &mut [f32]);
+
+    /// Whether the source has nothing to play for now. The stream pauses meanwhile, so it doesn't count as playing,
+    /// and resumes once the source has something again.
+    fn is_idle(&self) -> bool {
+        false
+    }
+}
+
+/// A [`Source`](trait.Source.html) calling a closure, see [`source_fn`](fn.source_fn.html).
+pub struct FnSource<F> {
+    sample_rate: u32,
+    channels: u32,
+    callback: F,
+}
+
+impl<F: FnMut(&mut [f32]) + Send + 'static> Source for FnSource<F> {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    fn fill(&mut self, out: &mut [f32]) {
+        (self.callback)(out)
+    }
+}
+
+/// A source producing `channels` interleaved channels at `sample_rate` by calling `callback`.
+pub fn source_fn<F>(sample_rate: u32, channels: u32, callback: F) -> FnSource<F>
+where
+    F: FnMut(&mut [f32]) + Send + 'static,
+{
+    FnSource {
+        sample_rate,
+        channels,
+        callback,
+    }
+}
+
+/// Converts `f32` samples to raylib's 8 bit unsigned, 16 bit signed or 32 bit float formats, as native endian bytes.
+/// The inverse of [`AudioSample::to_f32`](../audio/trait.AudioSample.html#tymethod.to_f32), so decoding and encoding
+/// again gives back the same samples.
+pub(crate) fn encode_samples(samples: &[f32], sample_size: u32, out: &mut Vec<u8>) {
+    out.clear();
+    match sample_size {
+        8 => out.extend(
+            samples
+                .iter()
+                .map(|s| (s * 127.0 + 128.0).round().clamp(0.0, 255.0) as u8),
+        ),
+        16 => {
+            for s in samples {
+                let value = (s * 32767.0).round().clamp(-32768.0, 32767.0) as i16;
+                out.extend_from_slice(&value.to_ne_bytes());
+            }
+        }
+        _ => {
+            for s in samples {
+                out.extend_from_slice(&s.to_ne_bytes());
+            }
+        }
+    }
+}
+
+/// Writes one frame of `input.len()` channels as `output.len()` channels. Mono is spread to every channel, going to
+/// mono averages, and other layouts repeat the input channels in order.
+pub(crate) fn remix_frame(input: &[f32], output: &mut [f32]) {
+    if input.len() == output.len() {
+        output.copy_from_slice(input);
+    } else if output.len() == 1 {
+        output[0] = input.iter().sum::<f32>() / input.len().max(1) as f32;
+    } else if input.is_empty() {
+        output.iter_mut().for_each(|s| *s = 0.0);
+    } else {
+        for (c, sample) in output.iter_mut().enumerate() {
+            *sample = input[c % input.len()];
+        }
+    }
+}
+
+/// Left and right gains for a stereo balance between `-1.0` and `1.0`. The centered side stays at full volume and
+/// the other fades out.
+pub(crate) fn balance(pan: f32) -> (f32, f32) {
+    ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
+}
+
+/// Pulls frames from a source, changing their rate and channel count. Resampling is linear.
+pub(crate) struct Converter {
+    pub out_rate: u32,
+    pub out_channels: usize,
+    /// Source frames not consumed yet.
+    pending: Vec<f32>,
+    /// Position of the next output frame in `pending`, in source frames.
+    position: f64,
+    output: Vec<f32>,
+}
+
+impl Converter {
+    pub fn new(out_rate: u32, out_channels: usize) -> Converter {
+        Converter {
+            out_rate,
+            out_channels,
+            pending: Vec::new(),
+            position: 0.0,
+            output: Vec::new(),
+        }
+    }
+
+    /// Produces `frames` frames, interleaved.
+    pub fn convert<S: Source + ?Sized>(&mut self, source: &mut S, frames: usize) -> &mut [f32] {
+        let in_channels = source.channels().max(1) as usize;
+        let step = source.sample_rate().max(1) as f64 / self.out_rate.max(1) as f64;
+        // the last frame is interpolated towards the one after it, so one extra frame is needed
+        let needed = (self.position + frames as f64 * step).floor() as usize + 2;
+        let have = self.pending.len() / in_channels;
+        if needed > have {
+            let start = self.pending.len();
+            self.pending.resize(needed * in_channels, 0.0);
+            source.fill(&mut self.pending[start..]);
+        }
+
+        self.output.resize(frames * self.out_channels, 0.0);
+        let mut frame = vec![0.0; in_channels];
+        for i in 0..frames {
+            let index = self.position.floor() as usize;
+            let t = (self.position - index as f64) as f32;
+            let a = &self.pending[index * in_channels..(index + 1) * in_channels];
+            let b = &self.pending[(index + 1) * in_channels..(index + 2) * in_channels];
+            for c in 0..in_channels {
+                frame[c] = a[c] + (b[c] - a[c]) * t;
+            }
+            remix_frame(
+                &frame,
+                &mut self.output[i * self.out_channels..(i + 1) * self.out_channels],
+            );
+            self.position += step;
+        }
+        let consumed = self.position.floor() as usize;
+        self.pending.drain(..consumed * in_channels);
+        self.position -= consumed as f64;
+        &mut self.output
+    }
+}
+
+/// The raw stream, moved to the pump thread.
+struct StreamHandle(ffi::AudioStream);
+
+// raylib's stream buffers are shared with its mixing thread already, refilling them from another thread is how
+// every caller of UpdateAudioStream works
+unsafe impl Send for StreamHandle {}
+
+struct Pump<S> {
+    stream: StreamHandle,
+    source: S,
+    converter: Converter,
+    buffer_frames: usize,
+    sample_size: u32,
+    /// Stereo balance, see [`ProceduralStream::set_pan`](struct.ProceduralStream.html#method.set_pan).
+    pan: f32,
+    /// Whether the stream was paused because the source went idle.
+    idle: bool,
+    bytes: Vec<u8>,
+}
+
+impl<S: Source> Pump<S> {
+    /// Refills every buffer raylib finished playing, returning how many.
+    fn refill(&mut self) -> usize {
+        if self.source.is_idle() {
+            if !self.idle && unsafe { ffi::IsAudioStreamPlaying(self.stream.0) } {
+                unsafe { ffi::PauseAudioStream(self.stream.0) };
+                self.idle = true;
+            }
+            return 0;
+        }
+        let mut filled = 0;
+        // raylib double buffers streams
+        while filled < 2 && unsafe { ffi::IsAudioStreamProcessed(self.stream.0) } {
+            let stereo = self.converter.out_channels == 2;
+            let samples = self.converter.convert(&mut self.source, self.buffer_frames);
+            if stereo && self.pan != 0.0 {
+                let (left, right) = balance(self.pan);
+                for frame in samples.chunks_mut(2) {
+                    frame[0] *= left;
+                    frame[1] *= right;
+                }
+            }
+            encode_samples(samples, self.sample_size, &mut self.bytes);
+            unsafe {
+                ffi::UpdateAudioStream(
+                    self.stream.0,
+                    self.bytes.as_ptr() as *const std::os::raw::c_void,
+                    samples.len() as i32,
+                );
+            }
+            filled += 1;
+        }
+        if self.idle {
+            unsafe { ffi::ResumeAudioStream(self.stream.0) };
+            self.idle = false;
+        }
+        filled
+    }
+}
+
+/// An audio stream fed by a [`Source`](trait.Source.html). See the [module docs](index.html).
+pub struct ProceduralStream<S: Source> {
+    stream: ffi::AudioStream,
+    pump: Arc<Mutex<Pump<S>>>,
+    thread: Option<(JoinHandle<()>, Arc<AtomicBool>)>,
+    _device: Arc<AudioDevice>,
+}
+
+impl<S: Source> ProceduralStream<S> {
+    /// Creates a stream of `channels` channels at `sample_rate`, with `sample_size` bits per sample (8, 16 or 32),
+    /// refilled `buffer_frames` frames at a time. Smaller buffers lower the latency but need refilling more often.
+    pub fn new(
+        audio: &RaylibAudio,
+        sample_rate: u32,
+        sample_size: u32,
+        channels: u32,
+        buffer_frames: u32,
+        source: S,
+    ) -> Result<ProceduralStream<S>, String> {
+        if ![8, 16, 32].contains(&sample_size) {
+            return Err(format!("unsupported sample size {}", sample_size));
+        }
+        if sample_rate == 0 || channels == 0 || buffer_frames == 0 {
+            return Err("sample rate, channels and buffer size must not be zero".to_string());
+        }
+        let stream =
+            init_stream_with_buffer(sample_rate, sample_size, channels, buffer_frames as i32);
+        if stream.buffer.is_null() {
+            return Err("failed to create audio stream".to_string());
+        }
+        let pump = Pump {
+            stream: StreamHandle(stream),
+            source,
+            converter: Converter::new(sample_rate, channels as usize),
+            buffer_frames: buffer_frames as usize,
+            sample_size,
+            pan: 0.0,
+            idle: false,
+            bytes: Vec::new(),
+        };
+        Ok(ProceduralStream {
+            stream,
+            pump: Arc::new(Mutex::new(pump)),
+            thread: None,
+            _device: audio.device(),
+        })
+    }
+
+    /// Refills the buffers that finished playing. Call this regularly, once a frame is usually enough, unless the
+    /// stream runs its own thread. Returns how many buffers were refilled.
+    pub fn pump(&mut self) -> usize {
+        self.pump.lock().map(|mut pump| pump.refill()).unwrap_or(0)
+    }
+
+    /// Refills the buffers from a thread of their own, checking four times per buffer length.
+    pub fn start_thread(&mut self) {
+        if self.thread.is_some() {
+            return;
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let pump = Arc::clone(&self.pump);
+        let flag = Arc::clone(&stop);
+        let (frames, rate) = {
+            let pump = self.pump.lock().unwrap();
+            (pump.buffer_frames, pump.converter.out_rate)
+        };
+        let interval = Duration::from_secs_f64(frames as f64 / rate as f64 / 4.0);
+        let handle = std::thread::spawn(move || {
+            while !flag.load(Ordering::Relaxed) {
+                if let Ok(mut pump) = pump.lock() {
+                    pump.refill();
+                }
+                std::thread::sleep(interval);
+            }
+        });
+        self.thread = Some((handle, stop));
+    }
+
+    /// Stops the refill thread. The buffers must be pumped by hand afterwards.
+    pub fn stop_thread(&mut self) {
+        if let Some((handle, stop)) = self.thread.take() {
+            stop.store(true, Ordering::Relaxed);
+            let _ = handle.join();
+        }
+    }
+
+    /// Gives access to the source, for example to change a synthesizer's parameters. The refill thread waits
+    /// meanwhile, so keep `f` short.
+    pub fn with_source<R>(&self, f: impl FnOnce(&mut S) -> R) -> R {
+        let mut pump = self.pump.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut pump.source)
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.stream.sampleRate
+    }
+
+    pub fn sample_size(&self) -> u32 {
+        self.stream.sampleSize
+    }
+
+    pub fn channels(&self) -> u32 {
+        self.stream.channels
+    }
+
+    /// Fills the buffers and starts playing.
+    pub fn play(&mut self) {
+        self.pump();
+        self.take_over_idle();
+        unsafe { ffi::PlayAudioStream(self.stream) }
+    }
+
+    pub fn pause(&mut self) {
+        self.take_over_idle();
+        unsafe { ffi::PauseAudioStream(self.stream) }
+    }
+
+    pub fn resume(&mut self) {
+        self.take_over_idle();
+        unsafe { ffi::ResumeAudioStream(self.stream) }
+    }
+
+    pub fn stop(&mut self) {
+        self.take_over_idle();
+        unsafe { ffi::StopAudioStream(self.stream) }
+    }
+
+    /// Keeps the pump from resuming a stream paused for an idle source, once it's played or paused by hand.
+    fn take_over_idle(&mut self) {
+        if let Ok(mut pump) = self.pump.lock() {
+            pump.idle = false;
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        unsafe { ffi::IsAudioStreamPlaying(self.stream) }
+    }
+
+    /// Sets the volume (`1.0` is max level).
+    pub fn set_volume(&mut self, volume: f32) {
+        unsafe { ffi::SetAudioStreamVolume(self.stream, volume) }
+    }
+
+    /// Sets the pitch (`1.0` is base level).
+    pub fn set_pitch(&mut self, pitch: f32) {
+        unsafe { ffi::SetAudioStreamPitch(self.stream, pitch) }
+    }
+
+    /// Sets the stereo balance, from `-1.0` (left only) through `0.0` (centered) to `1.0` (right only). Only
+    /// stereo streams are panned, raylib can't pan its own buffers.
+    pub fn set_pan(&mut self, pan: f32) {
+        if let Ok(mut pump) = self.pump.lock() {
+            pump.pan = pan.clamp(-1.0, 1.0);
+        }
+    }
+}
+
+impl<S: Source> AsRef<ffi::AudioStream> for ProceduralStream<S> {
+    fn as_ref(&self) -> &ffi::AudioStream {
+        &self.stream
+    }
+}
+
+impl<S: Source> Drop for ProceduralStream<S> {
+    fn drop(&mut self) {
+        self.stop_thread();
+        unsafe { ffi::CloseAudioStream(self.stream) }
+    }
+}
+
+#[cfg(test)]
+mod procedural_test {
+    use super::*;
+    use crate::core::audio::AudioSample;
+
+    /// Counts up by one per frame, on every channel.
+    fn ramp(sample_rate: u32, channels: u32) -> impl Source {
+        let mut next = 0.0;
+        source_fn(sample_rate, channels, move |out| {
+            for frame in out.chunks_mut(channels as usize) {
+                for s in frame.iter_mut() {
+                    *s = next;
+                }
+                next += 1.0;
+            }
+        })
+    }
+
+    #[test]
+    fn test_convert_rate() {
+        let mut source = ramp(100, 1);
+        let mut converter = Converter::new(200, 1);
+        assert_eq!(converter.convert(&mut source, 4), &[0.0, 0.5, 1.0, 1.5]);
+        // continues where it left off across calls
+        assert_eq!(converter.convert(&mut source, 3), &[2.0, 2.5, 3.0]);
+
+        let mut source = ramp(300, 1);
+        let mut converter = Converter::new(100, 1);
+        assert_eq!(converter.convert(&mut source, 3), &[0.0, 3.0, 6.0]);
+        assert_eq!(converter.convert(&mut source, 2), &[9.0, 12.0]);
+    }
+
+    #[test]
+    fn test_convert_channels() {
+        let mut source = ramp(10, 1);
+        let mut converter = Converter::new(10, 2);
+        assert_eq!(converter.convert(&mut source, 2), &[0.0, 0.0, 1.0, 1.0]);
+
+        let mut stereo = source_fn(10, 2, |out| {
+            for frame in out.chunks_mut(2) {
+                frame[0] = 1.0;
+                frame[1] = 0.0;
+            }
+        });
+        let mut converter = Converter::new(10, 1);
+        assert_eq!(converter.convert(&mut stereo, 2), &[0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_encode_samples() {
+        let mut bytes = Vec::new();
+        encode_samples(&[-1.0, 0.0, 1.0, 2.0], 8, &mut bytes);
+        assert_eq!(bytes, vec![1, 128, 255, 255]);
+        encode_samples(&[-1.0, 0.5], 16, &mut bytes);
+        let values: Vec<i16> = bytes
+            .chunks(2)
+            .map(|b| i16::from_ne_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(values, vec![-32767, 16384]);
+        encode_samples(&[0.25], 32, &mut bytes);
+        assert_eq!(bytes, 0.25f32.to_ne_bytes().to_vec());
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let mut bytes = Vec::new();
+        let all: Vec<u8> = (0..=255).collect();
+        let decoded: Vec<f32> = all.iter().map(|&x| x.to_f32()).collect();
+        encode_samples(&decoded, 8, &mut bytes);
+        assert_eq!(bytes, all);
+
+        let extremes = [i16::MIN, -1, 0, 1, i16::MAX];
+        let decoded: Vec<f32> = extremes.iter().map(|&x| x.to_f32()).collect();
+        encode_samples(&decoded, 16, &mut bytes);
+        let values: Vec<i16> = bytes
+            .chunks(2)
+            .map(|b| i16::from_ne_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(values, extremes);
+    }
+}
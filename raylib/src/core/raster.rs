@@ -0,0 +1,789 @@
+//! Software rasterizer for drawing shapes into an [`Image`]
+//!
+//! Unlike the `ImageDraw*` functions of raylib, shapes drawn here are anti-aliased and can be thick. Each pixel is
+//! covered according to its distance from the shape's outline, then alpha blended into the image. Everything runs
+//! on the CPU, so images can be baked without a window or GPU.
+//!
+//! [`Image`]: ../texture/struct.Image.html
+use crate::consts::{BlendMode, PixelFormat};
+use crate::core::color::Color;
+use crate::core::filter::over;
+use crate::core::math::{Rectangle, Vector2};
+use crate::core::texture::Image;
+use crate::ffi;
+
+/// Pixels of an image being drawn to.
+struct Canvas<'a> {
+    width: i32,
+    height: i32,
+    pixels: &'a mut [Color],
+}
+
+impl<'a> Canvas<'a> {
+    /// Covers every pixel within `bounds` whose center lies inside the shape described by the signed distance
+    /// function `sdf`, which is negative inside. Pixels straddling the outline are partially covered.
+    fn fill(&mut self, bounds: Bounds, color: Color, sdf: impl Fn(Vector2) -> f32) {
+        let x0 = (bounds.min.x.floor() as i32 - 1).max(0);
+        let y0 = (bounds.min.y.floor() as i32 - 1).max(0);
+        let x1 = (bounds.max.x.ceil() as i32 + 1).min(self.width);
+        let y1 = (bounds.max.y.ceil() as i32 + 1).min(self.height);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let coverage = (0.5 - sdf(Vector2::new(x as f32 + 0.5, y as f32 + 0.5))).min(1.0);
+                if coverage > 0.0 {
+                    let index = (y * self.width + x) as usize;
+                    let src = Color::new(
+                        color.r,
+                        color.g,
+                        color.b,
+                        (color.a as f32 * coverage).round() as u8,
+                    );
+                    self.pixels[index] = over(src, self.pixels[index]);
+                }
+            }
+        }
+    }
+}
+
+/// Axis aligned box around a shape.
+#[derive(Clone, Copy)]
+struct Bounds {
+    min: Vector2,
+    max: Vector2,
+}
+
+impl Bounds {
+    fn of(points: &[Vector2]) -> Bounds {
+        let mut bounds = Bounds {
+            min: Vector2::new(f32::MAX, f32::MAX),
+            max: Vector2::new(f32::MIN, f32::MIN),
+        };
+        for p in points {
+            bounds.min = Vector2::new(bounds.min.x.min(p.x), bounds.min.y.min(p.y));
+            bounds.max = Vector2::new(bounds.max.x.max(p.x), bounds.max.y.max(p.y));
+        }
+        bounds
+    }
+
+    fn grow(self, amount: f32) -> Bounds {
+        Bounds {
+            min: Vector2::new(self.min.x - amount, self.min.y - amount),
+            max: Vector2::new(self.max.x + amount, self.max.y + amount),
+        }
+    }
+}
+
+fn dot(a: Vector2, b: Vector2) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+/// Distance from `p` to the segment from `a` to `b`.
+fn segment_distance(p: Vector2, a: Vector2, b: Vector2) -> f32 {
+    let (pa, ba) = (p - a, b - a);
+    let length = dot(ba, ba);
+    let t = if length > 0.0 {
+        (dot(pa, ba) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (pa - ba * t).length()
+}
+
+/// Distance from `p` to the line strip through `points`.
+fn strip_distance(p: Vector2, points: &[Vector2]) -> f32 {
+    match points {
+        [] => f32::MAX,
+        [single] => (p - *single).length(),
+        _ => points
+            .windows(2)
+            .map(|w| segment_distance(p, w[0], w[1]))
+            .fold(f32::MAX, f32::min),
+    }
+}
+
+/// Vertices of a regular polygon as raylib's `DrawPoly` lays them out.
+pub(crate) fn poly_points(center: Vector2, sides: i32, radius: f32, rotation: f32) -> Vec<Vector2> {
+    let sides = sides.max(3);
+    (0..sides)
+        .map(|i| {
+            let a = (rotation + i as f32 * 360.0 / sides as f32).to_radians();
+            Vector2::new(center.x + a.sin() * radius, center.y + a.cos() * radius)
+        })
+        .collect()
+}
+
+/// Signed distance from `p` to the closed polygon through `points`, negative inside. Self intersecting polygons are
+/// filled with the even-odd rule.
+fn polygon_distance(p: Vector2, points: &[Vector2]) -> f32 {
+    let mut distance = f32::MAX;
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        distance = distance.min(segment_distance(p, a, b));
+        if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+        j = i;
+    }
+    if inside {
+        -distance
+    } else {
+        distance
+    }
+}
+
+/// Approximate signed distance from `p` to an axis aligned ellipse, exact on circles.
+fn ellipse_distance(p: Vector2, center: Vector2, radius: Vector2) -> f32 {
+    let d = p - center;
+    let k0 = Vector2::new(d.x / radius.x, d.y / radius.y).length();
+    let k1 = Vector2::new(d.x / (radius.x * radius.x), d.y / (radius.y * radius.y)).length();
+    if k1 == 0.0 {
+        -radius.x.min(radius.y)
+    } else {
+        k0 * (k0 - 1.0) / k1
+    }
+}
+
+/// Signed distance from `p` to a rectangle with corners rounded by `radius`.
+fn rounded_rectangle_distance(p: Vector2, rec: Rectangle, radius: f32) -> f32 {
+    let half = Vector2::new(rec.width / 2.0, rec.height / 2.0);
+    let d = p - Vector2::new(rec.x + half.x, rec.y + half.y);
+    let q = Vector2::new(d.x.abs() - half.x + radius, d.y.abs() - half.y + radius);
+    Vector2::new(q.x.max(0.0), q.y.max(0.0)).length() + q.x.max(q.y).min(0.0) - radius
+}
+
+/// Points along a curve evaluated at evenly spaced parameters, with enough segments to look smooth at its size.
+fn flatten(size: f32, curve: impl Fn(f32) -> Vector2) -> Vec<Vector2> {
+    let segments = ((size / 4.0).ceil() as usize).clamp(8, 256);
+    (0..=segments)
+        .map(|i| curve(i as f32 / segments as f32))
+        .collect()
+}
+
+/// Blends `src` into `dst` the way the matching [`BlendMode`] does on the GPU.
+fn blend(src: Color, dst: Color, mode: BlendMode) -> Color {
+    let sa = src.a as f32 / 255.0;
+    let alpha = (src.a as f32 + dst.a as f32 * (1.0 - sa)).round() as u8;
+    let channel = |f: &dyn Fn(f32, f32) -> f32, s: u8, d: u8| {
+        f(s as f32, d as f32).round().clamp(0.0, 255.0) as u8
+    };
+    match mode {
+        BlendMode::BLEND_ALPHA => over(src, dst),
+        BlendMode::BLEND_ADDITIVE => {
+            let add = |s: f32, d: f32| d + s * sa;
+            Color::new(
+                channel(&add, src.r, dst.r),
+                channel(&add, src.g, dst.g),
+                channel(&add, src.b, dst.b),
+                alpha,
+            )
+        }
+        BlendMode::BLEND_MULTIPLIED => {
+            let multiply = |s: f32, d: f32| s * d / 255.0 + d * (1.0 - sa);
+            Color::new(
+                channel(&multiply, src.r, dst.r),
+                channel(&multiply, src.g, dst.g),
+                channel(&multiply, src.b, dst.b),
+                alpha,
+            )
+        }
+    }
+}
+
+impl Image {
+    /// Runs `draw` on the image's pixels, in place when the image is R8G8B8A8.
+    fn paint(&mut self, draw: impl FnOnce(&mut Canvas)) {
+        let (width, height) = (self.width(), self.height());
+        if width <= 0 || height <= 0 || self.0.data.is_null() {
+            return;
+        }
+        if self.format() == PixelFormat::UNCOMPRESSED_R8G8B8A8 {
+            let pixels = unsafe {
+                std::slice::from_raw_parts_mut(self.0.data as *mut Color, (width * height) as usize)
+            };
+            draw(&mut Canvas {
+                width,
+                height,
+                pixels,
+            });
+        } else {
+            let mut pixels = self.colors();
+            draw(&mut Canvas {
+                width,
+                height,
+                pixels: &mut pixels,
+            });
+            self.set_colors(width, height, &pixels);
+        }
+    }
+
+    /// Draws an anti-aliased line `thick` pixels wide, with round caps.
+    pub fn draw_line_ex(
+        &mut self,
+        start_pos: impl Into<ffi::Vector2>,
+        end_pos: impl Into<ffi::Vector2>,
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        let points = [start_pos.into().into(), end_pos.into().into()];
+        self.draw_line_strip(&points, thick, color);
+    }
+
+    /// Draws connected anti-aliased lines through `points`, `thick` pixels wide.
+    pub fn draw_line_strip(
+        &mut self,
+        points: &[Vector2],
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        if points.is_empty() {
+            return;
+        }
+        let color = color.into().into();
+        let radius = thick / 2.0;
+        self.paint(|canvas| {
+            canvas.fill(Bounds::of(points).grow(radius), color, |p| {
+                strip_distance(p, points) - radius
+            })
+        });
+    }
+
+    /// Draws a cubic bezier line from `start_pos` to `end_pos`, easing in and out vertically like
+    /// `RaylibDraw::draw_line_bezier`.
+    pub fn draw_line_bezier(
+        &mut self,
+        start_pos: impl Into<ffi::Vector2>,
+        end_pos: impl Into<ffi::Vector2>,
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        let (start, end): (Vector2, Vector2) = (start_pos.into().into(), end_pos.into().into());
+        let middle = (start.x + end.x) / 2.0;
+        self.draw_line_bezier_cubic(
+            start,
+            end,
+            Vector2::new(middle, start.y),
+            Vector2::new(middle, end.y),
+            thick,
+            color,
+        );
+    }
+
+    /// Draws a quadratic bezier line from `start_pos` to `end_pos`, pulled towards `control_pos`.
+    pub fn draw_line_bezier_quad(
+        &mut self,
+        start_pos: impl Into<ffi::Vector2>,
+        end_pos: impl Into<ffi::Vector2>,
+        control_pos: impl Into<ffi::Vector2>,
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        let (a, b, c): (Vector2, Vector2, Vector2) = (
+            start_pos.into().into(),
+            control_pos.into().into(),
+            end_pos.into().into(),
+        );
+        let size = (b - a).length() + (c - b).length();
+        let points = flatten(size, |t| {
+            let u = 1.0 - t;
+            a * (u * u) + b * (2.0 * u * t) + c * (t * t)
+        });
+        self.draw_line_strip(&points, thick, color);
+    }
+
+    /// Draws a cubic bezier line from `start_pos` to `end_pos`, leaving towards `start_control_pos` and arriving from
+    /// `end_control_pos`.
+    pub fn draw_line_bezier_cubic(
+        &mut self,
+        start_pos: impl Into<ffi::Vector2>,
+        end_pos: impl Into<ffi::Vector2>,
+        start_control_pos: impl Into<ffi::Vector2>,
+        end_control_pos: impl Into<ffi::Vector2>,
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        let (a, b, c, d): (Vector2, Vector2, Vector2, Vector2) = (
+            start_pos.into().into(),
+            start_control_pos.into().into(),
+            end_control_pos.into().into(),
+            end_pos.into().into(),
+        );
+        let size = (b - a).length() + (c - b).length() + (d - c).length();
+        let points = flatten(size, |t| {
+            let u = 1.0 - t;
+            a * (u * u * u) + b * (3.0 * u * u * t) + c * (3.0 * u * t * t) + d * (t * t * t)
+        });
+        self.draw_line_strip(&points, thick, color);
+    }
+
+    /// Draws a filled triangle. The vertices may be in any order.
+    pub fn draw_triangle(
+        &mut self,
+        v1: impl Into<ffi::Vector2>,
+        v2: impl Into<ffi::Vector2>,
+        v3: impl Into<ffi::Vector2>,
+        color: impl Into<ffi::Color>,
+    ) {
+        let points = [v1.into().into(), v2.into().into(), v3.into().into()];
+        self.draw_polygon(&points, color);
+    }
+
+    /// Draws the outline of a triangle, `thick` pixels wide and centered on its edges.
+    pub fn draw_triangle_lines(
+        &mut self,
+        v1: impl Into<ffi::Vector2>,
+        v2: impl Into<ffi::Vector2>,
+        v3: impl Into<ffi::Vector2>,
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        let points = [v1.into().into(), v2.into().into(), v3.into().into()];
+        self.draw_polygon_lines(&points, thick, color);
+    }
+
+    /// Draws a regular polygon with `sides` sides, `rotation` given in degrees.
+    pub fn draw_poly(
+        &mut self,
+        center: impl Into<ffi::Vector2>,
+        sides: i32,
+        radius: f32,
+        rotation: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        let points = poly_points(center.into().into(), sides, radius, rotation);
+        self.draw_polygon(&points, color);
+    }
+
+    /// Fills the closed polygon through `points`. Concave and self intersecting polygons are supported, using the
+    /// even-odd rule.
+    pub fn draw_polygon(&mut self, points: &[Vector2], color: impl Into<ffi::Color>) {
+        if points.len() < 3 {
+            return;
+        }
+        let color = color.into().into();
+        self.paint(|canvas| {
+            canvas.fill(Bounds::of(points), color, |p| polygon_distance(p, points))
+        });
+    }
+
+    /// Draws the outline of the closed polygon through `points`, `thick` pixels wide and centered on its edges.
+    pub fn draw_polygon_lines(
+        &mut self,
+        points: &[Vector2],
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        if points.len() < 3 {
+            self.draw_line_strip(points, thick, color);
+            return;
+        }
+        let color = color.into().into();
+        let radius = thick / 2.0;
+        self.paint(|canvas| {
+            canvas.fill(Bounds::of(points).grow(radius), color, |p| {
+                polygon_distance(p, points).abs() - radius
+            })
+        });
+    }
+
+    /// Draws a filled, anti-aliased ellipse.
+    pub fn draw_ellipse(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        radius_h: f32,
+        radius_v: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        let center = Vector2::new(center_x as f32, center_y as f32);
+        let radius = Vector2::new(radius_h.max(0.01), radius_v.max(0.01));
+        let color = color.into().into();
+        self.paint(|canvas| {
+            canvas.fill(
+                Bounds::of(&[center - radius, center + radius]),
+                color,
+                |p| ellipse_distance(p, center, radius),
+            )
+        });
+    }
+
+    /// Draws the outline of an ellipse, `thick` pixels wide and centered on its edge.
+    pub fn draw_ellipse_lines(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        radius_h: f32,
+        radius_v: f32,
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        let center = Vector2::new(center_x as f32, center_y as f32);
+        let radius = Vector2::new(radius_h.max(0.01), radius_v.max(0.01));
+        let half = thick / 2.0;
+        let color = color.into().into();
+        self.paint(|canvas| {
+            let bounds = Bounds::of(&[center - radius, center + radius]).grow(half);
+            canvas.fill(bounds, color, |p| {
+                ellipse_distance(p, center, radius).abs() - half
+            })
+        });
+    }
+
+    /// Draws a filled rectangle with rounded corners. `roundness` goes from `0.0` for square corners to `1.0` for
+    /// fully round ends, as in `RaylibDraw::draw_rectangle_rounded`.
+    pub fn draw_rectangle_rounded(
+        &mut self,
+        rec: impl Into<ffi::Rectangle>,
+        roundness: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        let rec: Rectangle = rec.into().into();
+        let radius = roundness.clamp(0.0, 1.0) * rec.width.min(rec.height) / 2.0;
+        let color = color.into().into();
+        self.paint(|canvas| {
+            canvas.fill(rectangle_bounds(rec), color, |p| {
+                rounded_rectangle_distance(p, rec, radius)
+            })
+        });
+    }
+
+    /// Draws the outline of a rectangle with rounded corners, `thick` pixels wide and inside `rec`.
+    pub fn draw_rectangle_rounded_lines(
+        &mut self,
+        rec: impl Into<ffi::Rectangle>,
+        roundness: f32,
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        let rec: Rectangle = rec.into().into();
+        let radius = roundness.clamp(0.0, 1.0) * rec.width.min(rec.height) / 2.0;
+        let half = thick / 2.0;
+        let color = color.into().into();
+        self.paint(|canvas| {
+            canvas.fill(rectangle_bounds(rec), color, |p| {
+                (rounded_rectangle_distance(p, rec, radius) + half).abs() - half
+            })
+        });
+    }
+
+    /// Replaces the area connected to `(x, y)` whose colors are within `tolerance` of the color at `(x, y)` on every
+    /// channel, with `color`. Returns the number of pixels filled.
+    pub fn flood_fill(
+        &mut self,
+        x: i32,
+        y: i32,
+        color: impl Into<ffi::Color>,
+        tolerance: u8,
+    ) -> usize {
+        let color: Color = color.into().into();
+        let mut filled = 0;
+        self.paint(|canvas| {
+            let (width, height) = (canvas.width, canvas.height);
+            if x < 0 || y < 0 || x >= width || y >= height {
+                return;
+            }
+            let target = canvas.pixels[(y * width + x) as usize];
+            let close = |a: u8, b: u8| (a as i16 - b as i16).abs() <= tolerance as i16;
+            let matches = |c: Color| {
+                close(c.r, target.r)
+                    && close(c.g, target.g)
+                    && close(c.b, target.b)
+                    && close(c.a, target.a)
+            };
+            let mut visited = vec![false; (width * height) as usize];
+            let mut stack = vec![(x, y)];
+            while let Some((x, y)) = stack.pop() {
+                let index = |x: i32| (y * width + x) as usize;
+                if visited[index(x)] || !matches(canvas.pixels[index(x)]) {
+                    continue;
+                }
+                let mut left = x;
+                while left > 0
+                    && !visited[index(left - 1)]
+                    && matches(canvas.pixels[index(left - 1)])
+                {
+                    left -= 1;
+                }
+                let mut right = x;
+                while right + 1 < width
+                    && !visited[index(right + 1)]
+                    && matches(canvas.pixels[index(right + 1)])
+                {
+                    right += 1;
+                }
+                for sx in left..=right {
+                    visited[index(sx)] = true;
+                    canvas.pixels[index(sx)] = color;
+                    filled += 1;
+                    for &sy in &[y - 1, y + 1] {
+                        if sy >= 0 && sy < height && !visited[(sy * width + sx) as usize] {
+                            stack.push((sx, sy));
+                        }
+                    }
+                }
+            }
+        });
+        filled
+    }
+
+    /// Draws `src_rec` of `src` stretched over `dst_rec`, tinted by `tint` and blended with `blend_mode`. Unlike
+    /// [`draw`](#method.draw), the image is sampled with nearest neighbour filtering and additive or multiplied
+    /// blending are available. A negative `src_rec` width or height flips the image.
+    pub fn draw_image(
+        &mut self,
+        src: &Image,
+        src_rec: impl Into<ffi::Rectangle>,
+        dst_rec: impl Into<ffi::Rectangle>,
+        tint: impl Into<ffi::Color>,
+        blend_mode: BlendMode,
+    ) {
+        let (src_rec, dst_rec): (Rectangle, Rectangle) =
+            (src_rec.into().into(), dst_rec.into().into());
+        let tint: Color = tint.into().into();
+        if dst_rec.width <= 0.0 || dst_rec.height <= 0.0 {
+            return;
+        }
+        let (src_width, src_height) = (src.width(), src.height());
+        let src_pixels = src.colors();
+        let tinted = |c: Color| {
+            let mul = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+            Color::new(
+                mul(c.r, tint.r),
+                mul(c.g, tint.g),
+                mul(c.b, tint.b),
+                mul(c.a, tint.a),
+            )
+        };
+        self.paint(|canvas| {
+            let x0 = (dst_rec.x.floor() as i32).max(0);
+            let y0 = (dst_rec.y.floor() as i32).max(0);
+            let x1 = ((dst_rec.x + dst_rec.width).ceil() as i32).min(canvas.width);
+            let y1 = ((dst_rec.y + dst_rec.height).ceil() as i32).min(canvas.height);
+            for y in y0..y1 {
+                let v = (y as f32 + 0.5 - dst_rec.y) / dst_rec.height;
+                if !(0.0..1.0).contains(&v) {
+                    continue;
+                }
+                let sy = sample(src_rec.y, src_rec.height, v);
+                if sy < 0 || sy >= src_height {
+                    continue;
+                }
+                for x in x0..x1 {
+                    let u = (x as f32 + 0.5 - dst_rec.x) / dst_rec.width;
+                    if !(0.0..1.0).contains(&u) {
+                        continue;
+                    }
+                    let sx = sample(src_rec.x, src_rec.width, u);
+                    if sx < 0 || sx >= src_width {
+                        continue;
+                    }
+                    let color = tinted(src_pixels[(sy * src_width + sx) as usize]);
+                    let index = (y * canvas.width + x) as usize;
+                    canvas.pixels[index] = blend(color, canvas.pixels[index], blend_mode);
+                }
+            }
+        });
+    }
+}
+
+/// Source pixel at fraction `t` of a span starting at `start`, reading backwards when `size` is negative.
+fn sample(start: f32, size: f32, t: f32) -> i32 {
+    if size < 0.0 {
+        (start - size * (1.0 - t)).floor() as i32
+    } else {
+        (start + size * t).floor() as i32
+    }
+}
+
+fn rectangle_bounds(rec: Rectangle) -> Bounds {
+    Bounds::of(&[
+        Vector2::new(rec.x, rec.y),
+        Vector2::new(rec.x + rec.width, rec.y + rec.height),
+    ])
+}
+
+#[cfg(test)]
+mod raster_test {
+    use super::*;
+
+    const CLEAR: Color = Color {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 0,
+    };
+
+    fn canvas(width: i32, height: i32, color: Color) -> Image {
+        Image::from_colors(width, height, &vec![color; (width * height) as usize])
+    }
+
+    fn pixel(image: &Image, x: i32, y: i32) -> Color {
+        image.colors()[(y * image.width() + x) as usize]
+    }
+
+    #[test]
+    fn test_lines() {
+        let mut image = canvas(16, 8, CLEAR);
+        image.draw_line_ex(
+            Vector2::new(2.0, 4.0),
+            Vector2::new(14.0, 4.0),
+            2.0,
+            Color::RED,
+        );
+        assert_eq!(pixel(&image, 8, 3), Color::RED);
+        assert_eq!(pixel(&image, 8, 4), Color::RED);
+        assert_eq!(pixel(&image, 8, 2), CLEAR);
+        assert_eq!(pixel(&image, 8, 6), CLEAR);
+        // round caps cover half of the pixels at the ends
+        assert!(pixel(&image, 0, 4).a < 255);
+
+        let mut image = canvas(16, 16, CLEAR);
+        image.draw_line_bezier_quad(
+            Vector2::new(1.0, 1.0),
+            Vector2::new(15.0, 1.0),
+            Vector2::new(8.0, 15.0),
+            1.0,
+            Color::BLUE,
+        );
+        assert!(pixel(&image, 1, 1).a > 0);
+        assert!(pixel(&image, 8, 8).a > 0);
+        assert_eq!(pixel(&image, 8, 1), CLEAR);
+    }
+
+    #[test]
+    fn test_polygons() {
+        let mut image = canvas(10, 10, CLEAR);
+        image.draw_triangle(
+            Vector2::new(0.0, 0.0),
+            Vector2::new(0.0, 10.0),
+            Vector2::new(10.0, 0.0),
+            Color::GREEN,
+        );
+        assert_eq!(pixel(&image, 1, 1), Color::GREEN);
+        assert_eq!(pixel(&image, 8, 8), CLEAR);
+        // the diagonal crosses the pixel centers, covering them by half
+        assert!((126..=129).contains(&pixel(&image, 5, 4).a));
+
+        // an L shape leaves its concave corner empty
+        let l = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(4.0, 0.0),
+            Vector2::new(4.0, 6.0),
+            Vector2::new(10.0, 6.0),
+            Vector2::new(10.0, 10.0),
+            Vector2::new(0.0, 10.0),
+        ];
+        let mut image = canvas(10, 10, CLEAR);
+        image.draw_polygon(&l, Color::WHITE);
+        assert_eq!(pixel(&image, 2, 2), Color::WHITE);
+        assert_eq!(pixel(&image, 8, 8), Color::WHITE);
+        assert_eq!(pixel(&image, 8, 2), CLEAR);
+
+        let mut image = canvas(10, 10, CLEAR);
+        image.draw_polygon_lines(&l, 1.0, Color::WHITE);
+        assert_eq!(pixel(&image, 2, 2), CLEAR);
+        assert_eq!(pixel(&image, 3, 2).a, 128);
+
+        // the first vertex points down, as with raylib's DrawPoly
+        let first = poly_points(Vector2::new(10.0, 10.0), 3, 8.0, 0.0)[0];
+        assert!((first.x - 10.0).abs() < 1e-4 && (first.y - 18.0).abs() < 1e-4);
+        let mut image = canvas(20, 20, CLEAR);
+        image.draw_poly(Vector2::new(10.0, 10.0), 3, 8.0, 0.0, Color::WHITE);
+        assert_eq!(pixel(&image, 10, 15), Color::WHITE);
+        assert_eq!(pixel(&image, 10, 4), CLEAR);
+    }
+
+    #[test]
+    fn test_round_shapes() {
+        let mut image = canvas(20, 20, Color::BLACK);
+        image.draw_ellipse(10, 10, 8.0, 4.0, Color::WHITE);
+        assert_eq!(pixel(&image, 10, 10), Color::WHITE);
+        assert_eq!(pixel(&image, 3, 10), Color::WHITE);
+        assert_eq!(pixel(&image, 10, 4), Color::BLACK);
+
+        image.draw_ellipse_lines(10, 10, 8.0, 4.0, 1.0, Color::RED);
+        assert_eq!(pixel(&image, 10, 10), Color::WHITE);
+
+        let mut image = canvas(20, 20, CLEAR);
+        image.draw_rectangle_rounded(Rectangle::new(0.0, 0.0, 20.0, 20.0), 0.5, Color::WHITE);
+        assert_eq!(pixel(&image, 0, 0), CLEAR);
+        assert_eq!(pixel(&image, 10, 0), Color::WHITE);
+        assert_eq!(pixel(&image, 10, 10), Color::WHITE);
+
+        let mut image = canvas(20, 20, CLEAR);
+        image.draw_rectangle_rounded_lines(
+            Rectangle::new(0.0, 0.0, 20.0, 20.0),
+            0.0,
+            2.0,
+            Color::WHITE,
+        );
+        assert_eq!(pixel(&image, 0, 10), Color::WHITE);
+        assert_eq!(pixel(&image, 1, 10), Color::WHITE);
+        assert_eq!(pixel(&image, 2, 10), CLEAR);
+        assert_eq!(pixel(&image, 19, 19), Color::WHITE);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let mut pixels = vec![Color::WHITE; 25];
+        for y in 0..5 {
+            pixels[y * 5 + 2] = Color::BLACK;
+        }
+        pixels[0] = Color::new(250, 250, 250, 255);
+        let mut image = Image::from_colors(5, 5, &pixels);
+        assert_eq!(image.flood_fill(4, 4, Color::RED, 0), 10);
+        assert_eq!(image.flood_fill(1, 1, Color::BLUE, 8), 10);
+        assert_eq!(pixel(&image, 0, 0), Color::BLUE);
+        assert_eq!(pixel(&image, 2, 2), Color::BLACK);
+        assert_eq!(image.flood_fill(1, 1, Color::BLUE, 0), 10);
+    }
+
+    #[test]
+    fn test_draw_image() {
+        let src = Image::from_colors(
+            2,
+            1,
+            &[Color::new(100, 50, 200, 255), Color::new(10, 20, 30, 128)],
+        );
+        let whole = Rectangle::new(0.0, 0.0, 2.0, 1.0);
+
+        let mut image = canvas(4, 2, Color::new(100, 100, 100, 255));
+        image.draw_image(
+            &src,
+            whole,
+            Rectangle::new(0.0, 0.0, 4.0, 2.0),
+            Color::WHITE,
+            BlendMode::BLEND_ALPHA,
+        );
+        assert_eq!(pixel(&image, 1, 1), Color::new(100, 50, 200, 255));
+        assert_eq!(pixel(&image, 3, 0), Color::new(55, 60, 65, 255));
+
+        let mut image = canvas(2, 1, Color::new(100, 100, 100, 255));
+        image.draw_image(&src, whole, whole, Color::WHITE, BlendMode::BLEND_ADDITIVE);
+        assert_eq!(pixel(&image, 0, 0), Color::new(200, 150, 255, 255));
+        assert_eq!(pixel(&image, 1, 0), Color::new(105, 110, 115, 255));
+
+        let mut image = canvas(2, 1, Color::new(100, 100, 100, 255));
+        image.draw_image(
+            &src,
+            whole,
+            whole,
+            Color::WHITE,
+            BlendMode::BLEND_MULTIPLIED,
+        );
+        assert_eq!(pixel(&image, 0, 0), Color::new(39, 20, 78, 255));
+
+        // flipped horizontally and tinted
+        let mut image = canvas(2, 1, CLEAR);
+        let flipped = Rectangle::new(0.0, 0.0, -2.0, 1.0);
+        image.draw_image(
+            &src,
+            flipped,
+            whole,
+            Color::new(255, 0, 255, 255),
+            BlendMode::BLEND_ALPHA,
+        );
+        assert_eq!(pixel(&image, 1, 0), Color::new(100, 0, 200, 255));
+    }
+}
@@ -0,0 +1,2481 @@
+//! Recording draw calls for later replay.
+//!
+//! [`DrawRecorder`] implements [`RaylibDraw`] and [`RaylibDraw3D`], but instead of drawing it appends a
+//! [`DrawCommand`] for each call. The command list can be inspected, used to test drawing code without a window,
+//! or replayed onto a real draw handle.
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//!
+//! let (mut rl, thread) = raylib::init().size(640, 480).build();
+//! let mut recorder = DrawRecorder::new();
+//! recorder.clear_background(Color::WHITE);
+//! {
+//!     let mut camera = recorder.begin_mode2D(Camera2D {
+//!         zoom: 2.0,
+//!         ..Default::default()
+//!     });
+//!     camera.draw_circle(20, 20, 10.0, Color::RED);
+//! }
+//! assert_eq!(recorder.len(), 4);
+//!
+//! while !rl.window_should_close() {
+//!     let mut d = rl.begin_drawing(&thread);
+//!     recorder.replay(&mut d);
+//! }
+//! ```
+//!
+//! Textures are recorded by handle. Fonts and models are recorded as copies of their glyph metrics and meshes, which
+//! still refer to the font's texture and the models' GPU buffers, so all of them must still be loaded for a replay to
+//! draw anything useful.
+//! `replay` leaves out 3D commands, recordings holding some are replayed with `replay_3d` onto a 3D surface such as
+//! the guard returned by `begin_mode3D`.
+//!
+//! [`DrawRecorder`]: struct.DrawRecorder.html
+//! [`DrawCommand`]: enum.DrawCommand.html
+//! [`RaylibDraw`]: ../drawing/trait.RaylibDraw.html
+//! [`RaylibDraw3D`]: ../drawing/trait.RaylibDraw3D.html
+use crate::consts::BlendMode;
+use crate::core::camera::{Camera2D, Camera3D};
+use crate::core::color::Color;
+use crate::core::drawing::{DrawMode, DrawModeKind, RaylibDraw, RaylibDraw3D};
+use crate::core::math::{BoundingBox, Ray, Rectangle, Vector2, Vector3};
+use crate::core::models::WeakModel;
+use crate::core::text::WeakFont;
+use crate::core::texture::{NPatchInfo, Texture2D, WeakTexture2D};
+use crate::ffi;
+use std::mem::ManuallyDrop;
+use std::sync::Arc;
+
+/// The glyph metrics of a font, copied when it's recorded. Its texture is kept by handle, like recorded textures.
+#[derive(Debug, Clone)]
+pub struct RecordedFont {
+    base_size: i32,
+    texture: ffi::Texture2D,
+    recs: Arc<[ffi::Rectangle]>,
+    glyphs: Arc<[Glyph]>,
+}
+
+/// The part of a `CharInfo` drawing reads, without the glyph image.
+#[derive(Debug, Clone, Copy)]
+struct Glyph {
+    value: i32,
+    offset_x: i32,
+    offset_y: i32,
+    advance_x: i32,
+}
+
+impl RecordedFont {
+    fn copy(font: &ffi::Font) -> RecordedFont {
+        let count = if font.recs.is_null() || font.chars.is_null() {
+            0
+        } else {
+            font.charsCount.max(0) as usize
+        };
+        let (recs, chars) = unsafe { (raw_slice(font.recs, count), raw_slice(font.chars, count)) };
+        RecordedFont {
+            base_size: font.baseSize,
+            texture: font.texture,
+            recs: recs.into(),
+            glyphs: chars
+                .iter()
+                .map(|c| Glyph {
+                    value: c.value,
+                    offset_x: c.offsetX,
+                    offset_y: c.offsetY,
+                    advance_x: c.advanceX,
+                })
+                .collect(),
+        }
+    }
+
+    /// Calls `f` with a raylib font pointing at the copied metrics.
+    fn with_raw<R>(&self, f: impl FnOnce(WeakFont) -> R) -> R {
+        let mut recs = self.recs.to_vec();
+        let mut chars: Vec<ffi::CharInfo> = self
+            .glyphs
+            .iter()
+            .map(|g| ffi::CharInfo {
+                value: g.value,
+                offsetX: g.offset_x,
+                offsetY: g.offset_y,
+                advanceX: g.advance_x,
+                image: ffi::Image {
+                    data: std::ptr::null_mut(),
+                    width: 0,
+                    height: 0,
+                    mipmaps: 0,
+                    format: 0,
+                },
+            })
+            .collect();
+        f(WeakFont(ffi::Font {
+            baseSize: self.base_size,
+            charsCount: chars.len() as i32,
+            texture: self.texture,
+            recs: recs.as_mut_ptr(),
+            chars: chars.as_mut_ptr(),
+        }))
+    }
+}
+
+/// What drawing a model reads, copied when it's recorded: the transform, and each mesh's GPU buffers and material.
+/// The buffers, shaders and textures are kept by handle, like recorded textures.
+#[derive(Debug, Clone)]
+pub struct RecordedModel {
+    transform: ffi::Matrix,
+    meshes: Arc<[RecordedMesh]>,
+    materials: Arc<[RecordedMaterial]>,
+    mesh_material: Arc<[i32]>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RecordedMesh {
+    vertex_count: i32,
+    triangle_count: i32,
+    vao_id: u32,
+    vbo_id: [u32; MESH_VBOS],
+    indexed: bool,
+}
+
+#[derive(Debug, Clone)]
+struct RecordedMaterial {
+    shader_id: u32,
+    locs: Vec<i32>,
+    maps: Vec<ffi::MaterialMap>,
+}
+
+/// Vertex buffers raylib gives each mesh.
+const MESH_VBOS: usize = 7;
+
+impl RecordedModel {
+    fn copy(model: &ffi::Model) -> RecordedModel {
+        let meshes = unsafe { raw_slice(model.meshes, model.meshCount.max(0) as usize) };
+        let materials = unsafe { raw_slice(model.materials, model.materialCount.max(0) as usize) };
+        let mesh_material = unsafe { raw_slice(model.meshMaterial, meshes.len()) };
+        RecordedModel {
+            transform: model.transform,
+            meshes: meshes
+                .iter()
+                .map(|mesh| {
+                    let mut vbo_id = [0; MESH_VBOS];
+                    for (id, raw) in vbo_id
+                        .iter_mut()
+                        .zip(unsafe { raw_slice(mesh.vboId, MESH_VBOS) })
+                    {
+                        *id = *raw;
+                    }
+                    RecordedMesh {
+                        vertex_count: mesh.vertexCount,
+                        triangle_count: mesh.triangleCount,
+                        vao_id: mesh.vaoId,
+                        vbo_id,
+                        indexed: !mesh.indices.is_null(),
+                    }
+                })
+                .collect(),
+            materials: materials
+                .iter()
+                .map(|material| {
+                    let mut locs = unsafe {
+                        raw_slice(material.shader.locs, ffi::MAX_SHADER_LOCATIONS as usize)
+                    }
+                    .to_vec();
+                    locs.resize(ffi::MAX_SHADER_LOCATIONS as usize, -1);
+                    RecordedMaterial {
+                        shader_id: material.shader.id,
+                        locs,
+                        maps: unsafe { raw_slice(material.maps, ffi::MAX_MATERIAL_MAPS as usize) }
+                            .to_vec(),
+                    }
+                })
+                .collect(),
+            mesh_material: mesh_material.into(),
+        }
+    }
+
+    /// Calls `f` with a raylib model built from the copy. Nothing is drawn when the copy has no material for a mesh.
+    fn with_raw<R>(&self, f: impl FnOnce(WeakModel) -> R) -> Option<R> {
+        let valid = |&m: &i32| m >= 0 && (m as usize) < self.materials.len();
+        if self.mesh_material.len() != self.meshes.len() || !self.mesh_material.iter().all(valid) {
+            return None;
+        }
+        let mut vbo_ids: Vec<[u32; MESH_VBOS]> = self.meshes.iter().map(|m| m.vbo_id).collect();
+        // only checked for null, the indices are drawn from their buffer
+        let mut no_index = 0u16;
+        let mut meshes: Vec<ffi::Mesh> = self
+            .meshes
+            .iter()
+            .zip(vbo_ids.iter_mut())
+            .map(|(mesh, vbo_id)| {
+                let mut raw: ffi::Mesh = unsafe { std::mem::zeroed() };
+                raw.vertexCount = mesh.vertex_count;
+                raw.triangleCount = mesh.triangle_count;
+                raw.vaoId = mesh.vao_id;
+                raw.vboId = vbo_id.as_mut_ptr();
+                if mesh.indexed {
+                    raw.indices = &mut no_index;
+                }
+                raw
+            })
+            .collect();
+        let mut copies: Vec<(Vec<i32>, Vec<ffi::MaterialMap>)> = self
+            .materials
+            .iter()
+            .map(|m| (m.locs.clone(), m.maps.clone()))
+            .collect();
+        let mut materials: Vec<ffi::Material> = self
+            .materials
+            .iter()
+            .zip(copies.iter_mut())
+            .map(|(material, (locs, maps))| ffi::Material {
+                shader: ffi::Shader {
+                    id: material.shader_id,
+                    locs: locs.as_mut_ptr(),
+                },
+                maps: maps.as_mut_ptr(),
+                params: std::ptr::null_mut(),
+            })
+            .collect();
+        let mut mesh_material = self.mesh_material.to_vec();
+        let mut raw: ffi::Model = unsafe { std::mem::zeroed() };
+        raw.transform = self.transform;
+        raw.meshCount = meshes.len() as i32;
+        raw.meshes = meshes.as_mut_ptr();
+        raw.materialCount = materials.len() as i32;
+        raw.materials = materials.as_mut_ptr();
+        raw.meshMaterial = mesh_material.as_mut_ptr();
+        Some(f(WeakModel(raw)))
+    }
+}
+
+/// Copies `len` items from `ptr`, or nothing when it's null.
+///
+/// # Safety
+///
+/// A non-null `ptr` must point to at least `len` items.
+unsafe fn raw_slice<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
+    if ptr.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len)
+    }
+}
+
+/// A single recorded draw call. Variants mirror the methods of `RaylibDraw` and `RaylibDraw3D`, plus the
+/// beginning and end of drawing modes.
+#[derive(Debug, Clone)]
+pub enum DrawCommand {
+    ClearBackground(Color),
+    SetShapesTexture {
+        texture: ffi::Texture2D,
+        source: Rectangle,
+    },
+    Pixel {
+        position: Vector2,
+        color: Color,
+    },
+    Line {
+        start: Vector2,
+        end: Vector2,
+        color: Color,
+    },
+    LineEx {
+        start: Vector2,
+        end: Vector2,
+        thick: f32,
+        color: Color,
+    },
+    LineBezier {
+        start: Vector2,
+        end: Vector2,
+        thick: f32,
+        color: Color,
+    },
+    LineStrip {
+        points: Vec<Vector2>,
+        color: Color,
+    },
+    Circle {
+        center: Vector2,
+        radius: f32,
+        color: Color,
+    },
+    CircleSector {
+        center: Vector2,
+        radius: f32,
+        start_angle: i32,
+        end_angle: i32,
+        segments: i32,
+        color: Color,
+    },
+    CircleSectorLines {
+        center: Vector2,
+        radius: f32,
+        start_angle: i32,
+        end_angle: i32,
+        segments: i32,
+        color: Color,
+    },
+    CircleGradient {
+        center_x: i32,
+        center_y: i32,
+        radius: f32,
+        inner: Color,
+        outer: Color,
+    },
+    CircleLines {
+        center_x: i32,
+        center_y: i32,
+        radius: f32,
+        color: Color,
+    },
+    Ellipse {
+        center_x: i32,
+        center_y: i32,
+        radius_h: f32,
+        radius_v: f32,
+        color: Color,
+    },
+    EllipseLines {
+        center_x: i32,
+        center_y: i32,
+        radius_h: f32,
+        radius_v: f32,
+        color: Color,
+    },
+    Ring {
+        center: Vector2,
+        inner_radius: f32,
+        outer_radius: f32,
+        start_angle: i32,
+        end_angle: i32,
+        segments: i32,
+        color: Color,
+    },
+    RingLines {
+        center: Vector2,
+        inner_radius: f32,
+        outer_radius: f32,
+        start_angle: i32,
+        end_angle: i32,
+        segments: i32,
+        color: Color,
+    },
+    Rectangle {
+        rec: Rectangle,
+        color: Color,
+    },
+    RectanglePro {
+        rec: Rectangle,
+        origin: Vector2,
+        rotation: f32,
+        color: Color,
+    },
+    /// A rectangle with a color per corner: top left, bottom left, bottom right and top right.
+    RectangleGradient {
+        rec: Rectangle,
+        colors: [Color; 4],
+    },
+    RectangleLines {
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        color: Color,
+    },
+    RectangleLinesEx {
+        rec: Rectangle,
+        line_thick: i32,
+        color: Color,
+    },
+    RectangleRounded {
+        rec: Rectangle,
+        roundness: f32,
+        segments: i32,
+        color: Color,
+    },
+    RectangleRoundedLines {
+        rec: Rectangle,
+        roundness: f32,
+        segments: i32,
+        line_thickness: i32,
+        color: Color,
+    },
+    Triangle {
+        v1: Vector2,
+        v2: Vector2,
+        v3: Vector2,
+        color: Color,
+    },
+    TriangleLines {
+        v1: Vector2,
+        v2: Vector2,
+        v3: Vector2,
+        color: Color,
+    },
+    TriangleFan {
+        points: Vec<Vector2>,
+        color: Color,
+    },
+    TriangleStrip {
+        points: Vec<Vector2>,
+        color: Color,
+    },
+    Poly {
+        center: Vector2,
+        sides: i32,
+        radius: f32,
+        rotation: f32,
+        color: Color,
+    },
+    PolyLines {
+        center: Vector2,
+        sides: i32,
+        radius: f32,
+        rotation: f32,
+        color: Color,
+    },
+    /// Every texture call is recorded in the form of `draw_texture_pro`.
+    Texture {
+        texture: ffi::Texture2D,
+        source_rec: Rectangle,
+        dest_rec: Rectangle,
+        origin: Vector2,
+        rotation: f32,
+        tint: Color,
+    },
+    TextureQuad {
+        texture: ffi::Texture2D,
+        tiling: Vector2,
+        offset: Vector2,
+        quad: Rectangle,
+        tint: Color,
+    },
+    TextureNPatch {
+        texture: ffi::Texture2D,
+        n_patch_info: NPatchInfo,
+        dest_rec: Rectangle,
+        origin: Vector2,
+        rotation: f32,
+        tint: Color,
+    },
+    Fps {
+        x: i32,
+        y: i32,
+    },
+    /// Text using the default font.
+    Text {
+        text: String,
+        x: i32,
+        y: i32,
+        font_size: i32,
+        color: Color,
+    },
+    TextEx {
+        font: RecordedFont,
+        text: String,
+        position: Vector2,
+        font_size: f32,
+        spacing: f32,
+        tint: Color,
+    },
+    TextRec {
+        font: RecordedFont,
+        text: String,
+        rec: Rectangle,
+        font_size: f32,
+        spacing: f32,
+        word_wrap: bool,
+        tint: Color,
+        select_start: i32,
+        select_length: i32,
+        select_text: Color,
+        select_back: Color,
+    },
+    TextCodepoint {
+        font: RecordedFont,
+        codepoint: i32,
+        position: Vector2,
+        scale: f32,
+        tint: Color,
+    },
+    Point3D {
+        position: Vector3,
+        color: Color,
+    },
+    Line3D {
+        start: Vector3,
+        end: Vector3,
+        color: Color,
+    },
+    Circle3D {
+        center: Vector3,
+        radius: f32,
+        rotation_axis: Vector3,
+        rotation_angle: f32,
+        color: Color,
+    },
+    Cube {
+        position: Vector3,
+        size: Vector3,
+        color: Color,
+    },
+    CubeWires {
+        position: Vector3,
+        size: Vector3,
+        color: Color,
+    },
+    CubeTexture {
+        texture: ffi::Texture2D,
+        position: Vector3,
+        size: Vector3,
+        color: Color,
+    },
+    Sphere {
+        center: Vector3,
+        radius: f32,
+        rings: i32,
+        slices: i32,
+        color: Color,
+    },
+    SphereWires {
+        center: Vector3,
+        radius: f32,
+        rings: i32,
+        slices: i32,
+        color: Color,
+    },
+    Cylinder {
+        position: Vector3,
+        radius_top: f32,
+        radius_bottom: f32,
+        height: f32,
+        slices: i32,
+        color: Color,
+    },
+    CylinderWires {
+        position: Vector3,
+        radius_top: f32,
+        radius_bottom: f32,
+        height: f32,
+        slices: i32,
+        color: Color,
+    },
+    Plane {
+        center: Vector3,
+        size: Vector2,
+        color: Color,
+    },
+    Ray {
+        ray: Ray,
+        color: Color,
+    },
+    Grid {
+        slices: i32,
+        spacing: f32,
+    },
+    Gizmo {
+        position: Vector3,
+    },
+    Model {
+        model: RecordedModel,
+        position: Vector3,
+        rotation_axis: Vector3,
+        rotation_angle: f32,
+        scale: Vector3,
+        tint: Color,
+    },
+    ModelWires {
+        model: RecordedModel,
+        position: Vector3,
+        rotation_axis: Vector3,
+        rotation_angle: f32,
+        scale: Vector3,
+        tint: Color,
+    },
+    BoundingBox {
+        bbox: BoundingBox,
+        color: Color,
+    },
+    Billboard {
+        camera: Camera3D,
+        texture: ffi::Texture2D,
+        source_rec: Rectangle,
+        center: Vector3,
+        size: f32,
+        tint: Color,
+    },
+    BeginMode2D(Camera2D),
+    EndMode2D,
+    BeginMode3D(Camera3D),
+    EndMode3D,
+    BeginBlendMode(BlendMode),
+    EndBlendMode,
+    BeginScissorMode {
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+    EndScissorMode,
+}
+
+fn texture_rec(texture: &ffi::Texture2D) -> Rectangle {
+    Rectangle::new(0.0, 0.0, texture.width as f32, texture.height as f32)
+}
+
+impl DrawCommand {
+    /// Command entering `mode`.
+    pub fn begin(mode: DrawMode) -> DrawCommand {
+        match mode {
+            DrawMode::Mode2D(camera) => DrawCommand::BeginMode2D(camera),
+            DrawMode::Mode3D(camera) => DrawCommand::BeginMode3D(camera),
+            DrawMode::Blend(blend_mode) => DrawCommand::BeginBlendMode(blend_mode),
+            DrawMode::Scissor {
+                x,
+                y,
+                width,
+                height,
+            } => DrawCommand::BeginScissorMode {
+                x,
+                y,
+                width,
+                height,
+            },
+        }
+    }
+
+    /// Command leaving a mode of the given kind.
+    pub fn end(kind: DrawModeKind) -> DrawCommand {
+        match kind {
+            DrawModeKind::Mode2D => DrawCommand::EndMode2D,
+            DrawModeKind::Mode3D => DrawCommand::EndMode3D,
+            DrawModeKind::Blend => DrawCommand::EndBlendMode,
+            DrawModeKind::Scissor => DrawCommand::EndScissorMode,
+        }
+    }
+
+    /// Checks if this command can only be drawn on a `RaylibDraw3D` surface.
+    pub fn is_3d(&self) -> bool {
+        use DrawCommand::*;
+        matches!(
+            self,
+            Point3D { .. }
+                | Line3D { .. }
+                | Circle3D { .. }
+                | Cube { .. }
+                | CubeWires { .. }
+                | CubeTexture { .. }
+                | Sphere { .. }
+                | SphereWires { .. }
+                | Cylinder { .. }
+                | CylinderWires { .. }
+                | Plane { .. }
+                | Ray { .. }
+                | Grid { .. }
+                | Gizmo { .. }
+                | Model { .. }
+                | ModelWires { .. }
+                | BoundingBox { .. }
+                | Billboard { .. }
+        )
+    }
+
+    /// Issues this command onto `d`, drawing modes included. 3D commands are left out, see `replay_3d`.
+    pub fn replay<D: RaylibDraw>(&self, d: &mut D) {
+        use DrawCommand::*;
+        match self {
+            ClearBackground(color) => d.clear_background(*color),
+            SetShapesTexture { texture, source } => {
+                d.set_shapes_texture(WeakTexture2D(*texture), *source)
+            }
+            Pixel { position, color } => d.draw_pixel_v(*position, *color),
+            Line { start, end, color } => d.draw_line_v(*start, *end, *color),
+            LineEx {
+                start,
+                end,
+                thick,
+                color,
+            } => d.draw_line_ex(*start, *end, *thick, *color),
+            LineBezier {
+                start,
+                end,
+                thick,
+                color,
+            } => d.draw_line_bezier(*start, *end, *thick, *color),
+            LineStrip { points, color } => d.draw_line_strip(points, *color),
+            Circle {
+                center,
+                radius,
+                color,
+            } => d.draw_circle_v(*center, *radius, *color),
+            CircleSector {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+                segments,
+                color,
+            } => d.draw_circle_sector(
+                *center,
+                *radius,
+                *start_angle,
+                *end_angle,
+                *segments,
+                *color,
+            ),
+            CircleSectorLines {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+                segments,
+                color,
+            } => d.draw_circle_sector_lines(
+                *center,
+                *radius,
+                *start_angle,
+                *end_angle,
+                *segments,
+                *color,
+            ),
+            CircleGradient {
+                center_x,
+                center_y,
+                radius,
+                inner,
+                outer,
+            } => d.draw_circle_gradient(*center_x, *center_y, *radius, *inner, *outer),
+            CircleLines {
+                center_x,
+                center_y,
+                radius,
+                color,
+            } => d.draw_circle_lines(*center_x, *center_y, *radius, *color),
+            Ellipse {
+                center_x,
+                center_y,
+                radius_h,
+                radius_v,
+                color,
+            } => d.draw_ellipse(*center_x, *center_y, *radius_h, *radius_v, *color),
+            EllipseLines {
+                center_x,
+                center_y,
+                radius_h,
+                radius_v,
+                color,
+            } => d.draw_ellipse_lines(*center_x, *center_y, *radius_h, *radius_v, *color),
+            Ring {
+                center,
+                inner_radius,
+                outer_radius,
+                start_angle,
+                end_angle,
+                segments,
+                color,
+            } => d.draw_ring(
+                *center,
+                *inner_radius,
+                *outer_radius,
+                *start_angle,
+                *end_angle,
+                *segments,
+                *color,
+            ),
+            RingLines {
+                center,
+                inner_radius,
+                outer_radius,
+                start_angle,
+                end_angle,
+                segments,
+                color,
+            } => d.draw_ring_lines(
+                *center,
+                *inner_radius,
+                *outer_radius,
+                *start_angle,
+                *end_angle,
+                *segments,
+                *color,
+            ),
+            Rectangle { rec, color } => d.draw_rectangle_rec(*rec, *color),
+            RectanglePro {
+                rec,
+                origin,
+                rotation,
+                color,
+            } => d.draw_rectangle_pro(*rec, *origin, *rotation, *color),
+            RectangleGradient { rec, colors } => {
+                d.draw_rectangle_gradient_ex(*rec, colors[0], colors[1], colors[2], colors[3])
+            }
+            RectangleLines {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => d.draw_rectangle_lines(*x, *y, *width, *height, *color),
+            RectangleLinesEx {
+                rec,
+                line_thick,
+                color,
+            } => d.draw_rectangle_lines_ex(*rec, *line_thick, *color),
+            RectangleRounded {
+                rec,
+                roundness,
+                segments,
+                color,
+            } => d.draw_rectangle_rounded(*rec, *roundness, *segments, *color),
+            RectangleRoundedLines {
+                rec,
+                roundness,
+                segments,
+                line_thickness,
+                color,
+            } => {
+                d.draw_rectangle_rounded_lines(*rec, *roundness, *segments, *line_thickness, *color)
+            }
+            Triangle { v1, v2, v3, color } => d.draw_triangle(*v1, *v2, *v3, *color),
+            TriangleLines { v1, v2, v3, color } => d.draw_triangle_lines(*v1, *v2, *v3, *color),
+            TriangleFan { points, color } => d.draw_triangle_fan(points, *color),
+            TriangleStrip { points, color } => d.draw_triangle_strip(points, *color),
+            Poly {
+                center,
+                sides,
+                radius,
+                rotation,
+                color,
+            } => d.draw_poly(*center, *sides, *radius, *rotation, *color),
+            PolyLines {
+                center,
+                sides,
+                radius,
+                rotation,
+                color,
+            } => d.draw_poly_lines(*center, *sides, *radius, *rotation, *color),
+            Texture {
+                texture,
+                source_rec,
+                dest_rec,
+                origin,
+                rotation,
+                tint,
+            } => d.draw_texture_pro(
+                WeakTexture2D(*texture),
+                *source_rec,
+                *dest_rec,
+                *origin,
+                *rotation,
+                *tint,
+            ),
+            TextureQuad {
+                texture,
+                tiling,
+                offset,
+                quad,
+                tint,
+            } => d.draw_texture_quad(WeakTexture2D(*texture), *tiling, *offset, *quad, *tint),
+            TextureNPatch {
+                texture,
+                n_patch_info,
+                dest_rec,
+                origin,
+                rotation,
+                tint,
+            } => d.draw_texture_n_patch(
+                WeakTexture2D(*texture),
+                *n_patch_info,
+                *dest_rec,
+                *origin,
+                *rotation,
+                *tint,
+            ),
+            Fps { x, y } => d.draw_fps(*x, *y),
+            Text {
+                text,
+                x,
+                y,
+                font_size,
+                color,
+            } => d.draw_text(text, *x, *y, *font_size, *color),
+            TextEx {
+                font,
+                text,
+                position,
+                font_size,
+                spacing,
+                tint,
+            } => font.with_raw(|font| {
+                d.draw_text_ex(font, text, *position, *font_size, *spacing, *tint)
+            }),
+            TextRec {
+                font,
+                text,
+                rec,
+                font_size,
+                spacing,
+                word_wrap,
+                tint,
+                select_start,
+                select_length,
+                select_text,
+                select_back,
+            } => font.with_raw(|font| {
+                d.draw_text_rec_ex(
+                    font,
+                    text,
+                    *rec,
+                    *font_size,
+                    *spacing,
+                    *word_wrap,
+                    *tint,
+                    *select_start,
+                    *select_length,
+                    *select_text,
+                    *select_back,
+                )
+            }),
+            TextCodepoint {
+                font,
+                codepoint,
+                position,
+                scale,
+                tint,
+            } => font
+                .with_raw(|font| d.draw_text_codepoint(font, *codepoint, *position, *scale, *tint)),
+            BeginMode2D(camera) => d.begin_mode(DrawMode::Mode2D(*camera)),
+            EndMode2D => d.end_mode(DrawModeKind::Mode2D),
+            BeginMode3D(camera) => d.begin_mode(DrawMode::Mode3D(*camera)),
+            EndMode3D => d.end_mode(DrawModeKind::Mode3D),
+            BeginBlendMode(mode) => d.begin_mode(DrawMode::Blend(*mode)),
+            EndBlendMode => d.end_mode(DrawModeKind::Blend),
+            BeginScissorMode {
+                x,
+                y,
+                width,
+                height,
+            } => d.begin_mode(DrawMode::Scissor {
+                x: *x,
+                y: *y,
+                width: *width,
+                height: *height,
+            }),
+            EndScissorMode => d.end_mode(DrawModeKind::Scissor),
+            _ => {}
+        }
+    }
+
+    /// Issues this command onto `d`, 3D commands included.
+    pub fn replay_3d<D: RaylibDraw + RaylibDraw3D>(&self, d: &mut D) {
+        use DrawCommand::*;
+        match self {
+            Point3D { position, color } => d.draw_point3D(*position, *color),
+            Line3D { start, end, color } => d.draw_line_3D(*start, *end, *color),
+            Circle3D {
+                center,
+                radius,
+                rotation_axis,
+                rotation_angle,
+                color,
+            } => d.draw_circle_3D(*center, *radius, *rotation_axis, *rotation_angle, *color),
+            Cube {
+                position,
+                size,
+                color,
+            } => d.draw_cube_v(*position, *size, *color),
+            CubeWires {
+                position,
+                size,
+                color,
+            } => d.draw_cube_wires(*position, size.x, size.y, size.z, *color),
+            CubeTexture {
+                texture,
+                position,
+                size,
+                color,
+            } => {
+                // the texture isn't ours to unload
+                let texture = ManuallyDrop::new(Texture2D(*texture));
+                d.draw_cube_texture(&texture, *position, size.x, size.y, size.z, *color)
+            }
+            Sphere {
+                center,
+                radius,
+                rings,
+                slices,
+                color,
+            } => d.draw_sphere_ex(*center, *radius, *rings, *slices, *color),
+            SphereWires {
+                center,
+                radius,
+                rings,
+                slices,
+                color,
+            } => d.draw_sphere_wires(*center, *radius, *rings, *slices, *color),
+            Cylinder {
+                position,
+                radius_top,
+                radius_bottom,
+                height,
+                slices,
+                color,
+            } => d.draw_cylinder(
+                *position,
+                *radius_top,
+                *radius_bottom,
+                *height,
+                *slices,
+                *color,
+            ),
+            CylinderWires {
+                position,
+                radius_top,
+                radius_bottom,
+                height,
+                slices,
+                color,
+            } => d.draw_cylinder_wires(
+                *position,
+                *radius_top,
+                *radius_bottom,
+                *height,
+                *slices,
+                *color,
+            ),
+            Plane {
+                center,
+                size,
+                color,
+            } => d.draw_plane(*center, *size, *color),
+            Ray { ray, color } => d.draw_ray(*ray, *color),
+            Grid { slices, spacing } => d.draw_grid(*slices, *spacing),
+            Gizmo { position } => d.draw_gizmo(*position),
+            Model {
+                model,
+                position,
+                rotation_axis,
+                rotation_angle,
+                scale,
+                tint,
+            } => {
+                model.with_raw(|model| {
+                    d.draw_model_ex(
+                        model,
+                        *position,
+                        *rotation_axis,
+                        *rotation_angle,
+                        *scale,
+                        *tint,
+                    )
+                });
+            }
+            ModelWires {
+                model,
+                position,
+                rotation_axis,
+                rotation_angle,
+                scale,
+                tint,
+            } => {
+                model.with_raw(|model| {
+                    d.draw_model_wires_ex(
+                        model,
+                        *position,
+                        *rotation_axis,
+                        *rotation_angle,
+                        *scale,
+                        *tint,
+                    )
+                });
+            }
+            BoundingBox { bbox, color } => d.draw_bounding_box(*bbox, *color),
+            Billboard {
+                camera,
+                texture,
+                source_rec,
+                center,
+                size,
+                tint,
+            } => {
+                let texture = ManuallyDrop::new(Texture2D(*texture));
+                d.draw_billboard_rec(*camera, &texture, *source_rec, *center, *size, *tint)
+            }
+            command => command.replay(d),
+        }
+    }
+}
+
+/// A surface that records draw calls instead of issuing them.
+///
+/// Drawing modes are recorded with the `begin_*` methods of the recorder itself, which shadow the `RaylibMode2DExt`
+/// family of traits. Generic code taking a `D: RaylibDraw` only sees those traits, which enter the mode immediately,
+/// so it should change modes with `RaylibDraw::begin_mode` or `draw_in_mode` instead, which the recorder records.
+#[derive(Debug, Clone, Default)]
+pub struct DrawRecorder {
+    commands: Vec<DrawCommand>,
+}
+
+impl DrawRecorder {
+    pub fn new() -> DrawRecorder {
+        DrawRecorder::default()
+    }
+
+    /// The commands recorded so far, in order.
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// Takes the recorded commands, leaving the recorder empty.
+    pub fn take_commands(&mut self) -> Vec<DrawCommand> {
+        std::mem::take(&mut self.commands)
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Appends a command as if it had been drawn.
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    /// Issues every recorded command onto `d`, in order. 3D commands are left out, see `replay_3d`.
+    pub fn replay<D: RaylibDraw>(&self, d: &mut D) {
+        for command in &self.commands {
+            command.replay(d);
+        }
+    }
+
+    /// Issues every recorded command onto `d`, in order, 3D commands included.
+    pub fn replay_3d<D: RaylibDraw + RaylibDraw3D>(&self, d: &mut D) {
+        for command in &self.commands {
+            command.replay_3d(d);
+        }
+    }
+
+    /// Records entering 2D mode. Leaving is recorded when the returned guard is dropped.
+    #[allow(non_snake_case)]
+    pub fn begin_mode2D(&mut self, camera: impl Into<ffi::Camera2D>) -> RecordedMode<'_> {
+        self.mode(DrawMode::Mode2D(Camera2D::from(camera.into())))
+    }
+
+    /// Records entering 3D mode. Leaving is recorded when the returned guard is dropped.
+    #[allow(non_snake_case)]
+    pub fn begin_mode3D(&mut self, camera: impl Into<ffi::Camera3D>) -> RecordedMode<'_> {
+        self.mode(DrawMode::Mode3D(Camera3D::from(camera.into())))
+    }
+
+    /// Records a change of blend mode, reverted when the returned guard is dropped.
+    pub fn begin_blend_mode(&mut self, blend_mode: BlendMode) -> RecordedMode<'_> {
+        self.mode(DrawMode::Blend(blend_mode))
+    }
+
+    /// Records a scissor area, removed when the returned guard is dropped.
+    pub fn begin_scissor_mode(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> RecordedMode<'_> {
+        self.mode(DrawMode::Scissor {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    fn mode(&mut self, mode: DrawMode) -> RecordedMode<'_> {
+        self.commands.push(DrawCommand::begin(mode));
+        RecordedMode {
+            recorder: self,
+            end: Some(DrawCommand::end(mode.kind())),
+        }
+    }
+}
+
+/// A drawing mode being recorded, see `DrawRecorder::begin_mode2D`.
+pub struct RecordedMode<'a> {
+    recorder: &'a mut DrawRecorder,
+    end: Option<DrawCommand>,
+}
+
+impl<'a> Drop for RecordedMode<'a> {
+    fn drop(&mut self) {
+        if let Some(end) = self.end.take() {
+            self.recorder.commands.push(end);
+        }
+    }
+}
+
+impl<'a> std::ops::Deref for RecordedMode<'a> {
+    type Target = DrawRecorder;
+
+    fn deref(&self) -> &Self::Target {
+        self.recorder
+    }
+}
+
+impl<'a> std::ops::DerefMut for RecordedMode<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.recorder
+    }
+}
+
+impl RaylibDraw for DrawRecorder {
+    fn begin_mode(&mut self, mode: DrawMode) {
+        self.push(DrawCommand::begin(mode));
+    }
+
+    fn end_mode(&mut self, kind: DrawModeKind) {
+        self.push(DrawCommand::end(kind));
+    }
+
+    fn clear_background(&mut self, color: impl Into<ffi::Color>) {
+        self.push(DrawCommand::ClearBackground(color.into().into()));
+    }
+
+    fn set_shapes_texture(
+        &mut self,
+        texture: impl AsRef<ffi::Texture2D>,
+        source: impl Into<ffi::Rectangle>,
+    ) {
+        self.push(DrawCommand::SetShapesTexture {
+            texture: *texture.as_ref(),
+            source: source.into().into(),
+        });
+    }
+
+    fn draw_pixel(&mut self, x: i32, y: i32, color: impl Into<ffi::Color>) {
+        self.draw_pixel_v(Vector2::new(x as f32, y as f32), color);
+    }
+
+    fn draw_pixel_v(&mut self, position: impl Into<ffi::Vector2>, color: impl Into<ffi::Color>) {
+        self.push(DrawCommand::Pixel {
+            position: position.into().into(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_line(
+        &mut self,
+        start_pos_x: i32,
+        start_pos_y: i32,
+        end_pos_x: i32,
+        end_pos_y: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.draw_line_v(
+            Vector2::new(start_pos_x as f32, start_pos_y as f32),
+            Vector2::new(end_pos_x as f32, end_pos_y as f32),
+            color,
+        );
+    }
+
+    fn draw_line_v(
+        &mut self,
+        start_pos: impl Into<ffi::Vector2>,
+        end_pos: impl Into<ffi::Vector2>,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Line {
+            start: start_pos.into().into(),
+            end: end_pos.into().into(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_line_ex(
+        &mut self,
+        start_pos: impl Into<ffi::Vector2>,
+        end_pos: impl Into<ffi::Vector2>,
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::LineEx {
+            start: start_pos.into().into(),
+            end: end_pos.into().into(),
+            thick,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_line_bezier(
+        &mut self,
+        start_pos: impl Into<ffi::Vector2>,
+        end_pos: impl Into<ffi::Vector2>,
+        thick: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::LineBezier {
+            start: start_pos.into().into(),
+            end: end_pos.into().into(),
+            thick,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_line_strip(&mut self, points: &[Vector2], color: impl Into<ffi::Color>) {
+        self.push(DrawCommand::LineStrip {
+            points: points.to_vec(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_circle(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        radius: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.draw_circle_v(
+            Vector2::new(center_x as f32, center_y as f32),
+            radius,
+            color,
+        );
+    }
+
+    fn draw_circle_sector(
+        &mut self,
+        center: impl Into<ffi::Vector2>,
+        radius: f32,
+        start_angle: i32,
+        end_angle: i32,
+        segments: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::CircleSector {
+            center: center.into().into(),
+            radius,
+            start_angle,
+            end_angle,
+            segments,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_circle_sector_lines(
+        &mut self,
+        center: impl Into<ffi::Vector2>,
+        radius: f32,
+        start_angle: i32,
+        end_angle: i32,
+        segments: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::CircleSectorLines {
+            center: center.into().into(),
+            radius,
+            start_angle,
+            end_angle,
+            segments,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_circle_gradient(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        radius: f32,
+        color1: impl Into<ffi::Color>,
+        color2: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::CircleGradient {
+            center_x,
+            center_y,
+            radius,
+            inner: color1.into().into(),
+            outer: color2.into().into(),
+        });
+    }
+
+    fn draw_circle_v(
+        &mut self,
+        center: impl Into<ffi::Vector2>,
+        radius: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Circle {
+            center: center.into().into(),
+            radius,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_circle_lines(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        radius: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::CircleLines {
+            center_x,
+            center_y,
+            radius,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_ellipse(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        radius_h: f32,
+        radius_v: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Ellipse {
+            center_x,
+            center_y,
+            radius_h,
+            radius_v,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_ellipse_lines(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        radius_h: f32,
+        radius_v: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::EllipseLines {
+            center_x,
+            center_y,
+            radius_h,
+            radius_v,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_ring(
+        &mut self,
+        center: impl Into<ffi::Vector2>,
+        inner_radius: f32,
+        outer_radius: f32,
+        start_angle: i32,
+        end_angle: i32,
+        segments: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Ring {
+            center: center.into().into(),
+            inner_radius,
+            outer_radius,
+            start_angle,
+            end_angle,
+            segments,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_ring_lines(
+        &mut self,
+        center: impl Into<ffi::Vector2>,
+        inner_radius: f32,
+        outer_radius: f32,
+        start_angle: i32,
+        end_angle: i32,
+        segments: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::RingLines {
+            center: center.into().into(),
+            inner_radius,
+            outer_radius,
+            start_angle,
+            end_angle,
+            segments,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_rectangle(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.draw_rectangle_rec(
+            Rectangle::new(x as f32, y as f32, width as f32, height as f32),
+            color,
+        );
+    }
+
+    fn draw_rectangle_v(
+        &mut self,
+        position: impl Into<ffi::Vector2>,
+        size: impl Into<ffi::Vector2>,
+        color: impl Into<ffi::Color>,
+    ) {
+        let position = position.into();
+        let size = size.into();
+        self.draw_rectangle_rec(
+            Rectangle::new(position.x, position.y, size.x, size.y),
+            color,
+        );
+    }
+
+    fn draw_rectangle_rec(&mut self, rec: impl Into<ffi::Rectangle>, color: impl Into<ffi::Color>) {
+        self.push(DrawCommand::Rectangle {
+            rec: rec.into().into(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_rectangle_pro(
+        &mut self,
+        rec: impl Into<ffi::Rectangle>,
+        origin: impl Into<ffi::Vector2>,
+        rotation: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::RectanglePro {
+            rec: rec.into().into(),
+            origin: origin.into().into(),
+            rotation,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_rectangle_gradient_v(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        color1: impl Into<ffi::Color>,
+        color2: impl Into<ffi::Color>,
+    ) {
+        let (top, bottom) = (color1.into(), color2.into());
+        self.draw_rectangle_gradient_ex(
+            Rectangle::new(x as f32, y as f32, width as f32, height as f32),
+            top,
+            bottom,
+            bottom,
+            top,
+        );
+    }
+
+    fn draw_rectangle_gradient_h(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        color1: impl Into<ffi::Color>,
+        color2: impl Into<ffi::Color>,
+    ) {
+        let (left, right) = (color1.into(), color2.into());
+        self.draw_rectangle_gradient_ex(
+            Rectangle::new(x as f32, y as f32, width as f32, height as f32),
+            left,
+            left,
+            right,
+            right,
+        );
+    }
+
+    fn draw_rectangle_gradient_ex(
+        &mut self,
+        rec: impl Into<ffi::Rectangle>,
+        col1: impl Into<ffi::Color>,
+        col2: impl Into<ffi::Color>,
+        col3: impl Into<ffi::Color>,
+        col4: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::RectangleGradient {
+            rec: rec.into().into(),
+            colors: [
+                col1.into().into(),
+                col2.into().into(),
+                col3.into().into(),
+                col4.into().into(),
+            ],
+        });
+    }
+
+    fn draw_rectangle_lines(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::RectangleLines {
+            x,
+            y,
+            width,
+            height,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_rectangle_lines_ex(
+        &mut self,
+        rec: impl Into<ffi::Rectangle>,
+        line_thick: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::RectangleLinesEx {
+            rec: rec.into().into(),
+            line_thick,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_rectangle_rounded(
+        &mut self,
+        rec: impl Into<ffi::Rectangle>,
+        roundness: f32,
+        segments: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::RectangleRounded {
+            rec: rec.into().into(),
+            roundness,
+            segments,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_rectangle_rounded_lines(
+        &mut self,
+        rec: impl Into<ffi::Rectangle>,
+        roundness: f32,
+        segments: i32,
+        line_thickness: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::RectangleRoundedLines {
+            rec: rec.into().into(),
+            roundness,
+            segments,
+            line_thickness,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_triangle(
+        &mut self,
+        v1: impl Into<ffi::Vector2>,
+        v2: impl Into<ffi::Vector2>,
+        v3: impl Into<ffi::Vector2>,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Triangle {
+            v1: v1.into().into(),
+            v2: v2.into().into(),
+            v3: v3.into().into(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_triangle_lines(
+        &mut self,
+        v1: impl Into<ffi::Vector2>,
+        v2: impl Into<ffi::Vector2>,
+        v3: impl Into<ffi::Vector2>,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::TriangleLines {
+            v1: v1.into().into(),
+            v2: v2.into().into(),
+            v3: v3.into().into(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_triangle_fan(&mut self, points: &[Vector2], color: impl Into<ffi::Color>) {
+        self.push(DrawCommand::TriangleFan {
+            points: points.to_vec(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_triangle_strip(&mut self, points: &[Vector2], color: impl Into<ffi::Color>) {
+        self.push(DrawCommand::TriangleStrip {
+            points: points.to_vec(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_poly(
+        &mut self,
+        center: impl Into<ffi::Vector2>,
+        sides: i32,
+        radius: f32,
+        rotation: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Poly {
+            center: center.into().into(),
+            sides,
+            radius,
+            rotation,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_poly_lines(
+        &mut self,
+        center: impl Into<ffi::Vector2>,
+        sides: i32,
+        radius: f32,
+        rotation: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::PolyLines {
+            center: center.into().into(),
+            sides,
+            radius,
+            rotation,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_texture(
+        &mut self,
+        texture: impl AsRef<ffi::Texture2D>,
+        x: i32,
+        y: i32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.draw_texture_ex(texture, Vector2::new(x as f32, y as f32), 0.0, 1.0, tint);
+    }
+
+    fn draw_texture_v(
+        &mut self,
+        texture: impl AsRef<ffi::Texture2D>,
+        position: impl Into<ffi::Vector2>,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.draw_texture_ex(texture, position, 0.0, 1.0, tint);
+    }
+
+    fn draw_texture_ex(
+        &mut self,
+        texture: impl AsRef<ffi::Texture2D>,
+        position: impl Into<ffi::Vector2>,
+        rotation: f32,
+        scale: f32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        let texture = *texture.as_ref();
+        let position = position.into();
+        let source_rec = texture_rec(&texture);
+        let dest_rec = Rectangle::new(
+            position.x,
+            position.y,
+            source_rec.width * scale,
+            source_rec.height * scale,
+        );
+        self.push(DrawCommand::Texture {
+            texture,
+            source_rec,
+            dest_rec,
+            origin: Vector2::zero(),
+            rotation,
+            tint: tint.into().into(),
+        });
+    }
+
+    fn draw_texture_rec(
+        &mut self,
+        texture: impl AsRef<ffi::Texture2D>,
+        source_rec: impl Into<ffi::Rectangle>,
+        position: impl Into<ffi::Vector2>,
+        tint: impl Into<ffi::Color>,
+    ) {
+        let source_rec: Rectangle = source_rec.into().into();
+        let position = position.into();
+        let dest_rec = Rectangle::new(
+            position.x,
+            position.y,
+            source_rec.width.abs(),
+            source_rec.height.abs(),
+        );
+        self.draw_texture_pro(texture, source_rec, dest_rec, Vector2::zero(), 0.0, tint);
+    }
+
+    fn draw_texture_quad(
+        &mut self,
+        texture: impl AsRef<ffi::Texture2D>,
+        tiling: impl Into<ffi::Vector2>,
+        offset: impl Into<ffi::Vector2>,
+        quad: impl Into<ffi::Rectangle>,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::TextureQuad {
+            texture: *texture.as_ref(),
+            tiling: tiling.into().into(),
+            offset: offset.into().into(),
+            quad: quad.into().into(),
+            tint: tint.into().into(),
+        });
+    }
+
+    fn draw_texture_pro(
+        &mut self,
+        texture: impl AsRef<ffi::Texture2D>,
+        source_rec: impl Into<ffi::Rectangle>,
+        dest_rec: impl Into<ffi::Rectangle>,
+        origin: impl Into<ffi::Vector2>,
+        rotation: f32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Texture {
+            texture: *texture.as_ref(),
+            source_rec: source_rec.into().into(),
+            dest_rec: dest_rec.into().into(),
+            origin: origin.into().into(),
+            rotation,
+            tint: tint.into().into(),
+        });
+    }
+
+    fn draw_texture_n_patch(
+        &mut self,
+        texture: impl AsRef<ffi::Texture2D>,
+        n_patch_info: impl Into<ffi::NPatchInfo>,
+        dest_rec: impl Into<ffi::Rectangle>,
+        origin: impl Into<ffi::Vector2>,
+        rotation: f32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::TextureNPatch {
+            texture: *texture.as_ref(),
+            n_patch_info: n_patch_info.into().into(),
+            dest_rec: dest_rec.into().into(),
+            origin: origin.into().into(),
+            rotation,
+            tint: tint.into().into(),
+        });
+    }
+
+    fn draw_fps(&mut self, x: i32, y: i32) {
+        self.push(DrawCommand::Fps { x, y });
+    }
+
+    fn draw_text(
+        &mut self,
+        text: &str,
+        x: i32,
+        y: i32,
+        font_size: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Text {
+            text: text.to_owned(),
+            x,
+            y,
+            font_size,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_text_ex(
+        &mut self,
+        font: impl AsRef<ffi::Font>,
+        text: &str,
+        position: impl Into<ffi::Vector2>,
+        font_size: f32,
+        spacing: f32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::TextEx {
+            font: RecordedFont::copy(font.as_ref()),
+            text: text.to_owned(),
+            position: position.into().into(),
+            font_size,
+            spacing,
+            tint: tint.into().into(),
+        });
+    }
+
+    fn draw_text_rec(
+        &mut self,
+        font: impl AsRef<ffi::Font>,
+        text: &str,
+        rec: impl Into<ffi::Rectangle>,
+        font_size: f32,
+        spacing: f32,
+        word_wrap: bool,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.draw_text_rec_ex(
+            font,
+            text,
+            rec,
+            font_size,
+            spacing,
+            word_wrap,
+            tint,
+            0,
+            0,
+            Color::WHITE,
+            Color::WHITE,
+        );
+    }
+
+    fn draw_text_rec_ex(
+        &mut self,
+        font: impl AsRef<ffi::Font>,
+        text: &str,
+        rec: impl Into<ffi::Rectangle>,
+        font_size: f32,
+        spacing: f32,
+        word_wrap: bool,
+        tint: impl Into<ffi::Color>,
+        select_start: i32,
+        select_length: i32,
+        select_text: impl Into<ffi::Color>,
+        select_back: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::TextRec {
+            font: RecordedFont::copy(font.as_ref()),
+            text: text.to_owned(),
+            rec: rec.into().into(),
+            font_size,
+            spacing,
+            word_wrap,
+            tint: tint.into().into(),
+            select_start,
+            select_length,
+            select_text: select_text.into().into(),
+            select_back: select_back.into().into(),
+        });
+    }
+
+    fn draw_text_codepoint(
+        &mut self,
+        font: impl AsRef<ffi::Font>,
+        codepoint: i32,
+        position: impl Into<ffi::Vector2>,
+        scale: f32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::TextCodepoint {
+            font: RecordedFont::copy(font.as_ref()),
+            codepoint,
+            position: position.into().into(),
+            scale,
+            tint: tint.into().into(),
+        });
+    }
+}
+
+impl RaylibDraw3D for DrawRecorder {
+    fn draw_point3D(&mut self, position: impl Into<ffi::Vector3>, color: impl Into<ffi::Color>) {
+        self.push(DrawCommand::Point3D {
+            position: position.into().into(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_line_3D(
+        &mut self,
+        start_pos: impl Into<ffi::Vector3>,
+        end_pos: impl Into<ffi::Vector3>,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Line3D {
+            start: start_pos.into().into(),
+            end: end_pos.into().into(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_circle_3D(
+        &mut self,
+        center: impl Into<ffi::Vector3>,
+        radius: f32,
+        rotation_axis: impl Into<ffi::Vector3>,
+        rotation_angle: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Circle3D {
+            center: center.into().into(),
+            radius,
+            rotation_axis: rotation_axis.into().into(),
+            rotation_angle,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_cube(
+        &mut self,
+        position: impl Into<ffi::Vector3>,
+        width: f32,
+        height: f32,
+        length: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.draw_cube_v(position, Vector3::new(width, height, length), color);
+    }
+
+    fn draw_cube_v(
+        &mut self,
+        position: impl Into<ffi::Vector3>,
+        size: impl Into<ffi::Vector3>,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Cube {
+            position: position.into().into(),
+            size: size.into().into(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_cube_wires(
+        &mut self,
+        position: impl Into<ffi::Vector3>,
+        width: f32,
+        height: f32,
+        length: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::CubeWires {
+            position: position.into().into(),
+            size: Vector3::new(width, height, length),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_cube_texture(
+        &mut self,
+        texture: &Texture2D,
+        position: impl Into<ffi::Vector3>,
+        width: f32,
+        height: f32,
+        length: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::CubeTexture {
+            texture: texture.0,
+            position: position.into().into(),
+            size: Vector3::new(width, height, length),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_sphere(
+        &mut self,
+        center_pos: impl Into<ffi::Vector3>,
+        radius: f32,
+        color: impl Into<ffi::Color>,
+    ) {
+        // raylib's own defaults for DrawSphere
+        self.draw_sphere_ex(center_pos, radius, 16, 16, color);
+    }
+
+    fn draw_sphere_ex(
+        &mut self,
+        center_pos: impl Into<ffi::Vector3>,
+        radius: f32,
+        rings: i32,
+        slices: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Sphere {
+            center: center_pos.into().into(),
+            radius,
+            rings,
+            slices,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_sphere_wires(
+        &mut self,
+        center_pos: impl Into<ffi::Vector3>,
+        radius: f32,
+        rings: i32,
+        slices: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::SphereWires {
+            center: center_pos.into().into(),
+            radius,
+            rings,
+            slices,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_cylinder(
+        &mut self,
+        position: impl Into<ffi::Vector3>,
+        radius_top: f32,
+        radius_bottom: f32,
+        height: f32,
+        slices: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Cylinder {
+            position: position.into().into(),
+            radius_top,
+            radius_bottom,
+            height,
+            slices,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_cylinder_wires(
+        &mut self,
+        position: impl Into<ffi::Vector3>,
+        radius_top: f32,
+        radius_bottom: f32,
+        height: f32,
+        slices: i32,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::CylinderWires {
+            position: position.into().into(),
+            radius_top,
+            radius_bottom,
+            height,
+            slices,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_plane(
+        &mut self,
+        center_pos: impl Into<ffi::Vector3>,
+        size: impl Into<ffi::Vector2>,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Plane {
+            center: center_pos.into().into(),
+            size: size.into().into(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_ray(&mut self, ray: Ray, color: impl Into<ffi::Color>) {
+        self.push(DrawCommand::Ray {
+            ray,
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_grid(&mut self, slices: i32, spacing: f32) {
+        self.push(DrawCommand::Grid { slices, spacing });
+    }
+
+    fn draw_gizmo(&mut self, position: impl Into<ffi::Vector3>) {
+        self.push(DrawCommand::Gizmo {
+            position: position.into().into(),
+        });
+    }
+
+    fn draw_model(
+        &mut self,
+        model: impl AsRef<ffi::Model>,
+        position: impl Into<ffi::Vector3>,
+        scale: f32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.draw_model_ex(
+            model,
+            position,
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            Vector3::new(scale, scale, scale),
+            tint,
+        );
+    }
+
+    fn draw_model_ex(
+        &mut self,
+        model: impl AsRef<ffi::Model>,
+        position: impl Into<ffi::Vector3>,
+        rotation_axis: impl Into<ffi::Vector3>,
+        rotation_angle: f32,
+        scale: impl Into<ffi::Vector3>,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Model {
+            model: RecordedModel::copy(model.as_ref()),
+            position: position.into().into(),
+            rotation_axis: rotation_axis.into().into(),
+            rotation_angle,
+            scale: scale.into().into(),
+            tint: tint.into().into(),
+        });
+    }
+
+    fn draw_model_wires(
+        &mut self,
+        model: impl AsRef<ffi::Model>,
+        position: impl Into<ffi::Vector3>,
+        scale: f32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.draw_model_wires_ex(
+            model,
+            position,
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            Vector3::new(scale, scale, scale),
+            tint,
+        );
+    }
+
+    fn draw_model_wires_ex(
+        &mut self,
+        model: impl AsRef<ffi::Model>,
+        position: impl Into<ffi::Vector3>,
+        rotation_axis: impl Into<ffi::Vector3>,
+        rotation_angle: f32,
+        scale: impl Into<ffi::Vector3>,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::ModelWires {
+            model: RecordedModel::copy(model.as_ref()),
+            position: position.into().into(),
+            rotation_axis: rotation_axis.into().into(),
+            rotation_angle,
+            scale: scale.into().into(),
+            tint: tint.into().into(),
+        });
+    }
+
+    fn draw_bounding_box(
+        &mut self,
+        bbox: impl Into<ffi::BoundingBox>,
+        color: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::BoundingBox {
+            bbox: bbox.into().into(),
+            color: color.into().into(),
+        });
+    }
+
+    fn draw_billboard(
+        &mut self,
+        camera: impl Into<ffi::Camera3D>,
+        texture: &Texture2D,
+        center: impl Into<ffi::Vector3>,
+        size: f32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Billboard {
+            camera: camera.into().into(),
+            texture: texture.0,
+            source_rec: texture_rec(&texture.0),
+            center: center.into().into(),
+            size,
+            tint: tint.into().into(),
+        });
+    }
+
+    fn draw_billboard_rec(
+        &mut self,
+        camera: Camera3D,
+        texture: &Texture2D,
+        source_rec: impl Into<ffi::Rectangle>,
+        center: impl Into<ffi::Vector3>,
+        size: f32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        self.push(DrawCommand::Billboard {
+            camera,
+            texture: texture.0,
+            source_rec: source_rec.into().into(),
+            center: center.into().into(),
+            size,
+            tint: tint.into().into(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod recorder_test {
+    use super::*;
+
+    #[test]
+    fn test_record_commands() {
+        let mut recorder = DrawRecorder::new();
+        recorder.clear_background(Color::WHITE);
+        {
+            let mut camera = recorder.begin_mode2D(Camera2D {
+                zoom: 2.0,
+                ..Default::default()
+            });
+            camera.draw_rectangle(1, 2, 3, 4, Color::RED);
+            let mut scissor = camera.begin_scissor_mode(0, 0, 10, 10);
+            scissor.draw_text("hi", 0, 0, 10, Color::BLACK);
+        }
+        recorder.draw_grid(10, 1.0);
+
+        let commands = recorder.take_commands();
+        assert!(recorder.is_empty());
+        assert_eq!(commands.len(), 8);
+        assert!(matches!(
+            commands[0],
+            DrawCommand::ClearBackground(Color::WHITE)
+        ));
+        assert!(matches!(commands[1], DrawCommand::BeginMode2D(c) if c.zoom == 2.0));
+        match &commands[2] {
+            DrawCommand::Rectangle { rec, color } => {
+                assert_eq!(*rec, Rectangle::new(1.0, 2.0, 3.0, 4.0));
+                assert_eq!(*color, Color::RED);
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        assert!(matches!(
+            commands[3],
+            DrawCommand::BeginScissorMode { width: 10, .. }
+        ));
+        assert!(matches!(&commands[4], DrawCommand::Text { text, .. } if text == "hi"));
+        assert!(matches!(commands[5], DrawCommand::EndScissorMode));
+        assert!(matches!(commands[6], DrawCommand::EndMode2D));
+        assert!(matches!(commands[7], DrawCommand::Grid { slices: 10, .. }));
+    }
+
+    fn draw_generic<D: RaylibDraw>(d: &mut D) {
+        d.draw_in_mode(DrawMode::Blend(BlendMode::BLEND_ADDITIVE), |d| {
+            d.draw_pixel(1, 1, Color::RED)
+        });
+    }
+
+    #[test]
+    fn test_replay_through_surface() {
+        fn assert_send<T: Send>() {}
+        assert_send::<DrawCommand>();
+
+        let mut recorder = DrawRecorder::new();
+        draw_generic(&mut recorder);
+        {
+            let mut camera = recorder.begin_mode3D(Camera3D::perspective(
+                Vector3::new(0.0, 1.0, 0.0),
+                Vector3::zero(),
+                Vector3::up(),
+                45.0,
+            ));
+            camera.draw_grid(10, 1.0);
+        }
+        assert_eq!(recorder.len(), 6);
+        assert!(matches!(
+            recorder.commands()[0],
+            DrawCommand::BeginBlendMode(BlendMode::BLEND_ADDITIVE)
+        ));
+        assert!(matches!(recorder.commands()[2], DrawCommand::EndBlendMode));
+
+        // Modes go through the target surface, 3D commands only with replay_3d
+        let mut copy = DrawRecorder::new();
+        recorder.replay(&mut copy);
+        assert_eq!(copy.len(), 5);
+        assert!(!copy.commands().iter().any(DrawCommand::is_3d));
+        copy.clear();
+        recorder.replay_3d(&mut copy);
+        assert_eq!(copy.len(), 6);
+        assert!(matches!(
+            copy.commands()[4],
+            DrawCommand::Grid { slices: 10, .. }
+        ));
+    }
+
+    #[test]
+    fn test_fonts_and_models_are_copied() {
+        let mut recorder = DrawRecorder::new();
+        {
+            let mut recs = vec![ffi::Rectangle {
+                x: 1.0,
+                y: 2.0,
+                width: 3.0,
+                height: 4.0,
+            }];
+            let mut chars = vec![ffi::CharInfo {
+                value: 65,
+                offsetX: 1,
+                offsetY: 2,
+                advanceX: 5,
+                image: unsafe { std::mem::zeroed() },
+            }];
+            let font = ffi::Font {
+                baseSize: 10,
+                charsCount: 1,
+                texture: unsafe { std::mem::zeroed() },
+                recs: recs.as_mut_ptr(),
+                chars: chars.as_mut_ptr(),
+            };
+            recorder.draw_text_ex(
+                WeakFont(font),
+                "A",
+                Vector2::zero(),
+                10.0,
+                1.0,
+                Color::BLACK,
+            );
+
+            let mut vbo_id = [1, 2, 3, 4, 5, 6, 7];
+            let mut mesh: ffi::Mesh = unsafe { std::mem::zeroed() };
+            mesh.vertexCount = 3;
+            mesh.vboId = vbo_id.as_mut_ptr();
+            let mut locs = vec![-1; ffi::MAX_SHADER_LOCATIONS as usize];
+            let mut maps: Vec<ffi::MaterialMap> =
+                vec![unsafe { std::mem::zeroed() }; ffi::MAX_MATERIAL_MAPS as usize];
+            let mut material = ffi::Material {
+                shader: ffi::Shader {
+                    id: 9,
+                    locs: locs.as_mut_ptr(),
+                },
+                maps: maps.as_mut_ptr(),
+                params: std::ptr::null_mut(),
+            };
+            let mut mesh_material = 0;
+            let mut model: ffi::Model = unsafe { std::mem::zeroed() };
+            model.meshCount = 1;
+            model.meshes = &mut mesh;
+            model.materialCount = 1;
+            model.materials = &mut material;
+            model.meshMaterial = &mut mesh_material;
+            recorder.draw_model(WeakModel(model), Vector3::zero(), 1.0, Color::WHITE);
+        }
+
+        // the font and model data are gone, the recording replays from its copies
+        let mut copy = DrawRecorder::new();
+        recorder.replay_3d(&mut copy);
+        match &copy.commands()[0] {
+            DrawCommand::TextEx { font, .. } => {
+                assert_eq!(font.base_size, 10);
+                assert_eq!(font.recs[0].width, 3.0);
+                assert_eq!((font.glyphs[0].value, font.glyphs[0].advance_x), (65, 5));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match &copy.commands()[1] {
+            DrawCommand::Model { model, .. } => {
+                assert_eq!(model.meshes[0].vbo_id, [1, 2, 3, 4, 5, 6, 7]);
+                assert!(!model.meshes[0].indexed);
+                assert_eq!(model.materials[0].shader_id, 9);
+                assert_eq!(model.materials[0].maps.len(), 12);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
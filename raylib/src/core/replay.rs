@@ -0,0 +1,704 @@
+//! Deterministic input recording and playback
+//!
+//! While recording, the keyboard, mouse and gamepad state as well as the frame time are captured once per frame,
+//! right before the frame ends. While replaying, the `RaylibHandle` input queries (`is_key_pressed`,
+//! `get_mouse_position`, `get_frame_time`...) answer from the recorded frames instead of the real devices, so a game
+//! can be driven without a user in front of it.
+//!
+//! Recording and replay should be started at the same point of the game loop, typically right before the first frame.
+//! A game drawing random numbers should also store its seed with `InputRecording::set_seed` and reseed from
+//! `InputRecording::seed` before replaying.
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//!
+//! fn main() -> Result<(), String> {
+//!     let (mut rl, thread) = raylib::init().build();
+//!     rl.start_input_replay(InputRecording::load("bug_report.rlir")?);
+//!     while !rl.window_should_close() && !rl.is_input_replay_finished() {
+//!         let mut d = rl.begin_drawing(&thread);
+//!         d.clear_background(Color::WHITE);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+use crate::consts::{GamepadAxis, GamepadButton, GamepadNumber, KeyboardKey, MouseButton};
+use crate::core::input::key_from_i32;
+use crate::core::math::Vector2;
+use crate::core::RaylibHandle;
+use crate::ffi;
+use lazy_static::lazy_static;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+const REPLAY_MAGIC: &[u8; 4] = b"RLIR";
+const REPLAY_VERSION: u16 = 2;
+/// Number of gamepads raylib tracks.
+const MAX_GAMEPADS: usize = 4;
+/// Highest `KeyboardKey` value.
+const MAX_KEY: i32 = 348;
+/// Highest `GamepadButton` value.
+const MAX_GAMEPAD_BUTTON: u32 = 17;
+
+static IS_RECORDING: AtomicBool = AtomicBool::new(false);
+static IS_REPLAYING: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref REPLAY_STATE: Mutex<ReplayState> = Mutex::new(ReplayState::default());
+}
+
+#[derive(Default)]
+struct ReplayState {
+    recording: Vec<InputFrame>,
+    /// Characters handed out by `get_key_pressed` during the frame being recorded.
+    chars_pressed: Vec<u32>,
+    player: Option<ReplayPlayer>,
+}
+
+struct ReplayPlayer {
+    recording: InputRecording,
+    cursor: usize,
+    blank: InputFrame,
+    pending_chars: VecDeque<u32>,
+    pending_buttons: VecDeque<GamepadButton>,
+}
+
+impl ReplayPlayer {
+    fn new(recording: InputRecording) -> Self {
+        let mut player = ReplayPlayer {
+            recording,
+            cursor: 0,
+            blank: InputFrame::default(),
+            pending_chars: VecDeque::new(),
+            pending_buttons: VecDeque::new(),
+        };
+        player.queue_presses();
+        player
+    }
+
+    /// Every frame has been served once the cursor moves past the last one.
+    fn is_finished(&self) -> bool {
+        self.cursor >= self.recording.frames.len()
+    }
+
+    /// Current frame, holding the last one once the recording is exhausted.
+    fn current(&self) -> &InputFrame {
+        self.recording
+            .frames
+            .get(self.cursor)
+            .or_else(|| self.recording.frames.last())
+            .unwrap_or(&self.blank)
+    }
+
+    fn previous(&self) -> &InputFrame {
+        if self.cursor == 0 {
+            &self.blank
+        } else if self.is_finished() {
+            // Nothing is pressed or released while the last frame is held
+            self.current()
+        } else {
+            &self.recording.frames[self.cursor - 1]
+        }
+    }
+
+    fn advance(&mut self) {
+        if !self.is_finished() {
+            self.cursor += 1;
+            self.queue_presses();
+        }
+    }
+
+    /// Fills the `get_key_pressed` / `get_gamepad_button_pressed` queues for the current frame.
+    fn queue_presses(&mut self) {
+        let (cur, prev) = (self.current(), self.previous());
+        let chars = if std::ptr::eq(cur, prev) {
+            Vec::new()
+        } else {
+            cur.chars_pressed.clone()
+        };
+        let mut buttons = Vec::new();
+        for (gamepad, state) in cur.gamepads.iter().enumerate() {
+            let (now, before) = match state {
+                Some(state) => (
+                    state.buttons,
+                    prev.gamepads[gamepad].as_ref().map_or(0, |g| g.buttons),
+                ),
+                None => continue,
+            };
+            for button in 1..=MAX_GAMEPAD_BUTTON {
+                if now & !before & (1 << button) != 0 {
+                    buttons.push(unsafe { std::mem::transmute::<u32, GamepadButton>(button) });
+                }
+            }
+        }
+        self.pending_chars = chars.into();
+        self.pending_buttons = buttons.into();
+    }
+}
+
+/// Input state of a single gamepad during one frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GamepadFrame {
+    /// Bit `n` is set when the `GamepadButton` with value `n` is held down.
+    pub buttons: u32,
+    /// Axis movement values, indexed by `GamepadAxis` value.
+    pub axes: Vec<f32>,
+}
+
+/// Input state captured during one frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputFrame {
+    /// Value returned by `get_frame_time` during this frame.
+    pub frame_time: f32,
+    pub mouse_position: Vector2,
+    pub mouse_wheel_move: i32,
+    /// Bit `n` is set when the `MouseButton` with value `n` is held down.
+    pub mouse_buttons: u8,
+    /// `KeyboardKey` values held down during this frame.
+    pub keys_down: Vec<u16>,
+    /// Values returned by `get_key_pressed_number` during this frame, in order.
+    pub chars_pressed: Vec<u32>,
+    /// State of every gamepad, `None` when the gamepad is not available.
+    pub gamepads: [Option<GamepadFrame>; MAX_GAMEPADS],
+}
+
+impl InputFrame {
+    /// Captures the live device state. `chars_pressed` are the characters the game already took from the queue.
+    fn capture(chars_pressed: Vec<u32>) -> Self {
+        let mut frame = InputFrame {
+            chars_pressed,
+            ..Default::default()
+        };
+        unsafe {
+            // The rest of the queue is dropped by raylib when the frame ends
+            loop {
+                let key = ffi::GetKeyPressed();
+                if key <= 0 {
+                    break;
+                }
+                frame.chars_pressed.push(key as u32);
+            }
+            frame.frame_time = ffi::GetFrameTime();
+            frame.mouse_position = ffi::GetMousePosition().into();
+            frame.mouse_wheel_move = ffi::GetMouseWheelMove();
+            for button in 0..3 {
+                if ffi::IsMouseButtonDown(button) {
+                    frame.mouse_buttons |= 1 << button;
+                }
+            }
+            for key in 0..=MAX_KEY {
+                if key_from_i32(key).is_some() && ffi::IsKeyDown(key) {
+                    frame.keys_down.push(key as u16);
+                }
+            }
+            for (gamepad, slot) in frame.gamepads.iter_mut().enumerate() {
+                let gamepad = gamepad as i32;
+                if !ffi::IsGamepadAvailable(gamepad) {
+                    continue;
+                }
+                let mut state = GamepadFrame::default();
+                for button in 1..=MAX_GAMEPAD_BUTTON {
+                    if ffi::IsGamepadButtonDown(gamepad, button as i32) {
+                        state.buttons |= 1 << button;
+                    }
+                }
+                let axis_count = ffi::GetGamepadAxisCount(gamepad).max(0);
+                state.axes = (0..axis_count)
+                    .map(|axis| ffi::GetGamepadAxisMovement(gamepad, axis))
+                    .collect();
+                *slot = Some(state);
+            }
+        }
+        frame
+    }
+
+    /// Checks whether `key` is held down during this frame.
+    pub fn is_key_down(&self, key: KeyboardKey) -> bool {
+        self.keys_down.contains(&(key as u16))
+    }
+
+    /// Checks whether `button` is held down during this frame.
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.mouse_buttons & (1 << button as u32) != 0
+    }
+
+    /// Checks whether `gamepad` is connected during this frame.
+    pub fn is_gamepad_available(&self, gamepad: GamepadNumber) -> bool {
+        self.gamepads[gamepad as usize].is_some()
+    }
+
+    /// Checks whether `button` of `gamepad` is held down during this frame.
+    pub fn is_gamepad_button_down(&self, gamepad: GamepadNumber, button: GamepadButton) -> bool {
+        let buttons = self.gamepads[gamepad as usize]
+            .as_ref()
+            .map_or(0, |g| g.buttons);
+        buttons & (1 << button as u32) != 0
+    }
+
+    /// Returns the movement of `axis` of `gamepad` during this frame.
+    pub fn gamepad_axis_movement(&self, gamepad: GamepadNumber, axis: GamepadAxis) -> f32 {
+        self.gamepads[gamepad as usize]
+            .as_ref()
+            .and_then(|g| g.axes.get(axis as usize).copied())
+            .unwrap_or(0.0)
+    }
+
+    fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(&self.frame_time.to_le_bytes())?;
+        w.write_all(&self.mouse_position.x.to_le_bytes())?;
+        w.write_all(&self.mouse_position.y.to_le_bytes())?;
+        w.write_all(&self.mouse_wheel_move.to_le_bytes())?;
+        w.write_all(&[self.mouse_buttons])?;
+        write_len(w, self.keys_down.len())?;
+        for key in &self.keys_down {
+            w.write_all(&key.to_le_bytes())?;
+        }
+        write_len(w, self.chars_pressed.len())?;
+        for c in &self.chars_pressed {
+            w.write_all(&c.to_le_bytes())?;
+        }
+        let mut mask = 0u8;
+        for (i, g) in self.gamepads.iter().enumerate() {
+            if g.is_some() {
+                mask |= 1 << i;
+            }
+        }
+        w.write_all(&[mask])?;
+        for g in self.gamepads.iter().flatten() {
+            w.write_all(&g.buttons.to_le_bytes())?;
+            write_len(w, g.axes.len())?;
+            for axis in &g.axes {
+                w.write_all(&axis.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_from(r: &mut impl Read) -> io::Result<Self> {
+        let mut frame = InputFrame {
+            frame_time: read_f32(r)?,
+            mouse_position: Vector2::new(read_f32(r)?, read_f32(r)?),
+            mouse_wheel_move: read_u32(r)? as i32,
+            mouse_buttons: read_u8(r)?,
+            ..Default::default()
+        };
+        let key_count = read_u16(r)?;
+        for _ in 0..key_count {
+            frame.keys_down.push(read_u16(r)?);
+        }
+        let char_count = read_u16(r)?;
+        for _ in 0..char_count {
+            frame.chars_pressed.push(read_u32(r)?);
+        }
+        let mask = read_u8(r)?;
+        for (i, slot) in frame.gamepads.iter_mut().enumerate() {
+            if mask & (1 << i) == 0 {
+                continue;
+            }
+            let buttons = read_u32(r)?;
+            let axis_count = read_u16(r)?;
+            let mut axes = Vec::with_capacity(axis_count as usize);
+            for _ in 0..axis_count {
+                axes.push(read_f32(r)?);
+            }
+            *slot = Some(GamepadFrame { buttons, axes });
+        }
+        Ok(frame)
+    }
+}
+
+/// Writes a list length, refusing lists too long for the format.
+fn write_len(w: &mut impl Write, len: usize) -> io::Result<()> {
+    if len > u16::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} entries in a single input frame", len),
+        ));
+    }
+    w.write_all(&(len as u16).to_le_bytes())
+}
+
+fn read_u8(r: &mut impl Read) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16(r: &mut impl Read) -> io::Result<u16> {
+    let mut buf = [0u8; 2];
+    r.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_f32(r: &mut impl Read) -> io::Result<f32> {
+    read_u32(r).map(f32::from_bits)
+}
+
+/// A sequence of recorded input frames.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputRecording {
+    frames: Vec<InputFrame>,
+    seed: Option<u64>,
+}
+
+impl InputRecording {
+    /// Creates a recording from already captured frames.
+    pub fn from_frames(frames: Vec<InputFrame>) -> Self {
+        InputRecording { frames, seed: None }
+    }
+
+    /// Random seed the game was recorded with, if one was stored.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Stores the random seed the game was recorded with, so playback can restore it.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    /// Recorded frames, in playback order.
+    pub fn frames(&self) -> &[InputFrame] {
+        &self.frames
+    }
+
+    /// Number of recorded frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Writes the recording in its compact binary form.
+    pub fn write_to(&self, mut w: impl Write) -> io::Result<()> {
+        w.write_all(REPLAY_MAGIC)?;
+        w.write_all(&REPLAY_VERSION.to_le_bytes())?;
+        match self.seed {
+            Some(seed) => {
+                w.write_all(&[1])?;
+                w.write_all(&seed.to_le_bytes())?;
+            }
+            None => w.write_all(&[0])?,
+        }
+        w.write_all(&(self.frames.len() as u32).to_le_bytes())?;
+        for frame in &self.frames {
+            frame.write_to(&mut w)?;
+        }
+        w.flush()
+    }
+
+    /// Reads a recording previously written with `write_to`.
+    pub fn read_from(mut r: impl Read) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != REPLAY_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an input recording",
+            ));
+        }
+        let version = read_u16(&mut r)?;
+        if version != REPLAY_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported input recording version {}", version),
+            ));
+        }
+        let seed = match read_u8(&mut r)? {
+            0 => None,
+            _ => Some(read_u64(&mut r)?),
+        };
+        let count = read_u32(&mut r)?;
+        let mut frames = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            frames.push(InputFrame::read_from(&mut r)?);
+        }
+        Ok(InputRecording { frames, seed })
+    }
+
+    /// Saves the recording to a file.
+    pub fn save(&self, filename: &str) -> Result<(), String> {
+        let file = File::create(filename)
+            .map_err(|e| format!("Cannot create input recording {}: {}", filename, e))?;
+        self.write_to(BufWriter::new(file))
+            .map_err(|e| format!("Cannot write input recording {}: {}", filename, e))
+    }
+
+    /// Loads a recording from a file.
+    pub fn load(filename: &str) -> Result<InputRecording, String> {
+        let file = File::open(filename)
+            .map_err(|e| format!("Cannot open input recording {}: {}", filename, e))?;
+        Self::read_from(BufReader::new(file))
+            .map_err(|e| format!("Cannot read input recording {}: {}", filename, e))
+    }
+}
+
+impl RaylibHandle {
+    /// Starts capturing input, one frame each time a `RaylibDrawHandle` is dropped.
+    pub fn start_input_recording(&mut self) {
+        let mut state = REPLAY_STATE.lock().unwrap();
+        state.recording.clear();
+        state.chars_pressed.clear();
+        IS_RECORDING.store(true, Ordering::Relaxed);
+    }
+
+    /// Stops capturing input and returns what was recorded, or `None` if nothing was being recorded.
+    pub fn stop_input_recording(&mut self) -> Option<InputRecording> {
+        if !IS_RECORDING.swap(false, Ordering::Relaxed) {
+            return None;
+        }
+        let frames = std::mem::take(&mut REPLAY_STATE.lock().unwrap().recording);
+        Some(InputRecording::from_frames(frames))
+    }
+
+    /// Checks if input is being recorded.
+    pub fn is_recording_input(&self) -> bool {
+        IS_RECORDING.load(Ordering::Relaxed)
+    }
+
+    /// Replaces the live input with `recording`, starting with its first frame.
+    pub fn start_input_replay(&mut self, recording: InputRecording) {
+        REPLAY_STATE.lock().unwrap().player = Some(ReplayPlayer::new(recording));
+        IS_REPLAYING.store(true, Ordering::Relaxed);
+    }
+
+    /// Goes back to live input and returns the recording that was being replayed.
+    pub fn stop_input_replay(&mut self) -> Option<InputRecording> {
+        IS_REPLAYING.store(false, Ordering::Relaxed);
+        REPLAY_STATE
+            .lock()
+            .unwrap()
+            .player
+            .take()
+            .map(|p| p.recording)
+    }
+
+    /// Checks if input is being replayed.
+    pub fn is_replaying_input(&self) -> bool {
+        IS_REPLAYING.load(Ordering::Relaxed)
+    }
+
+    /// Checks if every recorded frame has been played back. The last frame is then held until the replay is stopped.
+    pub fn is_input_replay_finished(&self) -> bool {
+        REPLAY_STATE
+            .lock()
+            .unwrap()
+            .player
+            .as_ref()
+            .map(|p| p.is_finished())
+            .unwrap_or(true)
+    }
+}
+
+/// Answers an input query from the replayed frame and the frame before it, or returns `None` when not replaying.
+pub(crate) fn replayed<R>(query: impl FnOnce(&InputFrame, &InputFrame) -> R) -> Option<R> {
+    if !IS_REPLAYING.load(Ordering::Relaxed) {
+        return None;
+    }
+    let state = REPLAY_STATE.lock().unwrap();
+    state
+        .player
+        .as_ref()
+        .map(|p| query(p.current(), p.previous()))
+}
+
+/// Pops the next value `get_key_pressed` returned during the replayed frame.
+pub(crate) fn replayed_key_pressed() -> Option<Option<u32>> {
+    if !IS_REPLAYING.load(Ordering::Relaxed) {
+        return None;
+    }
+    let mut state = REPLAY_STATE.lock().unwrap();
+    state.player.as_mut().map(|p| p.pending_chars.pop_front())
+}
+
+/// Remembers a value `get_key_pressed` handed out live, so it ends up in the recorded frame.
+pub(crate) fn record_key_pressed(key: u32) {
+    if IS_RECORDING.load(Ordering::Relaxed) {
+        REPLAY_STATE.lock().unwrap().chars_pressed.push(key);
+    }
+}
+
+/// Pops the next gamepad button pressed during the replayed frame.
+pub(crate) fn replayed_gamepad_button_pressed() -> Option<Option<GamepadButton>> {
+    if !IS_REPLAYING.load(Ordering::Relaxed) {
+        return None;
+    }
+    let mut state = REPLAY_STATE.lock().unwrap();
+    state.player.as_mut().map(|p| p.pending_buttons.pop_front())
+}
+
+/// Called right before `EndDrawing` so the captured state is the one the frame observed.
+pub(crate) fn end_frame() {
+    let recording = IS_RECORDING.load(Ordering::Relaxed);
+    let replaying = IS_REPLAYING.load(Ordering::Relaxed);
+    if !recording && !replaying {
+        return;
+    }
+    let mut state = REPLAY_STATE.lock().unwrap();
+    if recording {
+        let chars_pressed = std::mem::take(&mut state.chars_pressed);
+        let frame = match &state.player {
+            Some(p) if replaying => p.current().clone(),
+            _ => InputFrame::capture(chars_pressed),
+        };
+        state.recording.push(frame);
+    }
+    if replaying {
+        if let Some(p) = state.player.as_mut() {
+            p.advance();
+        }
+    }
+}
+
+#[cfg(test)]
+mod replay_test {
+    use super::*;
+
+    #[test]
+    fn test_recording_roundtrip() {
+        let mut frame = InputFrame {
+            frame_time: 1.0 / 60.0,
+            mouse_position: Vector2::new(12.5, 40.0),
+            mouse_wheel_move: -1,
+            mouse_buttons: 0b101,
+            keys_down: vec![KeyboardKey::KEY_A as u16, KeyboardKey::KEY_SPACE as u16],
+            chars_pressed: vec!['a' as u32, ' ' as u32],
+            ..Default::default()
+        };
+        frame.gamepads[1] = Some(GamepadFrame {
+            buttons: 1 << GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN as u32,
+            axes: vec![0.0, 0.5, -0.25],
+        });
+        let mut recording = InputRecording::from_frames(vec![InputFrame::default(), frame]);
+        recording.set_seed(Some(0xDEAD_BEEF_0042));
+
+        let mut bytes = Vec::new();
+        recording.write_to(&mut bytes).unwrap();
+        let read = InputRecording::read_from(&bytes[..]).unwrap();
+        assert_eq!(read, recording, "recording changed after a roundtrip");
+        assert!(read.frames()[1].is_key_down(KeyboardKey::KEY_SPACE));
+        assert_eq!(
+            read.frames()[1].gamepad_axis_movement(
+                GamepadNumber::GAMEPAD_PLAYER2,
+                GamepadAxis::GAMEPAD_AXIS_LEFT_Y
+            ),
+            -0.25
+        );
+        assert_eq!(read.seed(), Some(0xDEAD_BEEF_0042));
+        assert!(InputRecording::read_from(&b"nope"[..]).is_err());
+    }
+
+    #[test]
+    fn test_oversized_frame_rejected() {
+        let frame = InputFrame {
+            chars_pressed: vec![0; u16::MAX as usize + 1],
+            ..Default::default()
+        };
+        let recording = InputRecording::from_frames(vec![frame]);
+        let err = recording.write_to(Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    fn keys(keys: &[KeyboardKey]) -> InputFrame {
+        InputFrame {
+            keys_down: keys.iter().map(|&k| k as u16).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn pressed(player: &ReplayPlayer, key: KeyboardKey) -> bool {
+        player.current().is_key_down(key) && !player.previous().is_key_down(key)
+    }
+
+    fn released(player: &ReplayPlayer, key: KeyboardKey) -> bool {
+        !player.current().is_key_down(key) && player.previous().is_key_down(key)
+    }
+
+    #[test]
+    fn test_playback_edges() {
+        use KeyboardKey::{KEY_A, KEY_B};
+        let mut player = ReplayPlayer::new(InputRecording::from_frames(vec![
+            keys(&[KEY_A]),
+            keys(&[KEY_A, KEY_B]),
+            keys(&[KEY_B]),
+        ]));
+
+        // The first frame is compared against nothing held
+        assert!(pressed(&player, KEY_A));
+        assert!(!released(&player, KEY_B));
+
+        player.advance();
+        assert!(!pressed(&player, KEY_A), "held key pressed again");
+        assert!(pressed(&player, KEY_B));
+
+        player.advance();
+        assert!(released(&player, KEY_A));
+        assert!(!pressed(&player, KEY_B));
+        assert!(!player.is_finished());
+
+        // The last frame is held without further edges
+        player.advance();
+        assert!(player.is_finished());
+        assert!(player.current().is_key_down(KEY_B));
+        assert!(!pressed(&player, KEY_B));
+        assert!(!released(&player, KEY_A));
+        player.advance();
+        assert!(player.current().is_key_down(KEY_B));
+    }
+
+    #[test]
+    fn test_playback_queues() {
+        let mut pad = InputFrame::default();
+        pad.gamepads[0] = Some(GamepadFrame {
+            buttons: 1 << GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN as u32,
+            axes: Vec::new(),
+        });
+        let typed = InputFrame {
+            chars_pressed: vec!['a' as u32, 'B' as u32],
+            ..pad.clone()
+        };
+        let mut player = ReplayPlayer::new(InputRecording::from_frames(vec![typed, pad]));
+
+        // Characters come back as typed, not as key codes
+        assert_eq!(player.pending_chars.pop_front(), Some('a' as u32));
+        assert_eq!(player.pending_chars.pop_front(), Some('B' as u32));
+        assert_eq!(player.pending_chars.pop_front(), None);
+        assert_eq!(
+            player.pending_buttons.pop_front(),
+            Some(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)
+        );
+
+        // Still held, so not pressed again
+        player.advance();
+        assert!(player.pending_chars.is_empty());
+        assert!(player.pending_buttons.is_empty());
+
+        // Unread characters are not served again while the last frame is held
+        let typed = InputFrame {
+            chars_pressed: vec!['z' as u32],
+            ..Default::default()
+        };
+        let mut player = ReplayPlayer::new(InputRecording::from_frames(vec![typed]));
+        assert_eq!(player.pending_chars.len(), 1);
+        player.advance();
+        player.advance();
+        assert!(player.pending_chars.is_empty());
+    }
+}
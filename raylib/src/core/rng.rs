@@ -0,0 +1,62 @@
+//! Small random number generator for the modules that replay from a seed, such as particles and sound effects.
+
+/// Xorshift generator. The same seed always gives the same numbers, on every platform.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        // splitmix64 spreads nearby seeds apart. The one seed it maps to zero is moved off it, as xorshift gets stuck
+        // on zero
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Rng((z ^ (z >> 31)).max(1))
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// In `0.0..1.0`.
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// In `min..max`.
+    pub(crate) fn range(&mut self, (min, max): (f32, f32)) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+#[cfg(test)]
+mod rng_test {
+    use super::*;
+
+    #[test]
+    fn test_seeded() {
+        let mut a = Rng::new(0);
+        let mut b = Rng::new(0);
+        let first: Vec<u64> = (0..4).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..4).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert!(first.iter().all(|&x| x != 0));
+        assert_ne!(Rng::new(1).next_u64(), first[0]);
+        assert_ne!(
+            Rng::new(0x9E37_79B9_7F4A_7C15u64.wrapping_neg()).next_u64(),
+            0
+        );
+        assert_ne!(Rng::new(0x9E37_79B9_7F4A_7C15).next_u64(), 0);
+
+        for _ in 0..1000 {
+            let x = a.next_f32();
+            assert!((0.0..1.0).contains(&x));
+            let y = a.range((-2.0, 3.0));
+            assert!((-2.0..3.0).contains(&y));
+        }
+    }
+}
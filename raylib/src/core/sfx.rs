@@ -0,0 +1,802 @@
+//! Retro sound effects generated from a handful of parameters, in the style of sfxr
+//!
+//! An [`SfxParams`] describes a bleep the way sfxr does: a waveform, a volume envelope, a frequency that slides,
+//! wobbles and jumps, a duty cycle sweep, a phaser, a repeat and a pair of filters. Every parameter is normalized to
+//! `0.0..=1.0`, or `-1.0..=1.0` for the ones that can go either way, so values copied out of sfxr or jsfxr sound the
+//! same here.
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run() -> Result<(), String> {
+//! let mut audio = RaylibAudio::init_audio_device()?;
+//! let mut params = SfxParams::preset(SfxPreset::Pickup, 7);
+//! params.mutate(1);
+//! let coin = audio.load_sound_from_wave(&params.to_wave())?;
+//! audio.play_sound(&coin);
+//! std::fs::write("coin.sfx", params.to_text()).map_err(|e| e.to_string())?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`SfxParams`]: struct.SfxParams.html
+
+use crate::core::audio::Wave;
+use crate::core::rng::Rng;
+use std::f64::consts::PI;
+
+/// Rate sounds are generated at; sfxr's timings are counted in samples at this rate.
+pub const SFX_SAMPLE_RATE: u32 = 44100;
+
+/// Shape of the oscillator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SfxWaveform {
+    /// Pulse wave, its width set by the duty cycle.
+    Square,
+    Sawtooth,
+    Sine,
+    Noise,
+}
+
+impl SfxWaveform {
+    /// Name used by the text format.
+    pub fn name(self) -> &'static str {
+        match self {
+            SfxWaveform::Square => "square",
+            SfxWaveform::Sawtooth => "sawtooth",
+            SfxWaveform::Sine => "sine",
+            SfxWaveform::Noise => "noise",
+        }
+    }
+
+    /// Waveform called `name` in the text format.
+    pub fn from_name(name: &str) -> Option<SfxWaveform> {
+        match name {
+            "square" => Some(SfxWaveform::Square),
+            "sawtooth" => Some(SfxWaveform::Sawtooth),
+            "sine" => Some(SfxWaveform::Sine),
+            "noise" => Some(SfxWaveform::Noise),
+            _ => None,
+        }
+    }
+
+    fn from_index(index: u64) -> SfxWaveform {
+        match index {
+            0 => SfxWaveform::Square,
+            1 => SfxWaveform::Sawtooth,
+            2 => SfxWaveform::Sine,
+            _ => SfxWaveform::Noise,
+        }
+    }
+}
+
+/// Starting points for common game sounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SfxPreset {
+    Pickup,
+    Laser,
+    Explosion,
+    PowerUp,
+    Hit,
+    Jump,
+    Blip,
+}
+
+/// One parameter of an [`SfxParams`](struct.SfxParams.html), with the range it's kept in.
+#[derive(Debug)]
+pub struct SfxField<'a> {
+    pub name: &'static str,
+    pub value: &'a mut f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Everything needed to generate one sound effect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SfxParams {
+    pub waveform: SfxWaveform,
+    /// Envelope: time to rise to full volume.
+    pub attack: f32,
+    /// Envelope: time held at full volume.
+    pub sustain: f32,
+    /// Envelope: extra volume at the start of the sustain, falling off over it.
+    pub punch: f32,
+    /// Envelope: time to fade out.
+    pub decay: f32,
+    /// Starting pitch.
+    pub base_frequency: f32,
+    /// Pitch at which a falling sound stops, if above zero.
+    pub frequency_limit: f32,
+    /// Pitch slide, up when positive.
+    pub frequency_slide: f32,
+    /// Change of the slide over time.
+    pub frequency_delta_slide: f32,
+    pub vibrato_depth: f32,
+    pub vibrato_speed: f32,
+    /// Pitch jump, up when positive.
+    pub arpeggio_change: f32,
+    /// How soon the pitch jumps; `1.0` never jumps.
+    pub arpeggio_speed: f32,
+    /// Square wave duty cycle, `0.0` for an even square.
+    pub duty: f32,
+    pub duty_sweep: f32,
+    /// How often pitch, duty and arpeggio restart; `0.0` never restarts.
+    pub repeat_speed: f32,
+    pub phaser_offset: f32,
+    pub phaser_sweep: f32,
+    /// Low-pass cutoff; `1.0` turns the filter off.
+    pub lpf_cutoff: f32,
+    pub lpf_cutoff_sweep: f32,
+    pub lpf_resonance: f32,
+    /// High-pass cutoff; `0.0` lets everything through.
+    pub hpf_cutoff: f32,
+    pub hpf_cutoff_sweep: f32,
+    pub volume: f32,
+}
+
+impl Default for SfxParams {
+    /// A short, plain square wave beep.
+    fn default() -> SfxParams {
+        SfxParams {
+            waveform: SfxWaveform::Square,
+            attack: 0.0,
+            sustain: 0.3,
+            punch: 0.0,
+            decay: 0.4,
+            base_frequency: 0.3,
+            frequency_limit: 0.0,
+            frequency_slide: 0.0,
+            frequency_delta_slide: 0.0,
+            vibrato_depth: 0.0,
+            vibrato_speed: 0.0,
+            arpeggio_change: 0.0,
+            arpeggio_speed: 0.0,
+            duty: 0.0,
+            duty_sweep: 0.0,
+            repeat_speed: 0.0,
+            phaser_offset: 0.0,
+            phaser_sweep: 0.0,
+            lpf_cutoff: 1.0,
+            lpf_cutoff_sweep: 0.0,
+            lpf_resonance: 0.0,
+            hpf_cutoff: 0.0,
+            hpf_cutoff_sweep: 0.0,
+            volume: 0.5,
+        }
+    }
+}
+
+/// `0.0..range`, as sfxr's `frnd`.
+fn frnd(rng: &mut Rng, range: f32) -> f32 {
+    rng.next_f32() * range
+}
+
+/// `0..=max`, as sfxr's `rnd`.
+fn rnd(rng: &mut Rng, max: u64) -> u64 {
+    rng.next_u64() % (max + 1)
+}
+
+/// `-1.0..1.0` raised to `power`, which biases towards zero.
+fn signed_pow(rng: &mut Rng, power: i32) -> f32 {
+    (frnd(rng, 2.0) - 1.0).powi(power)
+}
+
+impl SfxParams {
+    /// A random variation of `preset`, the same one for the same `seed`.
+    pub fn preset(preset: SfxPreset, seed: u64) -> SfxParams {
+        let rng = &mut Rng::new(seed);
+        let mut p = SfxParams::default();
+        match preset {
+            SfxPreset::Pickup => {
+                p.base_frequency = 0.4 + frnd(rng, 0.5);
+                p.sustain = frnd(rng, 0.1);
+                p.decay = 0.1 + frnd(rng, 0.4);
+                p.punch = 0.3 + frnd(rng, 0.3);
+                if rnd(rng, 1) == 1 {
+                    p.arpeggio_speed = 0.5 + frnd(rng, 0.2);
+                    p.arpeggio_change = 0.2 + frnd(rng, 0.4);
+                }
+            }
+            SfxPreset::Laser => {
+                p.waveform = SfxWaveform::from_index(rnd(rng, 2));
+                if p.waveform == SfxWaveform::Sine && rnd(rng, 1) == 1 {
+                    p.waveform = SfxWaveform::from_index(rnd(rng, 1));
+                }
+                p.base_frequency = 0.5 + frnd(rng, 0.5);
+                p.frequency_limit = (p.base_frequency - 0.2 - frnd(rng, 0.6)).max(0.2);
+                p.frequency_slide = -0.15 - frnd(rng, 0.2);
+                if rnd(rng, 2) == 0 {
+                    p.base_frequency = 0.3 + frnd(rng, 0.6);
+                    p.frequency_limit = frnd(rng, 0.1);
+                    p.frequency_slide = -0.35 - frnd(rng, 0.3);
+                }
+                if rnd(rng, 1) == 1 {
+                    p.duty = frnd(rng, 0.5);
+                    p.duty_sweep = frnd(rng, 0.2);
+                } else {
+                    p.duty = 0.4 + frnd(rng, 0.5);
+                    p.duty_sweep = -frnd(rng, 0.7);
+                }
+                p.sustain = 0.1 + frnd(rng, 0.2);
+                p.decay = frnd(rng, 0.4);
+                if rnd(rng, 1) == 1 {
+                    p.punch = frnd(rng, 0.3);
+                }
+                if rnd(rng, 2) == 0 {
+                    p.phaser_offset = frnd(rng, 0.2);
+                    p.phaser_sweep = -frnd(rng, 0.2);
+                }
+                if rnd(rng, 1) == 1 {
+                    p.hpf_cutoff = frnd(rng, 0.3);
+                }
+            }
+            SfxPreset::Explosion => {
+                p.waveform = SfxWaveform::Noise;
+                if rnd(rng, 1) == 1 {
+                    p.base_frequency = 0.1 + frnd(rng, 0.4);
+                    p.frequency_slide = -0.1 + frnd(rng, 0.4);
+                } else {
+                    p.base_frequency = 0.2 + frnd(rng, 0.7);
+                    p.frequency_slide = -0.2 - frnd(rng, 0.2);
+                }
+                p.base_frequency *= p.base_frequency;
+                if rnd(rng, 4) == 0 {
+                    p.frequency_slide = 0.0;
+                }
+                if rnd(rng, 2) == 0 {
+                    p.repeat_speed = 0.3 + frnd(rng, 0.5);
+                }
+                p.sustain = 0.1 + frnd(rng, 0.3);
+                p.decay = frnd(rng, 0.5);
+                if rnd(rng, 1) == 0 {
+                    p.phaser_offset = -0.3 + frnd(rng, 0.9);
+                    p.phaser_sweep = -frnd(rng, 0.3);
+                }
+                p.punch = 0.2 + frnd(rng, 0.6);
+                if rnd(rng, 1) == 1 {
+                    p.vibrato_depth = frnd(rng, 0.7);
+                    p.vibrato_speed = frnd(rng, 0.6);
+                }
+                if rnd(rng, 2) == 0 {
+                    p.arpeggio_speed = 0.6 + frnd(rng, 0.3);
+                    p.arpeggio_change = 0.8 - frnd(rng, 1.6);
+                }
+            }
+            SfxPreset::PowerUp => {
+                if rnd(rng, 1) == 1 {
+                    p.waveform = SfxWaveform::Sawtooth;
+                } else {
+                    p.duty = frnd(rng, 0.6);
+                }
+                p.base_frequency = 0.2 + frnd(rng, 0.3);
+                if rnd(rng, 1) == 1 {
+                    p.frequency_slide = 0.1 + frnd(rng, 0.4);
+                    p.repeat_speed = 0.4 + frnd(rng, 0.4);
+                } else {
+                    p.frequency_slide = 0.05 + frnd(rng, 0.2);
+                    if rnd(rng, 1) == 1 {
+                        p.vibrato_depth = frnd(rng, 0.7);
+                        p.vibrato_speed = frnd(rng, 0.6);
+                    }
+                }
+                p.sustain = frnd(rng, 0.4);
+                p.decay = 0.1 + frnd(rng, 0.4);
+            }
+            SfxPreset::Hit => {
+                p.waveform = match rnd(rng, 2) {
+                    0 => SfxWaveform::Square,
+                    1 => SfxWaveform::Sawtooth,
+                    _ => SfxWaveform::Noise,
+                };
+                if p.waveform == SfxWaveform::Square {
+                    p.duty = frnd(rng, 0.6);
+                }
+                p.base_frequency = 0.2 + frnd(rng, 0.6);
+                p.frequency_slide = -0.3 - frnd(rng, 0.4);
+                p.sustain = frnd(rng, 0.1);
+                p.decay = 0.1 + frnd(rng, 0.2);
+                if rnd(rng, 1) == 1 {
+                    p.hpf_cutoff = frnd(rng, 0.3);
+                }
+            }
+            SfxPreset::Jump => {
+                p.duty = frnd(rng, 0.6);
+                p.base_frequency = 0.3 + frnd(rng, 0.3);
+                p.frequency_slide = 0.1 + frnd(rng, 0.2);
+                p.sustain = 0.1 + frnd(rng, 0.3);
+                p.decay = 0.1 + frnd(rng, 0.2);
+                if rnd(rng, 1) == 1 {
+                    p.hpf_cutoff = frnd(rng, 0.3);
+                }
+                if rnd(rng, 1) == 1 {
+                    p.lpf_cutoff = 1.0 - frnd(rng, 0.6);
+                }
+            }
+            SfxPreset::Blip => {
+                p.waveform = SfxWaveform::from_index(rnd(rng, 1));
+                if p.waveform == SfxWaveform::Square {
+                    p.duty = frnd(rng, 0.6);
+                }
+                p.base_frequency = 0.2 + frnd(rng, 0.4);
+                p.sustain = 0.1 + frnd(rng, 0.1);
+                p.decay = frnd(rng, 0.2);
+                p.hpf_cutoff = 0.1;
+            }
+        }
+        p
+    }
+
+    /// Completely random parameters, biased towards ones that make an audible sound.
+    pub fn random(seed: u64) -> SfxParams {
+        let rng = &mut Rng::new(seed);
+        let mut p = SfxParams {
+            waveform: SfxWaveform::from_index(rnd(rng, 3)),
+            ..SfxParams::default()
+        };
+        p.base_frequency = if rnd(rng, 1) == 1 {
+            signed_pow(rng, 3) + 0.5
+        } else {
+            signed_pow(rng, 2)
+        };
+        p.frequency_slide = signed_pow(rng, 5);
+        if (p.base_frequency > 0.7 && p.frequency_slide > 0.2)
+            || (p.base_frequency < 0.2 && p.frequency_slide < -0.05)
+        {
+            p.frequency_slide = -p.frequency_slide;
+        }
+        p.frequency_delta_slide = signed_pow(rng, 3);
+        p.duty = signed_pow(rng, 1);
+        p.duty_sweep = signed_pow(rng, 3);
+        p.vibrato_depth = signed_pow(rng, 3);
+        p.vibrato_speed = signed_pow(rng, 1);
+        p.attack = signed_pow(rng, 3);
+        p.sustain = signed_pow(rng, 2);
+        p.decay = signed_pow(rng, 1);
+        p.punch = frnd(rng, 0.8).powi(2);
+        if p.attack + p.sustain + p.decay < 0.2 {
+            p.sustain += 0.2 + frnd(rng, 0.3);
+            p.decay += 0.2 + frnd(rng, 0.3);
+        }
+        p.lpf_resonance = signed_pow(rng, 1);
+        p.lpf_cutoff = 1.0 - frnd(rng, 1.0).powi(3);
+        p.lpf_cutoff_sweep = signed_pow(rng, 3);
+        if p.lpf_cutoff < 0.1 && p.lpf_cutoff_sweep < -0.05 {
+            p.lpf_cutoff_sweep = -p.lpf_cutoff_sweep;
+        }
+        p.hpf_cutoff = frnd(rng, 1.0).powi(5);
+        p.hpf_cutoff_sweep = signed_pow(rng, 5);
+        p.phaser_offset = signed_pow(rng, 3);
+        p.phaser_sweep = signed_pow(rng, 3);
+        p.repeat_speed = signed_pow(rng, 1);
+        p.arpeggio_speed = signed_pow(rng, 1);
+        p.arpeggio_change = signed_pow(rng, 1);
+        p.clamp();
+        p
+    }
+
+    /// Nudges about half the parameters a little, for a variation on the same sound.
+    pub fn mutate(&mut self, seed: u64) {
+        let rng = &mut Rng::new(seed);
+        for field in self.fields_mut() {
+            if rnd(rng, 1) == 1 {
+                *field.value += frnd(rng, 0.1) - 0.05;
+            }
+        }
+        self.clamp();
+    }
+
+    /// The numeric parameters by name, in the order the text format writes them.
+    pub fn fields_mut(&mut self) -> Vec<SfxField<'_>> {
+        fn field<'a>(name: &'static str, value: &'a mut f32, min: f32) -> SfxField<'a> {
+            SfxField {
+                name,
+                value,
+                min,
+                max: 1.0,
+            }
+        }
+        vec![
+            field("attack", &mut self.attack, 0.0),
+            field("sustain", &mut self.sustain, 0.0),
+            field("punch", &mut self.punch, 0.0),
+            field("decay", &mut self.decay, 0.0),
+            field("base_frequency", &mut self.base_frequency, 0.0),
+            field("frequency_limit", &mut self.frequency_limit, 0.0),
+            field("frequency_slide", &mut self.frequency_slide, -1.0),
+            field(
+                "frequency_delta_slide",
+                &mut self.frequency_delta_slide,
+                -1.0,
+            ),
+            field("vibrato_depth", &mut self.vibrato_depth, 0.0),
+            field("vibrato_speed", &mut self.vibrato_speed, 0.0),
+            field("arpeggio_change", &mut self.arpeggio_change, -1.0),
+            field("arpeggio_speed", &mut self.arpeggio_speed, 0.0),
+            field("duty", &mut self.duty, 0.0),
+            field("duty_sweep", &mut self.duty_sweep, -1.0),
+            field("repeat_speed", &mut self.repeat_speed, 0.0),
+            field("phaser_offset", &mut self.phaser_offset, -1.0),
+            field("phaser_sweep", &mut self.phaser_sweep, -1.0),
+            field("lpf_cutoff", &mut self.lpf_cutoff, 0.0),
+            field("lpf_cutoff_sweep", &mut self.lpf_cutoff_sweep, -1.0),
+            field("lpf_resonance", &mut self.lpf_resonance, 0.0),
+            field("hpf_cutoff", &mut self.hpf_cutoff, 0.0),
+            field("hpf_cutoff_sweep", &mut self.hpf_cutoff_sweep, -1.0),
+            field("volume", &mut self.volume, 0.0),
+        ]
+    }
+
+    /// Keeps every parameter within its range.
+    pub fn clamp(&mut self) {
+        for field in self.fields_mut() {
+            *field.value = field.value.clamp(field.min, field.max);
+        }
+    }
+
+    /// Writes the parameters as `name = value` lines, the waveform first.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("waveform = {}\n", self.waveform.name());
+        for field in self.clone().fields_mut() {
+            text += &format!("{} = {}\n", field.name, field.value);
+        }
+        text
+    }
+
+    /// Parses parameters written by [`to_text`](#method.to_text). Blank lines and lines starting with `#` are
+    /// skipped, and parameters that aren't given keep their default.
+    pub fn from_text(text: &str) -> Result<SfxParams, String> {
+        let mut params = SfxParams::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| format!("line {}: {}", number + 1, message);
+            let mut parts = line.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            let value = parts
+                .next()
+                .ok_or_else(|| error("expected `name = value`"))?
+                .trim();
+            if name == "waveform" {
+                params.waveform = SfxWaveform::from_name(value)
+                    .ok_or_else(|| error(&format!("unknown waveform \"{}\"", value)))?;
+                continue;
+            }
+            let value: f32 = value
+                .parse()
+                .map_err(|_| error(&format!("\"{}\" is not a number", value)))?;
+            let field = params
+                .fields_mut()
+                .into_iter()
+                .find(|field| field.name == name)
+                .ok_or_else(|| error(&format!("unknown parameter \"{}\"", name)))?;
+            *field.value = value;
+        }
+        params.clamp();
+        Ok(params)
+    }
+
+    /// Reads parameters from a text file.
+    pub fn load(filename: &str) -> Result<SfxParams, String> {
+        let text = std::fs::read_to_string(filename)
+            .map_err(|e| format!("Cannot read sound effect {}: {}", filename, e))?;
+        SfxParams::from_text(&text)
+            .map_err(|e| format!("Cannot parse sound effect {}: {}", filename, e))
+    }
+
+    /// Writes the parameters to a text file.
+    pub fn save(&self, filename: &str) -> Result<(), String> {
+        std::fs::write(filename, self.to_text())
+            .map_err(|e| format!("Cannot write sound effect {}: {}", filename, e))
+    }
+
+    /// Generates the mono samples of the sound at [`SFX_SAMPLE_RATE`](constant.SFX_SAMPLE_RATE.html).
+    pub fn generate(&self) -> Vec<f32> {
+        let mut synth = Synth::new(self);
+        let mut samples = Vec::new();
+        while let Some(sample) = synth.next() {
+            samples.push(sample);
+        }
+        samples
+    }
+
+    /// Generates the sound as a 32 bit mono wave.
+    pub fn to_wave(&self) -> Wave {
+        Wave::from_samples(&self.generate(), SFX_SAMPLE_RATE, 1)
+    }
+}
+
+/// Playback state of sfxr's synthesizer.
+struct Synth<'a> {
+    params: &'a SfxParams,
+    rng: Rng,
+    playing: bool,
+    phase: i32,
+    period: f64,
+    max_period: f64,
+    slide: f64,
+    delta_slide: f64,
+    square_duty: f32,
+    square_slide: f32,
+    arp_mod: f64,
+    arp_time: i32,
+    arp_limit: i32,
+    vib_phase: f64,
+    vib_speed: f64,
+    vib_amp: f64,
+    env_stage: usize,
+    env_time: i32,
+    env_length: [i32; 3],
+    phaser_phase: f32,
+    phaser_delta: f32,
+    phaser_offset: i32,
+    phaser: Box<[f32; 1024]>,
+    phaser_pos: i32,
+    noise: [f32; 32],
+    lp: f32,
+    lp_delta: f32,
+    lp_w: f32,
+    lp_w_delta: f32,
+    lp_damping: f32,
+    hp: f32,
+    hp_w: f32,
+    hp_w_delta: f32,
+    rep_time: i32,
+    rep_limit: i32,
+}
+
+impl<'a> Synth<'a> {
+    fn new(params: &'a SfxParams) -> Synth<'a> {
+        let p = params;
+        let env_length = |time: f32| ((time * time * 100_000.0) as i32).max(1);
+        let mut rng = Rng::new(0);
+        let mut noise = [0.0; 32];
+        for n in noise.iter_mut() {
+            *n = frnd(&mut rng, 2.0) - 1.0;
+        }
+        let phaser_phase = p.phaser_offset.powi(2) * 1020.0 * p.phaser_offset.signum();
+        let mut synth = Synth {
+            params,
+            rng,
+            playing: true,
+            phase: 0,
+            period: 0.0,
+            max_period: 0.0,
+            slide: 0.0,
+            delta_slide: 0.0,
+            square_duty: 0.0,
+            square_slide: 0.0,
+            arp_mod: 0.0,
+            arp_time: 0,
+            arp_limit: 0,
+            vib_phase: 0.0,
+            vib_speed: f64::from(p.vibrato_speed).powi(2) * 0.01,
+            vib_amp: f64::from(p.vibrato_depth) * 0.5,
+            env_stage: 0,
+            env_time: 0,
+            env_length: [
+                env_length(p.attack),
+                env_length(p.sustain),
+                env_length(p.decay),
+            ],
+            phaser_phase,
+            phaser_delta: p.phaser_sweep.powi(2) * p.phaser_sweep.signum(),
+            phaser_offset: (phaser_phase as i32).abs(),
+            phaser: Box::new([0.0; 1024]),
+            phaser_pos: 0,
+            noise,
+            lp: 0.0,
+            lp_delta: 0.0,
+            lp_w: p.lpf_cutoff.powi(3) * 0.1,
+            lp_w_delta: 1.0 + p.lpf_cutoff_sweep * 0.0001,
+            lp_damping: (5.0 / (1.0 + p.lpf_resonance.powi(2) * 20.0)
+                * (0.01 + p.lpf_cutoff.powi(3) * 0.1))
+                .min(0.8),
+            hp: 0.0,
+            hp_w: p.hpf_cutoff.powi(2) * 0.1,
+            hp_w_delta: 1.0 + p.hpf_cutoff_sweep * 0.0003,
+            rep_time: 0,
+            rep_limit: if p.repeat_speed > 0.0 {
+                ((1.0 - p.repeat_speed).powi(2) * 20000.0 + 32.0) as i32
+            } else {
+                0
+            },
+        };
+        synth.restart();
+        synth
+    }
+
+    /// Resets what repeats: pitch, slide, duty and arpeggio.
+    fn restart(&mut self) {
+        let p = self.params;
+        self.period = 100.0 / (f64::from(p.base_frequency).powi(2) + 0.001);
+        self.max_period = 100.0 / (f64::from(p.frequency_limit).powi(2) + 0.001);
+        self.slide = 1.0 - f64::from(p.frequency_slide).powi(3) * 0.01;
+        self.delta_slide = -f64::from(p.frequency_delta_slide).powi(3) * 0.000_001;
+        self.square_duty = 0.5 - p.duty * 0.5;
+        self.square_slide = -p.duty_sweep * 0.00005;
+        let change = f64::from(p.arpeggio_change);
+        self.arp_mod = if change >= 0.0 {
+            1.0 - change.powi(2) * 0.9
+        } else {
+            1.0 + change.powi(2) * 10.0
+        };
+        self.arp_time = 0;
+        self.arp_limit = if p.arpeggio_speed < 1.0 {
+            ((1.0 - p.arpeggio_speed).powi(2) * 20000.0 + 32.0) as i32
+        } else {
+            0
+        };
+    }
+
+    fn next(&mut self) -> Option<f32> {
+        if !self.playing {
+            return None;
+        }
+        let p = self.params;
+
+        self.rep_time += 1;
+        if self.rep_limit != 0 && self.rep_time >= self.rep_limit {
+            self.rep_time = 0;
+            self.restart();
+        }
+
+        self.arp_time += 1;
+        if self.arp_limit != 0 && self.arp_time >= self.arp_limit {
+            self.arp_limit = 0;
+            self.period *= self.arp_mod;
+        }
+        self.slide += self.delta_slide;
+        self.period *= self.slide;
+        if self.period > self.max_period {
+            self.period = self.max_period;
+            if p.frequency_limit > 0.0 {
+                self.playing = false;
+            }
+        }
+        let mut period = self.period;
+        if self.vib_amp > 0.0 {
+            self.vib_phase += self.vib_speed;
+            period *= 1.0 + self.vib_phase.sin() * self.vib_amp;
+        }
+        let period = (period as i32).max(8);
+        self.square_duty = (self.square_duty + self.square_slide).clamp(0.0, 0.5);
+
+        self.env_time += 1;
+        if self.env_time > self.env_length[self.env_stage] {
+            self.env_time = 0;
+            self.env_stage += 1;
+            if self.env_stage == 3 {
+                self.playing = false;
+                return None;
+            }
+        }
+        let progress = self.env_time as f32 / self.env_length[self.env_stage] as f32;
+        let env_vol = match self.env_stage {
+            0 => progress,
+            1 => 1.0 + (1.0 - progress) * 2.0 * p.punch,
+            _ => 1.0 - progress,
+        };
+
+        self.phaser_phase += self.phaser_delta;
+        self.phaser_offset = (self.phaser_phase as i32).abs().min(1023);
+        if self.hp_w_delta != 0.0 {
+            self.hp_w = (self.hp_w * self.hp_w_delta).clamp(0.00001, 0.1);
+        }
+
+        // 8x supersampling
+        let mut total = 0.0;
+        for _ in 0..8 {
+            self.phase += 1;
+            if self.phase >= period {
+                self.phase %= period;
+                if p.waveform == SfxWaveform::Noise {
+                    for n in self.noise.iter_mut() {
+                        *n = frnd(&mut self.rng, 2.0) - 1.0;
+                    }
+                }
+            }
+            let fp = self.phase as f32 / period as f32;
+            let mut sample = match p.waveform {
+                SfxWaveform::Square => {
+                    if fp < self.square_duty {
+                        0.5
+                    } else {
+                        -0.5
+                    }
+                }
+                SfxWaveform::Sawtooth => 1.0 - fp * 2.0,
+                SfxWaveform::Sine => (f64::from(fp) * 2.0 * PI).sin() as f32,
+                SfxWaveform::Noise => self.noise[(self.phase * 32 / period) as usize],
+            };
+
+            let previous = self.lp;
+            self.lp_w = (self.lp_w * self.lp_w_delta).clamp(0.0, 0.1);
+            if p.lpf_cutoff < 1.0 {
+                self.lp_delta += (sample - self.lp) * self.lp_w;
+                self.lp_delta -= self.lp_delta * self.lp_damping;
+            } else {
+                self.lp = sample;
+                self.lp_delta = 0.0;
+            }
+            self.lp += self.lp_delta;
+
+            self.hp += self.lp - previous;
+            self.hp -= self.hp * self.hp_w;
+            sample = self.hp;
+
+            self.phaser[(self.phaser_pos & 1023) as usize] = sample;
+            sample += self.phaser[((self.phaser_pos - self.phaser_offset + 1024) & 1023) as usize];
+            self.phaser_pos = (self.phaser_pos + 1) & 1023;
+
+            total += sample * env_vol;
+        }
+        // sfxr's master volume of 0.05, doubled
+        Some((total / 8.0 * 0.1 * p.volume).clamp(-1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod sfx_test {
+    use super::*;
+
+    #[test]
+    fn test_presets_are_seeded() {
+        let presets = [
+            SfxPreset::Pickup,
+            SfxPreset::Laser,
+            SfxPreset::Explosion,
+            SfxPreset::PowerUp,
+            SfxPreset::Hit,
+            SfxPreset::Jump,
+            SfxPreset::Blip,
+        ];
+        for &preset in presets.iter() {
+            let params = SfxParams::preset(preset, 3);
+            assert_eq!(params, SfxParams::preset(preset, 3));
+            let samples = params.generate();
+            assert!(!samples.is_empty(), "{:?} is silent", preset);
+            assert!(samples.iter().all(|s| s.abs() <= 1.0));
+            assert!(
+                samples.iter().any(|s| s.abs() > 0.01),
+                "{:?} is silent",
+                preset
+            );
+            assert_eq!(samples, params.generate());
+        }
+        assert_ne!(
+            SfxParams::preset(SfxPreset::Laser, 1),
+            SfxParams::preset(SfxPreset::Laser, 2)
+        );
+    }
+
+    #[test]
+    fn test_envelope_sets_length() {
+        let params = SfxParams {
+            attack: 0.1,
+            sustain: 0.2,
+            decay: 0.3,
+            ..SfxParams::default()
+        };
+        // sfxr's stages last time² * 100000 samples, plus one each
+        assert_eq!(params.generate().len(), 1000 + 4000 + 9000 + 2);
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let mut params = SfxParams::random(11);
+        params.mutate(5);
+        let fields = params.clone();
+        for field in params.fields_mut() {
+            assert!(*field.value >= field.min && *field.value <= field.max);
+        }
+        assert_eq!(SfxParams::from_text(&params.to_text()).unwrap(), fields);
+
+        let params = SfxParams::from_text("# laser\nwaveform = sine\n\ndecay=2\n").unwrap();
+        assert_eq!(params.waveform, SfxWaveform::Sine);
+        assert_eq!(params.decay, 1.0);
+        assert_eq!(params.sustain, SfxParams::default().sustain);
+        assert!(SfxParams::from_text("pitch = 1").is_err());
+        assert!(SfxParams::from_text("decay = loud").is_err());
+    }
+}
@@ -0,0 +1,441 @@
+//! Positional audio: volume, pan and pitch from where sounds are heard
+//!
+//! An [`AudioListener`] follows a [`Camera3D`], and each [`AudioEmitter`] places a sound in the world. Once a frame,
+//! [`AudioListener::spatialize`] works out how loud an emitter is after distance rolloff, where it sits between the
+//! speakers and how much doppler shifts its pitch, and [`Spatialize::apply_spatial`] hands the result to a
+//! `Sound`, `Music`, `AudioStream` or [`ProceduralStream`], or to the [`BusMember`] a mixer bus holds it as:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run(rl: &mut RaylibHandle, camera: &Camera3D, engine: &mut Sound, car: Vector3) {
+//! let mut listener = AudioListener::from_camera(camera);
+//! let mut emitter = AudioEmitter::new(car);
+//! emitter.rolloff = Rolloff::Inverse;
+//!
+//! while !rl.window_should_close() {
+//!     let dt = rl.get_frame_time();
+//!     listener.follow_camera(camera, dt);
+//!     emitter.move_to(car, dt);
+//!     engine.apply_spatial(&listener.spatialize(&emitter));
+//! }
+//! # }
+//! ```
+//!
+//! Side views and top down games use [`AudioListener2D`] with a `Camera2D` instead, panning by how far across the
+//! screen an emitter is.
+//!
+//! Only [`ProceduralStream`]s are panned, other sounds get the volume and pitch, see the [`audio`] module.
+//!
+//! [`AudioListener`]: struct.AudioListener.html
+//! [`AudioListener::spatialize`]: struct.AudioListener.html#method.spatialize
+//! [`AudioListener2D`]: struct.AudioListener2D.html
+//! [`AudioEmitter`]: struct.AudioEmitter.html
+//! [`Spatialize::apply_spatial`]: trait.Spatialize.html#tymethod.apply_spatial
+//! [`Camera3D`]: ../camera/struct.Camera3D.html
+//! [`audio`]: ../audio/index.html#limitations-of-raylib-30
+//! [`ProceduralStream`]: ../procedural/struct.ProceduralStream.html
+//! [`BusMember`]: ../mixer/struct.BusMember.html
+use crate::core::audio::{AudioSample, AudioStream, Music, Sound};
+use crate::core::camera::{Camera2D, Camera3D};
+use crate::core::math::{Vector2, Vector3};
+use crate::core::procedural::{ProceduralStream, Source};
+use crate::ffi;
+
+/// Speed of sound in air, in meters per second.
+pub const SPEED_OF_SOUND: f32 = 343.0;
+
+/// How volume falls off between an emitter's `min_distance` and `max_distance`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rolloff {
+    /// Full volume up to `max_distance`.
+    None,
+    /// Falls in a straight line to silence at `max_distance`.
+    Linear,
+    /// Halves each time the distance past `min_distance` grows by `min_distance`, like sound in open air.
+    Inverse,
+    /// Falls with the distance raised to the rolloff factor.
+    Exponential,
+}
+
+/// Volume, stereo balance and pitch for a sound, as heard by a listener.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpatialParams {
+    pub volume: f32,
+    /// From `-1.0`, fully left, to `1.0`, fully right.
+    pub pan: f32,
+    pub pitch: f32,
+}
+
+impl Default for SpatialParams {
+    fn default() -> SpatialParams {
+        SpatialParams {
+            volume: 1.0,
+            pan: 0.0,
+            pitch: 1.0,
+        }
+    }
+}
+
+/// A sound's place in the world and how it carries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioEmitter {
+    /// Position in world units. 2D listeners only look at `x` and `y`.
+    pub position: Vector3,
+    /// Units per second, for doppler.
+    pub velocity: Vector3,
+    /// Volume before rolloff.
+    pub volume: f32,
+    /// Pitch before doppler.
+    pub pitch: f32,
+    pub rolloff: Rolloff,
+    /// Closer than this, the volume doesn't rise any further.
+    pub min_distance: f32,
+    /// Further than this, the volume doesn't fall any further. Linear rolloff reaches silence here.
+    pub max_distance: f32,
+    /// How quickly inverse and exponential rolloff fall, `1.0` is physically plausible.
+    pub rolloff_factor: f32,
+    /// Whether movement shifts the pitch.
+    pub doppler: bool,
+}
+
+impl AudioEmitter {
+    /// An emitter at `position` with inverse rolloff from 1 to 100 units and doppler.
+    pub fn new(position: Vector3) -> AudioEmitter {
+        AudioEmitter {
+            position,
+            velocity: Vector3::zero(),
+            volume: 1.0,
+            pitch: 1.0,
+            rolloff: Rolloff::Inverse,
+            min_distance: 1.0,
+            max_distance: 100.0,
+            rolloff_factor: 1.0,
+            doppler: true,
+        }
+    }
+
+    /// An emitter for a [`AudioListener2D`](struct.AudioListener2D.html).
+    pub fn new_2d(position: Vector2) -> AudioEmitter {
+        AudioEmitter::new(Vector3::new(position.x, position.y, 0.0))
+    }
+
+    /// Moves to `position`, working out the velocity from the `dt` seconds since the last move.
+    pub fn move_to(&mut self, position: Vector3, dt: f32) {
+        if dt > 0.0 {
+            self.velocity = (position - self.position) / dt;
+        }
+        self.position = position;
+    }
+
+    /// Volume at `distance`, after rolloff.
+    pub fn attenuation(&self, distance: f32) -> f32 {
+        let min = self.min_distance.max(1e-3);
+        let max = self.max_distance.max(min);
+        let distance = distance.clamp(min, max);
+        let gain = match self.rolloff {
+            Rolloff::None => 1.0,
+            Rolloff::Linear => {
+                if max > min {
+                    1.0 - self.rolloff_factor * (distance - min) / (max - min)
+                } else {
+                    1.0
+                }
+            }
+            Rolloff::Inverse => min / (min + self.rolloff_factor * (distance - min)),
+            Rolloff::Exponential => (distance / min).powf(-self.rolloff_factor),
+        };
+        gain.clamp(0.0, 1.0) * self.volume
+    }
+}
+
+/// Pitch change of a sound moving at `source_velocity` heard by a listener moving at `listener_velocity`, both
+/// projected on the line between them, positive towards the other, in the OpenAL way.
+fn doppler_shift(
+    listener_velocity: f32,
+    source_velocity: f32,
+    speed_of_sound: f32,
+    doppler_factor: f32,
+) -> f32 {
+    if doppler_factor <= 0.0 || speed_of_sound <= 0.0 {
+        return 1.0;
+    }
+    // nothing catches up with its own sound
+    let limit = speed_of_sound / doppler_factor * 0.99;
+    let listener = listener_velocity.clamp(-limit, limit);
+    let source = source_velocity.clamp(-limit, limit);
+    (speed_of_sound + doppler_factor * listener) / (speed_of_sound - doppler_factor * source)
+}
+
+/// Where sounds are heard from in 3D, usually the camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioListener {
+    pub position: Vector3,
+    /// Direction faced, normalized.
+    pub forward: Vector3,
+    pub up: Vector3,
+    pub velocity: Vector3,
+    /// In world units per second, [`SPEED_OF_SOUND`](constant.SPEED_OF_SOUND.html) when a unit is a meter.
+    pub speed_of_sound: f32,
+    /// Scales the doppler effect, `0.0` turns it off.
+    pub doppler_factor: f32,
+}
+
+impl AudioListener {
+    /// A listener at `camera`'s position, facing its target.
+    pub fn from_camera(camera: &Camera3D) -> AudioListener {
+        AudioListener {
+            position: camera.position,
+            forward: (camera.target - camera.position).normalized(),
+            up: camera.up.normalized(),
+            velocity: Vector3::zero(),
+            speed_of_sound: SPEED_OF_SOUND,
+            doppler_factor: 1.0,
+        }
+    }
+
+    /// Moves to where `camera` is now, working out the velocity from the `dt` seconds since the last call.
+    pub fn follow_camera(&mut self, camera: &Camera3D, dt: f32) {
+        if dt > 0.0 {
+            self.velocity = (camera.position - self.position) / dt;
+        }
+        self.position = camera.position;
+        self.forward = (camera.target - camera.position).normalized();
+        self.up = camera.up.normalized();
+    }
+
+    /// How `emitter` sounds from here.
+    pub fn spatialize(&self, emitter: &AudioEmitter) -> SpatialParams {
+        let offset = emitter.position - self.position;
+        let distance = offset.length();
+        let direction = if distance > 0.0 {
+            offset / distance
+        } else {
+            Vector3::zero()
+        };
+        let right = self.forward.cross(self.up).normalized();
+        let pitch = if emitter.doppler {
+            doppler_shift(
+                self.velocity.dot(direction),
+                -emitter.velocity.dot(direction),
+                self.speed_of_sound,
+                self.doppler_factor,
+            )
+        } else {
+            1.0
+        };
+        SpatialParams {
+            volume: emitter.attenuation(distance),
+            pan: direction.dot(right).clamp(-1.0, 1.0),
+            pitch: emitter.pitch * pitch,
+        }
+    }
+}
+
+/// Where sounds are heard from in 2D. Emitters pan with their horizontal distance from the listener, fully to one
+/// side at `pan_width` or beyond, and roll off with their distance in the plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioListener2D {
+    pub position: Vector2,
+    pub velocity: Vector2,
+    /// Horizontal distance at which emitters are fully panned.
+    pub pan_width: f32,
+    /// In world units per second.
+    pub speed_of_sound: f32,
+    /// Scales the doppler effect, `0.0` turns it off.
+    pub doppler_factor: f32,
+}
+
+impl AudioListener2D {
+    /// A listener in the middle of what `camera` shows on a `screen_width` by `screen_height` screen, panning fully
+    /// at the edges of the view.
+    pub fn from_camera(
+        camera: &Camera2D,
+        screen_width: f32,
+        screen_height: f32,
+    ) -> AudioListener2D {
+        let area = camera.visible_area(screen_width, screen_height);
+        AudioListener2D {
+            position: Vector2::new(area.x + area.width / 2.0, area.y + area.height / 2.0),
+            velocity: Vector2::zero(),
+            pan_width: area.width / 2.0,
+            speed_of_sound: SPEED_OF_SOUND,
+            doppler_factor: 0.0,
+        }
+    }
+
+    /// Moves to the middle of what `camera` shows now, working out the velocity from the `dt` seconds since the last
+    /// call.
+    pub fn follow_camera(
+        &mut self,
+        camera: &Camera2D,
+        screen_width: f32,
+        screen_height: f32,
+        dt: f32,
+    ) {
+        let next = AudioListener2D::from_camera(camera, screen_width, screen_height);
+        if dt > 0.0 {
+            self.velocity = (next.position - self.position) / dt;
+        }
+        self.position = next.position;
+        self.pan_width = next.pan_width;
+    }
+
+    /// How `emitter` sounds from here.
+    pub fn spatialize(&self, emitter: &AudioEmitter) -> SpatialParams {
+        let offset = Vector2::new(emitter.position.x, emitter.position.y) - self.position;
+        let distance = offset.length();
+        let pitch = if emitter.doppler && distance > 0.0 {
+            let direction = offset / distance;
+            let source = Vector2::new(emitter.velocity.x, emitter.velocity.y);
+            doppler_shift(
+                self.velocity.dot(direction),
+                -source.dot(direction),
+                self.speed_of_sound,
+                self.doppler_factor,
+            )
+        } else {
+            1.0
+        };
+        let pan = if self.pan_width > 0.0 {
+            (offset.x / self.pan_width).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        SpatialParams {
+            volume: emitter.attenuation(distance),
+            pan,
+            pitch: emitter.pitch * pitch,
+        }
+    }
+}
+
+/// Audio that positional parameters can be applied to.
+pub trait Spatialize {
+    /// Sets the volume and pitch, and the pan where supported.
+    fn apply_spatial(&mut self, params: &SpatialParams);
+}
+
+impl Spatialize for Sound {
+    fn apply_spatial(&mut self, params: &SpatialParams) {
+        unsafe {
+            ffi::SetSoundVolume(self.0, params.volume);
+            ffi::SetSoundPitch(self.0, params.pitch);
+        }
+    }
+}
+
+impl Spatialize for Music {
+    fn apply_spatial(&mut self, params: &SpatialParams) {
+        unsafe {
+            ffi::SetMusicVolume(self.0, params.volume);
+            ffi::SetMusicPitch(self.0, params.pitch);
+        }
+    }
+}
+
+impl<T: AudioSample> Spatialize for AudioStream<T> {
+    fn apply_spatial(&mut self, params: &SpatialParams) {
+        unsafe {
+            ffi::SetAudioStreamVolume(self.0, params.volume);
+            ffi::SetAudioStreamPitch(self.0, params.pitch);
+        }
+    }
+}
+
+impl<S: Source> Spatialize for ProceduralStream<S> {
+    fn apply_spatial(&mut self, params: &SpatialParams) {
+        self.set_volume(params.volume);
+        self.set_pitch(params.pitch);
+        self.set_pan(params.pan);
+    }
+}
+
+#[cfg(test)]
+mod spatial_test {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_rolloff() {
+        let mut emitter = AudioEmitter::new(Vector3::zero());
+        emitter.min_distance = 2.0;
+        emitter.max_distance = 10.0;
+        assert_close(emitter.attenuation(1.0), 1.0);
+        assert_close(emitter.attenuation(4.0), 0.5);
+        assert_close(emitter.attenuation(50.0), 0.2);
+
+        emitter.rolloff = Rolloff::Linear;
+        assert_close(emitter.attenuation(6.0), 0.5);
+        assert_close(emitter.attenuation(10.0), 0.0);
+
+        emitter.rolloff = Rolloff::Exponential;
+        emitter.rolloff_factor = 2.0;
+        assert_close(emitter.attenuation(4.0), 0.25);
+
+        emitter.rolloff = Rolloff::None;
+        emitter.volume = 0.5;
+        assert_close(emitter.attenuation(8.0), 0.5);
+    }
+
+    #[test]
+    fn test_spatialize_3d() {
+        let camera = Camera3D::perspective(
+            Vector3::zero(),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::up(),
+            60.0,
+        );
+        let listener = AudioListener::from_camera(&camera);
+
+        let mut emitter = AudioEmitter::new(Vector3::new(4.0, 0.0, 0.0));
+        let params = listener.spatialize(&emitter);
+        assert_close(params.pan, 1.0);
+        assert_close(params.volume, 0.25);
+        assert_close(params.pitch, 1.0);
+
+        emitter.position = Vector3::new(0.0, 0.0, -2.0);
+        assert_close(listener.spatialize(&emitter).pan, 0.0);
+
+        // coming closer raises the pitch, going away lowers it
+        emitter.velocity = Vector3::new(0.0, 0.0, 34.3);
+        assert_close(listener.spatialize(&emitter).pitch, 343.0 / 308.7);
+        emitter.velocity = Vector3::new(0.0, 0.0, -34.3);
+        assert!(listener.spatialize(&emitter).pitch < 1.0);
+        emitter.doppler = false;
+        assert_close(listener.spatialize(&emitter).pitch, 1.0);
+
+        let mut moving = listener;
+        let mut camera = camera;
+        camera.position = Vector3::new(0.0, 0.0, -1.0);
+        camera.target = Vector3::new(-1.0, 0.0, -1.0);
+        moving.follow_camera(&camera, 0.5);
+        assert_eq!(moving.velocity, Vector3::new(0.0, 0.0, -2.0));
+        // facing -x, so -z is to the right
+        emitter.position = Vector3::new(0.0, 0.0, -3.0);
+        assert_close(moving.spatialize(&emitter).pan, 1.0);
+    }
+
+    #[test]
+    fn test_spatialize_2d() {
+        let camera = Camera2D {
+            offset: Vector2::new(400.0, 300.0),
+            target: Vector2::new(1000.0, 0.0),
+            rotation: 0.0,
+            zoom: 2.0,
+        };
+        let listener = AudioListener2D::from_camera(&camera, 800.0, 600.0);
+        assert_eq!(listener.position, Vector2::new(1000.0, 0.0));
+        assert_eq!(listener.pan_width, 200.0);
+
+        let mut emitter = AudioEmitter::new_2d(Vector2::new(900.0, 0.0));
+        emitter.rolloff = Rolloff::None;
+        let params = listener.spatialize(&emitter);
+        assert_close(params.pan, -0.5);
+        assert_close(params.volume, 1.0);
+        emitter.move_to(Vector3::new(1500.0, 0.0, 0.0), 1.0);
+        assert_close(listener.spatialize(&emitter).pan, 1.0);
+    }
+}
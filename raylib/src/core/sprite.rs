@@ -0,0 +1,945 @@
+//! Sprite sheets and frame by frame animation
+//!
+//! A [`SpriteSheet`] lists where each frame of a sprite lies in a texture. Sheets can be read from the JSON exported
+//! by Aseprite, including frame durations, tags and slices, and from TexturePacker's JSON hash and JSON array
+//! formats. Sheets made of equally sized cells can be described with [`SpriteSheet::from_grid`].
+//!
+//! An [`AnimatedSprite`] plays a sequence of frames from a sheet and draws the current one with `draw_texture_pro`:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+//! let sheet = SpriteSheet::load("hero.json")?;
+//! let texture = rl.load_texture(thread, "hero.png")?;
+//! let mut walk = AnimatedSprite::from_tag(&sheet, "walk").ok_or("no walk animation")?;
+//! walk.add_event(2, "footstep");
+//! walk.flip_x = true;
+//!
+//! while !rl.window_should_close() {
+//!     for event in walk.update(rl.get_frame_time()) {
+//!         if let AnimationEvent::Frame { name, .. } = event {
+//!             println!("{}", name);
+//!         }
+//!     }
+//!     let mut d = rl.begin_drawing(thread);
+//!     d.clear_background(Color::WHITE);
+//!     walk.draw(&mut d, &texture, Vector2::new(100.0, 100.0));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`SpriteSheet`]: struct.SpriteSheet.html
+//! [`SpriteSheet::from_grid`]: struct.SpriteSheet.html#method.from_grid
+//! [`AnimatedSprite`]: struct.AnimatedSprite.html
+use crate::core::atlas::AtlasMap;
+use crate::core::color::Color;
+use crate::core::drawing::RaylibDraw;
+use crate::core::json::JsonValue;
+use crate::core::math::{Rectangle, Vector2};
+use crate::ffi;
+
+/// Duration given to frames of sheets that don't specify one, in seconds.
+pub const DEFAULT_FRAME_DURATION: f32 = 0.1;
+
+/// One frame of a sprite sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteFrame {
+    pub name: String,
+    /// Area of the texture holding the frame. For rotated frames this has the width and height swapped.
+    pub source: Rectangle,
+    /// The frame is stored turned 90 degrees clockwise in the texture, as TexturePacker may do.
+    pub rotated: bool,
+    /// Position of the trimmed frame within the untrimmed one.
+    pub offset: Vector2,
+    /// Size of the frame before transparent borders were trimmed.
+    pub source_size: Vector2,
+    /// How long the frame shows, in seconds.
+    pub duration: f32,
+    /// Pivot as a fraction of the untrimmed size, when the sheet defines one.
+    pub pivot: Option<Vector2>,
+}
+
+impl SpriteFrame {
+    /// An untrimmed, unrotated frame covering `source`.
+    pub fn new(name: &str, source: Rectangle, duration: f32) -> SpriteFrame {
+        SpriteFrame {
+            name: name.to_string(),
+            source,
+            rotated: false,
+            offset: Vector2::zero(),
+            source_size: Vector2::new(source.width, source.height),
+            duration,
+            pivot: None,
+        }
+    }
+
+    /// Size of the trimmed frame, upright.
+    pub fn size(&self) -> Vector2 {
+        if self.rotated {
+            Vector2::new(self.source.height, self.source.width)
+        } else {
+            Vector2::new(self.source.width, self.source.height)
+        }
+    }
+}
+
+/// Order in which the frames of a tag play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationDirection {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+/// A named range of frames, as defined by Aseprite tags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameTag {
+    pub name: String,
+    /// First frame, inclusive.
+    pub from: usize,
+    /// Last frame, inclusive.
+    pub to: usize,
+    pub direction: AnimationDirection,
+    /// How many times the tag plays before stopping, when the sheet limits it.
+    pub repeat: Option<u32>,
+}
+
+/// Shape of a slice from a given frame on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SliceKey {
+    pub frame: usize,
+    /// Bounds relative to the untrimmed frame.
+    pub bounds: Rectangle,
+    /// Center area of a 9-slice, relative to `bounds`.
+    pub center: Option<Rectangle>,
+    /// Pivot relative to `bounds`.
+    pub pivot: Option<Vector2>,
+}
+
+/// A named area of the sprite, such as a hitbox, as defined by Aseprite slices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slice {
+    pub name: String,
+    /// Keys ordered by frame.
+    pub keys: Vec<SliceKey>,
+}
+
+impl Slice {
+    /// The key in effect on `frame`: the last one starting at or before it.
+    pub fn key_at(&self, frame: usize) -> Option<&SliceKey> {
+        self.keys.iter().rev().find(|key| key.frame <= frame)
+    }
+}
+
+/// Frames of a sprite and the animations defined over them.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SpriteSheet {
+    /// Image file named by the sheet, relative to the sheet.
+    pub image: Option<String>,
+    pub frames: Vec<SpriteFrame>,
+    pub tags: Vec<FrameTag>,
+    pub slices: Vec<Slice>,
+}
+
+fn json_rectangle(value: &JsonValue) -> Result<Rectangle, String> {
+    Ok(Rectangle::new(
+        value.number("x")? as f32,
+        value.number("y")? as f32,
+        value.number("w")? as f32,
+        value.number("h")? as f32,
+    ))
+}
+
+fn json_vector(value: &JsonValue, x: &str, y: &str) -> Result<Vector2, String> {
+    Ok(Vector2::new(
+        value.number(x)? as f32,
+        value.number(y)? as f32,
+    ))
+}
+
+fn json_frame(name: &str, value: &JsonValue) -> Result<SpriteFrame, String> {
+    let frame = value
+        .get("frame")
+        .ok_or_else(|| format!("frame {} has no \"frame\" rectangle", name))?;
+    let mut source = json_rectangle(frame)?;
+    let rotated = value
+        .get("rotated")
+        .and_then(JsonValue::as_bool)
+        .unwrap_or(false);
+    if rotated {
+        // the exported size is upright, the texture holds it sideways
+        std::mem::swap(&mut source.width, &mut source.height);
+    }
+    let trim = value.get("spriteSourceSize").map(json_rectangle);
+    let offset = match trim {
+        Some(trim) => {
+            let trim = trim?;
+            Vector2::new(trim.x, trim.y)
+        }
+        None => Vector2::zero(),
+    };
+    let source_size = match value.get("sourceSize") {
+        Some(size) => json_vector(size, "w", "h")?,
+        None if rotated => Vector2::new(source.height, source.width),
+        None => Vector2::new(source.width, source.height),
+    };
+    let duration = value
+        .get("duration")
+        .and_then(JsonValue::as_f32)
+        .map(|ms| ms / 1000.0)
+        .unwrap_or(DEFAULT_FRAME_DURATION);
+    let pivot = match value.get("pivot") {
+        Some(pivot) => Some(json_vector(pivot, "x", "y")?),
+        None => None,
+    };
+    Ok(SpriteFrame {
+        name: name.to_string(),
+        source,
+        rotated,
+        offset,
+        source_size,
+        duration,
+        pivot,
+    })
+}
+
+fn json_tag(value: &JsonValue) -> Result<FrameTag, String> {
+    let direction = match value.get("direction").and_then(JsonValue::as_str) {
+        Some("reverse") => AnimationDirection::Reverse,
+        Some("pingpong") => AnimationDirection::PingPong,
+        Some("pingpong_reverse") => AnimationDirection::PingPongReverse,
+        _ => AnimationDirection::Forward,
+    };
+    // Aseprite writes the repeat count as a string
+    let repeat = value.get("repeat").and_then(|r| match r {
+        JsonValue::String(s) => s.parse().ok(),
+        other => other.as_i64().map(|n| n as u32),
+    });
+    Ok(FrameTag {
+        name: value
+            .get("name")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        from: value.number("from")? as usize,
+        to: value.number("to")? as usize,
+        direction,
+        repeat: repeat.filter(|&n| n > 0),
+    })
+}
+
+fn json_slice(value: &JsonValue) -> Result<Slice, String> {
+    let mut keys = Vec::new();
+    for key in value
+        .get("keys")
+        .and_then(JsonValue::as_array)
+        .unwrap_or_default()
+    {
+        let bounds = key
+            .get("bounds")
+            .ok_or_else(|| "slice key has no bounds".to_string())?;
+        keys.push(SliceKey {
+            frame: key.number("frame")? as usize,
+            bounds: json_rectangle(bounds)?,
+            center: key.get("center").map(json_rectangle).transpose()?,
+            pivot: key
+                .get("pivot")
+                .map(|p| json_vector(p, "x", "y"))
+                .transpose()?,
+        });
+    }
+    keys.sort_by_key(|key| key.frame);
+    Ok(Slice {
+        name: value
+            .get("name")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        keys,
+    })
+}
+
+impl SpriteSheet {
+    /// Parses a sheet exported by Aseprite or TexturePacker as JSON, with frames either as an object keyed by name
+    /// (hash) or as an array of objects with a `filename`.
+    pub fn from_json(json: &str) -> Result<SpriteSheet, String> {
+        let value = JsonValue::parse(json)?;
+        let mut sheet = SpriteSheet::default();
+        match value.get("frames") {
+            Some(JsonValue::Object(frames)) => {
+                for (name, frame) in frames {
+                    sheet.frames.push(json_frame(name, frame)?);
+                }
+            }
+            Some(JsonValue::Array(frames)) => {
+                for frame in frames {
+                    let name = frame
+                        .get("filename")
+                        .and_then(JsonValue::as_str)
+                        .unwrap_or_default();
+                    sheet.frames.push(json_frame(name, frame)?);
+                }
+            }
+            _ => return Err("missing \"frames\"".to_string()),
+        }
+        if let Some(meta) = value.get("meta") {
+            sheet.image = meta
+                .get("image")
+                .and_then(JsonValue::as_str)
+                .map(str::to_string);
+            for tag in meta
+                .get("frameTags")
+                .and_then(JsonValue::as_array)
+                .unwrap_or_default()
+            {
+                sheet.tags.push(json_tag(tag)?);
+            }
+            for slice in meta
+                .get("slices")
+                .and_then(JsonValue::as_array)
+                .unwrap_or_default()
+            {
+                sheet.slices.push(json_slice(slice)?);
+            }
+        }
+        Ok(sheet)
+    }
+
+    /// Reads a JSON sheet from a file.
+    pub fn load(filename: &str) -> Result<SpriteSheet, String> {
+        let json = std::fs::read_to_string(filename)
+            .map_err(|e| format!("Cannot read sprite sheet {}: {}", filename, e))?;
+        SpriteSheet::from_json(&json)
+            .map_err(|e| format!("Cannot parse sprite sheet {}: {}", filename, e))
+    }
+
+    /// A sheet of `count` frames laid out in rows of `columns` equally sized cells, starting at the top left of the
+    /// texture. Frames are named by their index.
+    pub fn from_grid(
+        columns: usize,
+        count: usize,
+        frame_width: f32,
+        frame_height: f32,
+        duration: f32,
+    ) -> SpriteSheet {
+        let columns = columns.max(1);
+        let frames = (0..count)
+            .map(|i| {
+                let rec = Rectangle::new(
+                    (i % columns) as f32 * frame_width,
+                    (i / columns) as f32 * frame_height,
+                    frame_width,
+                    frame_height,
+                );
+                SpriteFrame::new(&i.to_string(), rec, duration)
+            })
+            .collect();
+        SpriteSheet {
+            frames,
+            ..Default::default()
+        }
+    }
+
+    /// A sheet with one frame per region of a packed atlas, in name order.
+    pub fn from_atlas(map: &AtlasMap, duration: f32) -> SpriteSheet {
+        let frames = map
+            .regions
+            .iter()
+            .map(|(name, region)| {
+                let mut frame = SpriteFrame::new(name, region.rec, duration);
+                frame.rotated = region.rotated;
+                frame.source_size = frame.size();
+                frame
+            })
+            .collect();
+        SpriteSheet {
+            frames,
+            ..Default::default()
+        }
+    }
+
+    /// Index of the frame called `name`.
+    pub fn frame_index(&self, name: &str) -> Option<usize> {
+        self.frames.iter().position(|frame| frame.name == name)
+    }
+
+    /// Indices of the frames whose names start with `prefix`, in sheet order. TexturePacker sheets have no tags, so
+    /// animations are usually grouped by name, like `walk_01`, `walk_02`.
+    pub fn frames_with_prefix(&self, prefix: &str) -> Vec<usize> {
+        (0..self.frames.len())
+            .filter(|&i| self.frames[i].name.starts_with(prefix))
+            .collect()
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&FrameTag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    pub fn slice(&self, name: &str) -> Option<&Slice> {
+        self.slices.iter().find(|slice| slice.name == name)
+    }
+}
+
+/// How an animation continues after its last frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayMode {
+    /// Starts over from the first frame.
+    Loop,
+    /// Plays backwards to the first frame, then forwards again.
+    PingPong,
+    /// Stops on the last frame.
+    Once,
+}
+
+/// Something that happened while advancing an animation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimationEvent {
+    /// A frame with an event attached was shown. `frame` is its position in the animation.
+    Frame { frame: usize, name: String },
+    /// The animation went back to its start, in `Loop` or `PingPong` mode.
+    Looped,
+    /// The last frame of a `Once` animation ended.
+    Finished,
+}
+
+/// Where to draw a frame with `draw_texture_pro`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteDrawParams {
+    pub source: Rectangle,
+    pub dest: Rectangle,
+    pub origin: Vector2,
+    pub rotation: f32,
+}
+
+/// Plays a sequence of sprite sheet frames.
+///
+/// The public fields control how the current frame is drawn. `origin` is the point of the untrimmed frame placed at
+/// the drawing position, which rotation and scaling happen around.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimatedSprite {
+    frames: Vec<SpriteFrame>,
+    events: Vec<(usize, String)>,
+    fired: Vec<AnimationEvent>,
+    mode: PlayMode,
+    speed: f32,
+    current: usize,
+    forward: bool,
+    elapsed: f32,
+    playing: bool,
+    entered: bool,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub origin: Vector2,
+    /// Rotation in degrees, clockwise.
+    pub rotation: f32,
+    pub scale: f32,
+    pub tint: Color,
+}
+
+impl AnimatedSprite {
+    /// Plays the frames of `sheet` at `indices`, in that order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index is out of range.
+    pub fn new(sheet: &SpriteSheet, indices: &[usize], mode: PlayMode) -> AnimatedSprite {
+        AnimatedSprite {
+            frames: indices.iter().map(|&i| sheet.frames[i].clone()).collect(),
+            events: Vec::new(),
+            fired: Vec::new(),
+            mode,
+            speed: 1.0,
+            current: 0,
+            forward: true,
+            elapsed: 0.0,
+            playing: true,
+            entered: false,
+            flip_x: false,
+            flip_y: false,
+            origin: Vector2::zero(),
+            rotation: 0.0,
+            scale: 1.0,
+            tint: Color::WHITE,
+        }
+    }
+
+    /// Loops through every frame of `sheet`.
+    pub fn from_sheet(sheet: &SpriteSheet) -> AnimatedSprite {
+        let indices: Vec<usize> = (0..sheet.frames.len()).collect();
+        AnimatedSprite::new(sheet, &indices, PlayMode::Loop)
+    }
+
+    /// Plays the frames of the tag called `name`, following its direction. Tags with a repeat count play that many
+    /// times then stop. Returns `None` when there's no such tag, or none of its frames are in the sheet.
+    pub fn from_tag(sheet: &SpriteSheet, name: &str) -> Option<AnimatedSprite> {
+        let tag = sheet.tag(name)?;
+        let last = tag.to.min(sheet.frames.len().checked_sub(1)?);
+        let mut indices: Vec<usize> = (tag.from..=last).collect();
+        if indices.is_empty() {
+            return None;
+        }
+        if let AnimationDirection::Reverse | AnimationDirection::PingPongReverse = tag.direction {
+            indices.reverse();
+        }
+        let pingpong = matches!(
+            tag.direction,
+            AnimationDirection::PingPong | AnimationDirection::PingPongReverse
+        );
+        let mode = match (tag.repeat, pingpong) {
+            (None, true) => PlayMode::PingPong,
+            (None, false) => PlayMode::Loop,
+            (Some(repeat), _) => {
+                let single = indices.clone();
+                for pass in 1..repeat {
+                    let mut next = single.clone();
+                    if pingpong {
+                        if pass % 2 == 1 {
+                            next.reverse();
+                        }
+                        // the turning frame was just shown by the previous pass
+                        next.remove(0);
+                    }
+                    indices.extend(next);
+                }
+                PlayMode::Once
+            }
+        };
+        Some(AnimatedSprite::new(sheet, &indices, mode))
+    }
+
+    /// Reports an `AnimationEvent::Frame` called `name` each time the animation enters `frame`, its position in the
+    /// animation.
+    pub fn add_event(&mut self, frame: usize, name: &str) {
+        self.events.push((frame, name.to_string()));
+    }
+
+    pub fn mode(&self) -> PlayMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: PlayMode) {
+        self.mode = mode;
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Scales how fast time passes for the animation. `2.0` plays twice as fast.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn frames(&self) -> &[SpriteFrame] {
+        &self.frames
+    }
+
+    /// Position of the current frame in the animation.
+    pub fn current_frame(&self) -> usize {
+        self.current
+    }
+
+    pub fn frame(&self) -> Option<&SpriteFrame> {
+        self.frames.get(self.current)
+    }
+
+    /// Jumps to `frame`, its position in the animation, at its start.
+    pub fn set_frame(&mut self, frame: usize) {
+        self.current = frame.min(self.frames.len().saturating_sub(1));
+        self.elapsed = 0.0;
+        self.entered = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Whether a `Once` animation reached its end.
+    pub fn is_finished(&self) -> bool {
+        self.mode == PlayMode::Once && !self.playing && self.current + 1 >= self.frames.len()
+    }
+
+    pub fn play(&mut self) {
+        if self.is_finished() {
+            self.restart();
+        }
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Goes back to the first frame and plays.
+    pub fn restart(&mut self) {
+        self.set_frame(0);
+        self.forward = true;
+        self.playing = true;
+    }
+
+    /// Advances the animation by `dt` seconds, returning what happened on the way.
+    pub fn update(&mut self, dt: f32) -> &[AnimationEvent] {
+        self.fired.clear();
+        if !self.playing || self.frames.is_empty() {
+            return &self.fired;
+        }
+        if !self.entered {
+            self.enter();
+        }
+        self.elapsed += dt * self.speed;
+        while self.playing {
+            // frames without a duration still show for an instant, so a zero length loop can't hang
+            let duration = self.frames[self.current].duration.max(0.001);
+            if self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
+            self.advance();
+        }
+        &self.fired
+    }
+
+    fn enter(&mut self) {
+        self.entered = true;
+        let current = self.current;
+        for (frame, name) in &self.events {
+            if *frame == current {
+                self.fired.push(AnimationEvent::Frame {
+                    frame: current,
+                    name: name.clone(),
+                });
+            }
+        }
+    }
+
+    fn advance(&mut self) {
+        let last = self.frames.len() - 1;
+        match self.mode {
+            PlayMode::Loop => {
+                if self.current == last {
+                    self.current = 0;
+                    self.fired.push(AnimationEvent::Looped);
+                } else {
+                    self.current += 1;
+                }
+            }
+            PlayMode::Once => {
+                if self.current == last {
+                    self.playing = false;
+                    self.elapsed = 0.0;
+                    self.fired.push(AnimationEvent::Finished);
+                    return;
+                }
+                self.current += 1;
+            }
+            PlayMode::PingPong => {
+                if last == 0 {
+                    self.fired.push(AnimationEvent::Looped);
+                } else if self.forward {
+                    if self.current >= last {
+                        self.forward = false;
+                        self.current = last - 1;
+                    } else {
+                        self.current += 1;
+                    }
+                } else if self.current == 0 {
+                    self.forward = true;
+                    self.current = 1;
+                } else {
+                    self.current -= 1;
+                    if self.current == 0 {
+                        self.fired.push(AnimationEvent::Looped);
+                    }
+                }
+            }
+        }
+        self.enter();
+    }
+
+    /// Where the current frame goes when drawn with its origin at `position`, or `None` without frames.
+    pub fn draw_params(&self, position: impl Into<Vector2>) -> Option<SpriteDrawParams> {
+        let frame = self.frame()?;
+        let position = position.into();
+        let size = frame.size();
+        // trim offset in the possibly mirrored frame
+        let offset = Vector2::new(
+            if self.flip_x {
+                frame.source_size.x - frame.offset.x - size.x
+            } else {
+                frame.offset.x
+            },
+            if self.flip_y {
+                frame.source_size.y - frame.offset.y - size.y
+            } else {
+                frame.offset.y
+            },
+        );
+        let origin = (self.origin - offset) * self.scale;
+        let mut source = frame.source;
+        let dest = Rectangle::new(
+            position.x,
+            position.y,
+            source.width * self.scale,
+            source.height * self.scale,
+        );
+        if frame.rotated {
+            // the texture holds the frame turned clockwise, so upright x runs along its height
+            if self.flip_x {
+                source.height = -source.height;
+            }
+            if self.flip_y {
+                source.width = -source.width;
+            }
+            Some(SpriteDrawParams {
+                source,
+                dest,
+                origin: Vector2::new(dest.width - origin.y, origin.x),
+                rotation: self.rotation - 90.0,
+            })
+        } else {
+            if self.flip_x {
+                source.width = -source.width;
+            }
+            if self.flip_y {
+                source.height = -source.height;
+            }
+            Some(SpriteDrawParams {
+                source,
+                dest,
+                origin,
+                rotation: self.rotation,
+            })
+        }
+    }
+
+    /// Draws the current frame of `texture` with its origin at `position`.
+    pub fn draw<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        texture: impl AsRef<ffi::Texture2D>,
+        position: impl Into<Vector2>,
+    ) {
+        if let Some(params) = self.draw_params(position) {
+            d.draw_texture_pro(
+                texture,
+                params.source,
+                params.dest,
+                params.origin,
+                params.rotation,
+                self.tint,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod sprite_test {
+    use super::*;
+    use crate::core::recorder::{DrawCommand, DrawRecorder};
+    use crate::core::texture::WeakTexture2D;
+
+    const ASEPRITE: &str = r##"{
+      "frames": {
+        "hero 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false,
+          "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 16 }, "sourceSize": { "w": 16, "h": 16 }, "duration": 100 },
+        "hero 1.aseprite": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false,
+          "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 16 }, "sourceSize": { "w": 16, "h": 16 }, "duration": 200 },
+        "hero 2.aseprite": { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false,
+          "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 16 }, "sourceSize": { "w": 16, "h": 16 }, "duration": 100 }
+      },
+      "meta": {
+        "app": "http://www.aseprite.org/", "image": "hero.png", "size": { "w": 48, "h": 16 },
+        "frameTags": [
+          { "name": "walk", "from": 0, "to": 2, "direction": "pingpong" },
+          { "name": "hit", "from": 1, "to": 2, "direction": "forward", "repeat": "2" },
+          { "name": "bounce", "from": 0, "to": 2, "direction": "pingpong", "repeat": "3" },
+          { "name": "past", "from": 5, "to": 7, "direction": "pingpong", "repeat": "2" }
+        ],
+        "slices": [
+          { "name": "hitbox", "color": "#0000ffff", "keys": [
+            { "frame": 0, "bounds": { "x": 4, "y": 2, "w": 8, "h": 12 } },
+            { "frame": 2, "bounds": { "x": 5, "y": 2, "w": 6, "h": 12 }, "pivot": { "x": 3, "y": 12 } }
+          ] }
+        ]
+      }
+    }"##;
+
+    const TEXTURE_PACKER: &str = r#"{"frames": [
+      { "filename": "coin_01", "frame": {"x":2,"y":2,"w":10,"h":12}, "rotated": false, "trimmed": true,
+        "spriteSourceSize": {"x":3,"y":2,"w":10,"h":12}, "sourceSize": {"w":16,"h":16}, "pivot": {"x":0.5,"y":0.5} },
+      { "filename": "coin_02", "frame": {"x":14,"y":2,"w":10,"h":12}, "rotated": true, "trimmed": true,
+        "spriteSourceSize": {"x":3,"y":2,"w":10,"h":12}, "sourceSize": {"w":16,"h":16} },
+      { "filename": "gem", "frame": {"x":28,"y":2,"w":8,"h":8}, "rotated": false, "trimmed": false }
+    ], "meta": { "app": "https://www.codeandweb.com/texturepacker", "image": "items.png" }}"#;
+
+    #[test]
+    fn test_parse_aseprite() {
+        let sheet = SpriteSheet::from_json(ASEPRITE).unwrap();
+        assert_eq!(sheet.image.as_deref(), Some("hero.png"));
+        assert_eq!(sheet.frames.len(), 3);
+        assert_eq!(
+            sheet.frames[1].source,
+            Rectangle::new(16.0, 0.0, 16.0, 16.0)
+        );
+        assert_eq!(sheet.frames[1].duration, 0.2);
+        assert_eq!(
+            sheet.tag("walk").unwrap().direction,
+            AnimationDirection::PingPong
+        );
+        assert_eq!(sheet.tag("hit").unwrap().repeat, Some(2));
+
+        let hitbox = sheet.slice("hitbox").unwrap();
+        assert_eq!(hitbox.key_at(1).unwrap().bounds.width, 8.0);
+        assert_eq!(
+            hitbox.key_at(2).unwrap().pivot,
+            Some(Vector2::new(3.0, 12.0))
+        );
+    }
+
+    #[test]
+    fn test_parse_texture_packer() {
+        let sheet = SpriteSheet::from_json(TEXTURE_PACKER).unwrap();
+        assert_eq!(sheet.frames_with_prefix("coin_"), vec![0, 1]);
+        let coin = &sheet.frames[1];
+        assert!(coin.rotated);
+        assert_eq!(coin.source, Rectangle::new(14.0, 2.0, 12.0, 10.0));
+        assert_eq!(coin.size(), Vector2::new(10.0, 12.0));
+        assert_eq!(coin.offset, Vector2::new(3.0, 2.0));
+        assert_eq!(coin.duration, DEFAULT_FRAME_DURATION);
+        assert_eq!(sheet.frames[0].pivot, Some(Vector2::new(0.5, 0.5)));
+        assert_eq!(sheet.frames[2].source_size, Vector2::new(8.0, 8.0));
+
+        let hash = r#"{"frames": {"a": {"frame": {"x":0,"y":0,"w":4,"h":4}}}}"#;
+        assert_eq!(SpriteSheet::from_json(hash).unwrap().frames[0].name, "a");
+        assert!(SpriteSheet::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_play_modes() {
+        let sheet = SpriteSheet::from_grid(4, 3, 8.0, 8.0, 0.1);
+        let mut sprite = AnimatedSprite::from_sheet(&sheet);
+        sprite.add_event(0, "start");
+        assert_eq!(
+            sprite.update(0.05),
+            &[AnimationEvent::Frame {
+                frame: 0,
+                name: "start".to_string()
+            }]
+        );
+        assert_eq!(sprite.update(0.2), &[]);
+        assert_eq!(sprite.current_frame(), 2);
+        assert_eq!(
+            sprite.update(0.1),
+            &[
+                AnimationEvent::Looped,
+                AnimationEvent::Frame {
+                    frame: 0,
+                    name: "start".to_string()
+                }
+            ]
+        );
+
+        let mut once = AnimatedSprite::new(&sheet, &[0, 1], PlayMode::Once);
+        once.set_speed(2.0);
+        assert_eq!(once.update(0.1), &[AnimationEvent::Finished]);
+        assert!(once.is_finished());
+        assert_eq!(once.current_frame(), 1);
+        assert_eq!(once.update(1.0), &[]);
+
+        let mut pingpong = AnimatedSprite::new(&sheet, &[0, 1, 2], PlayMode::PingPong);
+        let mut visited = vec![pingpong.current_frame()];
+        for _ in 0..5 {
+            pingpong.update(0.1);
+            visited.push(pingpong.current_frame());
+        }
+        assert_eq!(visited, vec![0, 1, 2, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_tags() {
+        let sheet = SpriteSheet::from_json(ASEPRITE).unwrap();
+        let walk = AnimatedSprite::from_tag(&sheet, "walk").unwrap();
+        assert_eq!(walk.mode(), PlayMode::PingPong);
+        let hit = AnimatedSprite::from_tag(&sheet, "hit").unwrap();
+        assert_eq!(hit.mode(), PlayMode::Once);
+        let names: Vec<&str> = hit.frames().iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "hero 1.aseprite",
+                "hero 2.aseprite",
+                "hero 1.aseprite",
+                "hero 2.aseprite"
+            ]
+        );
+
+        // each pass of a repeated ping-pong starts from where the last one turned
+        let bounce = AnimatedSprite::from_tag(&sheet, "bounce").unwrap();
+        assert_eq!(bounce.mode(), PlayMode::Once);
+        let names: Vec<&str> = bounce.frames().iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "hero 0.aseprite",
+                "hero 1.aseprite",
+                "hero 2.aseprite",
+                "hero 1.aseprite",
+                "hero 0.aseprite",
+                "hero 1.aseprite",
+                "hero 2.aseprite"
+            ]
+        );
+        assert!(AnimatedSprite::from_tag(&sheet, "missing").is_none());
+        assert!(AnimatedSprite::from_tag(&sheet, "past").is_none());
+    }
+
+    #[test]
+    fn test_draw_params() {
+        let sheet = SpriteSheet::from_json(TEXTURE_PACKER).unwrap();
+        let mut sprite = AnimatedSprite::new(&sheet, &[0, 1], PlayMode::Loop);
+        sprite.origin = Vector2::new(8.0, 16.0);
+        sprite.scale = 2.0;
+        let params = sprite.draw_params(Vector2::new(100.0, 50.0)).unwrap();
+        assert_eq!(params.source, Rectangle::new(2.0, 2.0, 10.0, 12.0));
+        assert_eq!(params.dest, Rectangle::new(100.0, 50.0, 20.0, 24.0));
+        assert_eq!(params.origin, Vector2::new(10.0, 28.0));
+
+        sprite.flip_x = true;
+        let params = sprite.draw_params(Vector2::new(100.0, 50.0)).unwrap();
+        assert_eq!(params.source.width, -10.0);
+        assert_eq!(params.origin, Vector2::new(10.0, 28.0));
+
+        // a rotated frame is drawn turned back a quarter turn
+        sprite.flip_x = false;
+        sprite.set_frame(1);
+        let params = sprite.draw_params(Vector2::new(100.0, 50.0)).unwrap();
+        assert_eq!(params.rotation, -90.0);
+        assert_eq!(params.dest, Rectangle::new(100.0, 50.0, 24.0, 20.0));
+        assert_eq!(params.origin, Vector2::new(24.0 - 28.0, 10.0));
+
+        let texture = unsafe {
+            WeakTexture2D::from_raw(ffi::Texture2D {
+                id: 1,
+                width: 48,
+                height: 16,
+                mipmaps: 1,
+                format: 7,
+            })
+        };
+        let mut recorder = DrawRecorder::new();
+        sprite.draw(&mut recorder, &texture, Vector2::new(100.0, 50.0));
+        match &recorder.commands()[0] {
+            DrawCommand::Texture {
+                source_rec,
+                rotation,
+                ..
+            } => {
+                assert_eq!(*source_rec, params.source);
+                assert_eq!(*rotation, -90.0);
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+}
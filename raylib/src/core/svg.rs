@@ -0,0 +1,1078 @@
+//! Exporting recorded 2D drawing as SVG.
+//!
+//! Draw into a [`DrawRecorder`] as usual, then turn the recorded commands into an SVG document. 2D mode becomes a
+//! group transform, scissor mode a clip path and textures are embedded as base64 PNG images.
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//!
+//! let mut recorder = DrawRecorder::new();
+//! recorder.clear_background(Color::RAYWHITE);
+//! recorder.draw_circle(100, 100, 50.0, Color::MAROON);
+//! recorder.draw_text("vector!", 60, 170, 20, Color::DARKGRAY);
+//! recorder.export_svg("circle.svg", 200, 200).expect("couldn't write svg");
+//! ```
+//!
+//! Embedding a texture reads it back from the GPU, so exporting a recording containing textures needs the window
+//! that loaded them. 3D commands have no SVG equivalent and are skipped.
+//!
+//! [`DrawRecorder`]: ../recorder/struct.DrawRecorder.html
+use crate::consts::BlendMode;
+use crate::core::camera::Camera2D;
+use crate::core::checksum::{adler32, crc32};
+use crate::core::color::Color;
+use crate::core::math::{Rectangle, Vector2};
+use crate::core::raster::poly_points;
+use crate::core::recorder::{DrawCommand, DrawRecorder};
+use crate::core::texture::{RaylibTexture2D, WeakTexture2D};
+use crate::ffi;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+impl DrawRecorder {
+    /// Renders the recorded commands as an SVG document of `width` by `height` pixels.
+    pub fn to_svg(&self, width: i32, height: i32) -> String {
+        SvgWriter::new(width, height).finish(self.commands())
+    }
+
+    /// Writes the recorded commands to `filename` as an SVG document.
+    pub fn export_svg(&self, filename: &str, width: i32, height: i32) -> Result<(), String> {
+        std::fs::write(filename, self.to_svg(width, height))
+            .map_err(|e| format!("failed to write {}: {}", filename, e))
+    }
+}
+
+enum Group {
+    Mode2D,
+    Scissor,
+    Blend,
+}
+
+struct SvgWriter {
+    width: i32,
+    height: i32,
+    defs: String,
+    body: String,
+    next_id: u32,
+    textures: HashSet<u32>,
+    tints: HashSet<[u8; 4]>,
+    groups: Vec<Group>,
+    camera: Option<Camera2D>,
+}
+
+/// Formats a coordinate without trailing zeroes.
+fn num(v: f32) -> String {
+    let rounded = (v * 1000.0).round() / 1000.0;
+    if rounded == 0.0 {
+        // avoids printing -0
+        "0".to_owned()
+    } else {
+        format!("{}", rounded)
+    }
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+fn fill(color: Color) -> String {
+    if color.a == 255 {
+        format!("fill=\"{}\"", hex(color))
+    } else {
+        format!(
+            "fill=\"{}\" fill-opacity=\"{}\"",
+            hex(color),
+            num(color.a as f32 / 255.0)
+        )
+    }
+}
+
+fn stroke(color: Color, width: f32) -> String {
+    let mut s = format!(
+        "fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"",
+        hex(color),
+        num(width)
+    );
+    if color.a != 255 {
+        write!(s, " stroke-opacity=\"{}\"", num(color.a as f32 / 255.0)).unwrap();
+    }
+    s
+}
+
+fn stop(offset: &str, color: Color) -> String {
+    format!(
+        "<stop offset=\"{}\" stop-color=\"{}\" stop-opacity=\"{}\"/>",
+        offset,
+        hex(color),
+        num(color.a as f32 / 255.0)
+    )
+}
+
+fn points(points: &[Vector2]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{},{}", num(p.x), num(p.y)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Point on a circle the way raylib's shapes module measures angles: 0 degrees points down, increasing towards
+/// the right.
+fn arc_point(center: Vector2, radius: f32, angle: i32) -> Vector2 {
+    let a = (angle as f32).to_radians();
+    Vector2::new(center.x + a.sin() * radius, center.y + a.cos() * radius)
+}
+
+/// Path data of an arc from `start_angle` to `end_angle`, starting with a move or a line to its first point.
+fn arc(center: Vector2, radius: f32, start_angle: i32, end_angle: i32, first: char) -> String {
+    let (from, to) = (
+        arc_point(center, radius, start_angle),
+        arc_point(center, radius, end_angle),
+    );
+    // raylib's angles go counter-clockwise on screen, the opposite of svg's positive sweep
+    let sweep = if end_angle >= start_angle { 0 } else { 1 };
+    let large = if (end_angle - start_angle).abs() > 180 {
+        1
+    } else {
+        0
+    };
+    format!(
+        "{} {} {} A {} {} 0 {} {} {} {}",
+        first,
+        num(from.x),
+        num(from.y),
+        num(radius),
+        num(radius),
+        large,
+        sweep,
+        num(to.x),
+        num(to.y)
+    )
+}
+
+/// Path data of a circle sector or ring section. Full turns are split in two arcs, as a single arc can't end where
+/// it starts.
+fn sector(center: Vector2, inner: f32, outer: f32, start_angle: i32, end_angle: i32) -> String {
+    if (end_angle - start_angle).abs() >= 360 {
+        let mid = start_angle + 180;
+        let mut d = format!(
+            "{} {} Z",
+            arc(center, outer, start_angle, mid, 'M'),
+            arc(center, outer, mid, start_angle + 360, 'L')
+        );
+        if inner > 0.0 {
+            write!(
+                d,
+                " {} {} Z",
+                arc(center, inner, start_angle, mid, 'M'),
+                arc(center, inner, mid, start_angle + 360, 'L')
+            )
+            .unwrap();
+        }
+        return d;
+    }
+    let outer_arc = arc(center, outer, start_angle, end_angle, 'M');
+    if inner > 0.0 {
+        format!(
+            "{} {} Z",
+            outer_arc,
+            arc(center, inner, end_angle, start_angle, 'L')
+        )
+    } else {
+        format!("{} L {} {} Z", outer_arc, num(center.x), num(center.y))
+    }
+}
+
+/// The transform that maps world coordinates to the screen under `camera`, as in `get_camera_matrix_2D`.
+fn camera_transform(camera: &Camera2D) -> String {
+    format!(
+        "translate({} {}) rotate({}) scale({}) translate({} {})",
+        num(camera.offset.x),
+        num(camera.offset.y),
+        num(camera.rotation),
+        num(camera.zoom),
+        num(-camera.target.x),
+        num(-camera.target.y)
+    )
+}
+
+fn inverse_camera_transform(camera: &Camera2D) -> String {
+    let zoom = if camera.zoom != 0.0 { camera.zoom } else { 1.0 };
+    format!(
+        "translate({} {}) scale({}) rotate({}) translate({} {})",
+        num(camera.target.x),
+        num(camera.target.y),
+        num(1.0 / zoom),
+        num(-camera.rotation),
+        num(-camera.offset.x),
+        num(-camera.offset.y)
+    )
+}
+
+impl SvgWriter {
+    fn new(width: i32, height: i32) -> SvgWriter {
+        SvgWriter {
+            width,
+            height,
+            defs: String::new(),
+            body: String::new(),
+            next_id: 0,
+            textures: HashSet::new(),
+            tints: HashSet::new(),
+            groups: Vec::new(),
+            camera: None,
+        }
+    }
+
+    fn id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}{}", prefix, self.next_id)
+    }
+
+    fn element(&mut self, element: String) {
+        self.body.push_str(&element);
+        self.body.push('\n');
+    }
+
+    fn finish(mut self, commands: &[DrawCommand]) -> String {
+        for command in commands {
+            self.command(command);
+        }
+        while self.groups.pop().is_some() {
+            self.body.push_str("</g>\n");
+        }
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = self.width,
+            h = self.height
+        );
+        if !self.defs.is_empty() {
+            write!(svg, "<defs>\n{}</defs>\n", self.defs).unwrap();
+        }
+        svg.push_str(&self.body);
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn open_group(&mut self, group: Group, attributes: String) {
+        self.element(format!("<g {}>", attributes));
+        self.groups.push(group);
+    }
+
+    fn close_group(&mut self) {
+        if let Some(group) = self.groups.pop() {
+            if let Group::Mode2D = group {
+                self.camera = None;
+            }
+            self.element("</g>".to_owned());
+        }
+    }
+
+    /// Defines a linear gradient going from `from` to `to` in the bounding box of the shape, returning its id.
+    fn linear_gradient(&mut self, from: Color, to: Color, horizontal: bool) -> String {
+        let id = self.id("gradient");
+        let end = if horizontal {
+            "x2=\"1\" y2=\"0\""
+        } else {
+            "x2=\"0\" y2=\"1\""
+        };
+        writeln!(
+            self.defs,
+            "<linearGradient id=\"{}\" x1=\"0\" y1=\"0\" {}>{}{}</linearGradient>",
+            id,
+            end,
+            stop("0", from),
+            stop("1", to)
+        )
+        .unwrap();
+        id
+    }
+
+    /// Makes sure the pixels of `texture` are in the document, returning the id of the image.
+    fn texture(&mut self, texture: &ffi::Texture2D) -> Option<String> {
+        let id = format!("texture{}", texture.id);
+        if self.textures.contains(&texture.id) {
+            return Some(id);
+        }
+        let image = WeakTexture2D(*texture).get_texture_data().ok()?;
+        let pixels = image.get_image_data();
+        let png = encode_png(image.width(), image.height(), &pixels);
+        writeln!(
+            self.defs,
+            "<image id=\"{}\" width=\"{}\" height=\"{}\" xlink:href=\"data:image/png;base64,{}\"/>",
+            id,
+            image.width(),
+            image.height(),
+            base64(&png)
+        )
+        .unwrap();
+        self.textures.insert(texture.id);
+        Some(id)
+    }
+
+    /// Defines a filter multiplying colors by `tint`, returning its id.
+    fn tint(&mut self, tint: Color) -> String {
+        let key = [tint.r, tint.g, tint.b, tint.a];
+        let id = format!(
+            "tint{:02x}{:02x}{:02x}{:02x}",
+            tint.r, tint.g, tint.b, tint.a
+        );
+        if self.tints.insert(key) {
+            let c = |v: u8| num(v as f32 / 255.0);
+            writeln!(
+                self.defs,
+                "<filter id=\"{}\"><feColorMatrix type=\"matrix\" values=\"{} 0 0 0 0 0 {} 0 0 0 0 0 {} 0 0 0 0 0 {} 0\"/></filter>",
+                id,
+                c(tint.r),
+                c(tint.g),
+                c(tint.b),
+                c(tint.a)
+            )
+            .unwrap();
+        }
+        id
+    }
+
+    /// Draws `source` of a texture stretched over `dest`, rotated around `origin` like `draw_texture_pro`.
+    fn texture_pro(
+        &mut self,
+        texture: &ffi::Texture2D,
+        source: Rectangle,
+        dest: Rectangle,
+        origin: Vector2,
+        rotation: f32,
+        tint: Color,
+    ) {
+        let id = match self.texture(texture) {
+            Some(id) => id,
+            None => {
+                self.element(format!("<!-- texture {} unavailable -->", texture.id));
+                return;
+            }
+        };
+        if source.width == 0.0 || source.height == 0.0 {
+            return;
+        }
+        // negative source sizes flip the image
+        let (sx, sy) = (source.width.signum(), source.height.signum());
+        let (sw, sh) = (source.width.abs(), source.height.abs());
+        let mut transform = format!(
+            "translate({} {}) rotate({}) translate({} {}) scale({} {})",
+            num(dest.x),
+            num(dest.y),
+            num(rotation),
+            num(-origin.x),
+            num(-origin.y),
+            num(dest.width / sw),
+            num(dest.height / sh)
+        );
+        if sx < 0.0 || sy < 0.0 {
+            write!(
+                transform,
+                " translate({} {}) scale({} {})",
+                num(if sx < 0.0 { sw } else { 0.0 }),
+                num(if sy < 0.0 { sh } else { 0.0 }),
+                num(sx),
+                num(sy)
+            )
+            .unwrap();
+        }
+        let filter = if tint != Color::WHITE {
+            format!(" filter=\"url(#{})\"", self.tint(tint))
+        } else {
+            String::new()
+        };
+        self.element(format!(
+            "<g transform=\"{}\"{}><svg width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\"><use xlink:href=\"#{}\"/></svg></g>",
+            transform,
+            filter,
+            num(sw),
+            num(sh),
+            num(source.x),
+            num(source.y),
+            num(sw),
+            num(sh),
+            id
+        ));
+    }
+
+    fn text(&mut self, text: &str, position: Vector2, font_size: f32, spacing: f32, color: Color) {
+        // raylib advances lines by one and a half times the font size
+        let mut element = format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" letter-spacing=\"{}\" dominant-baseline=\"hanging\" {}>",
+            num(position.x),
+            num(position.y),
+            num(font_size),
+            num(spacing),
+            fill(color)
+        );
+        for (i, line) in text.split('\n').enumerate() {
+            write!(
+                element,
+                "<tspan x=\"{}\" dy=\"{}\">{}</tspan>",
+                num(position.x),
+                num(if i == 0 { 0.0 } else { font_size * 1.5 }),
+                escape(line)
+            )
+            .unwrap();
+        }
+        element.push_str("</text>");
+        self.element(element);
+    }
+
+    fn command(&mut self, command: &DrawCommand) {
+        use DrawCommand::*;
+        match command {
+            ClearBackground(color) => {
+                if self.groups.is_empty() {
+                    // nothing drawn before a clear can be seen
+                    self.body.clear();
+                }
+                let element = format!("<rect width=\"100%\" height=\"100%\" {}/>", fill(*color));
+                self.element(element);
+            }
+            SetShapesTexture { .. } => {}
+            Pixel { position, color } => self.element(format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" {}/>",
+                num(position.x),
+                num(position.y),
+                fill(*color)
+            )),
+            Line { start, end, color } => self.element(format!(
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}/>",
+                num(start.x),
+                num(start.y),
+                num(end.x),
+                num(end.y),
+                stroke(*color, 1.0)
+            )),
+            LineEx {
+                start,
+                end,
+                thick,
+                color,
+            } => self.element(format!(
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}/>",
+                num(start.x),
+                num(start.y),
+                num(end.x),
+                num(end.y),
+                stroke(*color, *thick)
+            )),
+            LineBezier {
+                start,
+                end,
+                thick,
+                color,
+            } => {
+                // raylib eases the vertical position in and out, which a cubic with horizontal tangents matches
+                let mid = (start.x + end.x) / 2.0;
+                self.element(format!(
+                    "<path d=\"M {} {} C {} {} {} {} {} {}\" {}/>",
+                    num(start.x),
+                    num(start.y),
+                    num(mid),
+                    num(start.y),
+                    num(mid),
+                    num(end.y),
+                    num(end.x),
+                    num(end.y),
+                    stroke(*color, *thick)
+                ))
+            }
+            LineStrip { points: p, color } => self.element(format!(
+                "<polyline points=\"{}\" {}/>",
+                points(p),
+                stroke(*color, 1.0)
+            )),
+            Circle {
+                center,
+                radius,
+                color,
+            } => self.element(format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>",
+                num(center.x),
+                num(center.y),
+                num(*radius),
+                fill(*color)
+            )),
+            CircleSector {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+                color,
+                ..
+            } => self.element(format!(
+                "<path d=\"{}\" {}/>",
+                sector(*center, 0.0, *radius, *start_angle, *end_angle),
+                fill(*color)
+            )),
+            CircleSectorLines {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+                color,
+                ..
+            } => self.element(format!(
+                "<path d=\"{}\" {}/>",
+                sector(*center, 0.0, *radius, *start_angle, *end_angle),
+                stroke(*color, 1.0)
+            )),
+            CircleGradient {
+                center_x,
+                center_y,
+                radius,
+                inner,
+                outer,
+            } => {
+                let id = self.id("gradient");
+                writeln!(
+                    self.defs,
+                    "<radialGradient id=\"{}\">{}{}</radialGradient>",
+                    id,
+                    stop("0", *inner),
+                    stop("1", *outer)
+                )
+                .unwrap();
+                self.element(format!(
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"url(#{})\"/>",
+                    center_x,
+                    center_y,
+                    num(*radius),
+                    id
+                ))
+            }
+            CircleLines {
+                center_x,
+                center_y,
+                radius,
+                color,
+            } => self.element(format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>",
+                center_x,
+                center_y,
+                num(*radius),
+                stroke(*color, 1.0)
+            )),
+            Ellipse {
+                center_x,
+                center_y,
+                radius_h,
+                radius_v,
+                color,
+            } => self.element(format!(
+                "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" {}/>",
+                center_x,
+                center_y,
+                num(*radius_h),
+                num(*radius_v),
+                fill(*color)
+            )),
+            EllipseLines {
+                center_x,
+                center_y,
+                radius_h,
+                radius_v,
+                color,
+            } => self.element(format!(
+                "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" {}/>",
+                center_x,
+                center_y,
+                num(*radius_h),
+                num(*radius_v),
+                stroke(*color, 1.0)
+            )),
+            Ring {
+                center,
+                inner_radius,
+                outer_radius,
+                start_angle,
+                end_angle,
+                color,
+                ..
+            } => self.element(format!(
+                "<path d=\"{}\" fill-rule=\"evenodd\" {}/>",
+                sector(
+                    *center,
+                    *inner_radius,
+                    *outer_radius,
+                    *start_angle,
+                    *end_angle
+                ),
+                fill(*color)
+            )),
+            RingLines {
+                center,
+                inner_radius,
+                outer_radius,
+                start_angle,
+                end_angle,
+                color,
+                ..
+            } => self.element(format!(
+                "<path d=\"{}\" {}/>",
+                sector(
+                    *center,
+                    *inner_radius,
+                    *outer_radius,
+                    *start_angle,
+                    *end_angle
+                ),
+                stroke(*color, 1.0)
+            )),
+            Rectangle { rec, color } => self.element(format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+                num(rec.x),
+                num(rec.y),
+                num(rec.width),
+                num(rec.height),
+                fill(*color)
+            )),
+            RectanglePro {
+                rec,
+                origin,
+                rotation,
+                color,
+            } => self.element(format!(
+                "<rect transform=\"translate({} {}) rotate({}) translate({} {})\" width=\"{}\" height=\"{}\" {}/>",
+                num(rec.x),
+                num(rec.y),
+                num(*rotation),
+                num(-origin.x),
+                num(-origin.y),
+                num(rec.width),
+                num(rec.height),
+                fill(*color)
+            )),
+            RectangleGradient { rec, colors } => {
+                let [top_left, bottom_left, bottom_right, top_right] = *colors;
+                // svg gradients are linear, so four distinct corners are approximated by the average of each side
+                let id = if top_left == top_right && bottom_left == bottom_right {
+                    self.linear_gradient(top_left, bottom_left, false)
+                } else if top_left == bottom_left && top_right == bottom_right {
+                    self.linear_gradient(top_left, top_right, true)
+                } else {
+                    let average = |a: Color, b: Color| {
+                        Color::new(
+                            ((a.r as u16 + b.r as u16) / 2) as u8,
+                            ((a.g as u16 + b.g as u16) / 2) as u8,
+                            ((a.b as u16 + b.b as u16) / 2) as u8,
+                            ((a.a as u16 + b.a as u16) / 2) as u8,
+                        )
+                    };
+                    self.linear_gradient(
+                        average(top_left, top_right),
+                        average(bottom_left, bottom_right),
+                        false,
+                    )
+                };
+                self.element(format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"url(#{})\"/>",
+                    num(rec.x),
+                    num(rec.y),
+                    num(rec.width),
+                    num(rec.height),
+                    id
+                ))
+            }
+            RectangleLines {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => self.element(format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+                num(*x as f32 + 0.5),
+                num(*y as f32 + 0.5),
+                num(*width as f32 - 1.0),
+                num(*height as f32 - 1.0),
+                stroke(*color, 1.0)
+            )),
+            RectangleLinesEx {
+                rec,
+                line_thick,
+                color,
+            } => {
+                // the lines are drawn inside the rectangle
+                let half = *line_thick as f32 / 2.0;
+                self.element(format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+                    num(rec.x + half),
+                    num(rec.y + half),
+                    num(rec.width - 2.0 * half),
+                    num(rec.height - 2.0 * half),
+                    stroke(*color, *line_thick as f32)
+                ))
+            }
+            RectangleRounded {
+                rec,
+                roundness,
+                color,
+                ..
+            } => {
+                let radius = roundness.clamp(0.0, 1.0) * rec.width.min(rec.height) / 2.0;
+                self.element(format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" {}/>",
+                    num(rec.x),
+                    num(rec.y),
+                    num(rec.width),
+                    num(rec.height),
+                    num(radius),
+                    fill(*color)
+                ))
+            }
+            RectangleRoundedLines {
+                rec,
+                roundness,
+                line_thickness,
+                color,
+                ..
+            } => {
+                // the lines are drawn outside the rectangle
+                let radius = roundness.clamp(0.0, 1.0) * rec.width.min(rec.height) / 2.0;
+                let half = *line_thickness as f32 / 2.0;
+                self.element(format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" {}/>",
+                    num(rec.x - half),
+                    num(rec.y - half),
+                    num(rec.width + 2.0 * half),
+                    num(rec.height + 2.0 * half),
+                    num(radius + half),
+                    stroke(*color, *line_thickness as f32)
+                ))
+            }
+            Triangle { v1, v2, v3, color } => self.element(format!(
+                "<polygon points=\"{}\" {}/>",
+                points(&[*v1, *v2, *v3]),
+                fill(*color)
+            )),
+            TriangleLines { v1, v2, v3, color } => self.element(format!(
+                "<polygon points=\"{}\" {}/>",
+                points(&[*v1, *v2, *v3]),
+                stroke(*color, 1.0)
+            )),
+            TriangleFan { points: p, color } => self.element(format!(
+                "<polygon points=\"{}\" {}/>",
+                points(p),
+                fill(*color)
+            )),
+            TriangleStrip { points: p, color } => {
+                for triangle in p.windows(3) {
+                    self.element(format!(
+                        "<polygon points=\"{}\" {}/>",
+                        points(triangle),
+                        fill(*color)
+                    ));
+                }
+            }
+            Poly {
+                center,
+                sides,
+                radius,
+                rotation,
+                color,
+            } => self.element(format!(
+                "<polygon points=\"{}\" {}/>",
+                points(&poly_points(*center, *sides, *radius, *rotation)),
+                fill(*color)
+            )),
+            PolyLines {
+                center,
+                sides,
+                radius,
+                rotation,
+                color,
+            } => self.element(format!(
+                "<polygon points=\"{}\" {}/>",
+                points(&poly_points(*center, *sides, *radius, *rotation)),
+                stroke(*color, 1.0)
+            )),
+            Texture {
+                texture,
+                source_rec,
+                dest_rec,
+                origin,
+                rotation,
+                tint,
+            } => self.texture_pro(texture, *source_rec, *dest_rec, *origin, *rotation, *tint),
+            TextureQuad {
+                texture,
+                quad,
+                tint,
+                ..
+            } => {
+                // tiling and offset aren't kept, the whole texture is stretched over the quad
+                let source = crate::core::math::Rectangle::new(
+                    0.0,
+                    0.0,
+                    texture.width as f32,
+                    texture.height as f32,
+                );
+                self.texture_pro(texture, source, *quad, Vector2::zero(), 0.0, *tint)
+            }
+            TextureNPatch {
+                texture,
+                n_patch_info,
+                dest_rec,
+                origin,
+                rotation,
+                tint,
+            } => {
+                // borders aren't kept, the source is stretched over the destination
+                self.texture_pro(
+                    texture,
+                    n_patch_info.source_rec,
+                    *dest_rec,
+                    *origin,
+                    *rotation,
+                    *tint,
+                )
+            }
+            Fps { .. } => {}
+            Text {
+                text,
+                x,
+                y,
+                font_size,
+                color,
+            } => {
+                // the default font is spaced by a tenth of its size
+                let size = (*font_size).max(10) as f32;
+                self.text(
+                    text,
+                    Vector2::new(*x as f32, *y as f32),
+                    size,
+                    size / 10.0,
+                    *color,
+                )
+            }
+            TextEx {
+                text,
+                position,
+                font_size,
+                spacing,
+                tint,
+                ..
+            } => self.text(text, *position, *font_size, *spacing, *tint),
+            TextRec {
+                text,
+                rec,
+                font_size,
+                spacing,
+                tint,
+                ..
+            } => {
+                let id = self.id("clip");
+                writeln!(
+                    self.defs,
+                    "<clipPath id=\"{}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>",
+                    id,
+                    num(rec.x),
+                    num(rec.y),
+                    num(rec.width),
+                    num(rec.height)
+                )
+                .unwrap();
+                self.element(format!("<g clip-path=\"url(#{})\">", id));
+                self.text(
+                    text,
+                    Vector2::new(rec.x, rec.y),
+                    *font_size,
+                    *spacing,
+                    *tint,
+                );
+                self.element("</g>".to_owned());
+            }
+            TextCodepoint {
+                codepoint,
+                position,
+                scale,
+                tint,
+                ..
+            } => {
+                if let Some(c) = std::char::from_u32(*codepoint as u32) {
+                    self.text(&c.to_string(), *position, *scale, 0.0, *tint);
+                }
+            }
+            BeginMode2D(camera) => {
+                self.open_group(
+                    Group::Mode2D,
+                    format!("transform=\"{}\"", camera_transform(camera)),
+                );
+                self.camera = Some(*camera);
+            }
+            BeginScissorMode {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                // scissor areas are in screen space, so undo the camera if one is active
+                let transform = self
+                    .camera
+                    .as_ref()
+                    .map(|camera| format!(" transform=\"{}\"", inverse_camera_transform(camera)))
+                    .unwrap_or_default();
+                let id = self.id("clip");
+                writeln!(
+                    self.defs,
+                    "<clipPath id=\"{}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{}/></clipPath>",
+                    id, x, y, width, height, transform
+                )
+                .unwrap();
+                self.open_group(Group::Scissor, format!("clip-path=\"url(#{})\"", id));
+            }
+            BeginBlendMode(mode) => {
+                let blend = match mode {
+                    BlendMode::BLEND_ALPHA => "normal",
+                    BlendMode::BLEND_ADDITIVE => "plus-lighter",
+                    BlendMode::BLEND_MULTIPLIED => "multiply",
+                };
+                self.open_group(
+                    Group::Blend,
+                    format!("style=\"mix-blend-mode:{}\"", blend),
+                );
+            }
+            EndMode2D | EndScissorMode | EndBlendMode => self.close_group(),
+            // 3D has no svg equivalent
+            _ => {}
+        }
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        out.push(BASE64[(n >> 18) as usize & 63] as char);
+        out.push(BASE64[(n >> 12) as usize & 63] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64[(n >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            BASE64[n as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Encodes RGBA pixels as a PNG. The image data is stored without compression, which keeps the encoder small at
+/// the cost of larger documents.
+fn encode_png(width: i32, height: i32, pixels: &[Color]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(pixels.len() * 4 + height as usize);
+    for row in pixels.chunks(width.max(1) as usize) {
+        // filter type: none
+        raw.push(0);
+        for p in row {
+            raw.extend_from_slice(&[p.r, p.g, p.b, p.a]);
+        }
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = raw.chunks(65535);
+    let count = blocks.len();
+    for (i, block) in blocks.enumerate() {
+        zlib.push(if i + 1 == count { 1 } else { 0 });
+        let len = block.len() as u16;
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    if count == 0 {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8 bits per channel, RGBA, default compression, filtering and no interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png_chunk(&mut png, b"IHDR", &header);
+    png_chunk(&mut png, b"IDAT", &zlib);
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+#[cfg(test)]
+mod svg_test {
+    use super::*;
+    use crate::core::drawing::RaylibDraw;
+
+    #[test]
+    fn test_svg_export() {
+        let mut recorder = DrawRecorder::new();
+        recorder.draw_circle(5, 5, 1.0, Color::RED);
+        recorder.clear_background(Color::WHITE);
+        {
+            let mut camera = recorder.begin_mode2D(Camera2D {
+                offset: Vector2::new(10.0, 20.0),
+                target: Vector2::zero(),
+                rotation: 0.0,
+                zoom: 2.0,
+            });
+            let mut scissor = camera.begin_scissor_mode(0, 0, 50, 40);
+            scissor.draw_rectangle(1, 2, 3, 4, Color::new(0, 0, 255, 128));
+            scissor.draw_text("a < b", 0, 0, 20, Color::BLACK);
+        }
+        let svg = recorder.to_svg(100, 80);
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("viewBox=\"0 0 100 80\""));
+        // cleared away
+        assert!(!svg.contains("<circle"));
+        assert!(svg.contains("<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>"));
+        assert!(
+            svg.contains("<g transform=\"translate(10 20) rotate(0) scale(2) translate(0 0)\">")
+        );
+        assert!(svg.contains("<clipPath id=\"clip1\"><rect x=\"0\" y=\"0\" width=\"50\" height=\"40\" transform=\"translate(0 0) scale(0.5) rotate(0) translate(-10 -20)\"/></clipPath>"));
+        assert!(svg.contains("<rect x=\"1\" y=\"2\" width=\"3\" height=\"4\" fill=\"#0000ff\" fill-opacity=\"0.502\"/>"));
+        assert!(svg.contains(">a &lt; b</tspan>"));
+        assert_eq!(svg.matches("<g ").count(), svg.matches("</g>").count());
+    }
+
+    #[test]
+    fn test_png_encoding() {
+        assert_eq!(base64(b"raylib"), "cmF5bGli");
+        assert_eq!(base64(b"ray"), "cmF5");
+        assert_eq!(base64(b"ra"), "cmE=");
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+
+        let png = encode_png(2, 1, &[Color::RED, Color::BLANK]);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+    }
+}
@@ -217,6 +217,59 @@ impl Image {
         }
     }
 
+    /// Creates an R8G8B8A8 image holding a copy of `pixels`, without going through raylib.
+    pub(crate) fn from_colors(width: i32, height: i32, pixels: &[Color]) -> Image {
+        assert_eq!(pixels.len(), (width * height) as usize, "wrong pixel count");
+        unsafe {
+            let data =
+                libc::malloc(pixels.len().max(1) * std::mem::size_of::<Color>()) as *mut Color;
+            std::ptr::copy_nonoverlapping(pixels.as_ptr(), data, pixels.len());
+            Image(ffi::Image {
+                data: data as *mut std::os::raw::c_void,
+                width,
+                height,
+                mipmaps: 1,
+                format: crate::consts::PixelFormat::UNCOMPRESSED_R8G8B8A8 as i32,
+            })
+        }
+    }
+
+    /// Pixels of the image as colors, read in place when the image is already R8G8B8A8.
+    pub(crate) fn colors(&self) -> Vec<Color> {
+        if self.format() == crate::consts::PixelFormat::UNCOMPRESSED_R8G8B8A8 {
+            let len = (self.width * self.height) as usize;
+            unsafe { std::slice::from_raw_parts(self.data as *const Color, len).to_vec() }
+        } else {
+            self.get_image_data()
+        }
+    }
+
+    /// Replaces the pixels and size of the image, keeping its pixel format. Mipmaps are dropped.
+    pub(crate) fn set_colors(&mut self, width: i32, height: i32, pixels: &[Color]) {
+        let format = self.format();
+        if format == crate::consts::PixelFormat::UNCOMPRESSED_R8G8B8A8
+            && (self.width, self.height, self.mipmaps) == (width, height, 1)
+        {
+            assert_eq!(pixels.len(), (width * height) as usize, "wrong pixel count");
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    pixels.as_ptr(),
+                    self.data as *mut Color,
+                    pixels.len(),
+                );
+            }
+            return;
+        }
+        let replacement = Image::from_colors(width, height, pixels);
+        unsafe {
+            libc::free(self.0.data);
+            self.0 = replacement.unwrap();
+        }
+        if format != crate::consts::PixelFormat::UNCOMPRESSED_R8G8B8A8 {
+            self.set_format(format);
+        }
+    }
+
     /// Extract color palette from image to maximum size
     #[inline]
     pub fn extract_palette(&self, max_palette_size: u32) -> Vec<Color> {
@@ -468,6 +521,31 @@ impl Image {
         }
     }
 
+    /// Draws a rectangle within an image (Vector version).
+    #[inline]
+    pub fn draw_rectangle_v(
+        &mut self,
+        position: impl Into<ffi::Vector2>,
+        size: impl Into<ffi::Vector2>,
+        color: impl Into<ffi::Color>,
+    ) {
+        unsafe {
+            ffi::ImageDrawRectangleV(&mut self.0, position.into(), size.into(), color.into());
+        }
+    }
+
+    /// Draws a rectangle within an image.
+    #[inline]
+    pub fn draw_rectangle_rec(
+        &mut self,
+        rec: impl Into<ffi::Rectangle>,
+        color: impl Into<ffi::Color>,
+    ) {
+        unsafe {
+            ffi::ImageDrawRectangleRec(&mut self.0, rec.into(), color.into());
+        }
+    }
+
     /// Draws a rectangle within an image.
     #[inline]
     pub fn draw_rectangle_lines(
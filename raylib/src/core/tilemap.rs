@@ -0,0 +1,1972 @@
+//! Loading and drawing maps made with the [Tiled](https://www.mapeditor.org/) editor
+//!
+//! [`TileMap::load`] reads `.tmx` maps and `.json` maps, along with the external tilesets they use. Orthogonal,
+//! isometric, staggered and hexagonal maps are supported, with any number of tilesets, tile, object, image and group
+//! layers, flipped tiles, animated tiles and custom properties. Layer data can be stored as CSV or as Base64, either
+//! uncompressed or compressed with zlib or gzip. Zstandard compressed layers are not supported, and maps must not be
+//! infinite.
+//!
+//! Tile layers draw with [`TileMap::draw`], which only draws the tiles inside the visible area of a [`Camera2D`] and
+//! groups draws by tileset texture where the map allows it:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+//! let map = TileMap::load("level1.tmx")?;
+//! let textures = map.load_textures(rl, thread)?;
+//! let walls = map.layer("collision").and_then(Layer::objects).map(|o| o.rectangles());
+//! let camera = Camera2D {
+//!     zoom: 2.0,
+//!     ..Default::default()
+//! };
+//!
+//! while !rl.window_should_close() {
+//!     let time = rl.get_time() as f32;
+//!     let view = camera.visible_area(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+//!     let mut d = rl.begin_drawing(thread);
+//!     d.clear_background(map.background.unwrap_or(Color::BLACK));
+//!     let mut d = d.begin_mode2D(camera);
+//!     map.draw(&mut d, &textures, view, time);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Object coordinates are kept as Tiled stores them. On isometric maps they are measured along the tile axes, so use
+//! [`TileMap::project`] to get world positions.
+//!
+//! [`TileMap::load`]: struct.TileMap.html#method.load
+//! [`TileMap::draw`]: struct.TileMap.html#method.draw
+//! [`TileMap::project`]: struct.TileMap.html#method.project
+//! [`Camera2D`]: ../camera/struct.Camera2D.html
+use crate::core::color::Color;
+use crate::core::drawing::RaylibDraw;
+use crate::core::inflate;
+use crate::core::json::JsonValue;
+use crate::core::math::{Rectangle, Vector2};
+use crate::core::texture::Texture2D;
+use crate::core::xml::XmlElement;
+use crate::core::{RaylibHandle, RaylibThread};
+use crate::ffi;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const GID_MASK: u32 = 0x0FFF_FFFF;
+
+/// Segments used to turn ellipse objects into polygons.
+const ELLIPSE_SEGMENTS: usize = 16;
+
+/// A cell of a tile layer: a global tile id with Tiled's flip flags in the high bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LayerTile(pub u32);
+
+impl LayerTile {
+    pub const EMPTY: LayerTile = LayerTile(0);
+
+    /// The global tile id without flags, 0 for an empty cell.
+    pub fn gid(self) -> u32 {
+        self.0 & GID_MASK
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.gid() == 0
+    }
+
+    pub fn flip_horizontal(self) -> bool {
+        self.0 & FLIPPED_HORIZONTALLY != 0
+    }
+
+    pub fn flip_vertical(self) -> bool {
+        self.0 & FLIPPED_VERTICALLY != 0
+    }
+
+    /// The tile's x and y axes are swapped. This is applied before the other flips.
+    pub fn flip_diagonal(self) -> bool {
+        self.0 & FLIPPED_DIAGONALLY != 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Orthogonal,
+    Isometric,
+    /// Isometric tiles laid out in staggered rows or columns.
+    Staggered,
+    Hexagonal,
+}
+
+/// Which axis of a staggered or hexagonal map is shifted every other row or column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaggerAxis {
+    X,
+    Y,
+}
+
+/// Order in which tiles of orthogonal maps are drawn, which matters for tiles bigger than the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderOrder {
+    RightDown,
+    RightUp,
+    LeftDown,
+    LeftUp,
+}
+
+/// A custom property value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Color(Color),
+    /// A path, as written in the map.
+    File(String),
+    /// The id of an object on the map, 0 for none.
+    Object(u32),
+    /// The members of a custom class.
+    Class(Properties),
+}
+
+impl PropertyValue {
+    /// The value of string and file properties.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyValue::String(s) | PropertyValue::File(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            PropertyValue::Int(n) => Some(*n),
+            PropertyValue::Object(id) => Some(*id as i64),
+            _ => None,
+        }
+    }
+
+    /// The value of float and int properties.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            PropertyValue::Float(n) => Some(*n),
+            PropertyValue::Int(n) => Some(*n as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            PropertyValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            PropertyValue::Color(c) => Some(*c),
+            _ => None,
+        }
+    }
+}
+
+/// Custom properties by name.
+pub type Properties = BTreeMap<String, PropertyValue>;
+
+/// One frame of an animated tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileFrame {
+    /// Tile shown, local to the tileset.
+    pub tile_id: u32,
+    /// In seconds.
+    pub duration: f32,
+}
+
+/// Extra information a tileset holds about one of its tiles.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TileData {
+    pub class: String,
+    pub properties: Properties,
+    /// Image of the tile in image collection tilesets.
+    pub image: Option<String>,
+    pub animation: Vec<TileFrame>,
+    /// Collision shapes, relative to the tile.
+    pub collision: Vec<MapObject>,
+}
+
+/// A set of tiles cut from one image, or an image collection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tileset {
+    /// Global id of the first tile.
+    pub first_gid: u32,
+    pub name: String,
+    pub tile_width: i32,
+    pub tile_height: i32,
+    pub spacing: i32,
+    pub margin: i32,
+    pub tile_count: u32,
+    pub columns: u32,
+    /// Path of the image, relative to the directory the map was loaded with. `None` for image collections.
+    pub image: Option<String>,
+    pub image_width: i32,
+    pub image_height: i32,
+    /// Offset applied when drawing the tiles.
+    pub tile_offset: Vector2,
+    /// Tiles with extra information, by local id.
+    pub tiles: BTreeMap<u32, TileData>,
+    pub properties: Properties,
+}
+
+impl Tileset {
+    /// Area of the tileset image holding the tile with local id `tile_id`.
+    pub fn source_rec(&self, tile_id: u32) -> Rectangle {
+        let columns = self.columns.max(1);
+        let column = (tile_id % columns) as i32;
+        let row = (tile_id / columns) as i32;
+        Rectangle::new(
+            (self.margin + column * (self.tile_width + self.spacing)) as f32,
+            (self.margin + row * (self.tile_height + self.spacing)) as f32,
+            self.tile_width as f32,
+            self.tile_height as f32,
+        )
+    }
+
+    /// The tile shown at `time` seconds in place of `tile_id`, following its animation if it has one.
+    pub fn animated_tile(&self, tile_id: u32, time: f32) -> u32 {
+        let animation = match self.tiles.get(&tile_id) {
+            Some(data) if !data.animation.is_empty() => &data.animation,
+            _ => return tile_id,
+        };
+        let total: f32 = animation.iter().map(|frame| frame.duration).sum();
+        if total <= 0.0 {
+            return animation[0].tile_id;
+        }
+        let mut t = time.rem_euclid(total);
+        for frame in animation {
+            if t < frame.duration {
+                return frame.tile_id;
+            }
+            t -= frame.duration;
+        }
+        animation[animation.len() - 1].tile_id
+    }
+}
+
+/// Shape of an object.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectShape {
+    Rectangle,
+    Ellipse,
+    Point,
+    /// Closed outline, relative to the object's position.
+    Polygon(Vec<Vector2>),
+    /// Open outline, relative to the object's position.
+    Polyline(Vec<Vector2>),
+    Text(String),
+}
+
+/// An object of an object layer, or a collision shape of a tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapObject {
+    pub id: u32,
+    pub name: String,
+    pub class: String,
+    /// Top left corner, or bottom left corner for tile objects.
+    pub position: Vector2,
+    pub size: Vector2,
+    /// Rotation around `position`, in degrees clockwise.
+    pub rotation: f32,
+    pub visible: bool,
+    /// The tile shown by tile objects.
+    pub tile: Option<LayerTile>,
+    pub shape: ObjectShape,
+    pub properties: Properties,
+}
+
+impl MapObject {
+    /// The area covered by unrotated rectangles, ellipses and tile objects.
+    pub fn rectangle(&self) -> Option<Rectangle> {
+        if self.rotation != 0.0 {
+            return None;
+        }
+        match self.shape {
+            ObjectShape::Rectangle | ObjectShape::Ellipse => {
+                let y = if self.tile.is_some() {
+                    self.position.y - self.size.y
+                } else {
+                    self.position.y
+                };
+                Some(Rectangle::new(self.position.x, y, self.size.x, self.size.y))
+            }
+            _ => None,
+        }
+    }
+
+    /// The outline of the object with its rotation applied. Ellipses are approximated, points and text give their
+    /// single position.
+    pub fn polygon(&self) -> Vec<Vector2> {
+        let (w, h) = (self.size.x, self.size.y);
+        let local = match &self.shape {
+            ObjectShape::Rectangle if self.tile.is_some() => vec![
+                Vector2::new(0.0, -h),
+                Vector2::new(w, -h),
+                Vector2::new(w, 0.0),
+                Vector2::new(0.0, 0.0),
+            ],
+            ObjectShape::Rectangle => vec![
+                Vector2::new(0.0, 0.0),
+                Vector2::new(w, 0.0),
+                Vector2::new(w, h),
+                Vector2::new(0.0, h),
+            ],
+            ObjectShape::Ellipse => (0..ELLIPSE_SEGMENTS)
+                .map(|i| {
+                    let angle = i as f32 / ELLIPSE_SEGMENTS as f32 * std::f32::consts::PI * 2.0;
+                    Vector2::new(
+                        w / 2.0 + angle.cos() * w / 2.0,
+                        h / 2.0 + angle.sin() * h / 2.0,
+                    )
+                })
+                .collect(),
+            ObjectShape::Polygon(points) | ObjectShape::Polyline(points) => points.clone(),
+            ObjectShape::Point | ObjectShape::Text(_) => vec![Vector2::zero()],
+        };
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        local
+            .into_iter()
+            .map(|p| Vector2::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos) + self.position)
+            .collect()
+    }
+
+    /// Whether the object encloses an area, so can be used as a solid shape.
+    pub fn is_closed(&self) -> bool {
+        matches!(
+            self.shape,
+            ObjectShape::Rectangle | ObjectShape::Ellipse | ObjectShape::Polygon(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileLayer {
+    pub width: i32,
+    pub height: i32,
+    /// Cells row by row.
+    pub tiles: Vec<LayerTile>,
+}
+
+impl TileLayer {
+    /// The cell at column `x` and row `y`, empty outside the layer.
+    pub fn get(&self, x: i32, y: i32) -> LayerTile {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return LayerTile::EMPTY;
+        }
+        self.tiles
+            .get((y * self.width + x) as usize)
+            .copied()
+            .unwrap_or(LayerTile::EMPTY)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectLayer {
+    pub color: Option<Color>,
+    pub objects: Vec<MapObject>,
+}
+
+impl ObjectLayer {
+    pub fn object(&self, name: &str) -> Option<&MapObject> {
+        self.objects.iter().find(|object| object.name == name)
+    }
+
+    /// Areas of the unrotated rectangle, ellipse and tile objects, for simple collision.
+    pub fn rectangles(&self) -> Vec<Rectangle> {
+        self.objects
+            .iter()
+            .filter_map(MapObject::rectangle)
+            .collect()
+    }
+
+    /// Outlines of the objects enclosing an area.
+    pub fn polygons(&self) -> Vec<Vec<Vector2>> {
+        self.objects
+            .iter()
+            .filter(|object| object.is_closed())
+            .map(MapObject::polygon)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageLayer {
+    /// Path of the image, relative to the directory the map was loaded with.
+    pub image: Option<String>,
+    pub repeat_x: bool,
+    pub repeat_y: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerData {
+    Tiles(TileLayer),
+    Objects(ObjectLayer),
+    Image(ImageLayer),
+    Group(Vec<Layer>),
+}
+
+/// A layer of a map. Groups apply their visibility, opacity, offset, parallax and tint to the layers they hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    pub id: u32,
+    pub name: String,
+    pub class: String,
+    pub visible: bool,
+    pub opacity: f32,
+    pub offset: Vector2,
+    pub parallax: Vector2,
+    pub tint: Color,
+    pub properties: Properties,
+    pub data: LayerData,
+}
+
+impl Layer {
+    pub fn tiles(&self) -> Option<&TileLayer> {
+        match &self.data {
+            LayerData::Tiles(tiles) => Some(tiles),
+            _ => None,
+        }
+    }
+
+    pub fn objects(&self) -> Option<&ObjectLayer> {
+        match &self.data {
+            LayerData::Objects(objects) => Some(objects),
+            _ => None,
+        }
+    }
+
+    /// Layers inside a group, empty for other layers.
+    pub fn layers(&self) -> &[Layer] {
+        match &self.data {
+            LayerData::Group(layers) => layers,
+            _ => &[],
+        }
+    }
+}
+
+/// Where to draw one tile with `draw_texture_pro`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileDraw {
+    /// Index of the tileset, and of its texture.
+    pub tileset: usize,
+    pub x: i32,
+    pub y: i32,
+    pub source: Rectangle,
+    pub dest: Rectangle,
+    pub origin: Vector2,
+    pub rotation: f32,
+}
+
+/// A map made with Tiled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileMap {
+    pub orientation: Orientation,
+    pub render_order: RenderOrder,
+    /// Size in tiles.
+    pub width: i32,
+    pub height: i32,
+    /// Size of the grid cells in pixels.
+    pub tile_width: i32,
+    pub tile_height: i32,
+    /// Length of the straight sides of hexagonal tiles.
+    pub hex_side_length: i32,
+    pub stagger_axis: StaggerAxis,
+    /// Even rows or columns are shifted, rather than odd ones.
+    pub stagger_even: bool,
+    /// Position where layers with parallax line up with the others.
+    pub parallax_origin: Vector2,
+    pub background: Option<Color>,
+    pub tilesets: Vec<Tileset>,
+    pub layers: Vec<Layer>,
+    pub properties: Properties,
+}
+
+/// Layout sizes of staggered and hexagonal maps, as Tiled computes them.
+struct StaggerParams {
+    tile_width: f32,
+    tile_height: f32,
+    side_x: f32,
+    side_y: f32,
+    side_offset_x: f32,
+    side_offset_y: f32,
+    column_width: f32,
+    row_height: f32,
+}
+
+fn join(dir: &str, path: &str) -> String {
+    if dir.is_empty() || Path::new(path).is_absolute() {
+        path.to_string()
+    } else {
+        Path::new(dir).join(path).to_string_lossy().into_owned()
+    }
+}
+
+fn parent_dir(filename: &str) -> String {
+    Path::new(filename)
+        .parent()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Parses Tiled's `#AARRGGBB` and `#RRGGBB` colors.
+fn parse_color(text: &str) -> Result<Color, String> {
+    let hex = text.trim().trim_start_matches('#');
+    let value = u32::from_str_radix(hex, 16).map_err(|_| format!("invalid color \"{}\"", text))?;
+    let byte = |shift: u32| (value >> shift) as u8;
+    match hex.len() {
+        6 => Ok(Color::new(byte(16), byte(8), byte(0), 255)),
+        8 => Ok(Color::new(byte(16), byte(8), byte(0), byte(24))),
+        _ => Err(format!("invalid color \"{}\"", text)),
+    }
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut count = 0;
+    for byte in text.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            b if b.is_ascii_whitespace() => continue,
+            _ => return Err("invalid base64 layer data".to_string()),
+        };
+        bits = bits << 6 | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn decode_tiles(
+    encoding: Option<&str>,
+    compression: Option<&str>,
+    text: &str,
+) -> Result<Vec<LayerTile>, String> {
+    match encoding {
+        Some("csv") => text
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.parse()
+                    .map(LayerTile)
+                    .map_err(|_| format!("invalid tile \"{}\"", s))
+            })
+            .collect(),
+        Some("base64") => {
+            let bytes = decode_base64(text)?;
+            let bytes = match compression {
+                None | Some("") => bytes,
+                Some("zlib") => inflate::zlib_decompress(&bytes)?,
+                Some("gzip") => inflate::gzip_decompress(&bytes)?,
+                Some(other) => {
+                    return Err(format!(
+                        "{} compressed layer data is not supported, save the map with CSV, uncompressed, zlib or gzip Base64 layers",
+                        other
+                    ))
+                }
+            };
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|b| LayerTile(u32::from_le_bytes([b[0], b[1], b[2], b[3]])))
+                .collect())
+        }
+        other => Err(format!("unknown layer encoding {:?}", other)),
+    }
+}
+
+fn check_size(tiles: &[LayerTile], width: i32, height: i32) -> Result<(), String> {
+    if tiles.len() != (width.max(0) * height.max(0)) as usize {
+        return Err(format!(
+            "layer data has {} tiles, expected {}x{}",
+            tiles.len(),
+            width,
+            height
+        ));
+    }
+    Ok(())
+}
+
+fn tint_with_opacity(tint: Color, opacity: f32) -> Color {
+    Color::new(
+        tint.r,
+        tint.g,
+        tint.b,
+        (tint.a as f32 * opacity.clamp(0.0, 1.0)).round() as u8,
+    )
+}
+
+fn multiply(a: Color, b: Color) -> Color {
+    let mul = |x: u8, y: u8| ((x as u32 * y as u32 + 127) / 255) as u8;
+    Color::new(mul(a.r, b.r), mul(a.g, b.g), mul(a.b, b.b), mul(a.a, b.a))
+}
+
+fn overlaps(a: Rectangle, b: Rectangle) -> bool {
+    a.x < b.x + b.width && a.x + a.width > b.x && a.y < b.y + b.height && a.y + a.height > b.y
+}
+
+// ---- TMX ----
+
+fn xml_num(element: &XmlElement, name: &str, default: f64) -> f64 {
+    element.attr_f64(name).unwrap_or(default)
+}
+
+fn xml_str(element: &XmlElement, name: &str) -> String {
+    element.attr(name).unwrap_or_default().to_string()
+}
+
+fn xml_properties(element: &XmlElement) -> Result<Properties, String> {
+    let mut properties = Properties::new();
+    let list = match element.child("properties") {
+        Some(list) => list,
+        None => return Ok(properties),
+    };
+    for property in list.children_named("property") {
+        let name = xml_str(property, "name");
+        let kind = property.attr("type").unwrap_or("string");
+        let value = if kind == "class" {
+            PropertyValue::Class(xml_properties(property)?)
+        } else {
+            // multi-line strings are stored as text rather than in the value attribute
+            let text = property.attr("value").unwrap_or(&property.text);
+            property_from_str(kind, text).map_err(|e| format!("property \"{}\": {}", name, e))?
+        };
+        properties.insert(name, value);
+    }
+    Ok(properties)
+}
+
+fn property_from_str(kind: &str, text: &str) -> Result<PropertyValue, String> {
+    Ok(match kind {
+        "int" => PropertyValue::Int(text.trim().parse().map_err(|_| "invalid int")?),
+        "float" => PropertyValue::Float(text.trim().parse().map_err(|_| "invalid float")?),
+        "bool" => PropertyValue::Bool(text.trim() == "true"),
+        "color" if text.is_empty() => PropertyValue::Color(Color::new(0, 0, 0, 0)),
+        "color" => PropertyValue::Color(parse_color(text)?),
+        "file" => PropertyValue::File(text.to_string()),
+        "object" => PropertyValue::Object(text.trim().parse().unwrap_or(0)),
+        _ => PropertyValue::String(text.to_string()),
+    })
+}
+
+fn xml_points(text: &str) -> Result<Vec<Vector2>, String> {
+    text.split_whitespace()
+        .map(|pair| {
+            let mut parts = pair.split(',').map(|n| n.trim().parse::<f32>());
+            match (parts.next(), parts.next()) {
+                (Some(Ok(x)), Some(Ok(y))) => Ok(Vector2::new(x, y)),
+                _ => Err(format!("invalid point \"{}\"", pair)),
+            }
+        })
+        .collect()
+}
+
+fn xml_object(element: &XmlElement) -> Result<MapObject, String> {
+    let shape = if element.child("ellipse").is_some() {
+        ObjectShape::Ellipse
+    } else if element.child("point").is_some() {
+        ObjectShape::Point
+    } else if let Some(polygon) = element.child("polygon") {
+        ObjectShape::Polygon(xml_points(polygon.attr("points").unwrap_or_default())?)
+    } else if let Some(polyline) = element.child("polyline") {
+        ObjectShape::Polyline(xml_points(polyline.attr("points").unwrap_or_default())?)
+    } else if let Some(text) = element.child("text") {
+        ObjectShape::Text(text.text.clone())
+    } else {
+        ObjectShape::Rectangle
+    };
+    Ok(MapObject {
+        id: xml_num(element, "id", 0.0) as u32,
+        name: xml_str(element, "name"),
+        class: element
+            .attr("class")
+            .or_else(|| element.attr("type"))
+            .unwrap_or_default()
+            .to_string(),
+        position: Vector2::new(
+            xml_num(element, "x", 0.0) as f32,
+            xml_num(element, "y", 0.0) as f32,
+        ),
+        size: Vector2::new(
+            xml_num(element, "width", 0.0) as f32,
+            xml_num(element, "height", 0.0) as f32,
+        ),
+        rotation: xml_num(element, "rotation", 0.0) as f32,
+        visible: xml_num(element, "visible", 1.0) != 0.0,
+        tile: element
+            .attr("gid")
+            .and_then(|gid| gid.parse().ok())
+            .map(LayerTile),
+        shape,
+        properties: xml_properties(element)?,
+    })
+}
+
+fn xml_object_layer(element: &XmlElement) -> Result<ObjectLayer, String> {
+    Ok(ObjectLayer {
+        color: element.attr("color").map(parse_color).transpose()?,
+        objects: element
+            .children_named("object")
+            .map(xml_object)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn xml_tileset(element: &XmlElement, dir: &str) -> Result<Tileset, String> {
+    let first_gid = xml_num(element, "firstgid", 1.0) as u32;
+    if let Some(source) = element.attr("source") {
+        let mut tileset = load_tileset(&join(dir, source))?;
+        tileset.first_gid = first_gid;
+        return Ok(tileset);
+    }
+    let image = element.child("image");
+    let mut tiles = BTreeMap::new();
+    for tile in element.children_named("tile") {
+        let animation = match tile.child("animation") {
+            Some(animation) => animation
+                .children_named("frame")
+                .map(|frame| TileFrame {
+                    tile_id: xml_num(frame, "tileid", 0.0) as u32,
+                    duration: xml_num(frame, "duration", 0.0) as f32 / 1000.0,
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let collision = match tile.child("objectgroup") {
+            Some(group) => xml_object_layer(group)?.objects,
+            None => Vec::new(),
+        };
+        let data = TileData {
+            class: tile
+                .attr("class")
+                .or_else(|| tile.attr("type"))
+                .unwrap_or_default()
+                .to_string(),
+            properties: xml_properties(tile)?,
+            image: tile
+                .child("image")
+                .and_then(|image| image.attr("source"))
+                .map(|source| join(dir, source)),
+            animation,
+            collision,
+        };
+        tiles.insert(xml_num(tile, "id", 0.0) as u32, data);
+    }
+    let offset = element.child("tileoffset");
+    Ok(Tileset {
+        first_gid,
+        name: xml_str(element, "name"),
+        tile_width: xml_num(element, "tilewidth", 0.0) as i32,
+        tile_height: xml_num(element, "tileheight", 0.0) as i32,
+        spacing: xml_num(element, "spacing", 0.0) as i32,
+        margin: xml_num(element, "margin", 0.0) as i32,
+        tile_count: xml_num(element, "tilecount", 0.0) as u32,
+        columns: xml_num(element, "columns", 0.0) as u32,
+        image: image
+            .and_then(|image| image.attr("source"))
+            .map(|source| join(dir, source)),
+        image_width: image.map_or(0.0, |image| xml_num(image, "width", 0.0)) as i32,
+        image_height: image.map_or(0.0, |image| xml_num(image, "height", 0.0)) as i32,
+        tile_offset: offset.map_or(Vector2::zero(), |offset| {
+            Vector2::new(
+                xml_num(offset, "x", 0.0) as f32,
+                xml_num(offset, "y", 0.0) as f32,
+            )
+        }),
+        tiles,
+        properties: xml_properties(element)?,
+    })
+}
+
+fn xml_layers(element: &XmlElement, dir: &str) -> Result<Vec<Layer>, String> {
+    let mut layers = Vec::new();
+    for child in &element.children {
+        let data = match child.name.as_str() {
+            "layer" => {
+                let width = xml_num(child, "width", 0.0) as i32;
+                let height = xml_num(child, "height", 0.0) as i32;
+                let data = child
+                    .child("data")
+                    .ok_or_else(|| format!("layer \"{}\" has no data", xml_str(child, "name")))?;
+                if data.child("chunk").is_some() {
+                    return Err("infinite maps are not supported".to_string());
+                }
+                let tiles = match data.attr("encoding") {
+                    None => data
+                        .children_named("tile")
+                        .map(|tile| LayerTile(xml_num(tile, "gid", 0.0) as u32))
+                        .collect(),
+                    encoding => decode_tiles(encoding, data.attr("compression"), &data.text)?,
+                };
+                check_size(&tiles, width, height)?;
+                LayerData::Tiles(TileLayer {
+                    width,
+                    height,
+                    tiles,
+                })
+            }
+            "objectgroup" => LayerData::Objects(xml_object_layer(child)?),
+            "imagelayer" => LayerData::Image(ImageLayer {
+                image: child
+                    .child("image")
+                    .and_then(|image| image.attr("source"))
+                    .map(|source| join(dir, source)),
+                repeat_x: xml_num(child, "repeatx", 0.0) != 0.0,
+                repeat_y: xml_num(child, "repeaty", 0.0) != 0.0,
+            }),
+            "group" => LayerData::Group(xml_layers(child, dir)?),
+            _ => continue,
+        };
+        layers.push(Layer {
+            id: xml_num(child, "id", 0.0) as u32,
+            name: xml_str(child, "name"),
+            class: xml_str(child, "class"),
+            visible: xml_num(child, "visible", 1.0) != 0.0,
+            opacity: xml_num(child, "opacity", 1.0) as f32,
+            offset: Vector2::new(
+                xml_num(child, "offsetx", 0.0) as f32,
+                xml_num(child, "offsety", 0.0) as f32,
+            ),
+            parallax: Vector2::new(
+                xml_num(child, "parallaxx", 1.0) as f32,
+                xml_num(child, "parallaxy", 1.0) as f32,
+            ),
+            tint: child
+                .attr("tintcolor")
+                .map(parse_color)
+                .transpose()?
+                .unwrap_or(Color::WHITE),
+            properties: xml_properties(child)?,
+            data,
+        });
+    }
+    Ok(layers)
+}
+
+// ---- JSON ----
+
+fn json_num(value: &JsonValue, key: &str, default: f64) -> f64 {
+    value
+        .get(key)
+        .and_then(JsonValue::as_f64)
+        .unwrap_or(default)
+}
+
+fn json_str(value: &JsonValue, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn json_bool(value: &JsonValue, key: &str, default: bool) -> bool {
+    value
+        .get(key)
+        .and_then(JsonValue::as_bool)
+        .unwrap_or(default)
+}
+
+fn json_array<'a>(value: &'a JsonValue, key: &str) -> &'a [JsonValue] {
+    value
+        .get(key)
+        .and_then(JsonValue::as_array)
+        .unwrap_or_default()
+}
+
+fn json_class(value: &JsonValue) -> String {
+    value
+        .get("class")
+        .or_else(|| value.get("type"))
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn json_property_value(kind: &str, value: &JsonValue) -> Result<PropertyValue, String> {
+    Ok(match (kind, value) {
+        ("int", JsonValue::Number(n)) => PropertyValue::Int(*n as i64),
+        ("float", JsonValue::Number(n)) => PropertyValue::Float(*n),
+        ("object", JsonValue::Number(n)) => PropertyValue::Object(*n as u32),
+        ("class", JsonValue::Object(members)) => {
+            let mut properties = Properties::new();
+            for (name, member) in members {
+                properties.insert(name.clone(), json_property_value("", member)?);
+            }
+            PropertyValue::Class(properties)
+        }
+        (_, JsonValue::String(s)) => property_from_str(kind, s)?,
+        (_, JsonValue::Bool(b)) => PropertyValue::Bool(*b),
+        (_, JsonValue::Number(n)) => PropertyValue::Float(*n),
+        (_, JsonValue::Object(_)) => json_property_value("class", value)?,
+        _ => return Err(format!("unexpected {} value {}", kind, value)),
+    })
+}
+
+fn json_properties(value: &JsonValue) -> Result<Properties, String> {
+    let mut properties = Properties::new();
+    for property in json_array(value, "properties") {
+        let name = json_str(property, "name");
+        let kind = json_str(property, "type");
+        let value = property.get("value").unwrap_or(&JsonValue::Null);
+        let value = json_property_value(&kind, value)
+            .map_err(|e| format!("property \"{}\": {}", name, e))?;
+        properties.insert(name, value);
+    }
+    Ok(properties)
+}
+
+fn json_points(value: &JsonValue) -> Result<Vec<Vector2>, String> {
+    value
+        .as_array()
+        .unwrap_or_default()
+        .iter()
+        .map(|p| Ok(Vector2::new(p.number("x")? as f32, p.number("y")? as f32)))
+        .collect()
+}
+
+fn json_object(value: &JsonValue) -> Result<MapObject, String> {
+    let shape = if json_bool(value, "ellipse", false) {
+        ObjectShape::Ellipse
+    } else if json_bool(value, "point", false) {
+        ObjectShape::Point
+    } else if let Some(points) = value.get("polygon") {
+        ObjectShape::Polygon(json_points(points)?)
+    } else if let Some(points) = value.get("polyline") {
+        ObjectShape::Polyline(json_points(points)?)
+    } else if let Some(text) = value.get("text") {
+        ObjectShape::Text(json_str(text, "text"))
+    } else {
+        ObjectShape::Rectangle
+    };
+    Ok(MapObject {
+        id: json_num(value, "id", 0.0) as u32,
+        name: json_str(value, "name"),
+        class: json_class(value),
+        position: Vector2::new(
+            json_num(value, "x", 0.0) as f32,
+            json_num(value, "y", 0.0) as f32,
+        ),
+        size: Vector2::new(
+            json_num(value, "width", 0.0) as f32,
+            json_num(value, "height", 0.0) as f32,
+        ),
+        rotation: json_num(value, "rotation", 0.0) as f32,
+        visible: json_bool(value, "visible", true),
+        tile: value
+            .get("gid")
+            .and_then(JsonValue::as_f64)
+            .map(|gid| LayerTile(gid as u32)),
+        shape,
+        properties: json_properties(value)?,
+    })
+}
+
+fn json_object_layer(value: &JsonValue) -> Result<ObjectLayer, String> {
+    Ok(ObjectLayer {
+        color: value
+            .get("color")
+            .and_then(JsonValue::as_str)
+            .map(parse_color)
+            .transpose()?,
+        objects: json_array(value, "objects")
+            .iter()
+            .map(json_object)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn json_tileset(value: &JsonValue, dir: &str) -> Result<Tileset, String> {
+    let first_gid = json_num(value, "firstgid", 1.0) as u32;
+    if let Some(source) = value.get("source").and_then(JsonValue::as_str) {
+        let mut tileset = load_tileset(&join(dir, source))?;
+        tileset.first_gid = first_gid;
+        return Ok(tileset);
+    }
+    let mut tiles = BTreeMap::new();
+    for tile in json_array(value, "tiles") {
+        let animation = json_array(tile, "animation")
+            .iter()
+            .map(|frame| TileFrame {
+                tile_id: json_num(frame, "tileid", 0.0) as u32,
+                duration: json_num(frame, "duration", 0.0) as f32 / 1000.0,
+            })
+            .collect();
+        let collision = match tile.get("objectgroup") {
+            Some(group) => json_object_layer(group)?.objects,
+            None => Vec::new(),
+        };
+        let data = TileData {
+            class: json_class(tile),
+            properties: json_properties(tile)?,
+            image: tile
+                .get("image")
+                .and_then(JsonValue::as_str)
+                .map(|source| join(dir, source)),
+            animation,
+            collision,
+        };
+        tiles.insert(json_num(tile, "id", 0.0) as u32, data);
+    }
+    let offset = value.get("tileoffset");
+    Ok(Tileset {
+        first_gid,
+        name: json_str(value, "name"),
+        tile_width: json_num(value, "tilewidth", 0.0) as i32,
+        tile_height: json_num(value, "tileheight", 0.0) as i32,
+        spacing: json_num(value, "spacing", 0.0) as i32,
+        margin: json_num(value, "margin", 0.0) as i32,
+        tile_count: json_num(value, "tilecount", 0.0) as u32,
+        columns: json_num(value, "columns", 0.0) as u32,
+        image: value
+            .get("image")
+            .and_then(JsonValue::as_str)
+            .map(|source| join(dir, source)),
+        image_width: json_num(value, "imagewidth", 0.0) as i32,
+        image_height: json_num(value, "imageheight", 0.0) as i32,
+        tile_offset: offset.map_or(Vector2::zero(), |offset| {
+            Vector2::new(
+                json_num(offset, "x", 0.0) as f32,
+                json_num(offset, "y", 0.0) as f32,
+            )
+        }),
+        tiles,
+        properties: json_properties(value)?,
+    })
+}
+
+fn json_layers(value: &JsonValue, dir: &str) -> Result<Vec<Layer>, String> {
+    let mut layers = Vec::new();
+    for layer in json_array(value, "layers") {
+        let data = match layer.get("type").and_then(JsonValue::as_str) {
+            Some("tilelayer") => {
+                if layer.get("chunks").is_some() {
+                    return Err("infinite maps are not supported".to_string());
+                }
+                let width = json_num(layer, "width", 0.0) as i32;
+                let height = json_num(layer, "height", 0.0) as i32;
+                let tiles = match layer.get("data") {
+                    Some(JsonValue::Array(data)) => data
+                        .iter()
+                        .map(|gid| LayerTile(gid.as_f64().unwrap_or(0.0) as u32))
+                        .collect(),
+                    Some(JsonValue::String(data)) => decode_tiles(
+                        Some("base64"),
+                        layer.get("compression").and_then(JsonValue::as_str),
+                        data,
+                    )?,
+                    _ => return Err(format!("layer \"{}\" has no data", json_str(layer, "name"))),
+                };
+                check_size(&tiles, width, height)?;
+                LayerData::Tiles(TileLayer {
+                    width,
+                    height,
+                    tiles,
+                })
+            }
+            Some("objectgroup") => LayerData::Objects(json_object_layer(layer)?),
+            Some("imagelayer") => LayerData::Image(ImageLayer {
+                image: layer
+                    .get("image")
+                    .and_then(JsonValue::as_str)
+                    .filter(|image| !image.is_empty())
+                    .map(|source| join(dir, source)),
+                repeat_x: json_bool(layer, "repeatx", false),
+                repeat_y: json_bool(layer, "repeaty", false),
+            }),
+            Some("group") => LayerData::Group(json_layers(layer, dir)?),
+            _ => continue,
+        };
+        layers.push(Layer {
+            id: json_num(layer, "id", 0.0) as u32,
+            name: json_str(layer, "name"),
+            class: json_str(layer, "class"),
+            visible: json_bool(layer, "visible", true),
+            opacity: json_num(layer, "opacity", 1.0) as f32,
+            offset: Vector2::new(
+                json_num(layer, "offsetx", 0.0) as f32,
+                json_num(layer, "offsety", 0.0) as f32,
+            ),
+            parallax: Vector2::new(
+                json_num(layer, "parallaxx", 1.0) as f32,
+                json_num(layer, "parallaxy", 1.0) as f32,
+            ),
+            tint: layer
+                .get("tintcolor")
+                .and_then(JsonValue::as_str)
+                .map(parse_color)
+                .transpose()?
+                .unwrap_or(Color::WHITE),
+            properties: json_properties(layer)?,
+            data,
+        });
+    }
+    Ok(layers)
+}
+
+/// Reads an external tileset, `.tsx` or JSON.
+fn load_tileset(filename: &str) -> Result<Tileset, String> {
+    let text = std::fs::read_to_string(filename)
+        .map_err(|e| format!("Cannot read tileset {}: {}", filename, e))?;
+    let dir = parent_dir(filename);
+    let tileset = if filename.ends_with(".tsx") || filename.ends_with(".xml") {
+        XmlElement::parse(&text).and_then(|root| xml_tileset(&root, &dir))
+    } else {
+        JsonValue::parse(&text).and_then(|root| json_tileset(&root, &dir))
+    };
+    tileset.map_err(|e| format!("Cannot parse tileset {}: {}", filename, e))
+}
+
+fn orientation(name: &str) -> Result<Orientation, String> {
+    match name {
+        "orthogonal" => Ok(Orientation::Orthogonal),
+        "isometric" => Ok(Orientation::Isometric),
+        "staggered" => Ok(Orientation::Staggered),
+        "hexagonal" => Ok(Orientation::Hexagonal),
+        other => Err(format!("unknown orientation \"{}\"", other)),
+    }
+}
+
+fn render_order(name: &str) -> RenderOrder {
+    match name {
+        "right-up" => RenderOrder::RightUp,
+        "left-down" => RenderOrder::LeftDown,
+        "left-up" => RenderOrder::LeftUp,
+        _ => RenderOrder::RightDown,
+    }
+}
+
+impl TileMap {
+    /// Reads a map from a `.tmx` or `.json` file. Tilesets and images are looked up relative to the map.
+    pub fn load(filename: &str) -> Result<TileMap, String> {
+        let text = std::fs::read_to_string(filename)
+            .map_err(|e| format!("Cannot read map {}: {}", filename, e))?;
+        let dir = parent_dir(filename);
+        let map = if filename.ends_with(".tmx") || filename.ends_with(".xml") {
+            TileMap::from_tmx(&text, &dir)
+        } else {
+            TileMap::from_json(&text, &dir)
+        };
+        map.map_err(|e| format!("Cannot parse map {}: {}", filename, e))
+    }
+
+    /// Parses a map in Tiled's XML format. External tilesets are read from `dir`, which is also prepended to image
+    /// paths.
+    pub fn from_tmx(text: &str, dir: &str) -> Result<TileMap, String> {
+        let root = XmlElement::parse(text)?;
+        if root.name != "map" {
+            return Err(format!("expected <map>, found <{}>", root.name));
+        }
+        if xml_num(&root, "infinite", 0.0) != 0.0 {
+            return Err("infinite maps are not supported".to_string());
+        }
+        Ok(TileMap {
+            orientation: orientation(root.attr("orientation").unwrap_or("orthogonal"))?,
+            render_order: render_order(root.attr("renderorder").unwrap_or_default()),
+            width: xml_num(&root, "width", 0.0) as i32,
+            height: xml_num(&root, "height", 0.0) as i32,
+            tile_width: xml_num(&root, "tilewidth", 0.0) as i32,
+            tile_height: xml_num(&root, "tileheight", 0.0) as i32,
+            hex_side_length: xml_num(&root, "hexsidelength", 0.0) as i32,
+            stagger_axis: match root.attr("staggeraxis") {
+                Some("x") => StaggerAxis::X,
+                _ => StaggerAxis::Y,
+            },
+            stagger_even: root.attr("staggerindex") == Some("even"),
+            parallax_origin: Vector2::new(
+                xml_num(&root, "parallaxoriginx", 0.0) as f32,
+                xml_num(&root, "parallaxoriginy", 0.0) as f32,
+            ),
+            background: root.attr("backgroundcolor").map(parse_color).transpose()?,
+            tilesets: root
+                .children_named("tileset")
+                .map(|tileset| xml_tileset(tileset, dir))
+                .collect::<Result<_, _>>()?,
+            layers: xml_layers(&root, dir)?,
+            properties: xml_properties(&root)?,
+        })
+    }
+
+    /// Parses a map in Tiled's JSON format. External tilesets are read from `dir`, which is also prepended to image
+    /// paths.
+    pub fn from_json(text: &str, dir: &str) -> Result<TileMap, String> {
+        let root = JsonValue::parse(text)?;
+        if json_bool(&root, "infinite", false) {
+            return Err("infinite maps are not supported".to_string());
+        }
+        Ok(TileMap {
+            orientation: orientation(
+                root.get("orientation")
+                    .and_then(JsonValue::as_str)
+                    .unwrap_or("orthogonal"),
+            )?,
+            render_order: render_order(&json_str(&root, "renderorder")),
+            width: json_num(&root, "width", 0.0) as i32,
+            height: json_num(&root, "height", 0.0) as i32,
+            tile_width: json_num(&root, "tilewidth", 0.0) as i32,
+            tile_height: json_num(&root, "tileheight", 0.0) as i32,
+            hex_side_length: json_num(&root, "hexsidelength", 0.0) as i32,
+            stagger_axis: match root.get("staggeraxis").and_then(JsonValue::as_str) {
+                Some("x") => StaggerAxis::X,
+                _ => StaggerAxis::Y,
+            },
+            stagger_even: json_str(&root, "staggerindex") == "even",
+            parallax_origin: Vector2::new(
+                json_num(&root, "parallaxoriginx", 0.0) as f32,
+                json_num(&root, "parallaxoriginy", 0.0) as f32,
+            ),
+            background: root
+                .get("backgroundcolor")
+                .and_then(JsonValue::as_str)
+                .map(parse_color)
+                .transpose()?,
+            tilesets: json_array(&root, "tilesets")
+                .iter()
+                .map(|tileset| json_tileset(tileset, dir))
+                .collect::<Result<_, _>>()?,
+            layers: json_layers(&root, dir)?,
+            properties: json_properties(&root)?,
+        })
+    }
+
+    /// Loads the image of each tileset as a texture. Image collection tilesets get `None`, and their tiles aren't
+    /// drawn by [`draw`](#method.draw).
+    pub fn load_textures(
+        &self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) -> Result<Vec<Option<Texture2D>>, String> {
+        self.tilesets
+            .iter()
+            .map(|tileset| match &tileset.image {
+                Some(image) => rl.load_texture(thread, image).map(Some),
+                None => Ok(None),
+            })
+            .collect()
+    }
+
+    /// The layer called `name`, searching inside groups.
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        fn find<'a>(layers: &'a [Layer], name: &str) -> Option<&'a Layer> {
+            layers.iter().find_map(|layer| {
+                if layer.name == name {
+                    Some(layer)
+                } else {
+                    find(layer.layers(), name)
+                }
+            })
+        }
+        find(&self.layers, name)
+    }
+
+    /// The object called `name` in any object layer.
+    pub fn object(&self, name: &str) -> Option<&MapObject> {
+        fn find<'a>(layers: &'a [Layer], name: &str) -> Option<&'a MapObject> {
+            layers.iter().find_map(|layer| match &layer.data {
+                LayerData::Objects(objects) => objects.object(name),
+                LayerData::Group(layers) => find(layers, name),
+                _ => None,
+            })
+        }
+        find(&self.layers, name)
+    }
+
+    /// The index of the tileset holding `tile` and the tile's id within it.
+    pub fn tileset_for(&self, tile: LayerTile) -> Option<(usize, u32)> {
+        let gid = tile.gid();
+        if gid == 0 {
+            return None;
+        }
+        self.tilesets
+            .iter()
+            .enumerate()
+            .filter(|(_, tileset)| tileset.first_gid <= gid)
+            .max_by_key(|(_, tileset)| tileset.first_gid)
+            .map(|(i, tileset)| (i, gid - tileset.first_gid))
+    }
+
+    /// What the tileset knows about `tile`, such as its properties.
+    pub fn tile_data(&self, tile: LayerTile) -> Option<&TileData> {
+        let (tileset, id) = self.tileset_for(tile)?;
+        self.tilesets[tileset].tiles.get(&id)
+    }
+
+    /// Property `name` of `tile`.
+    pub fn tile_property(&self, tile: LayerTile, name: &str) -> Option<&PropertyValue> {
+        self.tile_data(tile)?.properties.get(name)
+    }
+
+    fn stagger_params(&self) -> StaggerParams {
+        let tile_width = (self.tile_width & !1) as f32;
+        let tile_height = (self.tile_height & !1) as f32;
+        let side = if self.orientation == Orientation::Hexagonal {
+            self.hex_side_length as f32
+        } else {
+            0.0
+        };
+        let (side_x, side_y) = match self.stagger_axis {
+            StaggerAxis::X => (side, 0.0),
+            StaggerAxis::Y => (0.0, side),
+        };
+        let side_offset_x = (tile_width - side_x) / 2.0;
+        let side_offset_y = (tile_height - side_y) / 2.0;
+        StaggerParams {
+            tile_width,
+            tile_height,
+            side_x,
+            side_y,
+            side_offset_x,
+            side_offset_y,
+            column_width: side_offset_x + side_x,
+            row_height: side_offset_y + side_y,
+        }
+    }
+
+    fn staggers(&self, index: i32) -> bool {
+        (index & 1 != 0) != self.stagger_even
+    }
+
+    /// Size of the map in pixels.
+    pub fn pixel_size(&self) -> Vector2 {
+        let (w, h) = (self.width as f32, self.height as f32);
+        let (tw, th) = (self.tile_width as f32, self.tile_height as f32);
+        match self.orientation {
+            Orientation::Orthogonal => Vector2::new(w * tw, h * th),
+            Orientation::Isometric => Vector2::new((w + h) * tw / 2.0, (w + h) * th / 2.0),
+            Orientation::Staggered | Orientation::Hexagonal => {
+                let p = self.stagger_params();
+                match self.stagger_axis {
+                    StaggerAxis::X => Vector2::new(
+                        w * p.column_width + p.side_offset_x,
+                        h * (p.tile_height + p.side_y)
+                            + if self.width > 1 { p.row_height } else { 0.0 },
+                    ),
+                    StaggerAxis::Y => Vector2::new(
+                        w * (p.tile_width + p.side_x)
+                            + if self.height > 1 { p.column_width } else { 0.0 },
+                        h * p.row_height + p.side_offset_y,
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Top left corner of the bounding box of the cell at column `x` and row `y`, in pixels.
+    pub fn tile_to_world(&self, x: i32, y: i32) -> Vector2 {
+        let (tw, th) = (self.tile_width as f32, self.tile_height as f32);
+        match self.orientation {
+            Orientation::Orthogonal => Vector2::new(x as f32 * tw, y as f32 * th),
+            Orientation::Isometric => Vector2::new(
+                (x - y) as f32 * tw / 2.0 + (self.height - 1) as f32 * tw / 2.0,
+                (x + y) as f32 * th / 2.0,
+            ),
+            Orientation::Staggered | Orientation::Hexagonal => {
+                let p = self.stagger_params();
+                match self.stagger_axis {
+                    StaggerAxis::X => Vector2::new(
+                        x as f32 * p.column_width,
+                        y as f32 * (p.tile_height + p.side_y)
+                            + if self.staggers(x) { p.row_height } else { 0.0 },
+                    ),
+                    StaggerAxis::Y => Vector2::new(
+                        x as f32 * (p.tile_width + p.side_x)
+                            + if self.staggers(y) {
+                                p.column_width
+                            } else {
+                                0.0
+                            },
+                        y as f32 * p.row_height,
+                    ),
+                }
+            }
+        }
+    }
+
+    /// The cell containing the point `position`, in pixels. The result may lie outside the map.
+    pub fn world_to_tile(&self, position: impl Into<Vector2>) -> (i32, i32) {
+        let position = position.into();
+        let (tw, th) = (self.tile_width as f32, self.tile_height as f32);
+        match self.orientation {
+            Orientation::Orthogonal => (
+                (position.x / tw).floor() as i32,
+                (position.y / th).floor() as i32,
+            ),
+            Orientation::Isometric => {
+                let x = position.x - self.height as f32 * tw / 2.0;
+                (
+                    (position.y / th + x / tw).floor() as i32,
+                    (position.y / th - x / tw).floor() as i32,
+                )
+            }
+            Orientation::Staggered | Orientation::Hexagonal => {
+                // guess from the grid, then pick the neighbouring cell whose center is closest
+                let p = self.stagger_params();
+                let (gx, gy) = match self.stagger_axis {
+                    StaggerAxis::X => {
+                        let x = (position.x / p.column_width).floor() as i32;
+                        (x, (position.y / (p.tile_height + p.side_y)).floor() as i32)
+                    }
+                    StaggerAxis::Y => {
+                        let y = (position.y / p.row_height).floor() as i32;
+                        ((position.x / (p.tile_width + p.side_x)).floor() as i32, y)
+                    }
+                };
+                let mut best = (gx, gy);
+                let mut best_distance = f32::INFINITY;
+                for y in gy - 1..=gy + 1 {
+                    for x in gx - 1..=gx + 1 {
+                        let center = self.tile_to_world(x, y) + Vector2::new(tw / 2.0, th / 2.0);
+                        let d = position - center;
+                        let distance = if self.orientation == Orientation::Staggered {
+                            (d.x / tw).abs() + (d.y / th).abs()
+                        } else {
+                            (d.x / tw).powi(2) + (d.y / th).powi(2)
+                        };
+                        if distance < best_distance {
+                            best_distance = distance;
+                            best = (x, y);
+                        }
+                    }
+                }
+                best
+            }
+        }
+    }
+
+    /// Turns object coordinates into world coordinates. On isometric maps objects are measured along the tile axes,
+    /// other orientations store world coordinates already.
+    pub fn project(&self, point: impl Into<Vector2>) -> Vector2 {
+        let point = point.into();
+        if self.orientation != Orientation::Isometric {
+            return point;
+        }
+        let (tw, th) = (self.tile_width as f32, self.tile_height as f32);
+        let (x, y) = (point.x / th, point.y / th);
+        Vector2::new(
+            (x - y) * tw / 2.0 + self.height as f32 * tw / 2.0,
+            (x + y) * th / 2.0,
+        )
+    }
+
+    /// Cells of a layer in drawing order, limited to the rectangle of columns `x0..x1` and rows `y0..y1`.
+    fn cells_in_order(&self, x0: i32, x1: i32, y0: i32, y1: i32) -> Vec<(i32, i32)> {
+        let mut cells = Vec::with_capacity(((x1 - x0).max(0) * (y1 - y0).max(0)) as usize);
+        let staggered_x = self.stagger_axis == StaggerAxis::X
+            && matches!(
+                self.orientation,
+                Orientation::Staggered | Orientation::Hexagonal
+            );
+        if staggered_x {
+            // the shifted columns sit lower, so they draw after the others of the same row
+            for y in y0..y1 {
+                for shifted in &[false, true] {
+                    cells.extend(
+                        (x0..x1)
+                            .filter(|&x| self.staggers(x) == *shifted)
+                            .map(|x| (x, y)),
+                    );
+                }
+            }
+            return cells;
+        }
+        let order = if self.orientation == Orientation::Orthogonal {
+            self.render_order
+        } else {
+            RenderOrder::RightDown
+        };
+        let rows: Vec<i32> = match order {
+            RenderOrder::RightDown | RenderOrder::LeftDown => (y0..y1).collect(),
+            RenderOrder::RightUp | RenderOrder::LeftUp => (y0..y1).rev().collect(),
+        };
+        for y in rows {
+            match order {
+                RenderOrder::RightDown | RenderOrder::RightUp => {
+                    cells.extend((x0..x1).map(|x| (x, y)))
+                }
+                RenderOrder::LeftDown | RenderOrder::LeftUp => {
+                    cells.extend((x0..x1).rev().map(|x| (x, y)))
+                }
+            }
+        }
+        cells
+    }
+
+    /// The tiles of `layer` that show inside `view` once moved by `offset`, in drawing order, as they look at `time`
+    /// seconds.
+    pub fn tile_draws(
+        &self,
+        layer: &TileLayer,
+        offset: Vector2,
+        view: Rectangle,
+        time: f32,
+    ) -> Vec<TileDraw> {
+        let (tw, th) = (self.tile_width as f32, self.tile_height as f32);
+        if tw <= 0.0 || th <= 0.0 {
+            return Vec::new();
+        }
+        // tiles bigger than the grid, or offset, can show from cells outside the view
+        let mut margin = Vector2::zero();
+        for tileset in &self.tilesets {
+            margin.x = margin
+                .x
+                .max((tileset.tile_width as f32 - tw).max(0.0) + tileset.tile_offset.x.abs());
+            margin.y = margin
+                .y
+                .max((tileset.tile_height as f32 - th).max(0.0) + tileset.tile_offset.y.abs());
+        }
+        let area = Rectangle::new(
+            view.x - offset.x - margin.x,
+            view.y - offset.y - margin.y,
+            view.width + margin.x * 2.0,
+            view.height + margin.y * 2.0,
+        );
+        let (x0, x1, y0, y1) = if self.orientation == Orientation::Orthogonal {
+            (
+                (area.x / tw).floor() as i32,
+                ((area.x + area.width) / tw).ceil() as i32,
+                (area.y / th).floor() as i32,
+                ((area.y + area.height) / th).ceil() as i32,
+            )
+        } else {
+            let corners = [
+                self.world_to_tile(Vector2::new(area.x, area.y)),
+                self.world_to_tile(Vector2::new(area.x + area.width, area.y)),
+                self.world_to_tile(Vector2::new(area.x, area.y + area.height)),
+                self.world_to_tile(Vector2::new(area.x + area.width, area.y + area.height)),
+            ];
+            (
+                corners.iter().map(|c| c.0).min().unwrap_or(0) - 1,
+                corners.iter().map(|c| c.0).max().unwrap_or(0) + 2,
+                corners.iter().map(|c| c.1).min().unwrap_or(0) - 1,
+                corners.iter().map(|c| c.1).max().unwrap_or(0) + 2,
+            )
+        };
+        let (x0, x1) = (x0.max(0), x1.min(layer.width));
+        let (y0, y1) = (y0.max(0), y1.min(layer.height));
+
+        let mut draws = Vec::new();
+        for (x, y) in self.cells_in_order(x0, x1, y0, y1) {
+            let tile = layer.get(x, y);
+            let (index, id) = match self.tileset_for(tile) {
+                Some(found) => found,
+                None => continue,
+            };
+            let tileset = &self.tilesets[index];
+            let mut source = tileset.source_rec(tileset.animated_tile(id, time));
+            let (w, h) = (source.width, source.height);
+            // the footprint is the area covered on screen, sideways for diagonal flips
+            let (fw, fh) = if tile.flip_diagonal() { (h, w) } else { (w, h) };
+            let bottom_left =
+                self.tile_to_world(x, y) + Vector2::new(0.0, th) + tileset.tile_offset + offset;
+            let footprint = Rectangle::new(bottom_left.x, bottom_left.y - fh, fw, fh);
+            if !overlaps(footprint, view) {
+                continue;
+            }
+            let rotation = if tile.flip_diagonal() {
+                // swapping axes is a vertical flip turned a quarter clockwise, after which the horizontal
+                // flip applies to the source's y axis and the vertical flip to its x axis
+                if tile.flip_vertical() {
+                    source.width = -source.width;
+                }
+                if !tile.flip_horizontal() {
+                    source.height = -source.height;
+                }
+                90.0
+            } else {
+                if tile.flip_horizontal() {
+                    source.width = -source.width;
+                }
+                if tile.flip_vertical() {
+                    source.height = -source.height;
+                }
+                0.0
+            };
+            draws.push(TileDraw {
+                tileset: index,
+                x,
+                y,
+                source,
+                dest: Rectangle::new(footprint.x + fw / 2.0, footprint.y + fh / 2.0, w, h),
+                origin: Vector2::new(w / 2.0, h / 2.0),
+                rotation,
+            });
+        }
+        // on orthogonal maps with grid sized tiles nothing overlaps, so tiles can be grouped by texture to keep the
+        // draws batched
+        let uniform = self.orientation == Orientation::Orthogonal
+            && self.tilesets.iter().all(|tileset| {
+                tileset.tile_width == self.tile_width
+                    && tileset.tile_height == self.tile_height
+                    && tileset.tile_offset == Vector2::zero()
+            });
+        if uniform {
+            draws.sort_by_key(|draw| draw.tileset);
+        }
+        draws
+    }
+
+    /// Draws the visible tile layers, with the parts inside `view` drawn, as they look at `time` seconds. `textures`
+    /// holds the texture of each tileset, as returned by [`load_textures`](#method.load_textures).
+    ///
+    /// Object and image layers are left to the caller.
+    pub fn draw<D: RaylibDraw, T: AsRef<ffi::Texture2D>>(
+        &self,
+        d: &mut D,
+        textures: &[Option<T>],
+        view: Rectangle,
+        time: f32,
+    ) {
+        for layer in &self.layers {
+            self.draw_layer(d, textures, layer, view, time);
+        }
+    }
+
+    /// Draws one layer, or the layers inside a group, like [`draw`](#method.draw).
+    pub fn draw_layer<D: RaylibDraw, T: AsRef<ffi::Texture2D>>(
+        &self,
+        d: &mut D,
+        textures: &[Option<T>],
+        layer: &Layer,
+        view: Rectangle,
+        time: f32,
+    ) {
+        self.draw_nested(
+            d,
+            textures,
+            layer,
+            Vector2::zero(),
+            Vector2::one(),
+            Color::WHITE,
+            view,
+            time,
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_nested<D: RaylibDraw, T: AsRef<ffi::Texture2D>>(
+        &self,
+        d: &mut D,
+        textures: &[Option<T>],
+        layer: &Layer,
+        offset: Vector2,
+        parallax: Vector2,
+        tint: Color,
+        view: Rectangle,
+        time: f32,
+    ) {
+        if !layer.visible {
+            return;
+        }
+        let offset = offset + layer.offset;
+        let parallax = parallax * layer.parallax;
+        let tint = multiply(tint, tint_with_opacity(layer.tint, layer.opacity));
+        match &layer.data {
+            LayerData::Tiles(tiles) => {
+                let center = Vector2::new(view.x + view.width / 2.0, view.y + view.height / 2.0);
+                let shift = (center - self.parallax_origin) * (Vector2::one() - parallax);
+                for draw in self.tile_draws(tiles, offset + shift, view, time) {
+                    if let Some(Some(texture)) = textures.get(draw.tileset) {
+                        d.draw_texture_pro(
+                            texture,
+                            draw.source,
+                            draw.dest,
+                            draw.origin,
+                            draw.rotation,
+                            tint,
+                        );
+                    }
+                }
+            }
+            LayerData::Group(layers) => {
+                for child in layers {
+                    self.draw_nested(d, textures, child, offset, parallax, tint, view, time);
+                }
+            }
+            LayerData::Objects(_) | LayerData::Image(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tilemap_test {
+    use super::*;
+    use crate::core::camera::Camera2D;
+    use crate::core::recorder::{DrawCommand, DrawRecorder};
+    use crate::core::texture::WeakTexture2D;
+
+    const TMX: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="4" height="3" tilewidth="16" tileheight="16" infinite="0" backgroundcolor="#ff102030">
+ <properties>
+  <property name="title" value="Cave"/>
+  <property name="gravity" type="float" value="9.8"/>
+  <property name="level" type="int" value="3"/>
+  <property name="dark" type="bool" value="true"/>
+  <property name="fog" type="color" value="#80ff0000"/>
+  <property name="door" type="class"><properties><property name="locked" type="bool" value="true"/></properties></property>
+ </properties>
+ <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" spacing="1" margin="1" tilecount="8" columns="4">
+  <image source="terrain.png" width="69" height="35"/>
+  <tile id="2" type="water">
+   <properties><property name="solid" type="bool" value="false"/></properties>
+   <animation><frame tileid="2" duration="100"/><frame tileid="3" duration="300"/></animation>
+  </tile>
+ </tileset>
+ <layer id="1" name="ground" width="4" height="3" opacity="0.5">
+  <data encoding="csv">
+1,2,3,4,
+5,0,2147483649,1610612737,
+0,0,0,3
+</data>
+ </layer>
+ <objectgroup id="2" name="collision">
+  <object id="1" name="wall" x="0" y="32" width="64" height="16"/>
+  <object id="2" name="ramp" x="16" y="16"><polygon points="0,0 16,16 0,16"/></object>
+  <object id="3" x="40" y="8" width="8" height="8"><ellipse/></object>
+  <object id="4" name="spawn" x="8" y="8"><point/></object>
+  <object id="5" gid="5" x="48" y="16" width="16" height="16"/>
+ </objectgroup>
+</map>
+"##;
+
+    const JSON: &str = r#"{"orientation": "isometric", "width": 2, "height": 2, "tilewidth": 32, "tileheight": 16,
+      "renderorder": "right-down", "infinite": false,
+      "tilesets": [{"firstgid": 1, "name": "iso", "tilewidth": 32, "tileheight": 32, "tilecount": 2, "columns": 2,
+        "image": "iso.png", "imagewidth": 64, "imageheight": 32, "tileoffset": {"x": 0, "y": 4}}],
+      "layers": [
+        {"type": "tilelayer", "id": 1, "name": "floor", "width": 2, "height": 2, "encoding": "base64",
+         "data": "AQAAAAIAAAAAAAAAAQAAAA==", "offsetx": 2, "offsety": 0,
+         "properties": [{"name": "z", "type": "int", "value": 1}]},
+        {"type": "group", "name": "things", "layers": [
+          {"type": "objectgroup", "name": "spawns", "objects": [
+            {"id": 1, "name": "a", "x": 16, "y": 0, "point": true},
+            {"id": 2, "x": 0, "y": 0, "rotation": 90, "polygon": [{"x": 0, "y": 0}, {"x": 8, "y": 0}, {"x": 0, "y": 8}]}
+          ]}
+        ]}
+      ]}"#;
+
+    fn texture() -> WeakTexture2D {
+        unsafe {
+            WeakTexture2D::from_raw(ffi::Texture2D {
+                id: 1,
+                width: 69,
+                height: 35,
+                mipmaps: 1,
+                format: 7,
+            })
+        }
+    }
+
+    #[test]
+    fn test_parse_tmx() {
+        let map = TileMap::from_tmx(TMX, "maps").unwrap();
+        assert_eq!(map.orientation, Orientation::Orthogonal);
+        assert_eq!(map.background, Some(Color::new(0x10, 0x20, 0x30, 0xff)));
+        assert_eq!(map.properties["title"].as_str(), Some("Cave"));
+        assert_eq!(map.properties["gravity"].as_f64(), Some(9.8));
+        assert_eq!(map.properties["level"].as_i64(), Some(3));
+        assert_eq!(map.properties["dark"].as_bool(), Some(true));
+        assert_eq!(
+            map.properties["fog"].as_color(),
+            Some(Color::new(255, 0, 0, 128))
+        );
+        match &map.properties["door"] {
+            PropertyValue::Class(members) => assert_eq!(members["locked"].as_bool(), Some(true)),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let tileset = &map.tilesets[0];
+        assert_eq!(
+            tileset.image.as_deref(),
+            Some(join("maps", "terrain.png").as_str())
+        );
+        assert_eq!(tileset.source_rec(1), Rectangle::new(18.0, 1.0, 16.0, 16.0));
+        assert_eq!(tileset.source_rec(4), Rectangle::new(1.0, 18.0, 16.0, 16.0));
+        assert_eq!(tileset.animated_tile(2, 0.05), 2);
+        assert_eq!(tileset.animated_tile(2, 0.15), 3);
+        assert_eq!(tileset.animated_tile(2, 0.45), 2);
+
+        let ground = map.layer("ground").unwrap().tiles().unwrap();
+        let flipped = ground.get(2, 1);
+        assert_eq!(flipped.gid(), 1);
+        assert!(flipped.flip_horizontal() && !flipped.flip_vertical());
+        let turned = ground.get(3, 1);
+        assert!(turned.flip_diagonal() && turned.flip_vertical());
+        assert_eq!(
+            map.tile_property(ground.get(2, 0), "solid")
+                .unwrap()
+                .as_bool(),
+            Some(false)
+        );
+        assert_eq!(map.tile_data(ground.get(2, 0)).unwrap().class, "water");
+        assert!(ground.get(-1, 0).is_empty());
+
+        let collision = map.layer("collision").unwrap().objects().unwrap();
+        assert_eq!(
+            collision.rectangles(),
+            vec![
+                Rectangle::new(0.0, 32.0, 64.0, 16.0),
+                Rectangle::new(40.0, 8.0, 8.0, 8.0),
+                Rectangle::new(48.0, 0.0, 16.0, 16.0),
+            ]
+        );
+        assert_eq!(collision.polygons().len(), 4);
+        assert_eq!(
+            map.object("ramp").unwrap().polygon(),
+            vec![
+                Vector2::new(16.0, 16.0),
+                Vector2::new(32.0, 32.0),
+                Vector2::new(16.0, 32.0),
+            ]
+        );
+        assert_eq!(map.object("spawn").unwrap().shape, ObjectShape::Point);
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let map = TileMap::from_json(JSON, "").unwrap();
+        assert_eq!(map.orientation, Orientation::Isometric);
+        assert_eq!(map.tilesets[0].tile_offset, Vector2::new(0.0, 4.0));
+        let floor = map.layer("floor").unwrap();
+        assert_eq!(floor.offset, Vector2::new(2.0, 0.0));
+        assert_eq!(floor.properties["z"].as_i64(), Some(1));
+        let tiles = floor.tiles().unwrap();
+        assert_eq!(
+            tiles.tiles,
+            vec![LayerTile(1), LayerTile(2), LayerTile(0), LayerTile(1)]
+        );
+
+        let spawns = map.layer("spawns").unwrap().objects().unwrap();
+        assert_eq!(
+            spawns.object("a").unwrap().position,
+            Vector2::new(16.0, 0.0)
+        );
+        let turned = spawns.objects[1].polygon();
+        assert!((turned[1] - Vector2::new(0.0, 8.0)).length() < 1e-4);
+        assert_eq!(
+            map.project(Vector2::new(16.0, 0.0)),
+            Vector2::new(48.0, 8.0)
+        );
+
+        assert!(TileMap::from_json(r#"{"infinite": true}"#, "").is_err());
+        let compressed = r#"{"width": 1, "height": 1, "tilewidth": 8, "tileheight": 8, "layers": [
+          {"type": "tilelayer", "width": 1, "height": 1, "data": "eJxjZGBgAAAACAAC", "compression": "zlib"}]}"#;
+        let map = TileMap::from_json(compressed, "").unwrap();
+        assert_eq!(map.layers[0].tiles().unwrap().tiles, vec![LayerTile(1)]);
+        let zstd = compressed.replace("zlib", "zstd");
+        assert!(TileMap::from_json(&zstd, "").unwrap_err().contains("zstd"));
+    }
+
+    #[test]
+    fn test_compressed_layers() {
+        let expected: Vec<LayerTile> = (0..64).map(|i| LayerTile((i * 7) % 13 + 1)).collect();
+        let zlib = "eNrdy7cBwCAMADDTS8L/76I7GDQqRcQksyhsKh+Nn85hkB46F91kAcE=";
+        let gzip = "H4sIAAAAAAACA93LtwHAIAwAMNNLwv/vojsYNCpFxCSzKGwqH42fzmGQHjoXBpVDbgABAAA=";
+        assert_eq!(
+            decode_tiles(Some("base64"), Some("zlib"), zlib).unwrap(),
+            expected
+        );
+        assert_eq!(
+            decode_tiles(Some("base64"), Some("gzip"), gzip).unwrap(),
+            expected
+        );
+        assert!(decode_tiles(Some("base64"), Some("zstd"), zlib).is_err());
+        assert!(decode_tiles(Some("base64"), Some("gzip"), zlib).is_err());
+    }
+
+    #[test]
+    fn test_coordinates() {
+        let mut map = TileMap::from_json(JSON, "").unwrap();
+        assert_eq!(map.tile_to_world(0, 0), Vector2::new(16.0, 0.0));
+        assert_eq!(map.tile_to_world(1, 0), Vector2::new(32.0, 8.0));
+        assert_eq!(map.world_to_tile(Vector2::new(32.0, 4.0)), (0, 0));
+        assert_eq!(map.world_to_tile(Vector2::new(48.0, 16.0)), (1, 0));
+        assert_eq!(map.pixel_size(), Vector2::new(64.0, 32.0));
+
+        map.orientation = Orientation::Hexagonal;
+        map.width = 3;
+        map.height = 3;
+        map.tile_width = 32;
+        map.tile_height = 32;
+        map.hex_side_length = 16;
+        assert_eq!(map.tile_to_world(0, 1), Vector2::new(16.0, 24.0));
+        assert_eq!(map.world_to_tile(Vector2::new(32.0, 40.0)), (0, 1));
+        assert_eq!(map.world_to_tile(Vector2::new(16.0, 16.0)), (0, 0));
+        assert_eq!(map.pixel_size(), Vector2::new(112.0, 80.0));
+
+        map.stagger_axis = StaggerAxis::X;
+        assert_eq!(map.tile_to_world(1, 0), Vector2::new(24.0, 16.0));
+        assert_eq!(map.world_to_tile(Vector2::new(40.0, 32.0)), (1, 0));
+    }
+
+    #[test]
+    fn test_draw_culled() {
+        let map = TileMap::from_tmx(TMX, "").unwrap();
+        let textures = [Some(texture())];
+        let mut recorder = DrawRecorder::new();
+        map.draw(
+            &mut recorder,
+            &textures,
+            Rectangle::new(0.0, 0.0, 32.0, 16.0),
+            0.0,
+        );
+        assert_eq!(recorder.commands().len(), 2);
+
+        let camera = Camera2D {
+            offset: Vector2::new(0.0, 0.0),
+            target: Vector2::new(0.0, 0.0),
+            rotation: 0.0,
+            zoom: 0.5,
+        };
+        let view = camera.visible_area(32.0, 24.0);
+        assert_eq!(view, Rectangle::new(0.0, 0.0, 64.0, 48.0));
+        let mut recorder = DrawRecorder::new();
+        map.draw(&mut recorder, &textures, view, 0.0);
+        let commands = recorder.commands();
+        assert_eq!(commands.len(), 8);
+        let textured: Vec<_> = commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Texture {
+                    source_rec,
+                    dest_rec,
+                    rotation,
+                    tint,
+                    ..
+                } => Some((*source_rec, *dest_rec, *rotation, *tint)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(textured[0].3.a, 128);
+        // horizontally flipped tile at (2, 1)
+        assert_eq!(textured[5].0, Rectangle::new(1.0, 1.0, -16.0, 16.0));
+        assert_eq!(textured[5].1, Rectangle::new(40.0, 24.0, 16.0, 16.0));
+        // diagonal and vertical flip at (3, 1)
+        assert_eq!(textured[6].0, Rectangle::new(1.0, 1.0, -16.0, -16.0));
+        assert_eq!(textured[6].2, 90.0);
+
+        let ground = map.layer("ground").unwrap().tiles().unwrap();
+        let animated = map.tile_draws(ground, Vector2::zero(), view, 0.2);
+        let water = animated
+            .iter()
+            .find(|draw| (draw.x, draw.y) == (2, 0))
+            .unwrap();
+        assert_eq!(water.source, map.tilesets[0].source_rec(3));
+    }
+}
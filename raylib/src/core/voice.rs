@@ -0,0 +1,490 @@
+//! Overlapping sounds that can be controlled one by one
+//!
+//! [`RaylibAudio::play_sound_multi`] starts copies of a sound that can only be stopped all together. A
+//! [`VoicePool`] plays [`SoundClip`]s on a fixed number of voices instead, and hands back a [`Voice`] for each one
+//! started, to change its volume, pitch or pan, pause it or cut it off:
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! # fn run(audio: &RaylibAudio, rl: &RaylibHandle) -> Result<(), String> {
+//! let shot = SoundClip::load("shot.wav")?;
+//! let mut voices = VoicePool::new(audio, 8)?;
+//! let mut firing = Vec::new();
+//! if rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) {
+//!     let params = VoiceParams { pan: -0.3, priority: 1, ..Default::default() };
+//!     firing.extend(voices.play_with(&shot, params));
+//!     // follows the mouse across the screen while it plays
+//!     let pan = rl.get_mouse_x() as f32 / rl.get_screen_width() as f32 * 2.0 - 1.0;
+//!     for &voice in &firing {
+//!         voices.set_pan(voice, pan);
+//!     }
+//! } else {
+//!     for voice in firing.drain(..) {
+//!         voices.stop(voice);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! When every voice is busy, the one with the lowest priority, and the oldest of those, is stolen for the new sound,
+//! unless its priority is higher. Handles to stolen or finished voices are ignored from then on.
+//!
+//! The voices are mixed in Rust into a [`ProceduralStream`] refilled on its own thread, which is what lets them pan
+//! (see the [`audio`] module). The pool is one stream to raylib, so it can be put on a [`Mixer`] bus as a whole.
+//!
+//! [`RaylibAudio::play_sound_multi`]: ../audio/struct.RaylibAudio.html#method.play_sound_multi
+//! [`VoicePool`]: struct.VoicePool.html
+//! [`SoundClip`]: struct.SoundClip.html
+//! [`Voice`]: struct.Voice.html
+//! [`ProceduralStream`]: ../procedural/struct.ProceduralStream.html
+//! [`audio`]: ../audio/index.html#limitations-of-raylib-30
+//! [`Mixer`]: ../mixer/struct.Mixer.html
+use crate::core::audio::{RaylibAudio, Wave, AUDIO_DEVICE_SAMPLE_RATE};
+use crate::core::dsp::resample;
+use crate::core::procedural::{balance, remix_frame, ProceduralStream, Source};
+use crate::ffi;
+use std::sync::Arc;
+
+/// Frames the pool's stream is refilled by, about 12ms.
+const BUFFER_FRAMES: u32 = 512;
+
+/// Audio data a [`VoicePool`](struct.VoicePool.html) can play on several voices at once.
+#[derive(Debug, Clone)]
+pub struct SoundClip {
+    /// The samples as the pool mixes them, stereo at the device's rate. Shared by the voices playing them.
+    frames: Arc<[f32]>,
+    sample_rate: u32,
+    channels: u32,
+}
+
+impl SoundClip {
+    /// Copies the samples of `wave`.
+    pub fn from_wave(wave: &Wave) -> SoundClip {
+        SoundClip::from_samples(&wave.samples(), wave.smaple_rate(), wave.channels())
+    }
+
+    /// Loads a clip from an audio file.
+    pub fn load(filename: &str) -> Result<SoundClip, String> {
+        Wave::load_wave(filename).map(|wave| SoundClip::from_wave(&wave))
+    }
+
+    fn from_samples(samples: &[f32], sample_rate: u32, channels: u32) -> SoundClip {
+        let from = channels.max(1) as usize;
+        let mut stereo = vec![0.0; samples.len() / from * 2];
+        for (input, output) in samples.chunks(from).zip(stereo.chunks_mut(2)) {
+            remix_frame(input, output);
+        }
+        SoundClip {
+            frames: resample(&stereo, 2, sample_rate.max(1), AUDIO_DEVICE_SAMPLE_RATE).into(),
+            sample_rate,
+            channels: channels.max(1),
+        }
+    }
+
+    /// Sample rate of the audio the clip was made from.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Channels of the audio the clip was made from.
+    pub fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    /// Length in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frames.len() as f32 / 2.0 / AUDIO_DEVICE_SAMPLE_RATE as f32
+    }
+}
+
+/// How a voice starts playing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoiceParams {
+    /// `1.0` is max level.
+    pub volume: f32,
+    /// `1.0` is base level.
+    pub pitch: f32,
+    /// From `-1.0`, fully left, to `1.0`, fully right.
+    pub pan: f32,
+    /// Voices only steal from voices of the same or lower priority.
+    pub priority: i32,
+}
+
+impl Default for VoiceParams {
+    fn default() -> VoiceParams {
+        VoiceParams {
+            volume: 1.0,
+            pitch: 1.0,
+            pan: 0.0,
+            priority: 0,
+        }
+    }
+}
+
+/// A sound playing in a [`VoicePool`](struct.VoicePool.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Voice {
+    slot: usize,
+    generation: u32,
+}
+
+#[derive(Default)]
+struct Slot {
+    /// Frames of the clip last played here.
+    clip: Option<Arc<[f32]>>,
+    /// Position in the clip, in frames. Fractional once the pitch isn't `1.0`.
+    position: f64,
+    volume: f32,
+    pitch: f32,
+    pan: f32,
+    /// Increased when the slot plays something else, so old handles stop matching.
+    generation: u32,
+    priority: i32,
+    started: u64,
+    /// Started and not finished or stopped yet, paused or not.
+    busy: bool,
+    paused: bool,
+}
+
+impl Slot {
+    fn is_playing(&self) -> bool {
+        self.busy && !self.paused
+    }
+
+    /// Adds the voice to the stereo frames of `out`, advancing by the pitch per frame.
+    fn mix(&mut self, out: &mut [f32]) {
+        let clip = match self.clip.as_ref() {
+            Some(clip) => clip,
+            None => {
+                self.busy = false;
+                return;
+            }
+        };
+        let frames = clip.len() / 2;
+        let (left, right) = balance(self.pan);
+        let gains = [left * self.volume, right * self.volume];
+        for frame in out.chunks_mut(2) {
+            let index = self.position as usize;
+            if index >= frames {
+                self.busy = false;
+                return;
+            }
+            let next = (index + 1).min(frames - 1);
+            let t = (self.position - index as f64) as f32;
+            for (c, sample) in frame.iter_mut().enumerate() {
+                let (a, b) = (clip[index * 2 + c], clip[next * 2 + c]);
+                *sample += (a + (b - a) * t) * gains[c];
+            }
+            self.position += self.pitch as f64;
+        }
+    }
+}
+
+/// The state of a slot [`choose_slot`] looks at.
+struct SlotState {
+    busy: bool,
+    priority: i32,
+    started: u64,
+}
+
+/// Picks the slot for a sound of `priority`: a free one, or the lowest priority and oldest busy one as long as its
+/// priority isn't higher.
+fn choose_slot(slots: &[SlotState], priority: i32) -> Option<usize> {
+    if let Some(free) = slots.iter().position(|s| !s.busy) {
+        return Some(free);
+    }
+    slots
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.priority <= priority)
+        .min_by_key(|(_, s)| (s.priority, s.started))
+        .map(|(i, _)| i)
+}
+
+/// The voices of a pool, mixed into its stream.
+struct Voices {
+    slots: Vec<Slot>,
+    /// Counts the voices started, to find the oldest.
+    clock: u64,
+}
+
+impl Voices {
+    fn new(size: usize) -> Voices {
+        Voices {
+            slots: (0..size.max(1)).map(|_| Slot::default()).collect(),
+            clock: 0,
+        }
+    }
+
+    fn play(&mut self, clip: &SoundClip, params: VoiceParams) -> Option<Voice> {
+        let states: Vec<SlotState> = self
+            .slots
+            .iter()
+            .map(|s| SlotState {
+                busy: s.busy,
+                priority: s.priority,
+                started: s.started,
+            })
+            .collect();
+        let index = choose_slot(&states, params.priority)?;
+        self.clock += 1;
+        let slot = &mut self.slots[index];
+        slot.clip = Some(Arc::clone(&clip.frames));
+        slot.position = 0.0;
+        slot.volume = params.volume.max(0.0);
+        slot.pitch = params.pitch.max(0.0);
+        slot.pan = params.pan.clamp(-1.0, 1.0);
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.priority = params.priority;
+        slot.started = self.clock;
+        slot.busy = true;
+        slot.paused = false;
+        Some(Voice {
+            slot: index,
+            generation: slot.generation,
+        })
+    }
+
+    /// The slot `voice` plays on, if it wasn't stolen since.
+    fn slot(&mut self, voice: Voice) -> Option<&mut Slot> {
+        self.slots
+            .get_mut(voice.slot)
+            .filter(|slot| slot.generation == voice.generation)
+    }
+}
+
+impl Source for Voices {
+    fn sample_rate(&self) -> u32 {
+        AUDIO_DEVICE_SAMPLE_RATE
+    }
+
+    fn channels(&self) -> u32 {
+        2
+    }
+
+    fn fill(&mut self, out: &mut [f32]) {
+        out.iter_mut().for_each(|s| *s = 0.0);
+        for slot in self.slots.iter_mut().filter(|s| s.is_playing()) {
+            slot.mix(out);
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        !self.slots.iter().any(Slot::is_playing)
+    }
+}
+
+/// A fixed number of voices playing [`SoundClip`](struct.SoundClip.html)s. See the [module docs](index.html).
+pub struct VoicePool {
+    stream: ProceduralStream<Voices>,
+}
+
+impl VoicePool {
+    /// Creates a pool of `size` voices, and starts the thread mixing them.
+    pub fn new(audio: &RaylibAudio, size: usize) -> Result<VoicePool, String> {
+        let mut stream = ProceduralStream::new(
+            audio,
+            AUDIO_DEVICE_SAMPLE_RATE,
+            32,
+            2,
+            BUFFER_FRAMES,
+            Voices::new(size),
+        )?;
+        stream.start_thread();
+        stream.play();
+        Ok(VoicePool { stream })
+    }
+
+    fn with_voice(&mut self, voice: Voice, f: impl FnOnce(&mut Slot)) {
+        self.stream.with_source(|voices| {
+            if let Some(slot) = voices.slot(voice) {
+                f(slot)
+            }
+        })
+    }
+
+    /// Number of voices.
+    pub fn size(&self) -> usize {
+        self.stream.with_source(|voices| voices.slots.len())
+    }
+
+    /// Number of voices playing or paused.
+    pub fn busy_count(&self) -> usize {
+        self.stream
+            .with_source(|voices| voices.slots.iter().filter(|s| s.busy).count())
+    }
+
+    /// Plays `clip` with the default parameters.
+    pub fn play(&mut self, clip: &SoundClip) -> Option<Voice> {
+        self.play_with(clip, VoiceParams::default())
+    }
+
+    /// Plays `clip`, stealing a voice if they are all busy. Returns `None` when every voice plays something of
+    /// higher priority.
+    pub fn play_with(&mut self, clip: &SoundClip, params: VoiceParams) -> Option<Voice> {
+        self.stream.with_source(|voices| voices.play(clip, params))
+    }
+
+    /// Whether `voice` is still playing. Paused voices aren't.
+    pub fn is_playing(&self, voice: Voice) -> bool {
+        self.stream
+            .with_source(|voices| voices.slot(voice).is_some_and(|s| s.is_playing()))
+    }
+
+    pub fn is_paused(&self, voice: Voice) -> bool {
+        self.stream
+            .with_source(|voices| voices.slot(voice).is_some_and(|s| s.busy && s.paused))
+    }
+
+    /// Sets the volume of `voice` (`1.0` is max level).
+    pub fn set_volume(&mut self, voice: Voice, volume: f32) {
+        self.with_voice(voice, |slot| slot.volume = volume.max(0.0));
+    }
+
+    /// Sets the pitch of `voice` (`1.0` is base level).
+    pub fn set_pitch(&mut self, voice: Voice, pitch: f32) {
+        self.with_voice(voice, |slot| slot.pitch = pitch.max(0.0));
+    }
+
+    /// Sets the stereo balance of `voice`, from `-1.0` (left only) through `0.0` (centered) to `1.0` (right only).
+    pub fn set_pan(&mut self, voice: Voice, pan: f32) {
+        self.with_voice(voice, |slot| slot.pan = pan.clamp(-1.0, 1.0));
+    }
+
+    /// Changes the priority `voice` defends its slot with.
+    pub fn set_priority(&mut self, voice: Voice, priority: i32) {
+        self.with_voice(voice, |slot| slot.priority = priority);
+    }
+
+    pub fn pause(&mut self, voice: Voice) {
+        self.with_voice(voice, |slot| slot.paused = slot.busy);
+    }
+
+    pub fn resume(&mut self, voice: Voice) {
+        self.with_voice(voice, |slot| slot.paused = false);
+    }
+
+    /// Cuts `voice` off, freeing its slot.
+    pub fn stop(&mut self, voice: Voice) {
+        self.with_voice(voice, |slot| {
+            slot.busy = false;
+            slot.paused = false;
+        });
+    }
+
+    /// Stops every voice.
+    pub fn stop_all(&mut self) {
+        self.stream.with_source(|voices| {
+            for slot in voices.slots.iter_mut() {
+                slot.busy = false;
+                slot.paused = false;
+            }
+        })
+    }
+}
+
+impl AsRef<ffi::AudioStream> for VoicePool {
+    fn as_ref(&self) -> &ffi::AudioStream {
+        self.stream.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod voice_test {
+    use super::*;
+
+    fn state(busy: bool, priority: i32, started: u64) -> SlotState {
+        SlotState {
+            busy,
+            priority,
+            started,
+        }
+    }
+
+    #[test]
+    fn test_choose_slot() {
+        let slots = [state(true, 0, 1), state(false, 0, 2), state(true, 0, 3)];
+        assert_eq!(choose_slot(&slots, 0), Some(1));
+
+        // the oldest of the lowest priority is stolen
+        let slots = [state(true, 1, 1), state(true, 0, 4), state(true, 0, 3)];
+        assert_eq!(choose_slot(&slots, 0), Some(2));
+        assert_eq!(choose_slot(&slots, 5), Some(2));
+        // but never for a lower priority sound
+        let slots = [state(true, 2, 1), state(true, 3, 2)];
+        assert_eq!(choose_slot(&slots, 1), None);
+        assert_eq!(choose_slot(&slots, 2), Some(0));
+    }
+
+    #[test]
+    fn test_clip_frames() {
+        let rate = AUDIO_DEVICE_SAMPLE_RATE;
+        let clip = SoundClip::from_samples(&vec![0.5; rate as usize / 10], rate, 1);
+        assert_eq!(clip.channels(), 1);
+        assert!((clip.duration() - 0.1).abs() < 1e-6);
+        assert!(clip.frames.iter().all(|&s| s == 0.5));
+    }
+
+    #[test]
+    fn test_mix() {
+        let clip = SoundClip {
+            frames: vec![1.0; 8].into(),
+            sample_rate: AUDIO_DEVICE_SAMPLE_RATE,
+            channels: 2,
+        };
+        let mut voices = Voices::new(2);
+        assert!(voices.is_idle());
+        let params = VoiceParams {
+            volume: 0.5,
+            pan: 0.5,
+            ..Default::default()
+        };
+        let voice = voices.play(&clip, params).unwrap();
+        assert!(!voices.is_idle());
+        let mut out = [0.0; 4];
+        voices.fill(&mut out);
+        assert_eq!(out, [0.25, 0.5, 0.25, 0.5]);
+
+        // the pan changes while the voice plays, and a second voice adds to it
+        voices.slot(voice).unwrap().pan = -1.0;
+        voices.play(&clip, VoiceParams::default()).unwrap();
+        voices.fill(&mut out);
+        assert_eq!(out, [1.5, 1.0, 1.5, 1.0]);
+
+        // the clip is four frames long
+        voices.fill(&mut out);
+        assert_eq!(out, [1.0; 4]);
+        assert!(voices.slot(voice).is_some_and(|s| !s.busy));
+        voices.fill(&mut out);
+        assert_eq!(out, [0.0; 4]);
+        assert!(voices.is_idle());
+    }
+
+    #[test]
+    fn test_mix_pitch() {
+        let clip = SoundClip {
+            frames: vec![0.0, 0.0, 1.0, 1.0, 2.0, 2.0].into(),
+            sample_rate: AUDIO_DEVICE_SAMPLE_RATE,
+            channels: 2,
+        };
+        let mut voices = Voices::new(1);
+        let params = VoiceParams {
+            pitch: 0.5,
+            ..Default::default()
+        };
+        let voice = voices.play(&clip, params).unwrap();
+        let mut out = [0.0; 8];
+        voices.fill(&mut out);
+        assert_eq!(out, [0.0, 0.0, 0.5, 0.5, 1.0, 1.0, 1.5, 1.5]);
+
+        // paused voices hold their place
+        voices.slot(voice).unwrap().paused = true;
+        assert!(voices.is_idle());
+        voices.fill(&mut out);
+        assert_eq!(out, [0.0; 8]);
+        voices.slot(voice).unwrap().paused = false;
+        voices.fill(&mut out);
+        assert_eq!(out, [2.0, 2.0, 2.0, 2.0, 0.0, 0.0, 0.0, 0.0]);
+    }
+}
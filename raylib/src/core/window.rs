@@ -1,5 +1,6 @@
 //! Window manipulation functions
 use crate::core::math::{Matrix, Ray, Vector2};
+use crate::core::replay::replayed;
 use crate::core::{RaylibHandle, RaylibThread};
 use crate::ffi;
 use std::ffi::{CStr, CString, IntoStringError, NulError};
@@ -221,6 +222,9 @@ impl RaylibHandle {
 
     /// Returns time in seconds for last frame drawn
     pub fn get_frame_time(&self) -> f32 {
+        if let Some(frame_time) = replayed(|frame, _| frame.frame_time) {
+            return frame_time;
+        }
         unsafe { ffi::GetFrameTime() }
     }
 
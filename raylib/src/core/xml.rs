@@ -0,0 +1,257 @@
+//! Minimal XML reading for the data files raylib-rs imports from tools, such as Tiled maps.
+//!
+//! Documents are read into a tree of elements. Comments, processing instructions and doctypes are skipped, and
+//! namespaces are left as part of the names.
+
+/// An element with its attributes, child elements and the text directly inside it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct XmlElement {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlElement>,
+    /// Text and CDATA inside the element, outside its children, with entities decoded.
+    pub text: String,
+}
+
+impl XmlElement {
+    /// Parses a document, returning its root element.
+    pub fn parse(text: &str) -> Result<XmlElement, String> {
+        let mut parser = Parser { text, pos: 0 };
+        parser.misc()?;
+        if !parser.rest().starts_with('<') {
+            return Err(parser.error("expected the root element"));
+        }
+        let root = parser.element()?;
+        parser.misc()?;
+        if parser.pos != text.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(root)
+    }
+
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Attribute `name` parsed as a number, if present and valid.
+    pub fn attr_f64(&self, name: &str) -> Option<f64> {
+        self.attr(name).and_then(|v| v.trim().parse().ok())
+    }
+
+    /// The first child element called `name`.
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// The child elements called `name`.
+    pub fn children_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a XmlElement> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ':'
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("invalid XML at byte {}: {}", self.pos, message)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skips past `end`, which must follow.
+    fn skip_past(&mut self, end: &str) -> Result<&'a str, String> {
+        match self.rest().find(end) {
+            Some(i) => {
+                let skipped = &self.rest()[..i];
+                self.pos += i + end.len();
+                Ok(skipped)
+            }
+            None => Err(self.error(&format!("missing \"{}\"", end))),
+        }
+    }
+
+    /// Skips whitespace, comments, processing instructions and doctypes.
+    fn misc(&mut self) -> Result<(), String> {
+        loop {
+            self.whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<!DOCTYPE") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn element(&mut self) -> Result<XmlElement, String> {
+        self.pos += 1;
+        let mut element = XmlElement {
+            name: self.name()?,
+            ..Default::default()
+        };
+        loop {
+            self.whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            }
+            if rest.starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let key = self.name()?;
+            self.whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(self.error("expected '='"));
+            }
+            self.pos += 1;
+            self.whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(q) if q == '"' || q == '\'' => q,
+                _ => return Err(self.error("expected a quoted value")),
+            };
+            self.pos += 1;
+            let value = self.skip_past(if quote == '"' { "\"" } else { "'" })?;
+            element.attributes.push((key, decode_entities(value)?));
+        }
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error(&format!("unclosed element <{}>", element.name)));
+            }
+            if rest.starts_with("</") {
+                self.pos += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(
+                        self.error(&format!("expected </{}>, found </{}>", element.name, name))
+                    );
+                }
+                self.whitespace();
+                if !self.rest().starts_with('>') {
+                    return Err(self.error("expected '>'"));
+                }
+                self.pos += 1;
+                return Ok(element);
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += 9;
+                let data = self.skip_past("]]>")?;
+                element.text.push_str(data);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with('<') {
+                let child = self.element()?;
+                element.children.push(child);
+            } else {
+                let len = rest.find('<').unwrap_or(rest.len());
+                element.text.push_str(&decode_entities(&rest[..len])?);
+                self.pos += len;
+            }
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| format!("unterminated entity in \"{}\"", text))?;
+        let entity = &rest[start + 1..start + end];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(dec) = entity.strip_prefix('#') {
+                    dec.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(std::char::from_u32)
+                    .ok_or_else(|| format!("unknown entity &{};", entity))?
+            }
+        };
+        out.push(c);
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod xml_test {
+    use super::*;
+
+    #[test]
+    fn test_xml_parse() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- a map -->
+<map version='1.10' name="a &amp; b">
+  <tileset firstgid="1" source="tiles.tsx"/>
+  <layer id="1"><data encoding="csv">1,2,&#51;</data></layer>
+  <script><![CDATA[x < y]]></script>
+</map>
+"#;
+        let root = XmlElement::parse(text).unwrap();
+        assert_eq!(root.name, "map");
+        assert_eq!(root.attr("name"), Some("a & b"));
+        assert_eq!(root.attr_f64("version"), Some(1.1));
+        assert_eq!(root.children.len(), 3);
+        assert_eq!(
+            root.child("tileset").unwrap().attr("source"),
+            Some("tiles.tsx")
+        );
+        let data = root.child("layer").unwrap().child("data").unwrap();
+        assert_eq!(data.text, "1,2,3");
+        assert_eq!(root.child("script").unwrap().text, "x < y");
+        assert_eq!(root.children_named("layer").count(), 1);
+
+        assert!(XmlElement::parse("<a><b></a>").is_err());
+        assert!(XmlElement::parse("<a>").is_err());
+        assert!(XmlElement::parse("<a/><b/>").is_err());
+    }
+}
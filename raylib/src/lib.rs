@@ -56,12 +56,14 @@ Permission is granted to anyone to use this software for any purpose, including
 //! ```
 #![cfg_attr(feature = "nightly", feature(optin_builtin_traits))]
 #![allow(dead_code)]
+pub mod app;
 pub mod consts;
 pub mod core;
 pub mod ease;
 pub mod prelude;
 pub mod rgui;
 pub mod rlights;
+pub mod scene;
 
 /// The raw, unsafe FFI binding, in case you need that escape hatch or the safe layer doesn't provide something you need.
 pub mod ffi {
@@ -25,17 +25,36 @@ Permission is granted to anyone to use this software for any purpose, including
 //! ```
 
 pub use crate::consts::*;
+pub use crate::core::analysis::*;
+pub use crate::core::atlas::*;
 pub use crate::core::audio::*;
 pub use crate::core::camera::*;
 pub use crate::core::color::*;
 pub use crate::core::data::*;
 pub use crate::core::drawing::*;
+pub use crate::core::dsp::*;
+pub use crate::core::filter::*;
+#[cfg(feature = "image")]
+pub use crate::core::image_interop::*;
+pub use crate::core::input::*;
 pub use crate::core::logging::*;
 pub use crate::core::math::*;
+pub use crate::core::mixer::*;
 pub use crate::core::models::*;
+pub use crate::core::music::*;
+pub use crate::core::particles::*;
+pub use crate::core::pixels::*;
+pub use crate::core::procedural::*;
+pub use crate::core::recorder::*;
+pub use crate::core::replay::*;
 pub use crate::core::shaders::*;
+pub use crate::core::sfx::*;
+pub use crate::core::spatial::*;
+pub use crate::core::sprite::*;
 pub use crate::core::text::*;
 pub use crate::core::texture::*;
+pub use crate::core::tilemap::*;
+pub use crate::core::voice::*;
 pub use crate::core::window::*;
 pub use crate::core::*;
 pub use crate::rgui::*;
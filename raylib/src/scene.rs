@@ -0,0 +1,649 @@
+/* raylib-rs
+   scene.rs - Scene stack for menus, gameplay and overlays
+
+Copyright (c) 2018-2019 Paul Clement (@deltaphc)
+
+This software is provided "as-is", without any express or implied warranty. In no event will the authors be held liable for any damages arising from the use of this software.
+
+Permission is granted to anyone to use this software for any purpose, including commercial applications, and to alter it and redistribute it freely, subject to the following restrictions:
+
+  1. The origin of this software must not be misrepresented; you must not claim that you wrote the original software. If you use this software in a product, an acknowledgment in the product documentation would be appreciated but is not required.
+
+  2. Altered source versions must be plainly marked as such, and must not be misrepresented as being the original software.
+
+  3. This notice may not be removed or altered from any source distribution.
+*/
+
+//! Scene stack for menus, gameplay and overlays.
+//!
+//! A [`SceneStack`] owns a stack of [`Scene`]s. Only the top scene is updated, and it changes the stack by returning a
+//! [`SceneCommand`] from `update`. Scenes that report `is_overlay` let the scenes below them keep drawing, which is how
+//! a pause menu stays on top of a frozen game. Stack changes can be animated with a [`Transition`], rendered through
+//! two `RenderTexture2D`s.
+//!
+//! `SceneStack` implements [`App`], so it can be handed directly to [`app::run`].
+//!
+//! ```no_run
+//! use raylib::prelude::*;
+//! use raylib::scene::*;
+//!
+//! struct Menu;
+//! struct Game;
+//!
+//! impl Scene for Menu {
+//!     fn update(&mut self, rl: &mut RaylibHandle, _: &RaylibThread, _dt: f32) -> SceneCommand {
+//!         if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+//!             return SceneCommand::Replace(Box::new(Game));
+//!         }
+//!         SceneCommand::None
+//!     }
+//!
+//!     fn draw(&mut self, d: &mut SceneDrawHandle) {
+//!         d.clear_background(Color::WHITE);
+//!         d.draw_text("Press ENTER", 12, 12, 20, Color::BLACK);
+//!     }
+//! }
+//!
+//! impl Scene for Game {
+//!     fn update(&mut self, _: &mut RaylibHandle, _: &RaylibThread, _dt: f32) -> SceneCommand {
+//!         SceneCommand::None
+//!     }
+//!
+//!     fn draw(&mut self, d: &mut SceneDrawHandle) {
+//!         d.clear_background(Color::DARKGREEN);
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let (mut rl, thread) = raylib::init().build();
+//!     let mut scenes = SceneStack::new(&thread).with_transition(Transition::fade(Color::BLACK, 0.5));
+//!     scenes.push(&mut rl, Box::new(Menu));
+//!     raylib::app::run(rl, thread, scenes);
+//! }
+//! ```
+//!
+//! [`SceneStack`]: struct.SceneStack.html
+//! [`Scene`]: trait.Scene.html
+//! [`SceneCommand`]: enum.SceneCommand.html
+//! [`Transition`]: struct.Transition.html
+//! [`App`]: ../app/trait.App.html
+//! [`app::run`]: ../app/fn.run.html
+
+use crate::app::App;
+use crate::core::color::Color;
+use crate::core::drawing::{RaylibDraw, RaylibDrawHandle, RaylibTextureModeExt};
+use crate::core::math::{Rectangle, Vector2};
+use crate::core::texture::{RaylibRenderTexture2D, RaylibTexture2D, RenderTexture2D};
+use crate::core::{RaylibHandle, RaylibThread};
+use crate::ease::{self, EaseFn, Tween};
+
+/// A screen of the application: a menu, a level, a pause overlay...
+pub trait Scene {
+    /// Called when the scene is pushed on the stack.
+    fn enter(&mut self, _rl: &mut RaylibHandle, _thread: &RaylibThread) {}
+
+    /// Called when the scene is popped or replaced.
+    fn exit(&mut self, _rl: &mut RaylibHandle, _thread: &RaylibThread) {}
+
+    /// Advances the scene by `dt` seconds. Only called on the top scene.
+    fn update(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, dt: f32) -> SceneCommand;
+
+    /// Draws the scene.
+    fn draw(&mut self, d: &mut SceneDrawHandle);
+
+    /// Whether the scenes below keep drawing under this one, as with a modal dialog or a pause menu.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// Transition used when this scene is pushed, replaces another or is popped. `None` uses the stack's default.
+    fn transition(&self) -> Option<Transition> {
+        None
+    }
+}
+
+/// Change to the stack requested by a scene.
+pub enum SceneCommand {
+    /// Keep the stack as it is.
+    None,
+    /// Put a new scene on top of the current one.
+    Push(Box<dyn Scene>),
+    /// Remove the current scene, going back to the one below.
+    Pop,
+    /// Swap the current scene for another.
+    Replace(Box<dyn Scene>),
+    /// Exit the application.
+    Quit,
+}
+
+/// Direction a [`Transition::slide`] moves the incoming scene towards.
+///
+/// [`Transition::slide`]: struct.Transition.html#method.slide
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlideDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// How the outgoing and incoming scenes are combined during a transition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionKind {
+    /// Switch immediately.
+    Cut,
+    /// Fade the outgoing scene out to a color, then the incoming scene in from it.
+    Fade(Color),
+    /// Blend the outgoing scene into the incoming one.
+    Crossfade,
+    /// Push the outgoing scene off screen with the incoming one.
+    Slide(SlideDirection),
+}
+
+/// An animated change between two states of the stack.
+#[derive(Clone, Copy)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    /// Duration in seconds.
+    pub duration: f32,
+    pub easing: EaseFn,
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Transition::cut()
+    }
+}
+
+impl Transition {
+    /// No animation.
+    pub fn cut() -> Transition {
+        Transition {
+            kind: TransitionKind::Cut,
+            duration: 0.0,
+            easing: ease::linear_none,
+        }
+    }
+
+    /// Fades through `color`.
+    pub fn fade(color: impl Into<Color>, duration: f32) -> Transition {
+        Transition {
+            kind: TransitionKind::Fade(color.into()),
+            duration,
+            easing: ease::linear_none,
+        }
+    }
+
+    /// Blends the two scenes.
+    pub fn crossfade(duration: f32) -> Transition {
+        Transition {
+            kind: TransitionKind::Crossfade,
+            duration,
+            easing: ease::linear_none,
+        }
+    }
+
+    /// Slides the incoming scene in.
+    pub fn slide(direction: SlideDirection, duration: f32) -> Transition {
+        Transition {
+            kind: TransitionKind::Slide(direction),
+            duration,
+            easing: ease::cubic_in_out,
+        }
+    }
+
+    /// Uses an easing function from the [`ease`] module for the transition's progress.
+    ///
+    /// [`ease`]: ../ease/index.html
+    pub fn with_easing(mut self, easing: EaseFn) -> Transition {
+        self.easing = easing;
+        self
+    }
+
+    fn is_cut(&self) -> bool {
+        self.kind == TransitionKind::Cut || self.duration <= 0.0
+    }
+}
+
+/// Drawing handle given to scenes, both when drawing to the screen and into a transition's render texture.
+pub struct SceneDrawHandle<'a> {
+    rl: &'a RaylibHandle,
+    alpha: f32,
+}
+
+impl<'a> SceneDrawHandle<'a> {
+    /// Interpolation factor between the last two fixed updates, see [`App::draw`].
+    ///
+    /// [`App::draw`]: ../app/trait.App.html#tymethod.draw
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+}
+
+impl<'a> std::ops::Deref for SceneDrawHandle<'a> {
+    type Target = RaylibHandle;
+
+    fn deref(&self) -> &Self::Target {
+        self.rl
+    }
+}
+
+impl<'a> RaylibDraw for SceneDrawHandle<'a> {}
+
+struct ActiveTransition {
+    kind: TransitionKind,
+    tween: Tween,
+    progress: f32,
+}
+
+/// A stack of scenes, with the active one on top.
+pub struct SceneStack {
+    thread: RaylibThread,
+    scenes: Vec<Box<dyn Scene>>,
+    transition: Transition,
+    active: Option<ActiveTransition>,
+    /// Outgoing and incoming views of the active transition.
+    targets: Option<(RenderTexture2D, RenderTexture2D)>,
+    quit: bool,
+}
+
+impl SceneStack {
+    /// Creates an empty stack.
+    pub fn new(thread: &RaylibThread) -> SceneStack {
+        SceneStack {
+            thread: thread.clone(),
+            scenes: Vec::new(),
+            transition: Transition::cut(),
+            active: None,
+            targets: None,
+            quit: false,
+        }
+    }
+
+    /// Sets the transition used by scenes that do not pick their own.
+    pub fn with_transition(mut self, transition: Transition) -> SceneStack {
+        self.transition = transition;
+        self
+    }
+
+    /// Number of scenes on the stack.
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// Checks if a scene returned `SceneCommand::Quit` or the last scene was popped.
+    pub fn is_finished(&self) -> bool {
+        self.quit || self.scenes.is_empty()
+    }
+
+    /// Checks if a transition is being played.
+    pub fn is_transitioning(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Puts `scene` on top of the stack.
+    pub fn push(&mut self, rl: &mut RaylibHandle, scene: Box<dyn Scene>) {
+        self.apply(rl, SceneCommand::Push(scene));
+    }
+
+    /// Removes the top scene.
+    pub fn pop(&mut self, rl: &mut RaylibHandle) {
+        self.apply(rl, SceneCommand::Pop);
+    }
+
+    /// Swaps the top scene for `scene`.
+    pub fn replace(&mut self, rl: &mut RaylibHandle, scene: Box<dyn Scene>) {
+        self.apply(rl, SceneCommand::Replace(scene));
+    }
+
+    /// Applies a command as if the top scene had returned it.
+    pub fn apply(&mut self, rl: &mut RaylibHandle, command: SceneCommand) {
+        let transition = match &command {
+            SceneCommand::None | SceneCommand::Quit => None,
+            SceneCommand::Push(scene) | SceneCommand::Replace(scene) => {
+                Some(scene.transition().unwrap_or(self.transition))
+            }
+            SceneCommand::Pop => self
+                .scenes
+                .last()
+                .map(|scene| scene.transition().unwrap_or(self.transition)),
+        };
+        if let Some(transition) = transition {
+            if !transition.is_cut() && !self.scenes.is_empty() {
+                self.begin_transition(rl, transition);
+            }
+        }
+
+        match command {
+            SceneCommand::None => {}
+            SceneCommand::Push(mut scene) => {
+                scene.enter(rl, &self.thread);
+                self.scenes.push(scene);
+            }
+            SceneCommand::Pop => {
+                if let Some(mut scene) = self.scenes.pop() {
+                    scene.exit(rl, &self.thread);
+                }
+            }
+            SceneCommand::Replace(mut scene) => {
+                if let Some(mut old) = self.scenes.pop() {
+                    old.exit(rl, &self.thread);
+                }
+                scene.enter(rl, &self.thread);
+                self.scenes.push(scene);
+            }
+            SceneCommand::Quit => self.quit = true,
+        }
+    }
+
+    /// Captures the current view as the outgoing side of `transition`.
+    fn begin_transition(&mut self, rl: &mut RaylibHandle, transition: Transition) {
+        let (width, height) = (rl.get_screen_width(), rl.get_screen_height());
+        let stale = match &self.targets {
+            Some((from, _)) => from.width() != width || from.height() != height,
+            None => true,
+        };
+        if stale {
+            self.targets = None;
+            let from = rl.load_render_texture(&self.thread, width as u32, height as u32);
+            let to = rl.load_render_texture(&self.thread, width as u32, height as u32);
+            match (from, to) {
+                (Ok(from), Ok(to)) => self.targets = Some((from, to)),
+                // Without render textures the change happens as a cut
+                _ => return,
+            }
+        }
+
+        let (from, _) = self.targets.as_mut().unwrap();
+        let mut handle = &mut *rl;
+        let mut target = handle.begin_texture_mode(&self.thread, from);
+        target.clear_background(Color::BLANK);
+        let mut d = SceneDrawHandle {
+            rl: &target,
+            alpha: 1.0,
+        };
+        draw_scenes(&mut self.scenes, &mut d);
+        drop(target);
+
+        self.active = Some(ActiveTransition {
+            kind: transition.kind,
+            tween: Tween::new(transition.easing, 0.0, 1.0, transition.duration),
+            progress: 0.0,
+        });
+    }
+}
+
+/// Draws the top scene and every overlay-covered scene below it, bottom first.
+fn draw_scenes(scenes: &mut [Box<dyn Scene>], d: &mut SceneDrawHandle) {
+    let mut first = scenes.len();
+    while first > 0 {
+        first -= 1;
+        if !scenes[first].is_overlay() {
+            break;
+        }
+    }
+    for scene in &mut scenes[first..] {
+        scene.draw(d);
+    }
+}
+
+fn composite(
+    d: &mut RaylibDrawHandle,
+    from: &RenderTexture2D,
+    to: &RenderTexture2D,
+    kind: TransitionKind,
+    progress: f32,
+) {
+    let (width, height) = (from.width() as f32, from.height() as f32);
+    // Render textures are stored upside down
+    let source = Rectangle::new(0.0, 0.0, width, -height);
+    let at = |x: f32, y: f32| Vector2::new(x, y);
+    match kind {
+        TransitionKind::Cut => d.draw_texture_rec(to.texture(), source, at(0.0, 0.0), Color::WHITE),
+        TransitionKind::Fade(color) => {
+            let (view, amount) = if progress < 0.5 {
+                (from, progress * 2.0)
+            } else {
+                (to, (1.0 - progress) * 2.0)
+            };
+            d.draw_texture_rec(view.texture(), source, at(0.0, 0.0), Color::WHITE);
+            let mut overlay = color;
+            overlay.a = (color.a as f32 * amount.clamp(0.0, 1.0)) as u8;
+            d.draw_rectangle(0, 0, width as i32, height as i32, overlay);
+        }
+        TransitionKind::Crossfade => {
+            d.draw_texture_rec(from.texture(), source, at(0.0, 0.0), Color::WHITE);
+            let mut tint = Color::WHITE;
+            tint.a = (255.0 * progress.clamp(0.0, 1.0)) as u8;
+            d.draw_texture_rec(to.texture(), source, at(0.0, 0.0), tint);
+        }
+        TransitionKind::Slide(direction) => {
+            let (dx, dy) = match direction {
+                SlideDirection::Left => (-width, 0.0),
+                SlideDirection::Right => (width, 0.0),
+                SlideDirection::Up => (0.0, -height),
+                SlideDirection::Down => (0.0, height),
+            };
+            let (x, y) = (dx * progress, dy * progress);
+            d.draw_texture_rec(from.texture(), source, at(x, y), Color::WHITE);
+            d.draw_texture_rec(to.texture(), source, at(x - dx, y - dy), Color::WHITE);
+        }
+    }
+}
+
+impl App for SceneStack {
+    fn update(&mut self, rl: &mut RaylibHandle, dt: f32) {
+        if let Some(active) = &mut self.active {
+            active.progress = active.tween.apply(dt);
+            if active.tween.has_completed() {
+                self.active = None;
+            }
+        }
+        let command = match self.scenes.last_mut() {
+            Some(scene) => scene.update(rl, &self.thread, dt),
+            None => return,
+        };
+        self.apply(rl, command);
+    }
+
+    fn draw(&mut self, d: &mut RaylibDrawHandle, alpha: f32) {
+        let (active, (from, to)) = match (&self.active, &mut self.targets) {
+            (Some(active), Some(targets)) => (active, targets),
+            _ => {
+                let mut scene_draw = SceneDrawHandle { rl: d, alpha };
+                draw_scenes(&mut self.scenes, &mut scene_draw);
+                return;
+            }
+        };
+
+        {
+            let mut target = d.begin_texture_mode(&self.thread, to);
+            target.clear_background(Color::BLANK);
+            let mut scene_draw = SceneDrawHandle { rl: &target, alpha };
+            draw_scenes(&mut self.scenes, &mut scene_draw);
+        }
+        composite(d, from, to, active.kind, active.progress);
+    }
+
+    fn on_resize(&mut self, _rl: &mut RaylibHandle, _width: i32, _height: i32) {
+        // Recreated at the new size by the next transition
+        if self.active.is_none() {
+            self.targets = None;
+        }
+    }
+
+    fn on_exit(&mut self, rl: &mut RaylibHandle) {
+        while let Some(mut scene) = self.scenes.pop() {
+            scene.exit(rl, &self.thread);
+        }
+    }
+
+    fn should_exit(&self) -> bool {
+        self.is_finished()
+    }
+}
+
+#[cfg(test)]
+mod scene_test {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    struct Probe {
+        name: &'static str,
+        log: Log,
+        overlay: bool,
+        next: Option<SceneCommand>,
+    }
+
+    fn probe(name: &'static str, log: &Log) -> Box<Probe> {
+        Box::new(Probe {
+            name,
+            log: log.clone(),
+            overlay: false,
+            next: None,
+        })
+    }
+
+    impl Scene for Probe {
+        fn enter(&mut self, _rl: &mut RaylibHandle, _thread: &RaylibThread) {
+            self.log.borrow_mut().push(format!("enter {}", self.name));
+        }
+
+        fn exit(&mut self, _rl: &mut RaylibHandle, _thread: &RaylibThread) {
+            self.log.borrow_mut().push(format!("exit {}", self.name));
+        }
+
+        fn update(&mut self, _rl: &mut RaylibHandle, _: &RaylibThread, _dt: f32) -> SceneCommand {
+            self.log.borrow_mut().push(format!("update {}", self.name));
+            self.next.take().unwrap_or(SceneCommand::None)
+        }
+
+        fn draw(&mut self, _d: &mut SceneDrawHandle) {
+            self.log.borrow_mut().push(format!("draw {}", self.name));
+        }
+
+        fn is_overlay(&self) -> bool {
+            self.overlay
+        }
+    }
+
+    fn take(log: &Log) -> Vec<String> {
+        log.borrow_mut().drain(..).collect()
+    }
+
+    #[test]
+    fn test_push_pop_replace() {
+        let (mut rl, thread) = RaylibHandle::for_test();
+        let log = Log::default();
+        let mut stack = SceneStack::new(&thread);
+
+        stack.push(&mut rl, probe("menu", &log));
+        stack.push(&mut rl, probe("game", &log));
+        assert_eq!(stack.len(), 2);
+        stack.replace(&mut rl, probe("pause", &log));
+        assert_eq!(stack.len(), 2);
+        assert_eq!(
+            take(&log),
+            ["enter menu", "enter game", "exit game", "enter pause"]
+        );
+
+        // Only the top scene is updated, and its command is applied
+        let mut quit = probe("quit", &log);
+        quit.next = Some(SceneCommand::Pop);
+        stack.push(&mut rl, quit);
+        take(&log);
+        stack.update(&mut rl, 0.1);
+        assert_eq!(take(&log), ["update quit", "exit quit"]);
+        assert_eq!(stack.len(), 2);
+        assert!(!stack.is_transitioning(), "cut transitions are not played");
+
+        stack.pop(&mut rl);
+        stack.pop(&mut rl);
+        assert!(stack.is_finished());
+        // Popping an empty stack does nothing
+        stack.pop(&mut rl);
+        assert_eq!(take(&log), ["exit pause", "exit menu"]);
+
+        let mut stack = SceneStack::new(&thread);
+        let mut quit = probe("quit", &log);
+        quit.next = Some(SceneCommand::Quit);
+        stack.push(&mut rl, quit);
+        stack.update(&mut rl, 0.1);
+        assert!(stack.is_finished() && stack.should_exit());
+        assert_eq!(stack.len(), 1);
+        stack.on_exit(&mut rl);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_overlays_draw_through() {
+        let (rl, _thread) = RaylibHandle::for_test();
+        let log = Log::default();
+        let overlay = |name| {
+            let mut scene = probe(name, &log);
+            scene.overlay = true;
+            scene as Box<dyn Scene>
+        };
+        let mut scenes: Vec<Box<dyn Scene>> = vec![
+            probe("title", &log),
+            probe("game", &log),
+            overlay("pause"),
+            overlay("confirm"),
+        ];
+        let mut d = SceneDrawHandle {
+            rl: &rl,
+            alpha: 1.0,
+        };
+
+        // Stops at the first opaque scene, drawing bottom first
+        draw_scenes(&mut scenes, &mut d);
+        assert_eq!(take(&log), ["draw game", "draw pause", "draw confirm"]);
+
+        scenes.truncate(2);
+        draw_scenes(&mut scenes, &mut d);
+        assert_eq!(take(&log), ["draw game"]);
+
+        // Overlays all the way down draw everything
+        let mut scenes = vec![overlay("hud"), overlay("toast")];
+        draw_scenes(&mut scenes, &mut d);
+        assert_eq!(take(&log), ["draw hud", "draw toast"]);
+    }
+
+    #[test]
+    fn test_transition_progress() {
+        let (mut rl, thread) = RaylibHandle::for_test();
+        let log = Log::default();
+        let mut stack = SceneStack::new(&thread);
+        stack.push(&mut rl, probe("game", &log));
+        assert!(Transition::fade(Color::BLACK, 0.0).is_cut());
+        assert!(!Transition::crossfade(0.5).is_cut());
+
+        let transition = Transition::crossfade(1.0);
+        stack.active = Some(ActiveTransition {
+            kind: transition.kind,
+            tween: Tween::new(transition.easing, 0.0, 1.0, transition.duration),
+            progress: 0.0,
+        });
+        stack.update(&mut rl, 0.25);
+        let progress = stack.active.as_ref().unwrap().progress;
+        assert!((progress - 0.25).abs() < 1e-5, "progress {}", progress);
+        stack.update(&mut rl, 0.5);
+        let progress = stack.active.as_ref().unwrap().progress;
+        assert!((progress - 0.75).abs() < 1e-5, "progress {}", progress);
+
+        // The scene keeps updating during the transition, which ends once the tween completes
+        stack.update(&mut rl, 0.5);
+        assert!(!stack.is_transitioning());
+        assert_eq!(
+            take(&log),
+            ["enter game", "update game", "update game", "update game"]
+        );
+    }
+}
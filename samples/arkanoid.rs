@@ -66,7 +66,7 @@ impl Default for Game {
 }
 
 fn main() {
-    let opt = options::Opt::from_args();
+    let mut opt = options::Opt::from_args();
     let (mut rl, thread) = opt.open_window("Arkanoid");
     let (_w, _h) = (opt.width, opt.height);
 
@@ -8,7 +8,7 @@ const MAX_BUILDINGS: usize = 100;
 
 fn main() {
     use raylib::consts::KeyboardKey::*;
-    let opt = options::Opt::from_args();
+    let mut opt = options::Opt::from_args();
     let (mut rl, thread) = opt.open_window("Camera 2D");
     let (w, h) = (opt.width, opt.height);
 
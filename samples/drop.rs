@@ -6,15 +6,15 @@ use structopt::StructOpt;
 mod options;
 
 fn main() {
-    let opt = options::Opt::from_args();
-    test_shader_dropping(&opt);
-    test_model_dropping(&opt);
-    test_audio_dropping(&opt);
-    test_font_dropping(&opt);
+    let mut opt = options::Opt::from_args();
+    test_shader_dropping(&mut opt);
+    test_model_dropping(&mut opt);
+    test_audio_dropping(&mut opt);
+    test_font_dropping(&mut opt);
 }
 
 /// Checks that shader files are droppable after window is closed
-fn test_shader_dropping(opt: &options::Opt) {
+fn test_shader_dropping(opt: &mut options::Opt) {
     let _ten_millis = time::Duration::from_millis(10);
     let _v = {
         let (mut rl, thread) = opt.open_window("Drop Shader");
@@ -24,7 +24,7 @@ fn test_shader_dropping(opt: &options::Opt) {
 }
 
 /// Checks that model files are droppable after window is closed
-fn test_model_dropping(opt: &options::Opt) {
+fn test_model_dropping(opt: &mut options::Opt) {
     let ten_millis = time::Duration::from_millis(10);
     let _m = {
         let (mut rl, thread) = opt.open_window("Drop Model");
@@ -47,7 +47,7 @@ fn test_model_dropping(opt: &options::Opt) {
 }
 
 /// Checks that audio files are droppable after window is closed
-fn test_audio_dropping(opt: &options::Opt) {
+fn test_audio_dropping(opt: &mut options::Opt) {
     let ten_millis = time::Duration::from_millis(10);
     let _w = {
         let (_, _thread) = raylib::init()
@@ -59,14 +59,17 @@ fn test_audio_dropping(opt: &options::Opt) {
     thread::sleep(ten_millis);
     let _s = {
         let (_rl, _thread) = opt.open_window("Drop Sound");
-        Sound::load_sound("static/wave.ogg").expect("couldn't load wave");
+        let audio = RaylibAudio::init_audio_device().expect("couldn't open audio device");
+        audio
+            .load_sound("static/wave.ogg")
+            .expect("couldn't load wave");
     };
     thread::sleep(ten_millis);
     // Broken on mac
     // let m = {
     //     let (_rl, thread) = opt.open_window("Drop Sound");
-    //     // let m = Music::load_music_stream(&thread, "static/wave.ogg");
-    //     let m = Music::load_music_stream(&thread, "static/wave.ogg").expect("couldn't load music");
+    //     let audio = RaylibAudio::init_audio_device().expect("couldn't open audio device");
+    //     let m = audio.load_music_stream("static/wave.ogg").expect("couldn't load music");
     //     println!("music {:?}", m);
     //     drop(m);
     //     ()
@@ -75,7 +78,7 @@ fn test_audio_dropping(opt: &options::Opt) {
 }
 
 /// checks that fonts can be dropped after window is closed
-fn test_font_dropping(opt: &options::Opt) {
+fn test_font_dropping(opt: &mut options::Opt) {
     let _f = {
         let (mut rl, thread) = raylib::init()
             .size(opt.width, opt.height)
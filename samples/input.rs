@@ -5,7 +5,7 @@ use structopt::StructOpt;
 mod options;
 
 fn main() {
-    let opt = options::Opt::from_args();
+    let mut opt = options::Opt::from_args();
     let (mut rl, thread) = opt.open_window("Input");
     let (_w, _h) = (opt.width, opt.height);
     let _rust_orange = Color::new(222, 165, 132, 255);
@@ -4,7 +4,7 @@ use structopt::StructOpt;
 mod options;
 
 fn main() {
-    let opt = options::Opt::from_args();
+    let mut opt = options::Opt::from_args();
     let (mut rl, thread) = opt.open_window("Model shader example");
     let (w, h) = (opt.width, opt.height);
 
@@ -12,6 +12,15 @@ pub struct Opt {
     pub height: i32,
     #[structopt(long = "fps", default_value = "60")]
     pub fps: u32,
+    /// Record input to this file, written when the sample exits
+    #[structopt(long = "record")]
+    pub record: Option<String>,
+    /// Replay input previously recorded with `--record`
+    #[structopt(long = "replay")]
+    pub replay: Option<String>,
+    /// Seed for samples using random numbers, so replays see the same world
+    #[structopt(long = "seed")]
+    pub seed: Option<u64>,
 }
 
 impl Opt {
@@ -19,7 +28,9 @@ impl Opt {
     pub fn new() -> Self {
         Opt::from_args()
     }
-    pub fn open_window(&self, name: &str) -> (raylib::RaylibHandle, raylib::RaylibThread) {
+    /// Opens the window and starts `--record` / `--replay`. A replay restores the seed it was recorded with, and a
+    /// recording without `--seed` picks one.
+    pub fn open_window(&mut self, name: &str) -> (raylib::RaylibHandle, raylib::RaylibThread) {
         let (mut rl, thread) = raylib::init()
             .size(self.width, self.height)
             .title(name)
@@ -27,6 +38,30 @@ impl Opt {
         let logo = raylib::prelude::Image::load_image("static/logo.png").unwrap();
         rl.set_window_icon(&logo);
         rl.set_target_fps(self.fps);
+        if let Some(replay) = &self.replay {
+            let recording = raylib::prelude::InputRecording::load(replay).unwrap();
+            if recording.seed().is_some() {
+                self.seed = recording.seed();
+            }
+            rl.start_input_replay(recording);
+        }
+        if self.record.is_some() {
+            // the recording is saved with the seed, so a replay sees the same world
+            if self.seed.is_none() {
+                let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH);
+                self.seed = Some(now.map_or(0, |t| t.as_nanos() as u64));
+            }
+            rl.start_input_recording();
+        }
         (rl, thread)
     }
+
+    /// Writes the input recorded since `open_window`, if any.
+    #[allow(dead_code)]
+    pub fn close_window(&self, rl: &mut raylib::RaylibHandle) {
+        if let (Some(record), Some(mut recording)) = (&self.record, rl.stop_input_recording()) {
+            recording.set_seed(self.seed);
+            recording.save(record).unwrap();
+        }
+    }
 }
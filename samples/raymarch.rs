@@ -7,7 +7,7 @@ mod options;
 const SHADER: &str = include_str!("static/raymarching.fs");
 
 pub fn main() {
-    let opt = options::Opt::from_args();
+    let mut opt = options::Opt::from_args();
     let (mut rl, thread) = opt.open_window("Camera 2D");
     let (w, h) = (opt.width, opt.height);
 
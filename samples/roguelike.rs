@@ -9,6 +9,7 @@ use rand::prelude::*;
 use rand::Rng;
 use raylib::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::error::Error;
 use std::fs::File;
 use std::io::{Read, Write};
@@ -17,6 +18,36 @@ use tcod::map::{FovAlgorithm, Map as FovMap};
 
 mod options;
 
+thread_local! {
+    /// Seedable so that input replays generate the same dungeon
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Handle to the game's random number generator
+struct GameRng;
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+fn game_rng() -> GameRng {
+    GameRng
+}
+
 /// Keep the player at index zero
 const PLAYER: usize = 0;
 
@@ -589,10 +620,10 @@ fn make_map(objects: &mut Vec<Object>, level: u32) -> Map {
 
     let mut rooms = vec![];
     for _ in 0..MAX_ROOMS {
-        let w = rand::thread_rng().gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
-        let h = rand::thread_rng().gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
-        let x = rand::thread_rng().gen_range(0, MAP_WIDTH - w);
-        let y = rand::thread_rng().gen_range(0, MAP_HEIGHT - h);
+        let w = game_rng().gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
+        let h = game_rng().gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
+        let x = game_rng().gen_range(0, MAP_WIDTH - w);
+        let y = game_rng().gen_range(0, MAP_HEIGHT - h);
 
         let new_room = Rectangle::new(x as f32, y as f32, w as f32, h as f32);
         let failed = rooms
@@ -612,7 +643,7 @@ fn make_map(objects: &mut Vec<Object>, level: u32) -> Map {
             } else {
                 let (prev_x, prev_y) = (&rooms[rooms.len() - 1]).center();
                 // toss a coin and pick if we move horizontally or vertically
-                if rand::random() {
+                if game_rng().gen() {
                     create_h_tunnel(prev_x, new_x, prev_y, &mut map);
                     create_v_tunnel(prev_y, new_y, new_x, &mut map);
                 } else {
@@ -646,11 +677,11 @@ fn place_objects(room: Rectangle, map: &Map, objects: &mut Vec<Object>, level: u
     );
 
     // choose random number of monsters
-    let num_monsters = rand::thread_rng().gen_range(0, max_monsters + 1);
+    let num_monsters = game_rng().gen_range(0, max_monsters + 1);
 
     for _ in 0..num_monsters {
-        let x = rand::thread_rng().gen_range(room.x + 1.0, room.x + room.width) as i32;
-        let y = rand::thread_rng().gen_range(room.y + 1.0, room.y + room.height) as i32;
+        let x = game_rng().gen_range(room.x + 1.0, room.x + room.width) as i32;
+        let y = game_rng().gen_range(room.y + 1.0, room.y + room.height) as i32;
 
         // monster random table
         let troll_chance = from_dungeon_level(
@@ -674,7 +705,7 @@ fn place_objects(room: Rectangle, map: &Map, objects: &mut Vec<Object>, level: u
         let monsters = ["orc", "troll"];
         let monster_weights = [80, troll_chance];
         let moster_distribution = WeightedIndex::new(&monster_weights).unwrap();
-        let mut rng = thread_rng();
+        let mut rng = game_rng();
 
         let mut monster = match monsters[moster_distribution.sample(&mut rng)] {
             "orc" => {
@@ -767,16 +798,16 @@ fn place_objects(room: Rectangle, map: &Map, objects: &mut Vec<Object>, level: u
     ];
 
     // choose random number of items
-    let num_items = rand::thread_rng().gen_range(0, max_items + 1);
+    let num_items = game_rng().gen_range(0, max_items + 1);
     for _ in 0..num_items {
         // choose random spot for this item
-        let x = rand::thread_rng().gen_range(room.x as i32 + 1, (room.x + room.width) as i32);
-        let y = rand::thread_rng().gen_range(room.y as i32 + 1, (room.y + room.height) as i32);
+        let x = game_rng().gen_range(room.x as i32 + 1, (room.x + room.width) as i32);
+        let y = game_rng().gen_range(room.y as i32 + 1, (room.y + room.height) as i32);
 
         // only place it if the tile is not blocked
         if !is_blocked(x, y, map, objects) {
             let item_distribution = WeightedIndex::new(&item_weights).unwrap();
-            let mut item = match items[item_distribution.sample(&mut thread_rng())] {
+            let mut item = match items[item_distribution.sample(&mut game_rng())] {
                 Item::Heal => {
                     // create a healing potion
                     let mut object =
@@ -966,6 +997,9 @@ fn main() {
     let (mut rl, thread) = opt.open_window("Roguelike");
     let (_w, _h) = (opt.width, opt.height);
     rl.set_target_fps(20);
+    if let Some(seed) = opt.seed {
+        RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+    }
 
     // build FOV map
     let mut tcod = Tcod {
@@ -974,6 +1008,7 @@ fn main() {
     };
 
     main_menu(&mut rl, &thread, &mut tcod);
+    opt.close_window(&mut rl);
 }
 
 fn handle_keys(
@@ -1411,8 +1446,8 @@ fn ai_confused(
         // move in a random direction, and decrease the number of turns confused
         move_by(
             monster_id,
-            rand::thread_rng().gen_range(-1, 2),
-            rand::thread_rng().gen_range(-1, 2),
+            game_rng().gen_range(-1, 2),
+            game_rng().gen_range(-1, 2),
             &game.map,
             objects,
         );
@@ -146,7 +146,7 @@ impl<'a> System<'a> for DrawSys {
 }
 
 fn main() {
-    let opt = options::Opt::from_args();
+    let mut opt = options::Opt::from_args();
     let (rl, thread) = opt.open_window("Specs Example");
     let (_w, _h) = (opt.width, opt.height);
 
@@ -5,7 +5,7 @@ use structopt::StructOpt;
 mod options;
 
 fn main() {
-    let opt = options::Opt::from_args();
+    let mut opt = options::Opt::from_args();
     let (mut rl, thread) = opt.open_window("Texture");
     let (_w, _h) = (opt.width, opt.height);
     let i = Image::load_image("static/billboard.png").expect("could not load image billboard");
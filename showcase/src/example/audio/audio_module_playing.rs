@@ -38,7 +38,7 @@ pub fn run(rl
     rl.set_window_title(thread, "raylib [audio] example - module playing (streaming)");
 
 
-    let mut audio = RaylibAudio::init_audio_device(); // Initialize audio device
+    let mut audio = RaylibAudio::init_audio_device().unwrap(); // Initialize audio device
 
     let colors = [Color::ORANGE,Color::RED, Color::GOLD, Color::LIME, Color::BLUE, Color::VIOLET, Color::BROWN, Color::LIGHTGRAY, Color::PINK,
                         Color::YELLOW, Color::GREEN, Color::SKYBLUE, Color::PURPLE, Color::BEIGE];
@@ -56,7 +56,7 @@ pub fn run(rl
         circles[i].color = colors[raylib::get_random_value::<i32>(0, 13) as usize];
     }
 
-    let mut music = Music::load_music_stream(thread, "original/audio/resources/mini1111.xm").unwrap();
+    let mut music = audio.load_music_stream("original/audio/resources/mini1111.xm").unwrap();
 
     audio.play_music_stream(&mut music);
 
@@ -25,10 +25,10 @@ pub fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> crate::SampleOut {
         "raylib [audio] example - Multichannel sound playing",
     );
 
-    let mut audio = RaylibAudio::init_audio_device(); // Initialize audio device
+    let mut audio = RaylibAudio::init_audio_device().unwrap(); // Initialize audio device
 
-    let mut fxWav = Sound::load_sound("original/audio/resources/sound.wav").unwrap(); // Load WAV audio file
-    let fxOgg = Sound::load_sound("original/audio/resources/tanatana.ogg").unwrap(); // Load OGG audio file
+    let mut fxWav = audio.load_sound("original/audio/resources/sound.wav").unwrap(); // Load WAV audio file
+    let fxOgg = audio.load_sound("original/audio/resources/tanatana.ogg").unwrap(); // Load OGG audio file
 
     audio.set_sound_volume(&mut fxWav, 0.2);
 
@@ -20,10 +20,10 @@ pub fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> crate::SampleOut {
     rl.set_window_title(thread, "raylib [audio] example - music playing (streaming)");
     rl.set_window_size(screen_width, screen_height);
 
-    let mut audio = RaylibAudio::init_audio_device();
+    let mut audio = RaylibAudio::init_audio_device().unwrap();
 
     let mut music =
-        Music::load_music_stream(thread, "original/audio/resources/guitar_noodling.ogg").unwrap();
+        audio.load_music_stream("original/audio/resources/guitar_noodling.ogg").unwrap();
 
     audio.play_music_stream(&mut music);
 
@@ -25,10 +25,10 @@ pub fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> crate::SampleOut {
     rl.set_window_size(screen_width, screen_height);
     rl.set_window_title(thread, "raylib [audio] example - raw audio streaming");
 
-    let mut audio = RaylibAudio::init_audio_device(); // Initialize audio device
+    let mut audio = RaylibAudio::init_audio_device().unwrap(); // Initialize audio device
 
     // Init raw audio stream (sample rate: 22050, sample size: 16bit-short, channels: 1-mono)
-    let mut stream = AudioStream::init_audio_stream(thread, 22050, 16, 1);
+    let mut stream = audio.init_audio_stream::<i16>(22050, 1).unwrap();
 
     // Buffer for the single cycle waveform we are synthesizing
     let mut data = [0i16; MAX_SAMPLES / std::mem::size_of::<i16>()];
@@ -131,7 +131,7 @@ pub fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> crate::SampleOut {
             }
 
             // Copy finished frame to audio stream
-            stream.update_audio_stream( &writeBuf);
+            stream.update(&writeBuf).unwrap();
         }
         //----------------------------------------------------------------------------------
 
@@ -0,0 +1,142 @@
+/*******************************************************************************************
+*
+*   raylib [audio] example - sfxr-style sound effect editor
+*
+*   Pick a preset, randomize or mutate it and tweak the parameters, sfxr style.
+*   SAVE and LOAD keep the parameters in sfx.txt.
+*
+********************************************************************************************/
+
+use raylib::prelude::*;
+use std::ffi::CString;
+
+const SFX_FILE: &str = "sfx.txt";
+
+pub fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> crate::SampleOut {
+    // Initialization
+    //--------------------------------------------------------------------------------------
+    let screen_width = 800;
+    let screen_height = 450;
+
+    rl.set_window_size(screen_width, screen_height);
+    rl.set_window_title(thread, "raylib [audio] example - sound effect editor");
+
+    let mut audio = RaylibAudio::init_audio_device().unwrap(); // Initialize audio device
+
+    let presets = [
+        (rstr!("PICKUP"), SfxPreset::Pickup),
+        (rstr!("LASER"), SfxPreset::Laser),
+        (rstr!("EXPLOSION"), SfxPreset::Explosion),
+        (rstr!("POWERUP"), SfxPreset::PowerUp),
+        (rstr!("HIT"), SfxPreset::Hit),
+        (rstr!("JUMP"), SfxPreset::Jump),
+        (rstr!("BLIP"), SfxPreset::Blip),
+    ];
+    let waveforms = [
+        SfxWaveform::Square,
+        SfxWaveform::Sawtooth,
+        SfxWaveform::Sine,
+        SfxWaveform::Noise,
+    ];
+
+    let mut params = SfxParams::default();
+    let labels: Vec<CString> = params
+        .fields_mut()
+        .iter()
+        .map(|field| CString::new(field.name).unwrap())
+        .collect();
+    let mut seed = 0u64;
+    let mut sound: Option<Sound> = None;
+    let mut status = String::new();
+
+    rl.set_target_fps(60); // Set our game to run at 60 frames-per-second
+                           //--------------------------------------------------------------------------------------
+
+    // Main game loop
+    return Box::new(
+        move |rl: &mut RaylibHandle, thread: &RaylibThread| -> () // Detect window close button or ESC key
+    {
+        // Draw
+        //----------------------------------------------------------------------------------
+        let mut d = rl.begin_drawing(thread);
+
+        d.clear_background(Color::RAYWHITE);
+
+        let mut play = false;
+
+        // Presets and generators
+        for (i, (name, preset)) in presets.iter().enumerate() {
+            if d.gui_button(rrect(20, 20 + i as i32 * 30, 120, 25), Some(name)) {
+                seed += 1;
+                params = SfxParams::preset(*preset, seed);
+                play = true;
+            }
+        }
+        if d.gui_button(rrect(20, 250, 120, 25), Some(rstr!("RANDOMIZE"))) {
+            seed += 1;
+            params = SfxParams::random(seed);
+            play = true;
+        }
+        if d.gui_button(rrect(20, 280, 120, 25), Some(rstr!("MUTATE"))) {
+            seed += 1;
+            params.mutate(seed);
+            play = true;
+        }
+        if d.gui_button(rrect(20, 330, 120, 25), Some(rstr!("PLAY"))) {
+            play = true;
+        }
+        if d.gui_button(rrect(20, 360, 120, 25), Some(rstr!("SAVE"))) {
+            status = match params.save(SFX_FILE) {
+                Ok(()) => format!("Saved {}", SFX_FILE),
+                Err(e) => e,
+            };
+        }
+        if d.gui_button(rrect(20, 390, 120, 25), Some(rstr!("LOAD"))) {
+            status = match SfxParams::load(SFX_FILE) {
+                Ok(loaded) => {
+                    params = loaded;
+                    play = true;
+                    format!("Loaded {}", SFX_FILE)
+                }
+                Err(e) => e,
+            };
+        }
+
+        // Waveform and parameters
+        let active = waveforms.iter().position(|&w| w == params.waveform).unwrap_or(0);
+        let active = d.gui_toggle_group(
+            rrect(200, 20, 140, 25),
+            Some(rstr!("SQUARE;SAWTOOTH;SINE;NOISE")),
+            active as i32,
+        );
+        params.waveform = waveforms[active as usize];
+
+        for (i, field) in params.fields_mut().into_iter().enumerate() {
+            let (x, y) = if i < 12 {
+                (330, 70 + i as i32 * 30)
+            } else {
+                (640, 70 + (i as i32 - 12) * 30)
+            };
+            *field.value = d.gui_slider_bar(
+                rrect(x, y, 110, 20),
+                Some(&labels[i]),
+                Some(&rstr!("{:.2}", *field.value)),
+                *field.value,
+                field.min,
+                field.max,
+            );
+        }
+
+        if play {
+            // Kept until the next one, so it isn't unloaded while playing
+            if let Ok(new_sound) = audio.load_sound_from_wave(&params.to_wave()) {
+                audio.play_sound(sound.insert(new_sound));
+            }
+        }
+
+        d.draw_text(&status, 20, 425, 10, Color::GRAY);
+
+        //----------------------------------------------------------------------------------
+    },
+    );
+}
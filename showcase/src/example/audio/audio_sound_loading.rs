@@ -20,10 +20,10 @@ pub fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> crate::SampleOut {
     rl.set_window_size(screen_width, screen_height);
     rl.set_window_title(thread, "raylib [audio] example - sound loading and playing");
 
-    let mut audio = RaylibAudio::init_audio_device(); // Initialize audio device
+    let mut audio = RaylibAudio::init_audio_device().unwrap(); // Initialize audio device
 
-    let mut fxWav = Sound::load_sound("original/audio/resources/sound.wav").unwrap(); // Load WAV audio file
-    let mut fxOgg = Sound::load_sound("original/audio/resources/tanatana.ogg").unwrap(); // Load OGG audio file
+    let mut fxWav = audio.load_sound("original/audio/resources/sound.wav").unwrap(); // Load WAV audio file
+    let mut fxOgg = audio.load_sound("original/audio/resources/tanatana.ogg").unwrap(); // Load OGG audio file
 
     rl.set_target_fps(60); // Set our game to run at 60 frames-per-second
                            //--------------------------------------------------------------------------------------
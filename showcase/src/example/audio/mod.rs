@@ -2,4 +2,5 @@ pub mod audio_module_playing;
 pub mod audio_multichannel_sound;
 pub mod audio_music_stream;
 pub mod audio_raw_stream;
+pub mod audio_sfx_editor;
 pub mod audio_sound_loading;
@@ -71,6 +71,10 @@ fn main() {
             rstr!("raylib [audio] example - sound loading and playing"),
             example::audio::audio_sound_loading::run,
         ),
+        (
+            rstr!("raylib [audio] example - sound effect editor"),
+            example::audio::audio_sfx_editor::run,
+        ),
         (
             rstr!("raylib [core] example - Camera"),
             example::core::core_2d_camera::run,